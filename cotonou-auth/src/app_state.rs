@@ -1,10 +1,12 @@
 use crate::error::Error;
 use axum::extract::FromRef;
 use cotonou_common::{
-    profile::{AccountManager, CoreProfileManager},
     database::{GenericDAL, IdGeneratorDAL},
+    http::HttpClient,
+    metrics::MetricsRegistry,
     mongo_db::MongoDbConfig,
-    steam::{SteamMicroTxnClient, SteamUserAuthClient, SteamUserClient}, http::HttpClient,
+    profile::{AccountManager, CoreProfileManager},
+    steam::{SteamMicroTxnClient, SteamUserAuthClient, SteamUserClient},
 };
 use hyper_tls::HttpsConnector;
 use std::sync::Arc;
@@ -17,6 +19,7 @@ pub struct AppState {
     steam_user_auth_client: Arc<SteamUserAuthClient>,
     steam_user_client: Arc<SteamUserClient>,
     steam_micro_tnx_client: Arc<SteamMicroTxnClient>,
+    pub metrics_registry: Arc<MetricsRegistry>,
 }
 
 impl AppState {
@@ -48,6 +51,7 @@ impl AppState {
             steam_user_auth_client: Arc::new(steam_user_auth_client),
             steam_user_client: Arc::new(steam_user_client),
             steam_micro_tnx_client: Arc::new(steam_micro_tnx_client),
+            metrics_registry: Arc::new(MetricsRegistry::new()),
         })
     }
 }
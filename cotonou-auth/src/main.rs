@@ -4,7 +4,10 @@ use axum::{
     routing::{get, put},
     Router,
 };
-use cotonou_common::authentication::{jwt_auth_middleware, JwtSecret};
+use cotonou_common::{
+    authentication::{jwt_auth_middleware, JwtSecret},
+    metrics::{get_metrics, metrics_middleware},
+};
 use error::Error;
 use std::net::SocketAddr;
 
@@ -25,6 +28,7 @@ async fn main() -> Result<(), Error> {
     // build our application with a route
     let app = Router::new()
         .route("/healthcheck", get(health_check))
+        .route("/metrics", get(get_metrics))
         .route(
             "/authentication",
             put(keep_alive)
@@ -34,6 +38,10 @@ async fn main() -> Result<(), Error> {
                 ))
                 .post(authenticate),
         )
+        .layer(middleware::from_fn_with_state(
+            app_state.metrics_registry.clone(),
+            metrics_middleware,
+        ))
         .with_state(app_state);
 
     // run it
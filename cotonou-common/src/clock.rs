@@ -0,0 +1,46 @@
+use crate::unix_now;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Source of the current time (unix timestamp, in seconds)
+pub trait Clock: Send + Sync {
+    fn now(&self) -> u64;
+}
+
+/// System time
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> u64 {
+        unix_now()
+    }
+}
+
+/// Time moved forward by hand, for tests and simulations
+pub struct ManualClock {
+    time: AtomicU64,
+}
+
+impl ManualClock {
+    //-------------------------------------------------------------------------------------------------
+    pub fn new(time: u64) -> Self {
+        Self {
+            time: AtomicU64::new(time),
+        }
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    pub fn set(&self, time: u64) {
+        self.time.store(time, Ordering::Relaxed);
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    pub fn advance(&self, seconds: u64) {
+        self.time.fetch_add(seconds, Ordering::Relaxed);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> u64 {
+        self.time.load(Ordering::Relaxed)
+    }
+}
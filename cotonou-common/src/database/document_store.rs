@@ -0,0 +1,65 @@
+use crate::database::Error;
+use futures::future::BoxFuture;
+use mongodb::bson::Document;
+use std::result;
+
+type Result<T> = result::Result<T, Error>;
+
+/// Low level storage of BSON documents, grouped by collection
+///
+/// Filters only need to support equality on top level properties and the `$in` operator.
+/// Updates only need to support the `$set` and `$inc` operators.
+pub trait DocumentStore: Send + Sync {
+    //-------------------------------------------------------------------------------------------------
+    fn find_one<'a>(
+        &'a self,
+        collection_name: &'a str,
+        filter: Document,
+        projection: Option<Document>,
+    ) -> BoxFuture<'a, Result<Option<Document>>>;
+
+    //-------------------------------------------------------------------------------------------------
+    fn find<'a>(
+        &'a self,
+        collection_name: &'a str,
+        filter: Document,
+        projection: Option<Document>,
+    ) -> BoxFuture<'a, Result<Vec<Document>>>;
+
+    //-------------------------------------------------------------------------------------------------
+    fn insert_one<'a>(
+        &'a self,
+        collection_name: &'a str,
+        document: Document,
+    ) -> BoxFuture<'a, Result<()>>;
+
+    //-------------------------------------------------------------------------------------------------
+    /// # Return
+    /// true if a document has been replaced or inserted
+    fn replace_one<'a>(
+        &'a self,
+        collection_name: &'a str,
+        filter: Document,
+        replacement: Document,
+        upsert: bool,
+    ) -> BoxFuture<'a, Result<bool>>;
+
+    //-------------------------------------------------------------------------------------------------
+    fn update_one<'a>(
+        &'a self,
+        collection_name: &'a str,
+        filter: Document,
+        update: Document,
+    ) -> BoxFuture<'a, Result<()>>;
+
+    //-------------------------------------------------------------------------------------------------
+    /// # Return
+    /// the document after the update
+    fn find_one_and_update<'a>(
+        &'a self,
+        collection_name: &'a str,
+        filter: Document,
+        update: Document,
+        upsert: bool,
+    ) -> BoxFuture<'a, Result<Option<Document>>>;
+}
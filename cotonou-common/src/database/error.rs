@@ -5,6 +5,15 @@ pub enum Error {
     #[error("Database Error")]
     Database,
 
+    #[error("Duplicate Key Error")]
+    DuplicateKey,
+
     #[error("MongoDb Error: {0}")]
     MongoDb(#[from] mongodb::error::Error),
+
+    #[error("Bson Serialization Error: {0}")]
+    BsonSerialization(#[from] mongodb::bson::ser::Error),
+
+    #[error("Bson Deserialization Error: {0}")]
+    BsonDeserialization(#[from] mongodb::bson::de::Error),
 }
@@ -1,39 +1,28 @@
 use crate::{
-    database::{master_entity, Error, MasterEntity, MongoDbCollection},
-    mongo_db::MongoDbConfig,
-};
-use futures::TryStreamExt;
-use mongodb::{
-    bson::{self, Bson, DateTime},
-    options::{
-        FindOneAndUpdateOptions, FindOneOptions, FindOptions, ReplaceOptions, ReturnDocument,
-        UpdateModifications,
+    database::{
+        master_entity, DocumentStore, Error, MasterEntity, MongoDbCollection, MongoDbDocumentStore,
     },
+    mongo_db::MongoDbConfig,
 };
+use mongodb::bson::{self, Bson, DateTime};
 use serde::{de::DeserializeOwned, Serialize};
-use std::result;
+use std::{result, sync::Arc};
 
 type Result<T> = result::Result<T, Error>;
 
 #[derive(Clone)]
 pub struct GenericDAL {
-    mongodb_database: mongodb::Database,
+    document_store: Arc<dyn DocumentStore>,
 }
 
 impl GenericDAL {
     pub async fn initialize(mongo_config: &MongoDbConfig) -> Result<GenericDAL> {
-        let mongo_options =
-            mongodb::options::ClientOptions::parse(&mongo_config.connection_string).await?;
-        let database_name = mongo_options
-            .default_database
-            .clone()
-            .ok_or(Error::Database)?;
-        let mongo_client = mongodb::Client::with_options(mongo_options)?;
-        let mongo_database = mongo_client.database(&database_name);
-
-        Ok(Self {
-            mongodb_database: mongo_database,
-        })
+        let document_store = MongoDbDocumentStore::initialize(mongo_config).await?;
+        Ok(Self::new(Arc::new(document_store)))
+    }
+
+    pub fn new(document_store: Arc<dyn DocumentStore>) -> GenericDAL {
+        Self { document_store }
     }
 
     pub async fn get_partial_entity<T, TI>(
@@ -45,12 +34,16 @@ impl GenericDAL {
         T: MongoDbCollection + DeserializeOwned + Unpin + Send + Sync,
         Bson: std::convert::From<TI>,
     {
-        let mongo_collection = self.get_collection::<T>();
         let filter = bson::doc! { "_id": entity_id };
-        let options = FindOneOptions::builder()
-            .projection(Self::get_projection(attributes_to_get))
-            .build();
-        Ok(mongo_collection.find_one(filter, options).await?)
+        let document = self
+            .document_store
+            .find_one(
+                T::get_collection_name(),
+                filter,
+                Some(Self::get_projection(attributes_to_get)),
+            )
+            .await?;
+        Ok(document.map(bson::from_document).transpose()?)
     }
 
     pub async fn get_partial_entities<T, TI>(
@@ -63,8 +56,6 @@ impl GenericDAL {
         Bson: From<TI>,
         TI: Clone,
     {
-        let mongo_collection = self.get_collection::<T>();
-
         let ids = entity_ids
             .iter()
             .map(|i| Bson::from(i.clone()))
@@ -75,15 +66,19 @@ impl GenericDAL {
             .or(Err(Error::Database))?
             .insert("$in", ids);
 
-        let options = FindOptions::builder()
-            .projection(Self::get_projection(attributes_to_get))
-            .build();
+        let documents = self
+            .document_store
+            .find(
+                T::get_collection_name(),
+                filter,
+                Some(Self::get_projection(attributes_to_get)),
+            )
+            .await?;
 
-        Ok(mongo_collection
-            .find(filter, options)
-            .await?
-            .try_collect()
-            .await?)
+        Ok(documents
+            .into_iter()
+            .map(bson::from_document)
+            .collect::<result::Result<Vec<T>, _>>()?)
     }
 
     pub async fn get_entity<T, TI>(&self, entity_id: TI) -> Result<Option<T>>
@@ -91,9 +86,12 @@ impl GenericDAL {
         T: MongoDbCollection + DeserializeOwned + Unpin + Send + Sync,
         Bson: std::convert::From<TI>,
     {
-        let mongo_collection = self.get_collection::<T>();
         let filter = bson::doc! { "_id": entity_id };
-        Ok(mongo_collection.find_one(filter, None).await?)
+        let document = self
+            .document_store
+            .find_one(T::get_collection_name(), filter, None)
+            .await?;
+        Ok(document.map(bson::from_document).transpose()?)
     }
 
     pub async fn save_master_entity<T, TI>(&self, entity: &mut T) -> Result<bool>
@@ -116,21 +114,21 @@ impl GenericDAL {
 
         entity.set_data_version(Some(entity.get_data_version().unwrap_or(0) + 1));
 
-        let options = ReplaceOptions::builder().upsert(true).build();
+        let replacement = bson::to_document(entity)?;
 
-        let mongo_collection = self.get_collection::<T>();
-        let result = mongo_collection.replace_one(query, entity, options).await?;
-
-        Ok(result.modified_count == 1 || result.upserted_id.is_some())
+        self.document_store
+            .replace_one(T::get_collection_name(), query, replacement, true)
+            .await
     }
 
     pub async fn save_entity<T>(&self, entity: &mut T) -> Result<()>
     where
         T: MongoDbCollection + Serialize + Unpin + Send + Sync,
     {
-        let mongo_collection = self.get_collection::<T>();
-        let _result = mongo_collection.insert_one(entity, None).await?;
-        Ok(())
+        let document = bson::to_document(entity)?;
+        self.document_store
+            .insert_one(T::get_collection_name(), document)
+            .await
     }
 
     pub async fn update_property<T, TI, TP>(
@@ -144,15 +142,31 @@ impl GenericDAL {
         Bson: std::convert::From<TI>,
         Bson: std::convert::From<TP>,
     {
-        let mongo_collection = self.get_collection::<T>();
-
         let query = bson::doc! { "_id": entity_id };
-        let update =
-            UpdateModifications::Document(bson::doc! { "$set": {property_name: new_value} });
+        let update = bson::doc! { "$set": {property_name: new_value} };
 
-        mongo_collection.update_one(query, update, None).await?;
+        self.document_store
+            .update_one(T::get_collection_name(), query, update)
+            .await
+    }
 
-        Ok(())
+    /// Atomically update the entity matching the filter
+    ///
+    /// # Return
+    /// the entity after the update, None if no entity matches the filter
+    pub async fn find_and_update_entity<T>(
+        &self,
+        filter: bson::Document,
+        update: bson::Document,
+    ) -> Result<Option<T>>
+    where
+        T: MongoDbCollection + DeserializeOwned + Unpin + Send + Sync,
+    {
+        let document = self
+            .document_store
+            .find_one_and_update(T::get_collection_name(), filter, update, false)
+            .await?;
+        Ok(document.map(bson::from_document).transpose()?)
     }
 
     pub async fn increment_property<T, TI>(
@@ -185,17 +199,12 @@ impl GenericDAL {
         Bson: std::convert::From<TI>,
         Bson: std::convert::From<TP>,
     {
-        let mongo_collection = self.get_bson_document_collection::<T>();
-
         let filter = bson::doc! { "_id": entity_id };
-        let update = UpdateModifications::Document(bson::doc! { "$inc": {property_name: value} });
-        let options = FindOneAndUpdateOptions::builder()
-            .upsert(true)
-            .return_document(ReturnDocument::After)
-            .build();
-
-        let document = mongo_collection
-            .find_one_and_update(filter, update, options)
+        let update = bson::doc! { "$inc": {property_name: value} };
+
+        let document = self
+            .document_store
+            .find_one_and_update(T::get_collection_name(), filter, update, true)
             .await?;
 
         match document {
@@ -207,20 +216,6 @@ impl GenericDAL {
         }
     }
 
-    fn get_collection<T>(&self) -> mongodb::Collection<T>
-    where
-        T: MongoDbCollection,
-    {
-        self.mongodb_database.collection(T::get_collection_name())
-    }
-
-    fn get_bson_document_collection<T>(&self) -> mongodb::Collection<bson::Document>
-    where
-        T: MongoDbCollection,
-    {
-        self.mongodb_database.collection(T::get_collection_name())
-    }
-
     fn get_projection(attributes_to_get: &[&str]) -> bson::Document {
         let mut doc = bson::Document::new();
         for attribute in attributes_to_get {
@@ -0,0 +1,324 @@
+use crate::database::{master_entity, DocumentStore, Error};
+use futures::future::{self, BoxFuture};
+use mongodb::bson::{Bson, Document};
+use std::{
+    collections::HashMap,
+    result,
+    sync::{Arc, Mutex},
+};
+
+type Result<T> = result::Result<T, Error>;
+
+/// In-memory [`DocumentStore`], meant for tests
+#[derive(Clone, Default)]
+pub struct MemoryDocumentStore {
+    collections: Arc<Mutex<HashMap<String, Vec<Document>>>>,
+}
+
+impl MemoryDocumentStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn find_impl(
+        &self,
+        collection_name: &str,
+        filter: &Document,
+        projection: Option<&Document>,
+    ) -> Vec<Document> {
+        let collections = self.collections.lock().unwrap();
+        let Some(documents) = collections.get(collection_name) else {
+            return Vec::new();
+        };
+
+        documents
+            .iter()
+            .filter(|d| is_match(d, filter))
+            .map(|d| project(d, projection))
+            .collect()
+    }
+
+    fn update_impl(
+        &self,
+        collection_name: &str,
+        filter: &Document,
+        update: &Document,
+        upsert: bool,
+    ) -> Result<Option<Document>> {
+        let mut collections = self.collections.lock().unwrap();
+        let documents = collections.entry(collection_name.to_owned()).or_default();
+
+        if let Some(document) = documents.iter_mut().find(|d| is_match(d, filter)) {
+            apply_update(document, update)?;
+            return Ok(Some(document.clone()));
+        }
+
+        if !upsert {
+            return Ok(None);
+        }
+
+        let mut document = equality_properties(filter);
+        apply_update(&mut document, update)?;
+        insert(documents, document.clone())?;
+        Ok(Some(document))
+    }
+}
+
+impl DocumentStore for MemoryDocumentStore {
+    //-------------------------------------------------------------------------------------------------
+    fn find_one<'a>(
+        &'a self,
+        collection_name: &'a str,
+        filter: Document,
+        projection: Option<Document>,
+    ) -> BoxFuture<'a, Result<Option<Document>>> {
+        let document = self
+            .find_impl(collection_name, &filter, projection.as_ref())
+            .into_iter()
+            .next();
+        Box::pin(future::ready(Ok(document)))
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn find<'a>(
+        &'a self,
+        collection_name: &'a str,
+        filter: Document,
+        projection: Option<Document>,
+    ) -> BoxFuture<'a, Result<Vec<Document>>> {
+        let documents = self.find_impl(collection_name, &filter, projection.as_ref());
+        Box::pin(future::ready(Ok(documents)))
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn insert_one<'a>(
+        &'a self,
+        collection_name: &'a str,
+        document: Document,
+    ) -> BoxFuture<'a, Result<()>> {
+        let mut collections = self.collections.lock().unwrap();
+        let documents = collections.entry(collection_name.to_owned()).or_default();
+        let result = insert(documents, document);
+        Box::pin(future::ready(result))
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn replace_one<'a>(
+        &'a self,
+        collection_name: &'a str,
+        filter: Document,
+        mut replacement: Document,
+        upsert: bool,
+    ) -> BoxFuture<'a, Result<bool>> {
+        let mut collections = self.collections.lock().unwrap();
+        let documents = collections.entry(collection_name.to_owned()).or_default();
+
+        let result = if let Some(document) = documents.iter_mut().find(|d| is_match(d, &filter)) {
+            if let Some(id) = document.get(master_entity::KEY) {
+                replacement.insert(master_entity::KEY, id.clone());
+            }
+            *document = replacement;
+            Ok(true)
+        } else if upsert {
+            if !replacement.contains_key(master_entity::KEY) {
+                if let Some(id) = filter.get(master_entity::KEY) {
+                    replacement.insert(master_entity::KEY, id.clone());
+                }
+            }
+            insert(documents, replacement).map(|_| true)
+        } else {
+            Ok(false)
+        };
+
+        Box::pin(future::ready(result))
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn update_one<'a>(
+        &'a self,
+        collection_name: &'a str,
+        filter: Document,
+        update: Document,
+    ) -> BoxFuture<'a, Result<()>> {
+        let result = self
+            .update_impl(collection_name, &filter, &update, false)
+            .map(|_| ());
+        Box::pin(future::ready(result))
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn find_one_and_update<'a>(
+        &'a self,
+        collection_name: &'a str,
+        filter: Document,
+        update: Document,
+        upsert: bool,
+    ) -> BoxFuture<'a, Result<Option<Document>>> {
+        let result = self.update_impl(collection_name, &filter, &update, upsert);
+        Box::pin(future::ready(result))
+    }
+}
+
+//-------------------------------------------------------------------------------------------------
+fn insert(documents: &mut Vec<Document>, document: Document) -> Result<()> {
+    let Some(id) = document.get(master_entity::KEY) else {
+        return Err(Error::Database);
+    };
+
+    // unique index on _id
+    if documents
+        .iter()
+        .any(|d| d.get(master_entity::KEY).is_some_and(|i| bson_eq(i, id)))
+    {
+        return Err(Error::DuplicateKey);
+    }
+
+    documents.push(document);
+    Ok(())
+}
+
+//-------------------------------------------------------------------------------------------------
+fn is_match(document: &Document, filter: &Document) -> bool {
+    filter.iter().all(|(key, condition)| {
+        let value = get_path(document, key);
+        match condition {
+            Bson::Document(operators) if operators.keys().any(|k| k.starts_with('$')) => operators
+                .iter()
+                .all(|(operator, operand)| match (operator.as_str(), operand) {
+                    ("$in", Bson::Array(values)) => match value {
+                        Some(v) => values.iter().any(|i| bson_eq(v, i)),
+                        // null matches missing properties
+                        None => values.iter().any(|i| matches!(i, Bson::Null)),
+                    },
+                    ("$exists", Bson::Boolean(exists)) => value.is_some() == *exists,
+                    _ => false,
+                }),
+            _ => value.is_some_and(|v| bson_eq(v, condition)),
+        }
+    })
+}
+
+//-------------------------------------------------------------------------------------------------
+fn project(document: &Document, projection: Option<&Document>) -> Document {
+    let Some(projection) = projection else {
+        return document.clone();
+    };
+
+    // _id is always returned by MongoDB unless explicitly excluded
+    let mut result = Document::new();
+    if let Some(id) = document.get(master_entity::KEY) {
+        result.insert(master_entity::KEY, id.clone());
+    }
+
+    for key in projection.keys() {
+        if let Some(value) = document.get(key) {
+            result.insert(key, value.clone());
+        }
+    }
+
+    result
+}
+
+//-------------------------------------------------------------------------------------------------
+fn equality_properties(filter: &Document) -> Document {
+    filter
+        .iter()
+        .filter(|(key, value)| {
+            !key.contains('.')
+                && !matches!(value, Bson::Document(d) if d.keys().any(|k| k.starts_with('$')))
+        })
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect()
+}
+
+//-------------------------------------------------------------------------------------------------
+fn apply_update(document: &mut Document, update: &Document) -> Result<()> {
+    for (operator, properties) in update {
+        let Bson::Document(properties) = properties else {
+            return Err(Error::Database);
+        };
+
+        for (path, value) in properties {
+            match operator.as_str() {
+                "$set" => set_path(document, path, value.clone()),
+                "$inc" => {
+                    let new_value = match get_path(document, path) {
+                        None => value.clone(),
+                        Some(current) => add(current, value).ok_or(Error::Database)?,
+                    };
+                    set_path(document, path, new_value);
+                }
+                _ => return Err(Error::Database),
+            }
+        }
+    }
+
+    Ok(())
+}
+
+//-------------------------------------------------------------------------------------------------
+fn get_path<'a>(document: &'a Document, path: &str) -> Option<&'a Bson> {
+    match path.split_once('.') {
+        Some((key, rest)) => match document.get(key) {
+            Some(Bson::Document(sub_document)) => get_path(sub_document, rest),
+            _ => None,
+        },
+        None => document.get(path),
+    }
+}
+
+//-------------------------------------------------------------------------------------------------
+fn set_path(document: &mut Document, path: &str, value: Bson) {
+    match path.split_once('.') {
+        Some((key, rest)) => {
+            if !matches!(document.get(key), Some(Bson::Document(_))) {
+                document.insert(key, Document::new());
+            }
+            if let Some(Bson::Document(sub_document)) = document.get_mut(key) {
+                set_path(sub_document, rest, value);
+            }
+        }
+        None => {
+            document.insert(path, value);
+        }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------
+fn add(value1: &Bson, value2: &Bson) -> Option<Bson> {
+    match (value1, value2) {
+        (Bson::Int32(v1), Bson::Int32(v2)) => Some(Bson::Int32(v1 + v2)),
+        (Bson::Double(_), _) | (_, Bson::Double(_)) => {
+            Some(Bson::Double(as_f64(value1)? + as_f64(value2)?))
+        }
+        _ => Some(Bson::Int64(as_i64(value1)? + as_i64(value2)?)),
+    }
+}
+
+//-------------------------------------------------------------------------------------------------
+/// MongoDB compares numbers by value, whatever their BSON type
+fn bson_eq(value1: &Bson, value2: &Bson) -> bool {
+    match (as_f64(value1), as_f64(value2)) {
+        (Some(v1), Some(v2)) => v1 == v2,
+        _ => value1 == value2,
+    }
+}
+
+//-------------------------------------------------------------------------------------------------
+fn as_i64(value: &Bson) -> Option<i64> {
+    match value {
+        Bson::Int32(v) => Some(*v as i64),
+        Bson::Int64(v) => Some(*v),
+        _ => None,
+    }
+}
+
+//-------------------------------------------------------------------------------------------------
+fn as_f64(value: &Bson) -> Option<f64> {
+    match value {
+        Bson::Int32(v) => Some(*v as f64),
+        Bson::Int64(v) => Some(*v as f64),
+        Bson::Double(v) => Some(*v),
+        _ => None,
+    }
+}
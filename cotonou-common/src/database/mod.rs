@@ -1,11 +1,19 @@
+mod document_store;
 mod error;
 mod generic_dal;
 mod id_generator_dal;
 pub mod master_entity;
+#[cfg(feature = "memory")]
+mod memory_document_store;
 mod mongo_db_collection;
+mod mongo_db_document_store;
 
+pub use document_store::*;
 pub use error::*;
 pub use generic_dal::*;
 pub use id_generator_dal::*;
 pub use master_entity::MasterEntity;
-pub use mongo_db_collection::*;
\ No newline at end of file
+#[cfg(feature = "memory")]
+pub use memory_document_store::*;
+pub use mongo_db_collection::*;
+pub use mongo_db_document_store::*;
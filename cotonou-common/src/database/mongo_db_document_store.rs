@@ -0,0 +1,155 @@
+use crate::{
+    database::{DocumentStore, Error},
+    mongo_db::MongoDbConfig,
+};
+use futures::{future::BoxFuture, TryStreamExt};
+use mongodb::{
+    bson::Document,
+    error::{ErrorKind, WriteFailure},
+    options::{
+        FindOneAndUpdateOptions, FindOneOptions, FindOptions, ReplaceOptions, ReturnDocument,
+        UpdateModifications,
+    },
+};
+use std::result;
+
+type Result<T> = result::Result<T, Error>;
+
+const DUPLICATE_KEY_ERROR_CODE: i32 = 11000;
+
+#[derive(Clone)]
+pub struct MongoDbDocumentStore {
+    mongodb_database: mongodb::Database,
+}
+
+impl MongoDbDocumentStore {
+    pub async fn initialize(mongo_config: &MongoDbConfig) -> Result<Self> {
+        let mongo_options =
+            mongodb::options::ClientOptions::parse(&mongo_config.connection_string).await?;
+        let database_name = mongo_options
+            .default_database
+            .clone()
+            .ok_or(Error::Database)?;
+        let mongo_client = mongodb::Client::with_options(mongo_options)?;
+        let mongo_database = mongo_client.database(&database_name);
+
+        Ok(Self {
+            mongodb_database: mongo_database,
+        })
+    }
+
+    fn get_collection(&self, collection_name: &str) -> mongodb::Collection<Document> {
+        self.mongodb_database.collection(collection_name)
+    }
+}
+
+impl DocumentStore for MongoDbDocumentStore {
+    //-------------------------------------------------------------------------------------------------
+    fn find_one<'a>(
+        &'a self,
+        collection_name: &'a str,
+        filter: Document,
+        projection: Option<Document>,
+    ) -> BoxFuture<'a, Result<Option<Document>>> {
+        Box::pin(async move {
+            let options = FindOneOptions::builder().projection(projection).build();
+            Ok(self
+                .get_collection(collection_name)
+                .find_one(filter, options)
+                .await?)
+        })
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn find<'a>(
+        &'a self,
+        collection_name: &'a str,
+        filter: Document,
+        projection: Option<Document>,
+    ) -> BoxFuture<'a, Result<Vec<Document>>> {
+        Box::pin(async move {
+            let options = FindOptions::builder().projection(projection).build();
+            Ok(self
+                .get_collection(collection_name)
+                .find(filter, options)
+                .await?
+                .try_collect()
+                .await?)
+        })
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn insert_one<'a>(
+        &'a self,
+        collection_name: &'a str,
+        document: Document,
+    ) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            self.get_collection(collection_name)
+                .insert_one(document, None)
+                .await
+                .map_err(|e| match *e.kind {
+                    ErrorKind::Write(WriteFailure::WriteError(ref write_error))
+                        if write_error.code == DUPLICATE_KEY_ERROR_CODE =>
+                    {
+                        Error::DuplicateKey
+                    }
+                    _ => Error::MongoDb(e),
+                })?;
+            Ok(())
+        })
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn replace_one<'a>(
+        &'a self,
+        collection_name: &'a str,
+        filter: Document,
+        replacement: Document,
+        upsert: bool,
+    ) -> BoxFuture<'a, Result<bool>> {
+        Box::pin(async move {
+            let options = ReplaceOptions::builder().upsert(upsert).build();
+            let result = self
+                .get_collection(collection_name)
+                .replace_one(filter, replacement, options)
+                .await?;
+            Ok(result.modified_count == 1 || result.upserted_id.is_some())
+        })
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn update_one<'a>(
+        &'a self,
+        collection_name: &'a str,
+        filter: Document,
+        update: Document,
+    ) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            self.get_collection(collection_name)
+                .update_one(filter, UpdateModifications::Document(update), None)
+                .await?;
+            Ok(())
+        })
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn find_one_and_update<'a>(
+        &'a self,
+        collection_name: &'a str,
+        filter: Document,
+        update: Document,
+        upsert: bool,
+    ) -> BoxFuture<'a, Result<Option<Document>>> {
+        Box::pin(async move {
+            let options = FindOneAndUpdateOptions::builder()
+                .upsert(upsert)
+                .return_document(ReturnDocument::After)
+                .build();
+            Ok(self
+                .get_collection(collection_name)
+                .find_one_and_update(filter, UpdateModifications::Document(update), options)
+                .await?)
+        })
+    }
+}
@@ -1,11 +1,14 @@
 #[cfg(feature = "authentication")]
 pub mod authentication;
+pub mod clock;
 #[cfg(feature = "database")]
 pub mod database;
 #[cfg(feature = "http")]
 pub mod http;
 #[cfg(feature = "matchmaking")]
 pub mod matchmaking;
+#[cfg(feature = "metrics")]
+pub mod metrics;
 #[cfg(feature = "database")]
 pub mod mongo_db;
 #[cfg(feature = "notifications")]
@@ -18,7 +21,7 @@ pub mod redis;
 pub mod steam;
 pub mod types;
 
-use chrono::{DateTime, NaiveDateTime, Utc};
+use chrono::{DateTime, Utc};
 use serde::{de, Deserialize, Deserializer};
 
 pub fn unix_now() -> u64 {
@@ -43,7 +46,6 @@ where
     D: Deserializer<'de>,
 {
     let unix_timestamp = i64::deserialize(deserializer)?;
-    let naive = NaiveDateTime::from_timestamp_opt(unix_timestamp, 0)
-        .ok_or_else(|| de::Error::custom("Cannot parse UNIX timestamp"))?;
-    Ok(DateTime::from_utc(naive, Utc))
+    DateTime::from_timestamp(unix_timestamp, 0)
+        .ok_or_else(|| de::Error::custom("Cannot parse UNIX timestamp"))
 }
@@ -0,0 +1,76 @@
+use crate::{
+    matchmaking::{Error, SessionId},
+    types::ProfileId,
+};
+use jsonwebtoken::{decode, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+
+/// Claims of the token allowing a player to connect to the game server hosting its session.
+/// Signed by the backend, verified offline by the game server
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct ConnectTokenClaims {
+    #[serde(rename = "sub")]
+    pub profile_id: ProfileId,
+
+    #[serde(rename = "sid")]
+    pub session_id: SessionId,
+
+    #[serde(rename = "exp")]
+    pub expiration_time: u64,
+}
+
+impl ConnectTokenClaims {
+    //-------------------------------------------------------------------------------------------------
+    pub fn new(profile_id: ProfileId, session_id: SessionId, expiration_time: u64) -> Self {
+        Self {
+            profile_id,
+            session_id,
+            expiration_time,
+        }
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    /// Sign the claims with the connect token secret
+    pub fn encode(&self, connect_token_secret: &str) -> Result<String, Error> {
+        Ok(encode(
+            &Header::new(Algorithm::HS256),
+            self,
+            &EncodingKey::from_secret(connect_token_secret.as_ref()),
+        )?)
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    /// Verify the signature and the expiration time of a connect token
+    pub fn decode(connect_token: &str, connect_token_secret: &str) -> Result<Self, Error> {
+        Ok(decode::<Self>(
+            connect_token,
+            &DecodingKey::from_secret(connect_token_secret.as_ref()),
+            &Validation::new(Algorithm::HS256),
+        )?
+        .claims)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::unix_now;
+
+    #[test]
+    fn connect_token_is_verified_with_the_secret() {
+        let profile_id: ProfileId = "1".parse().unwrap();
+        let claims = ConnectTokenClaims::new(profile_id, SessionId::new(), unix_now() + 60);
+        let connect_token = claims.encode("secret").unwrap();
+
+        assert_eq!(
+            claims,
+            ConnectTokenClaims::decode(&connect_token, "secret").unwrap()
+        );
+        assert!(ConnectTokenClaims::decode(&connect_token, "other").is_err());
+
+        let expired_token = ConnectTokenClaims::new(profile_id, SessionId::new(), 1)
+            .encode("secret")
+            .unwrap();
+        assert!(ConnectTokenClaims::decode(&expired_token, "secret").is_err());
+    }
+}
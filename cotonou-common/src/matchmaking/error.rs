@@ -7,4 +7,13 @@ pub enum Error {
 
     #[error("Json Error")]
     Json(#[from] serde_json::Error),
+
+    #[error("Connect Token Error")]
+    ConnectToken(#[from] jsonwebtoken::errors::Error),
+
+    #[error("Configuration Error: {0}")]
+    Configuration(String),
+
+    #[error("Stale Fencing Token: {0}")]
+    StaleFencingToken(u64),
 }
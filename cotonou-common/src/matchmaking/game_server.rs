@@ -1,6 +1,6 @@
-use crate::types::GameServerId;
 use super::matchmaking_session::SessionId;
-use serde::{Deserialize, Serialize};
+use crate::types::GameServerId;
+use serde::{Deserialize, Deserializer, Serialize};
 use std::fmt;
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -56,18 +56,83 @@ pub struct GameServer {
     #[serde(rename = "p")]
     pub port: u16,
 
-    /// Matchmaking session id
-    #[serde(rename = "s")]
-    pub session_id: Option<SessionId>,
+    /// Matchmaking sessions hosted by the server
+    #[serde(
+        rename = "ss",
+        alias = "s",
+        default,
+        deserialize_with = "deserialize_session_ids"
+    )]
+    pub session_ids: Vec<SessionId>,
+
+    /// Maximum number of sessions hosted at the same time
+    #[serde(rename = "c", default = "default_capacity")]
+    pub capacity: usize,
+
+    /// Load reported by the last keep alive (0: idle, 1: fully loaded)
+    #[serde(rename = "l", default)]
+    pub load: f32,
+
+    /// Unhealthy servers failed to activate a session and do not receive new sessions
+    #[serde(rename = "h", default = "default_is_healthy")]
+    pub is_healthy: bool,
+
+    /// Time the server was marked unhealthy (unix timestamp)
+    #[serde(rename = "ut", default)]
+    pub unhealthy_time: u64,
+
+    /// Draining servers finish their sessions but do not receive new sessions
+    #[serde(rename = "d", default)]
+    pub is_draining: bool,
 
     /// Last keep alive time (unix timestamp)
     #[serde(rename = "t")]
     pub keep_alive_time: u64,
 }
 
+impl GameServer {
+    /// Number of sessions the server can still host
+    pub fn get_free_slots(&self) -> usize {
+        self.capacity.saturating_sub(self.session_ids.len())
+    }
+
+    /// Can the server host a new session
+    pub fn is_available(&self) -> bool {
+        self.is_healthy && !self.is_draining && self.get_free_slots() > 0
+    }
+}
+
+/// Servers registered without capacity host a single session
+pub fn default_capacity() -> usize {
+    1
+}
+
+fn default_is_healthy() -> bool {
+    true
+}
+
+/// Servers saved before capacities existed host an optional single session ("s")
+fn deserialize_session_ids<'de, D>(deserializer: D) -> Result<Vec<SessionId>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum SessionIds {
+        Many(Vec<SessionId>),
+        One(Option<SessionId>),
+    }
+
+    Ok(match SessionIds::deserialize(deserializer)? {
+        SessionIds::Many(session_ids) => session_ids,
+        SessionIds::One(session_id) => session_id.into_iter().collect(),
+    })
+}
+
 #[cfg(feature = "matchmaking")]
 #[cfg(test)]
 mod tests {
+    use super::GameServer;
     use crate::types::{GameServerId, UniqueId};
 
     const TEST_UUID: &str = "1f6cf4f5d977453394c6ba33b7a3e299";
@@ -101,7 +166,20 @@ mod tests {
     fn serialize() {
         let expected_id = format!("\"{TEST_UUID}\"");
         let actual_id =
-            serde_json::to_string(&GameServerId::from(UniqueId::try_parse(TEST_UUID).unwrap())).unwrap();
+            serde_json::to_string(&GameServerId::from(UniqueId::try_parse(TEST_UUID).unwrap()))
+                .unwrap();
         assert_eq!(expected_id, actual_id);
     }
+
+    #[test]
+    fn deserialize_server_with_single_session() {
+        for (session, num_sessions) in [(format!("\"{TEST_UUID}\""), 1), ("null".to_owned(), 0)] {
+            let json = format!(
+                r#"{{"i":"{TEST_UUID}","ii":"host","ht":"Dynamic","hb":0,"hp":"AWS","gv":"1.0","pid":1,"ip":"127.0.0.1","p":7777,"s":{session},"t":0}}"#
+            );
+            let server = serde_json::from_str::<GameServer>(&json).unwrap();
+            assert_eq!(num_sessions, server.session_ids.len());
+            assert_eq!(1, server.capacity);
+        }
+    }
 }
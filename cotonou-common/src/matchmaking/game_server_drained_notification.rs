@@ -0,0 +1,11 @@
+use crate::{notifications::Notification, types::GameServerId};
+use serde::{Deserialize, Serialize};
+
+/// Sent to a draining game server once its last session is over, it can now shut down
+#[derive(Serialize, Deserialize)]
+pub struct GameServerDrainedNotification {
+    pub game_server_id: GameServerId,
+}
+
+#[typetag::serde]
+impl Notification for GameServerDrainedNotification {}
@@ -0,0 +1,23 @@
+use serde::{Deserialize, Serialize};
+
+/// Scheduled period during which players cannot look for a match
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Maintenance {
+    /// Start of the maintenance (unix timestamp)
+    #[serde(rename = "s")]
+    pub start_time: u64,
+
+    /// End of the maintenance (unix timestamp)
+    #[serde(rename = "e")]
+    pub end_time: u64,
+
+    /// Message displayed to players
+    #[serde(rename = "m")]
+    pub message: String,
+}
+
+impl Maintenance {
+    pub fn is_active(&self, now: u64) -> bool {
+        self.start_time <= now && now < self.end_time
+    }
+}
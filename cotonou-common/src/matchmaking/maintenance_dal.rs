@@ -0,0 +1,24 @@
+use crate::matchmaking::{Error, Maintenance};
+use futures::future::BoxFuture;
+
+/// Maintenances of a region, or of all regions when `region_system_name` is None
+pub trait MaintenanceDAL: Send + Sync {
+    //-------------------------------------------------------------------------------------------------
+    fn get_maintenance<'a>(
+        &'a self,
+        region_system_name: Option<&'a str>,
+    ) -> BoxFuture<'a, Result<Option<Maintenance>, Error>>;
+
+    //-------------------------------------------------------------------------------------------------
+    fn set_maintenance<'a>(
+        &'a self,
+        region_system_name: Option<&'a str>,
+        maintenance: &'a Maintenance,
+    ) -> BoxFuture<'a, Result<(), Error>>;
+
+    //-------------------------------------------------------------------------------------------------
+    fn delete_maintenance<'a>(
+        &'a self,
+        region_system_name: Option<&'a str>,
+    ) -> BoxFuture<'a, Result<(), Error>>;
+}
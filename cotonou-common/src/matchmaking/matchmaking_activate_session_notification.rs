@@ -1,4 +1,4 @@
-use crate::{matchmaking::SessionId, notifications::Notification};
+use crate::{matchmaking::SessionId, notifications::Notification, types::ProfileId};
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize)]
@@ -8,6 +8,8 @@ pub struct MatchmakingActivateSessionNotification {
     pub encryption_key: String,
     pub max_players: usize,
     pub team_player_count: usize,
+    /// Profile ids of the players of each team
+    pub teams: Vec<Vec<ProfileId>>,
 }
 
 #[typetag::serde]
@@ -0,0 +1,19 @@
+use crate::{matchmaking::SessionId, notifications::Notification, types::ProfileId};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+pub struct BackfillPlayer {
+    pub profile_id: ProfileId,
+    pub team: Option<usize>,
+}
+
+/// Sent to a game server when its backfill request is fulfilled
+#[derive(Serialize, Deserialize)]
+pub struct MatchmakingBackfillCompletedNotification {
+    pub matchmaking_session_id: SessionId,
+    /// Players on their way to the server
+    pub players: Vec<BackfillPlayer>,
+}
+
+#[typetag::serde]
+impl Notification for MatchmakingBackfillCompletedNotification {}
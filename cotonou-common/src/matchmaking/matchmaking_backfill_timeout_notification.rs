@@ -0,0 +1,16 @@
+use crate::{
+    matchmaking::{BackfillPlayer, SessionId},
+    notifications::Notification,
+};
+use serde::{Deserialize, Serialize};
+
+/// Sent to a game server when its backfill request expires before being fulfilled
+#[derive(Serialize, Deserialize)]
+pub struct MatchmakingBackfillTimeoutNotification {
+    pub matchmaking_session_id: SessionId,
+    /// Players found before the timeout, on their way to the server
+    pub players: Vec<BackfillPlayer>,
+}
+
+#[typetag::serde]
+impl Notification for MatchmakingBackfillTimeoutNotification {}
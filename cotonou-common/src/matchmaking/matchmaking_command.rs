@@ -1,11 +1,16 @@
 use crate::{
-    matchmaking::{game_server::GameServerHostType, matchmaking_ticket::MatchmakingTicket},
+    matchmaking::{
+        game_server::{default_capacity, GameServerHostType},
+        maintenance::Maintenance,
+        matchmaking_ticket::MatchmakingTicket,
+    },
     types::GameServerId,
     types::ProfileId,
 };
 use serde::{Deserialize, Serialize};
+use std::fmt;
 
-use super::matchmaking_session::SessionId;
+use super::matchmaking_session::{BackfillRequest, JoinCode, SessionId};
 
 #[derive(Serialize, Deserialize)]
 pub enum MatchmakingCommand {
@@ -15,6 +20,18 @@ pub enum MatchmakingCommand {
     },
     ActivateSession {
         session_id: SessionId,
+        /// server activating the session, must be the server the session was sent to
+        game_server_id: GameServerId,
+    },
+    /// Cancel a ticket on behalf of the matchmaking service
+    CancelTicket {
+        owner_profile_id: ProfileId,
+    },
+    CreatePrivateSession {
+        session_id: SessionId,
+        join_code: JoinCode,
+        expiration_time: u64,
+        ticket: MatchmakingTicket,
     },
     CreateTicket {
         ticket: MatchmakingTicket,
@@ -30,6 +47,10 @@ pub enum MatchmakingCommand {
         owner_profile_id: ProfileId,
         player_who_canceled_profile_id: ProfileId,
     },
+    /// Stop sending new sessions to a game server
+    DrainGameServer {
+        game_server_id: GameServerId,
+    },
     InitializeGameServer {
         game_server_id: GameServerId,
         host_name: String,
@@ -40,11 +61,29 @@ pub enum MatchmakingCommand {
         process_id: u32,
         ip_address: String,
         port: u16,
+        #[serde(default = "default_capacity")]
+        capacity: usize,
+    },
+    JoinPrivateSession {
+        join_code: JoinCode,
+        ticket: MatchmakingTicket,
     },
     KeepAliveGameServer {
         game_server_id: GameServerId,
+        #[serde(default)]
+        load: f32,
+    },
+    RequestBackfill {
+        session_id: SessionId,
+        backfill_request: BackfillRequest,
     },
     ResetMatchmaking,
+    /// Schedule the maintenance of the region, or of all regions if `is_global`,
+    /// None to cancel it
+    SetMaintenance {
+        is_global: bool,
+        maintenance: Option<Maintenance>,
+    },
     ShutdownGameServer {
         game_server_id: GameServerId,
     },
@@ -53,3 +92,38 @@ pub enum MatchmakingCommand {
         is_open: bool,
     },
 }
+
+/// Id given to a command when it is queued (Redis stream entry id)
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct CommandId(String);
+
+impl CommandId {
+    pub fn new(id: String) -> Self {
+        Self(id)
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for CommandId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// Command delivered to the matchmaking job, to be acknowledged once processed
+pub struct QueuedMatchmakingCommand {
+    pub command_id: CommandId,
+    pub command: MatchmakingCommand,
+}
+
+/// Command moved out of the queue because it could not be processed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadLetterCommand {
+    pub command_id: CommandId,
+    /// raw command json
+    pub command: String,
+    pub reason: String,
+}
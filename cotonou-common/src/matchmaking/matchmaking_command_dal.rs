@@ -1,50 +1,79 @@
-use crate::{
-    matchmaking::{redis_key_names, Error, MatchmakingCommand},
-    redis::RedisConnectionManager,
+use crate::matchmaking::{
+    CommandId, DeadLetterCommand, Error, MatchmakingCommand, QueuedMatchmakingCommand,
 };
-use rustis::{client::Client, commands::ListCommands};
-use std::result;
+use futures::future::BoxFuture;
+use std::{collections::HashSet, time::Duration};
 
-type Result<T> = result::Result<T, Error>;
+/// Number of deliveries after which an unacknowledged command is moved to the dead letter queue
+pub const MAX_COMMAND_DELIVERIES: usize = 3;
 
-#[derive(Clone)]
-pub struct MatchmakingCommandDAL {
-    client: Client,
-}
+/// How long the ids of processed commands are kept, to skip the commands redelivered in the meantime
+pub const PROCESSED_COMMAND_RETENTION: Duration = Duration::from_secs(3600);
+
+/// At-least-once command queue:
+/// dequeued commands stay pending until they are acknowledged
+pub trait MatchmakingCommandDAL: Send + Sync {
+    //-------------------------------------------------------------------------------------------------
+    fn queue_command<'a>(
+        &'a self,
+        region_system_name: &'a str,
+        command: &'a MatchmakingCommand,
+    ) -> BoxFuture<'a, Result<CommandId, Error>>;
+
+    //-------------------------------------------------------------------------------------------------
+    /// Dequeue new commands.
+    /// Undecodable commands are moved to the dead letter queue
+    fn dequeue_commands<'a>(
+        &'a self,
+        region_system_name: &'a str,
+    ) -> BoxFuture<'a, Result<Vec<QueuedMatchmakingCommand>, Error>>;
+
+    //-------------------------------------------------------------------------------------------------
+    /// Redeliver commands dequeued but never acknowledged, typically after a crash of the job.
+    /// Commands already delivered [`MAX_COMMAND_DELIVERIES`] times are moved to the dead letter queue
+    fn recover_commands<'a>(
+        &'a self,
+        region_system_name: &'a str,
+    ) -> BoxFuture<'a, Result<Vec<QueuedMatchmakingCommand>, Error>>;
+
+    //-------------------------------------------------------------------------------------------------
+    /// Commands among `command_ids` whose effects have already been saved,
+    /// redelivered because they were not acknowledged in time
+    fn get_processed_commands<'a>(
+        &'a self,
+        region_system_name: &'a str,
+        command_ids: &'a [CommandId],
+    ) -> BoxFuture<'a, Result<HashSet<CommandId>, Error>>;
+
+    //-------------------------------------------------------------------------------------------------
+    /// Remember processed commands for [`PROCESSED_COMMAND_RETENTION`], saved along with their effects
+    fn save_processed_commands<'a>(
+        &'a self,
+        region_system_name: &'a str,
+        fencing_token: u64,
+        command_ids: &'a [CommandId],
+    ) -> BoxFuture<'a, Result<(), Error>>;
+
+    //-------------------------------------------------------------------------------------------------
+    /// Refused with [`Error::StaleFencingToken`] once the region has been leased again,
+    /// the commands are then redelivered to the new leader
+    fn ack_commands<'a>(
+        &'a self,
+        region_system_name: &'a str,
+        fencing_token: u64,
+        command_ids: &'a [CommandId],
+    ) -> BoxFuture<'a, Result<(), Error>>;
+
+    //-------------------------------------------------------------------------------------------------
+    /// Number of commands queued or pending acknowledgement
+    fn get_queue_length<'a>(
+        &'a self,
+        region_system_name: &'a str,
+    ) -> BoxFuture<'a, Result<usize, Error>>;
 
-impl MatchmakingCommandDAL {
-    pub fn new(redis_connection_manager: &RedisConnectionManager) -> Self {
-        Self {
-            client: redis_connection_manager.get_client("MATCHMAKING").unwrap(),
-        }
-    }
-
-    pub async fn queue_command(
-        &self,
-        region_system_name: &str,
-        command: &MatchmakingCommand,
-    ) -> Result<()> {
-        let key = self.build_queue_key_name(region_system_name);
-        let json = serde_json::to_string(command)?;
-        self.client.rpush(key, json).await?;
-        Ok(())
-    }
-
-    pub async fn dequeue_commands(
-        &self,
-        region_system_name: &str,
-    ) -> Result<Vec<MatchmakingCommand>> {
-        let key = self.build_queue_key_name(region_system_name);
-        Ok(self
-            .client
-            .lpop::<_, _, Vec<String>>(key, 1000)
-            .await?
-            .iter()
-            .map(|c| serde_json::from_str::<MatchmakingCommand>(c).unwrap())
-            .collect())
-    }
-
-    fn build_queue_key_name(&self, region_system_name: &str) -> String {
-        format!("{}:{}", region_system_name, redis_key_names::COMMAND_QUEUE)
-    }
+    //-------------------------------------------------------------------------------------------------
+    fn get_dead_letter_commands<'a>(
+        &'a self,
+        region_system_name: &'a str,
+    ) -> BoxFuture<'a, Result<Vec<DeadLetterCommand>, Error>>;
 }
@@ -1,4 +1,4 @@
-use crate::{notifications::Notification, matchmaking::SessionId};
+use crate::{matchmaking::SessionId, notifications::Notification};
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize)]
@@ -8,6 +8,8 @@ pub struct MatchmakingCompletedNotification {
     pub ip_address: String,
     pub port: u16,
     pub encryption_key: String,
+    /// Token to present to the game server, see [`ConnectTokenClaims`](crate::matchmaking::ConnectTokenClaims)
+    pub connect_token: String,
 }
 
 #[typetag::serde]
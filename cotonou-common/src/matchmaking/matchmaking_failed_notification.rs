@@ -1,7 +1,7 @@
 use crate::{notifications::Notification, types::ProfileId};
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum MatchmakingFailureReason {
     CancelledByFriend = 1,
     CancelledByMatchmakingService = 2,
@@ -11,12 +11,17 @@ pub enum MatchmakingFailureReason {
     ServerSessionClosed = 6,
     ExpiredInvitation = 7,
     InvalidInvitation = 8,
+    Timeout = 9,
+    SessionActivationTimeout = 10,
+    Maintenance = 11,
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct MatchmakingFailedNotification {
     pub onwer_profile_id: ProfileId,
     pub failure_reason: MatchmakingFailureReason,
+    /// Game mode the client may try instead, on timeout
+    pub fallback_game_mode: Option<String>,
 }
 
 #[typetag::serde]
@@ -0,0 +1,21 @@
+use serde::{Deserialize, Serialize};
+
+/// Matchmaking job process, as reported to the other processes
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MatchmakingJobInstance {
+    #[serde(rename = "i")]
+    pub instance_id: String,
+
+    /// system names of the regions processed by this instance
+    #[serde(rename = "r")]
+    pub regions: Vec<String>,
+
+    /// last time the instance reported itself (unix timestamp)
+    #[serde(rename = "h")]
+    pub last_heartbeat_time: u64,
+
+    /// the instance only processes statically assigned regions,
+    /// it does not take a share of the other regions
+    #[serde(rename = "s", default)]
+    pub is_static: bool,
+}
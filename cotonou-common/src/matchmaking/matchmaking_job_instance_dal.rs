@@ -0,0 +1,20 @@
+use crate::matchmaking::{Error, MatchmakingJobInstance};
+use futures::future::BoxFuture;
+
+/// Seconds without heartbeat after which an instance is considered dead
+pub const JOB_INSTANCE_TIMEOUT: u64 = 10;
+
+pub trait MatchmakingJobInstanceDAL: Send + Sync {
+    //-------------------------------------------------------------------------------------------------
+    fn report_instance<'a>(
+        &'a self,
+        instance: &'a MatchmakingJobInstance,
+    ) -> BoxFuture<'a, Result<(), Error>>;
+
+    //-------------------------------------------------------------------------------------------------
+    /// Live instances; instances without heartbeat for [`JOB_INSTANCE_TIMEOUT`] are removed
+    fn get_instances(&self) -> BoxFuture<'_, Result<Vec<MatchmakingJobInstance>, Error>>;
+
+    //-------------------------------------------------------------------------------------------------
+    fn remove_instance<'a>(&'a self, instance_id: &'a str) -> BoxFuture<'a, Result<(), Error>>;
+}
@@ -0,0 +1,46 @@
+use crate::matchmaking::Error;
+use futures::future::BoxFuture;
+use std::time::Duration;
+
+/// Exclusive lease on a region, so that only one matchmaking job processes it.
+/// Each acquisition gets a strictly increasing fencing token,
+/// which a former leader cannot reuse once its lease has expired
+pub trait MatchmakingLeaseDAL: Send + Sync {
+    //-------------------------------------------------------------------------------------------------
+    /// Returns the fencing token if the lease was free
+    /// and if the holder holds less than `max_leases` leases
+    fn acquire_lease<'a>(
+        &'a self,
+        region_system_name: &'a str,
+        holder_id: &'a str,
+        max_leases: usize,
+        duration: Duration,
+    ) -> BoxFuture<'a, Result<Option<u64>, Error>>;
+
+    //-------------------------------------------------------------------------------------------------
+    /// Returns `false` if the lease expired or was acquired by another holder
+    fn renew_lease<'a>(
+        &'a self,
+        region_system_name: &'a str,
+        holder_id: &'a str,
+        fencing_token: u64,
+        duration: Duration,
+    ) -> BoxFuture<'a, Result<bool, Error>>;
+
+    //-------------------------------------------------------------------------------------------------
+    /// Returns `true` if the lease is still held with this fencing token
+    fn check_lease<'a>(
+        &'a self,
+        region_system_name: &'a str,
+        holder_id: &'a str,
+        fencing_token: u64,
+    ) -> BoxFuture<'a, Result<bool, Error>>;
+
+    //-------------------------------------------------------------------------------------------------
+    fn release_lease<'a>(
+        &'a self,
+        region_system_name: &'a str,
+        holder_id: &'a str,
+        fencing_token: u64,
+    ) -> BoxFuture<'a, Result<(), Error>>;
+}
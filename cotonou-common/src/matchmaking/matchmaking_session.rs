@@ -1,6 +1,6 @@
 use crate::{
     matchmaking::MatchmakingPlayer,
-    types::{GameServerId, UniqueId},
+    types::{GameServerId, ProfileId, UniqueId},
 };
 use serde::{Deserialize, Serialize};
 use std::fmt;
@@ -57,6 +57,68 @@ pub struct MatchmakingSession {
     /// Encryption key for communication between the game server and the game client
     #[serde(rename = "k")]
     pub encryption_key: String,
+
+    /// Invitation of a private session, which is never matched with other tickets
+    #[serde(rename = "pv", default)]
+    pub private_session: Option<PrivateSession>,
+
+    /// Players requested by the game server to replace players who left
+    #[serde(rename = "bf", default)]
+    pub backfill_request: Option<BackfillRequest>,
+
+    /// Time the session activation was sent to the game server (unix timestamp)
+    #[serde(rename = "at", default)]
+    pub activation_time: u64,
+
+    /// Number of game servers the session activation was sent to
+    #[serde(rename = "aa", default)]
+    pub activation_attempts: u32,
+}
+
+/// Request of a game server to fill its session with new players
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BackfillRequest {
+    /// Number of players to find
+    #[serde(rename = "n")]
+    pub num_players: usize,
+
+    /// Team of the new players, teams are balanced if not set
+    #[serde(rename = "tm", default)]
+    pub team: Option<usize>,
+
+    /// Role the new players must accept to play
+    #[serde(rename = "r", default)]
+    pub role: Option<String>,
+
+    #[serde(rename = "mi", default)]
+    pub min_mmr: Option<u32>,
+
+    #[serde(rename = "ma", default)]
+    pub max_mmr: Option<u32>,
+
+    /// The request is given up after this time (unix timestamp)
+    #[serde(rename = "e")]
+    pub expiration_time: u64,
+
+    /// Players found so far
+    #[serde(rename = "ip", default)]
+    pub incoming_players: Vec<ProfileId>,
+}
+
+/// Private session, joined by players who received its join code
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PrivateSession {
+    /// Code shared by the owner to invite other players
+    #[serde(rename = "c")]
+    pub join_code: JoinCode,
+
+    /// The join code cannot be used after this time (unix timestamp)
+    #[serde(rename = "e")]
+    pub expiration_time: u64,
+
+    /// Profile id of the player who created the session
+    #[serde(rename = "o")]
+    pub owner_profile_id: ProfileId,
 }
 
 #[derive(Debug, Serialize, Deserialize, Copy, Clone, Eq, PartialEq, PartialOrd, Ord, Hash)]
@@ -96,3 +158,63 @@ impl rustis::resp::SingleArg for SessionId {}
 
 #[cfg(feature = "redis")]
 impl rustis::resp::PrimitiveResponse for SessionId {}
+
+/// Alphabet of join codes, without ambiguous characters (0/O, 1/I)
+const JOIN_CODE_ALPHABET: &[u8] = b"23456789ABCDEFGHJKLMNPQRSTUVWXYZ";
+const JOIN_CODE_LENGTH: usize = 8;
+
+/// Short random code to join a private session
+#[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq, Hash)]
+pub struct JoinCode(String);
+
+impl JoinCode {
+    pub fn new() -> Self {
+        let mut random = uuid::Uuid::new_v4().as_u128();
+        let code = (0..JOIN_CODE_LENGTH)
+            .map(|_| {
+                let c = JOIN_CODE_ALPHABET[(random % JOIN_CODE_ALPHABET.len() as u128) as usize];
+                random /= JOIN_CODE_ALPHABET.len() as u128;
+                c as char
+            })
+            .collect();
+        Self(code)
+    }
+
+    /// Parse a code typed by a player, case insensitive
+    pub fn try_parse(input: &str) -> Option<Self> {
+        let code = input.to_ascii_uppercase();
+        if code.len() == JOIN_CODE_LENGTH && code.bytes().all(|c| JOIN_CODE_ALPHABET.contains(&c)) {
+            Some(Self(code))
+        } else {
+            None
+        }
+    }
+}
+
+impl Default for JoinCode {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Display for JoinCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn join_codes_are_parsed_case_insensitively() {
+        let join_code = JoinCode::new();
+        assert_eq!(
+            Some(join_code.clone()),
+            JoinCode::try_parse(&join_code.to_string().to_ascii_lowercase())
+        );
+        assert_eq!(None, JoinCode::try_parse("ABCD0123"));
+        assert_eq!(None, JoinCode::try_parse("ABC"));
+    }
+}
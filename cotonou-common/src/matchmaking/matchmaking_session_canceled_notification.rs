@@ -0,0 +1,11 @@
+use crate::{matchmaking::SessionId, notifications::Notification};
+use serde::{Deserialize, Serialize};
+
+/// Sent to a game server which did not activate a session in time, the session must not be started anymore
+#[derive(Serialize, Deserialize)]
+pub struct MatchmakingSessionCanceledNotification {
+    pub matchmaking_session_id: SessionId,
+}
+
+#[typetag::serde]
+impl Notification for MatchmakingSessionCanceledNotification {}
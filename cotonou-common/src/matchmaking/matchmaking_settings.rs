@@ -1,5 +1,5 @@
 #[cfg(feature = "matchmaking")]
-use crate::matchmaking::GameRegion;
+use crate::matchmaking::{GameRegion, RatingSystemConfig};
 
 #[cfg(feature = "matchmaking")]
 #[derive(Debug, Clone)]
@@ -11,6 +11,50 @@ pub struct GameModeConfig {
     pub min_players: usize,
     pub max_players: usize,
     pub team_player_count: usize,
+    pub rating_system: RatingSystemConfig,
+    /// Ticket attributes which must be compatible, whatever the waiting time
+    pub filters: Vec<TicketFilterConfig>,
+    /// Ticket attributes which should be compatible, penalizing the score of a match otherwise
+    pub preferences: Vec<TicketPreferenceConfig>,
+    /// Maximum waiting time of a ticket in seconds before it is failed, no limit if None
+    pub ticket_timeout: Option<u64>,
+    /// Game mode suggested to the players of a ticket which timed out
+    pub fallback_game_mode: Option<String>,
+    /// Maximum latency of the players of a session, no limit if `None`
+    pub latency_limit: Option<LatencyLimitConfig>,
+}
+
+#[cfg(feature = "matchmaking")]
+#[derive(Debug, Clone)]
+pub struct LatencyLimitConfig {
+    /// maximum latency (ms) of a player who just started matchmaking
+    pub max_latency: u32,
+    /// latency (ms) tolerated in addition for each second of waiting time
+    pub waiting_time_weight: u32,
+    /// maximum latency (ms) whatever the waiting time
+    pub max_relaxed_latency: u32,
+}
+
+#[cfg(feature = "matchmaking")]
+#[derive(Debug, Clone)]
+pub enum TicketFilterConfig {
+    /// All players must use the same client version
+    ClientVersion,
+    /// Players without cross-play only play with players of their platform
+    CrossPlay,
+    /// All players must share a voice language
+    VoiceLanguage,
+    /// All players must share a preferred map
+    MapPreference,
+}
+
+#[cfg(feature = "matchmaking")]
+#[derive(Debug, Clone)]
+pub enum TicketPreferenceConfig {
+    /// Penalty added to the score when players do not share a voice language
+    VoiceLanguage { penalty: f64 },
+    /// Penalty added to the score when players do not share a preferred map
+    MapPreference { penalty: f64 },
 }
 
 #[cfg(feature = "matchmaking")]
@@ -29,12 +73,137 @@ pub enum MatchFunctionsConfig {
         max_mmr_distance: u32,
         waiting_time_weight: u32,
     },
+    /// All criteria must be met, each one relaxing with the waiting time
+    Criteria {
+        criteria: Vec<MatchCriterionConfig>,
+        /// pick the best scored candidate instead of the first matching one
+        select_best_match: bool,
+    },
+}
+
+#[cfg(feature = "matchmaking")]
+#[derive(Debug, Clone)]
+pub enum MatchCriterionConfig {
+    /// Maximum distance between the average MMRs of the groups
+    Mmr { max_distance: RelaxationCurve },
+    /// Maximum latency (ms) of the players
+    Latency { max_latency: RelaxationCurve },
+    /// Maximum difference between the biggest parties of the groups
+    PartySize { max_difference: RelaxationCurve },
+    /// Maximum number of distinct platforms
+    Platform { max_platforms: RelaxationCurve },
+    /// All players must use the same client version
+    GameVersion,
+}
+
+/// Limit of a criterion as a function of the waiting time (seconds)
+#[cfg(feature = "matchmaking")]
+#[derive(Debug, Clone)]
+pub enum RelaxationCurve {
+    /// value of the last reached step, steps being sorted by waiting time
+    Step { steps: Vec<(u64, f64)> },
+    /// `initial + waiting_time * increase_per_second`, up to `max`
+    Linear {
+        initial: f64,
+        increase_per_second: f64,
+        max: f64,
+    },
+    /// `initial * (1 + growth_rate) ^ waiting_time`, up to `max`
+    Exponential {
+        initial: f64,
+        growth_rate: f64,
+        max: f64,
+    },
+}
+
+#[cfg(feature = "matchmaking")]
+impl RelaxationCurve {
+    pub fn get_value(&self, waiting_time: u64) -> f64 {
+        match self {
+            RelaxationCurve::Step { steps } => steps
+                .iter()
+                .take_while(|(step_waiting_time, _)| *step_waiting_time <= waiting_time)
+                .last()
+                .or(steps.first())
+                .map(|(_, value)| *value)
+                .unwrap_or_default(),
+            RelaxationCurve::Linear {
+                initial,
+                increase_per_second,
+                max,
+            } => (initial + waiting_time as f64 * increase_per_second).min(*max),
+            RelaxationCurve::Exponential {
+                initial,
+                growth_rate,
+                max,
+            } => (initial * (1. + growth_rate).powf(waiting_time as f64)).min(*max),
+        }
+    }
+}
+
+/// How sessions are placed on game servers with free slots
+#[derive(Debug, Clone)]
+pub enum ServerPlacementConfig {
+    /// Fill the most loaded servers first, so that idle servers can be scaled down
+    Pack,
+    /// Use the least loaded servers first, for better performance
+    Spread,
 }
 
 #[cfg(feature = "matchmaking")]
 #[derive(Debug, Clone)]
 pub struct MatchmakingSettings {
     pub game_mode_configs: Vec<GameModeConfig>,
+    /// Client versions allowed to create tickets, any version if empty
+    pub supported_client_versions: Vec<String>,
     pub reserved_player_session_timeout: u64,
+    /// Duration (seconds) a game server has to activate a session before it is moved to another server
+    pub session_activation_timeout: u64,
+    /// Number of servers a session is sent to before failing its tickets
+    pub max_session_activation_attempts: u32,
+    /// Duration (seconds) during which the join code of a private session can be used
+    pub private_session_invitation_duration: u64,
+    /// Maximum number of members of a party
+    pub max_party_size: usize,
+    /// Duration (seconds) during which a party invitation can be accepted
+    pub party_invitation_duration: u64,
+    pub server_placement: ServerPlacementConfig,
+    /// Host providers to use first, by order of preference
+    pub preferred_host_providers: Vec<String>,
+    /// Secret shared with game servers to sign player connect tokens
+    pub connect_token_secret: String,
+    /// Duration (seconds) during which a connect token is valid
+    pub connect_token_duration: u64,
     pub supported_regions: Vec<GameRegion>,
 }
+
+#[cfg(all(test, feature = "matchmaking"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn relaxation_curves() {
+        let step = RelaxationCurve::Step {
+            steps: vec![(0, 1.), (30, 2.), (60, 4.)],
+        };
+        assert_eq!(1., step.get_value(29));
+        assert_eq!(2., step.get_value(30));
+        assert_eq!(4., step.get_value(1000));
+
+        let linear = RelaxationCurve::Linear {
+            initial: 50.,
+            increase_per_second: 10.,
+            max: 200.,
+        };
+        assert_eq!(150., linear.get_value(10));
+        assert_eq!(200., linear.get_value(100));
+
+        let exponential = RelaxationCurve::Exponential {
+            initial: 50.,
+            growth_rate: 1.,
+            max: 300.,
+        };
+        assert_eq!(200., exponential.get_value(2));
+        assert_eq!(300., exponential.get_value(3));
+    }
+}
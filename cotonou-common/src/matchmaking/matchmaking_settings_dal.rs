@@ -1,7 +1,15 @@
 use crate::matchmaking::{
-    GameModeConfig, GameRegion, MatchFunctionsConfig, MatchmakerConfig, MatchmakingSettings,
+    Error, GameModeConfig, GameRegion, LatencyLimitConfig, MatchCriterionConfig,
+    MatchFunctionsConfig, MatchmakerConfig, MatchmakingSettings, RatingSystemConfig,
+    RelaxationCurve, ServerPlacementConfig, TicketFilterConfig, TicketPreferenceConfig,
 };
 use std::sync::Arc;
+use uuid::Uuid;
+
+/// Environment variable holding the secret shared with game servers to sign connect tokens
+const CONNECT_TOKEN_SECRET_VAR: &str = "COTONOU_CONNECT_TOKEN_SECRET";
+/// 256 bits, the size of the HS256 hash
+const MIN_CONNECT_TOKEN_SECRET_LENGTH: usize = 32;
 
 #[derive(Clone)]
 pub struct MatchmakingSettingsDAL {
@@ -9,6 +17,8 @@ pub struct MatchmakingSettingsDAL {
 }
 
 impl MatchmakingSettingsDAL {
+    /// Default settings, with a connect token secret generated for this process only.
+    /// Use [`Self::from_env`] for connect tokens verified by game servers
     pub fn new() -> Self {
         Self {
             matchmaking_settings: Arc::new(MatchmakingSettings {
@@ -21,18 +31,57 @@ impl MatchmakingSettingsDAL {
                         min_players: 2,
                         max_players: 8,
                         team_player_count: 4,
+                        rating_system: RatingSystemConfig::Elo { k_factor: 32. },
+                        filters: vec![TicketFilterConfig::ClientVersion],
+                        preferences: vec![TicketPreferenceConfig::VoiceLanguage { penalty: 0.5 }],
+                        ticket_timeout: Some(300),
+                        fallback_game_mode: None,
+                        latency_limit: None,
                     },
                     GameModeConfig {
                         name: "Ranked".to_owned(),
                         short_name: "r".to_owned(),
                         matchmaker_type: MatchmakerConfig::CutLists { mmr_range: 100 },
-                        match_functions_type: MatchFunctionsConfig::Mmr {
-                            max_mmr_distance: 300,
-                            waiting_time_weight: 10,
+                        match_functions_type: MatchFunctionsConfig::Criteria {
+                            criteria: vec![
+                                MatchCriterionConfig::Mmr {
+                                    max_distance: RelaxationCurve::Linear {
+                                        initial: 0.,
+                                        increase_per_second: 10.,
+                                        max: 300.,
+                                    },
+                                },
+                                MatchCriterionConfig::Latency {
+                                    max_latency: RelaxationCurve::Linear {
+                                        initial: 80.,
+                                        increase_per_second: 5.,
+                                        max: 200.,
+                                    },
+                                },
+                                MatchCriterionConfig::PartySize {
+                                    max_difference: RelaxationCurve::Step {
+                                        steps: vec![(0, 0.), (30, 1.), (60, 3.)],
+                                    },
+                                },
+                                MatchCriterionConfig::GameVersion,
+                            ],
+                            select_best_match: true,
                         },
                         min_players: 2,
                         max_players: 8,
                         team_player_count: 4,
+                        rating_system: RatingSystemConfig::Glicko2 { tau: 0.5 },
+                        filters: vec![
+                            TicketFilterConfig::ClientVersion,
+                            TicketFilterConfig::CrossPlay,
+                        ],
+                        preferences: vec![
+                            TicketPreferenceConfig::VoiceLanguage { penalty: 0.5 },
+                            TicketPreferenceConfig::MapPreference { penalty: 0.2 },
+                        ],
+                        ticket_timeout: Some(600),
+                        fallback_game_mode: Some("QuickMatch".to_owned()),
+                        latency_limit: None,
                     },
                     GameModeConfig {
                         name: "MTRanked".to_owned(),
@@ -45,18 +94,70 @@ impl MatchmakingSettingsDAL {
                         min_players: 2,
                         max_players: 8,
                         team_player_count: 4,
+                        rating_system: RatingSystemConfig::Glicko2 { tau: 0.5 },
+                        filters: vec![
+                            TicketFilterConfig::ClientVersion,
+                            TicketFilterConfig::CrossPlay,
+                        ],
+                        preferences: vec![
+                            TicketPreferenceConfig::VoiceLanguage { penalty: 0.5 },
+                            TicketPreferenceConfig::MapPreference { penalty: 0.2 },
+                        ],
+                        ticket_timeout: Some(600),
+                        fallback_game_mode: Some("QuickMatch".to_owned()),
+                        latency_limit: Some(LatencyLimitConfig {
+                            max_latency: 80,
+                            waiting_time_weight: 5,
+                            max_relaxed_latency: 200,
+                        }),
                     },
                 ],
+                supported_client_versions: Vec::new(),
                 supported_regions: vec![GameRegion {
                     region_system_name: "eu-central-1".to_owned(),
                     region_prefix: "eu".to_owned(),
                     region_endpoint: "http://ec2.eu-central-1.amazonaws.com/".to_owned(),
                 }],
                 reserved_player_session_timeout: 30,
+                session_activation_timeout: 30,
+                max_session_activation_attempts: 3,
+                private_session_invitation_duration: 3600,
+                max_party_size: 4,
+                party_invitation_duration: 60,
+                server_placement: ServerPlacementConfig::Pack,
+                preferred_host_providers: Vec::new(),
+                connect_token_secret: format!(
+                    "{}{}",
+                    Uuid::new_v4().simple(),
+                    Uuid::new_v4().simple()
+                ),
+                connect_token_duration: 300,
             }),
         }
     }
 
+    /// Default settings with the connect token secret of the environment
+    pub fn from_env() -> Result<Self, Error> {
+        let connect_token_secret = std::env::var(CONNECT_TOKEN_SECRET_VAR)
+            .map_err(|_| Error::Configuration(format!("{CONNECT_TOKEN_SECRET_VAR} is not set")))?;
+        Self::new().with_connect_token_secret(connect_token_secret)
+    }
+
+    /// Replace the connect token secret, rejected if too short to be safe
+    pub fn with_connect_token_secret(
+        mut self,
+        connect_token_secret: String,
+    ) -> Result<Self, Error> {
+        if connect_token_secret.len() < MIN_CONNECT_TOKEN_SECRET_LENGTH {
+            return Err(Error::Configuration(format!(
+                "connect token secret must be at least {MIN_CONNECT_TOKEN_SECRET_LENGTH} bytes long"
+            )));
+        }
+
+        Arc::make_mut(&mut self.matchmaking_settings).connect_token_secret = connect_token_secret;
+        Ok(self)
+    }
+
     pub fn get_matchmaking_settings(&self) -> &MatchmakingSettings {
         &self.matchmaking_settings
     }
@@ -71,6 +172,14 @@ impl MatchmakingSettingsDAL {
             .iter()
             .any(|r| r.region_system_name == region_system_name)
     }
+
+    pub fn is_client_version_supported(&self, client_version: &str) -> bool {
+        let supported_client_versions = &self.get_matchmaking_settings().supported_client_versions;
+        supported_client_versions.is_empty()
+            || supported_client_versions
+                .iter()
+                .any(|v| v == client_version)
+    }
 }
 
 impl Default for MatchmakingSettingsDAL {
@@ -78,3 +187,26 @@ impl Default for MatchmakingSettingsDAL {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn short_connect_token_secret_is_rejected() {
+        assert!(MatchmakingSettingsDAL::new()
+            .with_connect_token_secret("secret".to_owned())
+            .is_err());
+
+        let connect_token_secret = "0123456789abcdef0123456789abcdef".to_owned();
+        let matchmaking_settings_dal = MatchmakingSettingsDAL::new()
+            .with_connect_token_secret(connect_token_secret.clone())
+            .unwrap();
+        assert_eq!(
+            connect_token_secret,
+            matchmaking_settings_dal
+                .get_matchmaking_settings()
+                .connect_token_secret
+        );
+    }
+}
@@ -2,9 +2,10 @@ use super::matchmaking_session::SessionId;
 use crate::types::ProfileId;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
 pub enum MatchmakingPlayerStatus {
     /// Player to match
+    #[default]
     Created,
     /// Player matched to a session
     Matched,
@@ -14,7 +15,7 @@ pub enum MatchmakingPlayerStatus {
     Active,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct MatchmakingPlayer {
     #[serde(rename = "i")]
     pub profile_id: ProfileId,
@@ -34,6 +35,37 @@ pub struct MatchmakingPlayer {
     pub time_until_open_session: u64,
     #[serde(rename = "tcs")]
     pub time_until_close_session: u64,
+    /// Owner of the ticket the player joined with, players of a party play in the same team
+    #[serde(rename = "pa", default)]
+    pub party_id: Option<ProfileId>,
+    /// Team index in the session, once matched
+    #[serde(rename = "tm", default)]
+    pub team: Option<usize>,
+    /// Platform reported by the client
+    #[serde(rename = "pf", default)]
+    pub platform: String,
+    /// Roles the player accepts to play (e.g. tank, healer), used by backfill requests
+    #[serde(rename = "rl", default)]
+    pub roles: Vec<String>,
+    /// Attributes of the ticket the player joined with
+    #[serde(rename = "at", default)]
+    pub attributes: TicketAttributes,
+}
+
+/// Attributes shared by the players of a ticket, filtered by game modes
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct TicketAttributes {
+    #[serde(rename = "cv", default)]
+    pub client_version: String,
+    /// Accept players of other platforms
+    #[serde(rename = "xp", default)]
+    pub cross_play: bool,
+    /// Voice chat languages (ISO 639-1), any language if empty
+    #[serde(rename = "vl", default)]
+    pub voice_languages: Vec<String>,
+    /// Preferred maps, any map if empty
+    #[serde(rename = "mp", default)]
+    pub map_preferences: Vec<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -50,4 +82,9 @@ pub struct MatchmakingTicket {
     pub session_id: Option<SessionId>,
     #[serde(rename = "f")]
     pub servers_full_notification_last_time_sent: u64,
+    #[serde(rename = "a", default)]
+    pub attributes: TicketAttributes,
+    /// Waiting time estimated on creation, compared to the actual waiting time once matched
+    #[serde(rename = "e", default)]
+    pub estimated_wait_time: u64,
 }
@@ -0,0 +1,29 @@
+use crate::matchmaking::Error;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+/// Highest fencing token that wrote to each region,
+/// so that the in-memory DALs refuse the writes of a stale leader like the Redis ones
+#[derive(Clone, Default)]
+pub(crate) struct MemoryFencingTokens {
+    fencing_tokens: Arc<Mutex<HashMap<String, u64>>>,
+}
+
+impl MemoryFencingTokens {
+    //-------------------------------------------------------------------------------------------------
+    /// Returns [`Error::StaleFencingToken`] if a higher fencing token already wrote to the region
+    pub fn check(&self, region_system_name: &str, fencing_token: u64) -> Result<(), Error> {
+        let mut fencing_tokens = self.fencing_tokens.lock().unwrap();
+        let latest_fencing_token = fencing_tokens
+            .entry(region_system_name.to_owned())
+            .or_default();
+        if *latest_fencing_token > fencing_token {
+            return Err(Error::StaleFencingToken(fencing_token));
+        }
+
+        *latest_fencing_token = fencing_token;
+        Ok(())
+    }
+}
@@ -0,0 +1,141 @@
+use crate::{
+    matchmaking::{memory_fencing_tokens::MemoryFencingTokens, Error, GameServer, GameServerDAL},
+    types::GameServerId,
+};
+use futures::future::{self, BoxFuture};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+/// In-memory [`GameServerDAL`], meant for tests
+#[derive(Clone, Default)]
+pub struct MemoryGameServerDAL {
+    /// game server jsons indexed by region and game server id
+    game_servers: Arc<Mutex<HashMap<(String, GameServerId), String>>>,
+    fencing_tokens: MemoryFencingTokens,
+}
+
+impl MemoryGameServerDAL {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn set_game_servers(
+        &self,
+        region_system_name: &str,
+        fencing_token: u64,
+        game_servers: &[&GameServer],
+    ) -> Result<(), Error> {
+        self.fencing_tokens
+            .check(region_system_name, fencing_token)?;
+
+        let mut values = self.game_servers.lock().unwrap();
+        for game_server in game_servers {
+            values.insert(
+                (region_system_name.to_owned(), game_server.game_server_id),
+                serde_json::to_string(game_server)?,
+            );
+        }
+        Ok(())
+    }
+}
+
+impl GameServerDAL for MemoryGameServerDAL {
+    //-------------------------------------------------------------------------------------------------
+    fn get_game_servers<'a>(
+        &'a self,
+        region_system_name: &'a str,
+    ) -> BoxFuture<'a, Result<Vec<GameServer>, Error>> {
+        let result = self
+            .game_servers
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|((region, _), _)| region == region_system_name)
+            .map(|(_, json)| serde_json::from_str::<GameServer>(json))
+            .collect::<serde_json::Result<Vec<_>>>()
+            .map_err(Error::from);
+        Box::pin(future::ready(result))
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn get_game_server<'a>(
+        &'a self,
+        region_system_name: &'a str,
+        game_server_id: &'a GameServerId,
+    ) -> BoxFuture<'a, Result<Option<GameServer>, Error>> {
+        let result = self
+            .game_servers
+            .lock()
+            .unwrap()
+            .get(&(region_system_name.to_owned(), *game_server_id))
+            .map(|json| serde_json::from_str(json))
+            .transpose()
+            .map_err(Error::from);
+        Box::pin(future::ready(result))
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn create_game_servers<'a>(
+        &'a self,
+        region_system_name: &'a str,
+        fencing_token: u64,
+        game_servers: &'a [&'a GameServer],
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        Box::pin(future::ready(self.set_game_servers(
+            region_system_name,
+            fencing_token,
+            game_servers,
+        )))
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn update_game_servers<'a>(
+        &'a self,
+        region_system_name: &'a str,
+        fencing_token: u64,
+        game_servers: &'a [&'a GameServer],
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        Box::pin(future::ready(self.set_game_servers(
+            region_system_name,
+            fencing_token,
+            game_servers,
+        )))
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn delete_game_servers<'a>(
+        &'a self,
+        region_system_name: &'a str,
+        fencing_token: u64,
+        game_server_ids: &'a [GameServerId],
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        if let Err(e) = self.fencing_tokens.check(region_system_name, fencing_token) {
+            return Box::pin(future::ready(Err(e)));
+        }
+
+        let mut game_servers = self.game_servers.lock().unwrap();
+        for game_server_id in game_server_ids {
+            game_servers.remove(&(region_system_name.to_owned(), *game_server_id));
+        }
+        Box::pin(future::ready(Ok(())))
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn reset<'a>(
+        &'a self,
+        region_system_name: &'a str,
+        fencing_token: u64,
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        if let Err(e) = self.fencing_tokens.check(region_system_name, fencing_token) {
+            return Box::pin(future::ready(Err(e)));
+        }
+
+        self.game_servers
+            .lock()
+            .unwrap()
+            .retain(|(region, _), _| region != region_system_name);
+        Box::pin(future::ready(Ok(())))
+    }
+}
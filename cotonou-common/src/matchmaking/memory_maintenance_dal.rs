@@ -0,0 +1,60 @@
+use crate::matchmaking::{Error, Maintenance, MaintenanceDAL};
+use futures::future::{self, BoxFuture};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+/// In-memory [`MaintenanceDAL`], meant for tests
+#[derive(Clone, Default)]
+pub struct MemoryMaintenanceDAL {
+    /// maintenances indexed by region, None for all regions
+    maintenances: Arc<Mutex<HashMap<Option<String>, Maintenance>>>,
+}
+
+impl MemoryMaintenanceDAL {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl MaintenanceDAL for MemoryMaintenanceDAL {
+    //-------------------------------------------------------------------------------------------------
+    fn get_maintenance<'a>(
+        &'a self,
+        region_system_name: Option<&'a str>,
+    ) -> BoxFuture<'a, Result<Option<Maintenance>, Error>> {
+        let maintenance = self
+            .maintenances
+            .lock()
+            .unwrap()
+            .get(&region_system_name.map(str::to_owned))
+            .cloned();
+        Box::pin(future::ready(Ok(maintenance)))
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn set_maintenance<'a>(
+        &'a self,
+        region_system_name: Option<&'a str>,
+        maintenance: &'a Maintenance,
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        self.maintenances
+            .lock()
+            .unwrap()
+            .insert(region_system_name.map(str::to_owned), maintenance.clone());
+        Box::pin(future::ready(Ok(())))
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn delete_maintenance<'a>(
+        &'a self,
+        region_system_name: Option<&'a str>,
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        self.maintenances
+            .lock()
+            .unwrap()
+            .remove(&region_system_name.map(str::to_owned));
+        Box::pin(future::ready(Ok(())))
+    }
+}
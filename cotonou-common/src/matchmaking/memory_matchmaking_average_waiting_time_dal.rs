@@ -0,0 +1,64 @@
+use crate::matchmaking::{Error, MatchmakingWaitingTimeDAL, WaitingTimeStatistics};
+use futures::future::{self, BoxFuture};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+/// In-memory [`MatchmakingWaitingTimeDAL`], meant for tests
+#[derive(Clone, Default)]
+pub struct MemoryMatchmakingWaitingTimeDAL {
+    /// statistics indexed by region and game mode
+    waiting_time_statistics: Arc<Mutex<HashMap<(String, String), WaitingTimeStatistics>>>,
+}
+
+impl MemoryMatchmakingWaitingTimeDAL {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl MatchmakingWaitingTimeDAL for MemoryMatchmakingWaitingTimeDAL {
+    //-------------------------------------------------------------------------------------------------
+    fn set_waiting_time_statistics<'a>(
+        &'a self,
+        region_system_name: &'a str,
+        game_mode: &'a str,
+        waiting_time_statistics: &'a WaitingTimeStatistics,
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        self.waiting_time_statistics.lock().unwrap().insert(
+            (region_system_name.to_owned(), game_mode.to_owned()),
+            waiting_time_statistics.clone(),
+        );
+        Box::pin(future::ready(Ok(())))
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn get_waiting_time_statistics<'a>(
+        &'a self,
+        region_system_name: &'a str,
+        game_mode: &'a str,
+    ) -> BoxFuture<'a, Result<WaitingTimeStatistics, Error>> {
+        let waiting_time_statistics = self
+            .waiting_time_statistics
+            .lock()
+            .unwrap()
+            .get(&(region_system_name.to_owned(), game_mode.to_owned()))
+            .cloned()
+            .unwrap_or_default();
+        Box::pin(future::ready(Ok(waiting_time_statistics)))
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn reset<'a>(
+        &'a self,
+        region_system_name: &'a str,
+        game_mode: &'a str,
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        self.waiting_time_statistics
+            .lock()
+            .unwrap()
+            .remove(&(region_system_name.to_owned(), game_mode.to_owned()));
+        Box::pin(future::ready(Ok(())))
+    }
+}
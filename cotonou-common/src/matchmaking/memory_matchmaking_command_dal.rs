@@ -0,0 +1,285 @@
+use crate::matchmaking::{
+    memory_fencing_tokens::MemoryFencingTokens, CommandId, DeadLetterCommand, Error,
+    MatchmakingCommand, MatchmakingCommandDAL, QueuedMatchmakingCommand, MAX_COMMAND_DELIVERIES,
+};
+use futures::future::{self, BoxFuture};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet, VecDeque},
+    sync::{Arc, Mutex},
+};
+
+#[derive(Default)]
+struct CommandQueue {
+    next_id: u64,
+    /// command jsons not delivered yet
+    queue: VecDeque<(u64, String)>,
+    /// command jsons delivered but not acknowledged, with their number of deliveries
+    pending: BTreeMap<u64, (String, usize)>,
+    dead_letters: Vec<DeadLetterCommand>,
+    /// kept forever, unlike the Redis ones
+    processed: HashSet<CommandId>,
+}
+
+impl CommandQueue {
+    fn push(&mut self, json: String) -> CommandId {
+        self.next_id += 1;
+        self.queue.push_back((self.next_id, json));
+        build_command_id(self.next_id)
+    }
+
+    fn decode(&mut self, id: u64, json: String) -> Option<QueuedMatchmakingCommand> {
+        match serde_json::from_str::<MatchmakingCommand>(&json) {
+            Ok(command) => Some(QueuedMatchmakingCommand {
+                command_id: build_command_id(id),
+                command,
+            }),
+            Err(e) => {
+                self.dead_letter(id, json, e.to_string());
+                None
+            }
+        }
+    }
+
+    fn dead_letter(&mut self, id: u64, json: String, reason: String) {
+        self.pending.remove(&id);
+        self.dead_letters.push(DeadLetterCommand {
+            command_id: build_command_id(id),
+            command: json,
+            reason,
+        });
+    }
+}
+
+/// In-memory [`MatchmakingCommandDAL`], meant for tests
+#[derive(Clone, Default)]
+pub struct MemoryMatchmakingCommandDAL {
+    /// command queues indexed by region
+    queues: Arc<Mutex<HashMap<String, CommandQueue>>>,
+    fencing_tokens: MemoryFencingTokens,
+}
+
+impl MemoryMatchmakingCommandDAL {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue a raw json, which may not be a valid command
+    pub fn queue_raw_command(&self, region_system_name: &str, json: &str) -> CommandId {
+        self.queues
+            .lock()
+            .unwrap()
+            .entry(region_system_name.to_owned())
+            .or_default()
+            .push(json.to_owned())
+    }
+}
+
+impl MatchmakingCommandDAL for MemoryMatchmakingCommandDAL {
+    //-------------------------------------------------------------------------------------------------
+    fn queue_command<'a>(
+        &'a self,
+        region_system_name: &'a str,
+        command: &'a MatchmakingCommand,
+    ) -> BoxFuture<'a, Result<CommandId, Error>> {
+        let result = serde_json::to_string(command)
+            .map(|json| self.queue_raw_command(region_system_name, &json))
+            .map_err(Error::from);
+        Box::pin(future::ready(result))
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn dequeue_commands<'a>(
+        &'a self,
+        region_system_name: &'a str,
+    ) -> BoxFuture<'a, Result<Vec<QueuedMatchmakingCommand>, Error>> {
+        let mut queues = self.queues.lock().unwrap();
+        let queue = queues.entry(region_system_name.to_owned()).or_default();
+
+        let num_commands = queue.queue.len().min(1000);
+        let entries = queue.queue.drain(..num_commands).collect::<Vec<_>>();
+        let mut commands = Vec::with_capacity(entries.len());
+        for (id, json) in entries {
+            queue.pending.insert(id, (json.clone(), 1));
+            commands.extend(queue.decode(id, json));
+        }
+
+        Box::pin(future::ready(Ok(commands)))
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn recover_commands<'a>(
+        &'a self,
+        region_system_name: &'a str,
+    ) -> BoxFuture<'a, Result<Vec<QueuedMatchmakingCommand>, Error>> {
+        let mut queues = self.queues.lock().unwrap();
+        let queue = queues.entry(region_system_name.to_owned()).or_default();
+
+        let entries = queue
+            .pending
+            .iter_mut()
+            .map(|(id, (json, num_deliveries))| {
+                let previous_num_deliveries = *num_deliveries;
+                *num_deliveries += 1;
+                (*id, json.clone(), previous_num_deliveries)
+            })
+            .collect::<Vec<_>>();
+
+        let mut commands = Vec::with_capacity(entries.len());
+        for (id, json, num_deliveries) in entries {
+            if num_deliveries >= MAX_COMMAND_DELIVERIES {
+                queue.dead_letter(
+                    id,
+                    json,
+                    format!("delivered {num_deliveries} times without acknowledgement"),
+                );
+            } else {
+                commands.extend(queue.decode(id, json));
+            }
+        }
+
+        Box::pin(future::ready(Ok(commands)))
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn get_processed_commands<'a>(
+        &'a self,
+        region_system_name: &'a str,
+        command_ids: &'a [CommandId],
+    ) -> BoxFuture<'a, Result<HashSet<CommandId>, Error>> {
+        let processed_commands = self
+            .queues
+            .lock()
+            .unwrap()
+            .get(region_system_name)
+            .map(|queue| {
+                command_ids
+                    .iter()
+                    .filter(|id| queue.processed.contains(*id))
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default();
+        Box::pin(future::ready(Ok(processed_commands)))
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn save_processed_commands<'a>(
+        &'a self,
+        region_system_name: &'a str,
+        fencing_token: u64,
+        command_ids: &'a [CommandId],
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        if let Err(e) = self.fencing_tokens.check(region_system_name, fencing_token) {
+            return Box::pin(future::ready(Err(e)));
+        }
+
+        self.queues
+            .lock()
+            .unwrap()
+            .entry(region_system_name.to_owned())
+            .or_default()
+            .processed
+            .extend(command_ids.iter().cloned());
+        Box::pin(future::ready(Ok(())))
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn ack_commands<'a>(
+        &'a self,
+        region_system_name: &'a str,
+        fencing_token: u64,
+        command_ids: &'a [CommandId],
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        if let Err(e) = self.fencing_tokens.check(region_system_name, fencing_token) {
+            return Box::pin(future::ready(Err(e)));
+        }
+
+        if let Some(queue) = self.queues.lock().unwrap().get_mut(region_system_name) {
+            for command_id in command_ids {
+                if let Some(id) = parse_command_id(command_id) {
+                    queue.pending.remove(&id);
+                }
+            }
+        }
+        Box::pin(future::ready(Ok(())))
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn get_queue_length<'a>(
+        &'a self,
+        region_system_name: &'a str,
+    ) -> BoxFuture<'a, Result<usize, Error>> {
+        let queue_length = self
+            .queues
+            .lock()
+            .unwrap()
+            .get(region_system_name)
+            .map(|queue| queue.queue.len() + queue.pending.len())
+            .unwrap_or_default();
+        Box::pin(future::ready(Ok(queue_length)))
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn get_dead_letter_commands<'a>(
+        &'a self,
+        region_system_name: &'a str,
+    ) -> BoxFuture<'a, Result<Vec<DeadLetterCommand>, Error>> {
+        let dead_letters = self
+            .queues
+            .lock()
+            .unwrap()
+            .get(region_system_name)
+            .map(|queue| queue.dead_letters.clone())
+            .unwrap_or_default();
+        Box::pin(future::ready(Ok(dead_letters)))
+    }
+}
+
+/// same format as Redis stream entry ids
+fn build_command_id(id: u64) -> CommandId {
+    CommandId::new(format!("{id}-0"))
+}
+
+fn parse_command_id(command_id: &CommandId) -> Option<u64> {
+    command_id
+        .as_str()
+        .strip_suffix("-0")
+        .and_then(|id| id.parse().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REGION: &str = "eu-central-1";
+
+    #[tokio::test]
+    async fn unacknowledged_commands_are_redelivered_then_dead_lettered() -> Result<(), Error> {
+        let dal = MemoryMatchmakingCommandDAL::new();
+        let acked_id = dal
+            .queue_command(REGION, &MatchmakingCommand::ResetMatchmaking)
+            .await?;
+        let unacked_id = dal
+            .queue_command(REGION, &MatchmakingCommand::ResetMatchmaking)
+            .await?;
+        let undecodable_id = dal.queue_raw_command(REGION, "not a command");
+
+        let commands = dal.dequeue_commands(REGION).await?;
+        assert_eq!(2, commands.len());
+        dal.ack_commands(REGION, 1, &[acked_id]).await?;
+
+        for _ in 1..MAX_COMMAND_DELIVERIES {
+            let commands = dal.recover_commands(REGION).await?;
+            assert_eq!(1, commands.len());
+            assert_eq!(unacked_id, commands[0].command_id);
+        }
+        assert!(dal.recover_commands(REGION).await?.is_empty());
+
+        let dead_letters = dal.get_dead_letter_commands(REGION).await?;
+        assert_eq!(2, dead_letters.len());
+        assert_eq!(undecodable_id, dead_letters[0].command_id);
+        assert_eq!(unacked_id, dead_letters[1].command_id);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,50 @@
+use crate::{
+    matchmaking::{Error, MatchmakingJobInstance, MatchmakingJobInstanceDAL, JOB_INSTANCE_TIMEOUT},
+    unix_now,
+};
+use futures::future::{self, BoxFuture};
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+};
+
+/// In-memory [`MatchmakingJobInstanceDAL`], meant for tests
+#[derive(Clone, Default)]
+pub struct MemoryMatchmakingJobInstanceDAL {
+    /// instances indexed by instance id
+    instances: Arc<Mutex<BTreeMap<String, MatchmakingJobInstance>>>,
+}
+
+impl MemoryMatchmakingJobInstanceDAL {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl MatchmakingJobInstanceDAL for MemoryMatchmakingJobInstanceDAL {
+    //-------------------------------------------------------------------------------------------------
+    fn report_instance<'a>(
+        &'a self,
+        instance: &'a MatchmakingJobInstance,
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        self.instances
+            .lock()
+            .unwrap()
+            .insert(instance.instance_id.clone(), instance.clone());
+        Box::pin(future::ready(Ok(())))
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn get_instances(&self) -> BoxFuture<'_, Result<Vec<MatchmakingJobInstance>, Error>> {
+        let now = unix_now();
+        let mut instances = self.instances.lock().unwrap();
+        instances.retain(|_, i| i.last_heartbeat_time + JOB_INSTANCE_TIMEOUT >= now);
+        Box::pin(future::ready(Ok(instances.values().cloned().collect())))
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn remove_instance<'a>(&'a self, instance_id: &'a str) -> BoxFuture<'a, Result<(), Error>> {
+        self.instances.lock().unwrap().remove(instance_id);
+        Box::pin(future::ready(Ok(())))
+    }
+}
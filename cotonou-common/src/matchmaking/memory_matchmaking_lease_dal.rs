@@ -0,0 +1,176 @@
+use crate::matchmaking::{Error, MatchmakingLeaseDAL};
+use futures::future::{self, BoxFuture};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+struct Lease {
+    holder_id: String,
+    fencing_token: u64,
+    expiration: Instant,
+}
+
+/// In-memory [`MatchmakingLeaseDAL`], meant for tests
+#[derive(Clone, Default)]
+pub struct MemoryMatchmakingLeaseDAL {
+    /// leases indexed by region
+    leases: Arc<Mutex<HashMap<String, Lease>>>,
+    /// last fencing tokens indexed by region
+    fencing_tokens: Arc<Mutex<HashMap<String, u64>>>,
+}
+
+impl MemoryMatchmakingLeaseDAL {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn is_held(&self, region_system_name: &str, holder_id: &str, fencing_token: u64) -> bool {
+        self.leases
+            .lock()
+            .unwrap()
+            .get(region_system_name)
+            .is_some_and(|l| {
+                l.holder_id == holder_id
+                    && l.fencing_token == fencing_token
+                    && l.expiration > Instant::now()
+            })
+    }
+}
+
+impl MatchmakingLeaseDAL for MemoryMatchmakingLeaseDAL {
+    //-------------------------------------------------------------------------------------------------
+    fn acquire_lease<'a>(
+        &'a self,
+        region_system_name: &'a str,
+        holder_id: &'a str,
+        max_leases: usize,
+        duration: Duration,
+    ) -> BoxFuture<'a, Result<Option<u64>, Error>> {
+        let mut leases = self.leases.lock().unwrap();
+        let now = Instant::now();
+        if leases
+            .get(region_system_name)
+            .is_some_and(|l| l.expiration > now)
+        {
+            return Box::pin(future::ready(Ok(None)));
+        }
+
+        let num_leases = leases
+            .values()
+            .filter(|l| l.holder_id == holder_id && l.expiration > now)
+            .count();
+        if num_leases >= max_leases {
+            return Box::pin(future::ready(Ok(None)));
+        }
+
+        let mut fencing_tokens = self.fencing_tokens.lock().unwrap();
+        let fencing_token = fencing_tokens
+            .entry(region_system_name.to_owned())
+            .or_default();
+        *fencing_token += 1;
+
+        leases.insert(
+            region_system_name.to_owned(),
+            Lease {
+                holder_id: holder_id.to_owned(),
+                fencing_token: *fencing_token,
+                expiration: now + duration,
+            },
+        );
+
+        Box::pin(future::ready(Ok(Some(*fencing_token))))
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn renew_lease<'a>(
+        &'a self,
+        region_system_name: &'a str,
+        holder_id: &'a str,
+        fencing_token: u64,
+        duration: Duration,
+    ) -> BoxFuture<'a, Result<bool, Error>> {
+        let renewed = self.is_held(region_system_name, holder_id, fencing_token);
+        if renewed {
+            if let Some(lease) = self.leases.lock().unwrap().get_mut(region_system_name) {
+                lease.expiration = Instant::now() + duration;
+            }
+        }
+        Box::pin(future::ready(Ok(renewed)))
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn check_lease<'a>(
+        &'a self,
+        region_system_name: &'a str,
+        holder_id: &'a str,
+        fencing_token: u64,
+    ) -> BoxFuture<'a, Result<bool, Error>> {
+        Box::pin(future::ready(Ok(self.is_held(
+            region_system_name,
+            holder_id,
+            fencing_token,
+        ))))
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn release_lease<'a>(
+        &'a self,
+        region_system_name: &'a str,
+        holder_id: &'a str,
+        fencing_token: u64,
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        if self.is_held(region_system_name, holder_id, fencing_token) {
+            self.leases.lock().unwrap().remove(region_system_name);
+        }
+        Box::pin(future::ready(Ok(())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REGION: &str = "eu-central-1";
+
+    #[tokio::test]
+    async fn expired_lease_is_taken_over_with_a_greater_fencing_token() -> Result<(), Error> {
+        let dal = MemoryMatchmakingLeaseDAL::new();
+
+        let leader_token = dal
+            .acquire_lease(REGION, "leader", 1, Duration::ZERO)
+            .await?
+            .unwrap();
+        let standby_token = dal
+            .acquire_lease(REGION, "standby", 1, Duration::from_secs(10))
+            .await?
+            .unwrap();
+        assert!(standby_token > leader_token);
+
+        assert!(!dal.check_lease(REGION, "leader", leader_token).await?);
+        assert!(dal.check_lease(REGION, "standby", standby_token).await?);
+        assert!(
+            !dal.renew_lease(REGION, "leader", leader_token, Duration::from_secs(10))
+                .await?
+        );
+        assert!(dal
+            .acquire_lease(REGION, "leader", 1, Duration::from_secs(10))
+            .await?
+            .is_none());
+
+        dal.release_lease(REGION, "standby", standby_token).await?;
+        assert!(dal
+            .acquire_lease(REGION, "leader", 1, Duration::from_secs(10))
+            .await?
+            .is_some());
+
+        // a holder does not get more leases than allowed
+        assert!(dal
+            .acquire_lease("us-east-1", "leader", 1, Duration::from_secs(10))
+            .await?
+            .is_none());
+
+        Ok(())
+    }
+}
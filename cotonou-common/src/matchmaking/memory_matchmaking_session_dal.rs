@@ -0,0 +1,172 @@
+use crate::{
+    matchmaking::{
+        memory_fencing_tokens::MemoryFencingTokens, Error, MatchmakingSession,
+        MatchmakingSessionDAL, SessionId,
+    },
+    types::ProfileId,
+};
+use futures::future::{self, BoxFuture};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+/// In-memory [`MatchmakingSessionDAL`], meant for tests
+#[derive(Clone, Default)]
+pub struct MemoryMatchmakingSessionDAL {
+    /// session jsons indexed by region and session id
+    sessions: Arc<Mutex<HashMap<(String, SessionId), String>>>,
+    fencing_tokens: MemoryFencingTokens,
+}
+
+impl MemoryMatchmakingSessionDAL {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn set_sessions(
+        &self,
+        region_system_name: &str,
+        fencing_token: u64,
+        sessions: &[&MatchmakingSession],
+    ) -> Result<(), Error> {
+        self.fencing_tokens
+            .check(region_system_name, fencing_token)?;
+
+        let mut values = self.sessions.lock().unwrap();
+        for session in sessions {
+            values.insert(
+                (region_system_name.to_owned(), session.session_id),
+                serde_json::to_string(session)?,
+            );
+        }
+        Ok(())
+    }
+}
+
+impl MatchmakingSessionDAL for MemoryMatchmakingSessionDAL {
+    //-------------------------------------------------------------------------------------------------
+    fn get_sessions<'a>(
+        &'a self,
+        region_system_name: &'a str,
+    ) -> BoxFuture<'a, Result<Vec<MatchmakingSession>, Error>> {
+        let result = self
+            .sessions
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|((region, _), _)| region == region_system_name)
+            .map(|(_, json)| serde_json::from_str::<MatchmakingSession>(json))
+            .collect::<serde_json::Result<Vec<_>>>()
+            .map(|mut sessions| {
+                sessions.sort_by_key(|s| s.creation_time);
+                sessions
+            })
+            .map_err(Error::from);
+        Box::pin(future::ready(result))
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn get_session<'a>(
+        &'a self,
+        region_system_name: &'a str,
+        session_id: &'a SessionId,
+    ) -> BoxFuture<'a, Result<Option<MatchmakingSession>, Error>> {
+        let result = self
+            .sessions
+            .lock()
+            .unwrap()
+            .get(&(region_system_name.to_owned(), *session_id))
+            .map(|json| serde_json::from_str(json))
+            .transpose()
+            .map_err(Error::from);
+        Box::pin(future::ready(result))
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn get_player_session<'a>(
+        &'a self,
+        region_system_name: &'a str,
+        profile_id: ProfileId,
+    ) -> BoxFuture<'a, Result<Option<MatchmakingSession>, Error>> {
+        let result = self
+            .sessions
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|((region, _), _)| region == region_system_name)
+            .map(|(_, json)| serde_json::from_str::<MatchmakingSession>(json))
+            .collect::<serde_json::Result<Vec<_>>>()
+            .map(|sessions| {
+                sessions
+                    .into_iter()
+                    .filter(|s| s.players.iter().any(|p| p.profile_id == profile_id))
+                    .max_by_key(|s| s.creation_time)
+            })
+            .map_err(Error::from);
+        Box::pin(future::ready(result))
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn create_sessions<'a>(
+        &'a self,
+        region_system_name: &'a str,
+        fencing_token: u64,
+        sessions: &'a [&'a MatchmakingSession],
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        Box::pin(future::ready(self.set_sessions(
+            region_system_name,
+            fencing_token,
+            sessions,
+        )))
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn update_sessions<'a>(
+        &'a self,
+        region_system_name: &'a str,
+        fencing_token: u64,
+        sessions: &'a [&'a MatchmakingSession],
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        Box::pin(future::ready(self.set_sessions(
+            region_system_name,
+            fencing_token,
+            sessions,
+        )))
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn delete_sessions<'a>(
+        &'a self,
+        region_system_name: &'a str,
+        fencing_token: u64,
+        session_ids: &'a [SessionId],
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        if let Err(e) = self.fencing_tokens.check(region_system_name, fencing_token) {
+            return Box::pin(future::ready(Err(e)));
+        }
+
+        let mut sessions = self.sessions.lock().unwrap();
+        for session_id in session_ids {
+            sessions.remove(&(region_system_name.to_owned(), *session_id));
+        }
+        Box::pin(future::ready(Ok(())))
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn reset<'a>(
+        &'a self,
+        region_system_name: &'a str,
+        fencing_token: u64,
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        if let Err(e) = self.fencing_tokens.check(region_system_name, fencing_token) {
+            return Box::pin(future::ready(Err(e)));
+        }
+
+        self.sessions
+            .lock()
+            .unwrap()
+            .retain(|(region, _), _| region != region_system_name);
+        Box::pin(future::ready(Ok(())))
+    }
+}
@@ -0,0 +1,176 @@
+use crate::{
+    matchmaking::{
+        memory_fencing_tokens::MemoryFencingTokens, Error, MatchmakingTicket, MatchmakingTicketDAL,
+    },
+    types::ProfileId,
+};
+use futures::future::{self, BoxFuture};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+/// In-memory [`MatchmakingTicketDAL`], meant for tests
+#[derive(Clone, Default)]
+pub struct MemoryMatchmakingTicketDAL {
+    /// ticket jsons indexed by region and owner profile id
+    tickets: Arc<Mutex<HashMap<(String, ProfileId), String>>>,
+    fencing_tokens: MemoryFencingTokens,
+}
+
+impl MemoryMatchmakingTicketDAL {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn set_tickets(
+        &self,
+        region_system_name: &str,
+        fencing_token: u64,
+        tickets: &[&MatchmakingTicket],
+    ) -> Result<(), Error> {
+        self.fencing_tokens
+            .check(region_system_name, fencing_token)?;
+
+        let mut values = self.tickets.lock().unwrap();
+        for ticket in tickets {
+            values.insert(
+                (region_system_name.to_owned(), ticket.owner_profile_id),
+                serde_json::to_string(ticket)?,
+            );
+        }
+        Ok(())
+    }
+}
+
+impl MatchmakingTicketDAL for MemoryMatchmakingTicketDAL {
+    //-------------------------------------------------------------------------------------------------
+    fn get_tickets<'a>(
+        &'a self,
+        region_system_name: &'a str,
+    ) -> BoxFuture<'a, Result<Vec<MatchmakingTicket>, Error>> {
+        let result = self
+            .tickets
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|((region, _), _)| region == region_system_name)
+            .map(|(_, json)| serde_json::from_str::<MatchmakingTicket>(json))
+            .collect::<serde_json::Result<Vec<_>>>()
+            .map(|mut tickets| {
+                tickets.sort_by_key(|t| t.creation_time);
+                tickets
+            })
+            .map_err(Error::from);
+        Box::pin(future::ready(result))
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn get_ticket<'a>(
+        &'a self,
+        region_system_name: &'a str,
+        owner_profile_id: ProfileId,
+    ) -> BoxFuture<'a, Result<Option<MatchmakingTicket>, Error>> {
+        let result = self
+            .tickets
+            .lock()
+            .unwrap()
+            .get(&(region_system_name.to_owned(), owner_profile_id))
+            .map(|json| serde_json::from_str(json))
+            .transpose()
+            .map_err(Error::from);
+        Box::pin(future::ready(result))
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn get_queue_position<'a>(
+        &'a self,
+        region_system_name: &'a str,
+        ticket: &'a MatchmakingTicket,
+    ) -> BoxFuture<'a, Result<usize, Error>> {
+        let result = self
+            .tickets
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|((region, _), _)| region == region_system_name)
+            .map(|(_, json)| serde_json::from_str::<MatchmakingTicket>(json))
+            .collect::<serde_json::Result<Vec<_>>>()
+            .map(|tickets| {
+                tickets
+                    .iter()
+                    .filter(|t| {
+                        t.game_mode == ticket.game_mode
+                            && t.session_id.is_none()
+                            && t.creation_time < ticket.creation_time
+                    })
+                    .count()
+                    + 1
+            })
+            .map_err(Error::from);
+        Box::pin(future::ready(result))
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn create_tickets<'a>(
+        &'a self,
+        region_system_name: &'a str,
+        fencing_token: u64,
+        tickets: &'a [&'a MatchmakingTicket],
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        Box::pin(future::ready(self.set_tickets(
+            region_system_name,
+            fencing_token,
+            tickets,
+        )))
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn update_tickets<'a>(
+        &'a self,
+        region_system_name: &'a str,
+        fencing_token: u64,
+        tickets: &'a [&'a MatchmakingTicket],
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        Box::pin(future::ready(self.set_tickets(
+            region_system_name,
+            fencing_token,
+            tickets,
+        )))
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn delete_tickets<'a>(
+        &'a self,
+        region_system_name: &'a str,
+        fencing_token: u64,
+        ticket_ids: &'a [ProfileId],
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        if let Err(e) = self.fencing_tokens.check(region_system_name, fencing_token) {
+            return Box::pin(future::ready(Err(e)));
+        }
+
+        let mut tickets = self.tickets.lock().unwrap();
+        for ticket_id in ticket_ids {
+            tickets.remove(&(region_system_name.to_owned(), *ticket_id));
+        }
+        Box::pin(future::ready(Ok(())))
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn reset<'a>(
+        &'a self,
+        region_system_name: &'a str,
+        fencing_token: u64,
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        if let Err(e) = self.fencing_tokens.check(region_system_name, fencing_token) {
+            return Box::pin(future::ready(Err(e)));
+        }
+
+        self.tickets
+            .lock()
+            .unwrap()
+            .retain(|(region, _), _| region != region_system_name);
+        Box::pin(future::ready(Ok(())))
+    }
+}
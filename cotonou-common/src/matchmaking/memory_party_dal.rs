@@ -0,0 +1,123 @@
+use crate::{
+    matchmaking::{Error, Party, PartyDAL, PartyId},
+    types::ProfileId,
+};
+use futures::future::{self, BoxFuture};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+/// In-memory [`PartyDAL`], meant for tests
+#[derive(Clone, Default)]
+pub struct MemoryPartyDAL {
+    /// party jsons indexed by party id
+    parties: Arc<Mutex<HashMap<PartyId, String>>>,
+    /// party ids indexed by member profile id
+    party_ids: Arc<Mutex<HashMap<ProfileId, PartyId>>>,
+}
+
+impl MemoryPartyDAL {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn set_party(&self, party: &mut Party) -> Result<bool, Error> {
+        let mut parties = self.parties.lock().unwrap();
+        let mut party_ids = self.party_ids.lock().unwrap();
+
+        if get_stored_version(&parties, &party.party_id)?.unwrap_or(0) != party.version {
+            return Ok(false);
+        }
+
+        if party.members.iter().any(|m| {
+            party_ids
+                .get(&m.profile_id)
+                .is_some_and(|id| *id != party.party_id)
+        }) {
+            return Ok(false);
+        }
+
+        party.version += 1;
+        parties.insert(party.party_id, serde_json::to_string(party)?);
+        for member in &party.members {
+            party_ids.insert(member.profile_id, party.party_id);
+        }
+        Ok(true)
+    }
+
+    fn remove_party(&self, party: &Party) -> Result<bool, Error> {
+        let mut parties = self.parties.lock().unwrap();
+        if get_stored_version(&parties, &party.party_id)? != Some(party.version) {
+            return Ok(false);
+        }
+
+        parties.remove(&party.party_id);
+        let mut party_ids = self.party_ids.lock().unwrap();
+        for member in &party.members {
+            party_ids.remove(&member.profile_id);
+        }
+        Ok(true)
+    }
+}
+
+impl PartyDAL for MemoryPartyDAL {
+    //-------------------------------------------------------------------------------------------------
+    fn get_party<'a>(
+        &'a self,
+        party_id: &'a PartyId,
+    ) -> BoxFuture<'a, Result<Option<Party>, Error>> {
+        let result = self
+            .parties
+            .lock()
+            .unwrap()
+            .get(party_id)
+            .map(|json| serde_json::from_str(json))
+            .transpose()
+            .map_err(Error::from);
+        Box::pin(future::ready(result))
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn get_party_id<'a>(
+        &'a self,
+        profile_id: ProfileId,
+    ) -> BoxFuture<'a, Result<Option<PartyId>, Error>> {
+        let party_id = self.party_ids.lock().unwrap().get(&profile_id).copied();
+        Box::pin(future::ready(Ok(party_id)))
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn save_party<'a>(&'a self, party: &'a mut Party) -> BoxFuture<'a, Result<bool, Error>> {
+        Box::pin(future::ready(self.set_party(party)))
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn delete_party_members<'a>(
+        &'a self,
+        profile_ids: &'a [ProfileId],
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        let mut party_ids = self.party_ids.lock().unwrap();
+        for profile_id in profile_ids {
+            party_ids.remove(profile_id);
+        }
+        Box::pin(future::ready(Ok(())))
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn delete_party<'a>(&'a self, party: &'a Party) -> BoxFuture<'a, Result<bool, Error>> {
+        Box::pin(future::ready(self.remove_party(party)))
+    }
+}
+
+//-------------------------------------------------------------------------------------------------
+fn get_stored_version(
+    parties: &HashMap<PartyId, String>,
+    party_id: &PartyId,
+) -> Result<Option<u64>, Error> {
+    parties
+        .get(party_id)
+        .map(|json| serde_json::from_str::<Party>(json).map(|p| p.version))
+        .transpose()
+        .map_err(Error::from)
+}
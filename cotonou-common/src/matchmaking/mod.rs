@@ -1,37 +1,128 @@
+mod connect_token;
 mod error;
 mod game_region;
 mod game_server;
 mod game_server_dal;
+mod game_server_drained_notification;
+mod maintenance;
+mod maintenance_dal;
 mod matchmaking_activate_session_notification;
 mod matchmaking_average_waiting_time_dal;
+mod matchmaking_backfill_completed_notification;
+mod matchmaking_backfill_timeout_notification;
 mod matchmaking_command;
 mod matchmaking_command_dal;
 mod matchmaking_completed_notification;
 mod matchmaking_failed_notification;
+mod matchmaking_job_instance;
+mod matchmaking_job_instance_dal;
+mod matchmaking_lease_dal;
 mod matchmaking_servers_full_notification;
 mod matchmaking_session;
+mod matchmaking_session_canceled_notification;
 mod matchmaking_session_dal;
 mod matchmaking_settings;
 mod matchmaking_settings_dal;
 mod matchmaking_ticket;
 mod matchmaking_ticket_dal;
+#[cfg(feature = "memory")]
+mod memory_fencing_tokens;
+#[cfg(feature = "memory")]
+mod memory_game_server_dal;
+#[cfg(feature = "memory")]
+mod memory_maintenance_dal;
+#[cfg(feature = "memory")]
+mod memory_matchmaking_average_waiting_time_dal;
+#[cfg(feature = "memory")]
+mod memory_matchmaking_command_dal;
+#[cfg(feature = "memory")]
+mod memory_matchmaking_job_instance_dal;
+#[cfg(feature = "memory")]
+mod memory_matchmaking_lease_dal;
+#[cfg(feature = "memory")]
+mod memory_matchmaking_session_dal;
+#[cfg(feature = "memory")]
+mod memory_matchmaking_ticket_dal;
+#[cfg(feature = "memory")]
+mod memory_party_dal;
+mod party;
+mod party_dal;
+mod party_invitation_notification;
+mod party_removed_notification;
+mod party_updated_notification;
+mod rating;
+mod redis_fenced_writes;
+mod redis_game_server_dal;
 mod redis_key_names;
+mod redis_maintenance_dal;
+mod redis_matchmaking_average_waiting_time_dal;
+mod redis_matchmaking_command_dal;
+mod redis_matchmaking_job_instance_dal;
+mod redis_matchmaking_lease_dal;
+mod redis_matchmaking_session_dal;
+mod redis_matchmaking_ticket_dal;
+mod redis_party_dal;
+mod waiting_time_statistics;
 
+pub use connect_token::*;
 pub use error::*;
 pub use game_region::*;
 pub use game_server::*;
 pub use game_server_dal::*;
+pub use game_server_drained_notification::*;
+pub use maintenance::*;
+pub use maintenance_dal::*;
 pub use matchmaking_activate_session_notification::*;
 pub use matchmaking_average_waiting_time_dal::*;
+pub use matchmaking_backfill_completed_notification::*;
+pub use matchmaking_backfill_timeout_notification::*;
 pub use matchmaking_command::*;
 pub use matchmaking_command_dal::*;
 pub use matchmaking_completed_notification::*;
 pub use matchmaking_failed_notification::*;
+pub use matchmaking_job_instance::*;
+pub use matchmaking_job_instance_dal::*;
+pub use matchmaking_lease_dal::*;
 pub use matchmaking_servers_full_notification::*;
 pub use matchmaking_session::*;
+pub use matchmaking_session_canceled_notification::*;
 pub use matchmaking_session_dal::*;
 pub use matchmaking_settings::*;
 pub use matchmaking_settings_dal::*;
 pub use matchmaking_ticket::*;
 pub use matchmaking_ticket_dal::*;
+#[cfg(feature = "memory")]
+pub use memory_game_server_dal::*;
+#[cfg(feature = "memory")]
+pub use memory_maintenance_dal::*;
+#[cfg(feature = "memory")]
+pub use memory_matchmaking_average_waiting_time_dal::*;
+#[cfg(feature = "memory")]
+pub use memory_matchmaking_command_dal::*;
+#[cfg(feature = "memory")]
+pub use memory_matchmaking_job_instance_dal::*;
+#[cfg(feature = "memory")]
+pub use memory_matchmaking_lease_dal::*;
+#[cfg(feature = "memory")]
+pub use memory_matchmaking_session_dal::*;
+#[cfg(feature = "memory")]
+pub use memory_matchmaking_ticket_dal::*;
+#[cfg(feature = "memory")]
+pub use memory_party_dal::*;
+pub use party::*;
+pub use party_dal::*;
+pub use party_invitation_notification::*;
+pub use party_removed_notification::*;
+pub use party_updated_notification::*;
+pub use rating::*;
+pub use redis_game_server_dal::*;
 pub use redis_key_names::*;
+pub use redis_maintenance_dal::*;
+pub use redis_matchmaking_average_waiting_time_dal::*;
+pub use redis_matchmaking_command_dal::*;
+pub use redis_matchmaking_job_instance_dal::*;
+pub use redis_matchmaking_lease_dal::*;
+pub use redis_matchmaking_session_dal::*;
+pub use redis_matchmaking_ticket_dal::*;
+pub use redis_party_dal::*;
+pub use waiting_time_statistics::*;
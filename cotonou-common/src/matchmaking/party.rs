@@ -0,0 +1,185 @@
+use crate::types::{ProfileId, UniqueId};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Group of players who agreed to play together, before matchmaking
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Party {
+    /// Party unique id (uuid)
+    #[serde(rename = "i")]
+    pub party_id: PartyId,
+
+    /// Member allowed to invite, kick and create matchmaking tickets
+    #[serde(rename = "l")]
+    pub leader_profile_id: ProfileId,
+
+    /// Members, by joining order
+    #[serde(rename = "m")]
+    pub members: Vec<PartyMember>,
+
+    /// Pending invitations
+    #[serde(rename = "v", default)]
+    pub invitations: Vec<PartyInvitation>,
+
+    /// Party creation time (unix timestamp)
+    #[serde(rename = "t")]
+    pub creation_time: u64,
+
+    /// Region of the last matchmaking ticket created by the party
+    #[serde(rename = "r", default)]
+    pub ticket_region_system_name: Option<String>,
+
+    /// Incremented on each save, to detect concurrent modifications
+    #[serde(rename = "n", default)]
+    pub version: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PartyMember {
+    #[serde(rename = "p")]
+    pub profile_id: ProfileId,
+
+    /// Answer to the ready check, reset when the party changes
+    #[serde(rename = "r")]
+    pub is_ready: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PartyInvitation {
+    #[serde(rename = "p")]
+    pub profile_id: ProfileId,
+
+    /// The invitation cannot be accepted after this time (unix timestamp)
+    #[serde(rename = "e")]
+    pub expiration_time: u64,
+}
+
+impl Party {
+    pub fn new(leader_profile_id: ProfileId, creation_time: u64) -> Self {
+        Self {
+            party_id: PartyId::new(),
+            leader_profile_id,
+            members: vec![PartyMember {
+                profile_id: leader_profile_id,
+                is_ready: false,
+            }],
+            invitations: Vec::new(),
+            creation_time,
+            ticket_region_system_name: None,
+            version: 0,
+        }
+    }
+
+    pub fn is_member(&self, profile_id: ProfileId) -> bool {
+        self.members.iter().any(|m| m.profile_id == profile_id)
+    }
+
+    pub fn get_member_mut(&mut self, profile_id: ProfileId) -> Option<&mut PartyMember> {
+        self.members.iter_mut().find(|m| m.profile_id == profile_id)
+    }
+
+    pub fn get_invitation(&self, profile_id: ProfileId) -> Option<&PartyInvitation> {
+        self.invitations.iter().find(|i| i.profile_id == profile_id)
+    }
+
+    /// All members but the leader are ready
+    pub fn is_ready(&self) -> bool {
+        self.members
+            .iter()
+            .all(|m| m.is_ready || m.profile_id == self.leader_profile_id)
+    }
+
+    /// Start a new ready check
+    pub fn reset_ready_check(&mut self) {
+        for member in &mut self.members {
+            member.is_ready = false;
+        }
+    }
+
+    /// Remove a member, the oldest remaining member becomes the leader if needed
+    pub fn remove_member(&mut self, profile_id: ProfileId) -> bool {
+        let num_members = self.members.len();
+        self.members.retain(|m| m.profile_id != profile_id);
+        if self.members.len() == num_members {
+            return false;
+        }
+
+        if self.leader_profile_id == profile_id {
+            if let Some(member) = self.members.first() {
+                self.leader_profile_id = member.profile_id;
+            }
+        }
+
+        self.reset_ready_check();
+        true
+    }
+
+    /// Remove expired invitations
+    pub fn purge_invitations(&mut self, now: u64) {
+        self.invitations.retain(|i| i.expiration_time >= now);
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Copy, Clone, Eq, PartialEq, PartialOrd, Ord, Hash)]
+pub struct PartyId(UniqueId);
+
+impl PartyId {
+    pub fn new() -> Self {
+        Self(UniqueId::new())
+    }
+
+    pub fn try_parse(input: &str) -> Option<Self> {
+        UniqueId::try_parse(input).map(Self)
+    }
+}
+
+impl Default for PartyId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Display for PartyId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+#[cfg(feature = "redis")]
+impl rustis::resp::ToArgs for PartyId {
+    fn write_args(&self, args: &mut rustis::resp::CommandArgs) {
+        args.arg(self.0);
+    }
+}
+
+#[cfg(feature = "redis")]
+impl rustis::resp::SingleArg for PartyId {}
+
+#[cfg(feature = "redis")]
+impl rustis::resp::PrimitiveResponse for PartyId {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn leadership_is_transferred_to_oldest_member() {
+        let profile_id1 = ProfileId::try_from(1).unwrap();
+        let profile_id2 = ProfileId::try_from(2).unwrap();
+        let profile_id3 = ProfileId::try_from(3).unwrap();
+
+        let mut party = Party::new(profile_id1, 0);
+        for profile_id in [profile_id2, profile_id3] {
+            party.members.push(PartyMember {
+                profile_id,
+                is_ready: true,
+            });
+        }
+        assert!(party.is_ready());
+
+        assert!(party.remove_member(profile_id1));
+        assert_eq!(profile_id2, party.leader_profile_id);
+        assert!(!party.is_ready());
+        assert!(!party.remove_member(profile_id1));
+    }
+}
@@ -0,0 +1,37 @@
+use crate::{
+    matchmaking::{Error, Party, PartyId},
+    types::ProfileId,
+};
+use futures::future::BoxFuture;
+
+pub trait PartyDAL: Send + Sync {
+    //-------------------------------------------------------------------------------------------------
+    fn get_party<'a>(
+        &'a self,
+        party_id: &'a PartyId,
+    ) -> BoxFuture<'a, Result<Option<Party>, Error>>;
+
+    //-------------------------------------------------------------------------------------------------
+    /// Id of the party of a player
+    fn get_party_id<'a>(
+        &'a self,
+        profile_id: ProfileId,
+    ) -> BoxFuture<'a, Result<Option<PartyId>, Error>>;
+
+    //-------------------------------------------------------------------------------------------------
+    /// Create or update a party and the party id of its members, then increment its version.
+    /// Returns `false`, without saving, if the party was saved by someone else since it was read
+    /// or if one of its members is already in another party
+    fn save_party<'a>(&'a self, party: &'a mut Party) -> BoxFuture<'a, Result<bool, Error>>;
+
+    //-------------------------------------------------------------------------------------------------
+    /// Forget the party id of players who left a party
+    fn delete_party_members<'a>(
+        &'a self,
+        profile_ids: &'a [ProfileId],
+    ) -> BoxFuture<'a, Result<(), Error>>;
+
+    //-------------------------------------------------------------------------------------------------
+    /// Returns `false`, without deleting, if the party was saved by someone else since it was read
+    fn delete_party<'a>(&'a self, party: &'a Party) -> BoxFuture<'a, Result<bool, Error>>;
+}
@@ -0,0 +1,12 @@
+use crate::{matchmaking::PartyId, notifications::Notification, types::ProfileId};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+pub struct PartyInvitationNotification {
+    pub party_id: PartyId,
+    pub leader_profile_id: ProfileId,
+    pub expiration_time: u64,
+}
+
+#[typetag::serde]
+impl Notification for PartyInvitationNotification {}
@@ -0,0 +1,12 @@
+use crate::{matchmaking::PartyId, notifications::Notification, types::ProfileId};
+use serde::{Deserialize, Serialize};
+
+/// Sent to a member kicked from a party
+#[derive(Serialize, Deserialize)]
+pub struct PartyRemovedNotification {
+    pub party_id: PartyId,
+    pub leader_profile_id: ProfileId,
+}
+
+#[typetag::serde]
+impl Notification for PartyRemovedNotification {}
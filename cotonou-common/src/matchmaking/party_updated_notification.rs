@@ -0,0 +1,11 @@
+use crate::{matchmaking::Party, notifications::Notification};
+use serde::{Deserialize, Serialize};
+
+/// Sent to members when the party changes
+#[derive(Serialize, Deserialize)]
+pub struct PartyUpdatedNotification {
+    pub party: Party,
+}
+
+#[typetag::serde]
+impl Notification for PartyUpdatedNotification {}
@@ -0,0 +1,289 @@
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+
+/// MMR of a player never rated in a game mode, their first rating starts from it
+pub const DEFAULT_MMR: u32 = 0;
+/// Rating of a new player
+pub const DEFAULT_RATING: f64 = 1500.;
+/// Rating deviation of a new player
+pub const DEFAULT_DEVIATION: f64 = 350.;
+/// Rating volatility of a new player
+pub const DEFAULT_VOLATILITY: f64 = 0.06;
+
+/// Glicko-2 scale factor between the Glicko and Glicko-2 scales
+const GLICKO2_SCALE: f64 = 173.7178;
+/// Convergence tolerance of the Glicko-2 volatility iteration
+const GLICKO2_EPSILON: f64 = 0.000001;
+
+/// Rating system of a game mode
+#[derive(Debug, Clone, Copy)]
+pub enum RatingSystemConfig {
+    Elo {
+        /// maximum rating change for a single match
+        k_factor: f64,
+    },
+    Glicko2 {
+        /// constrains the change in volatility over time (reasonable values are between 0.3 and 1.2)
+        tau: f64,
+    },
+}
+
+/// Skill rating of a player for a game mode.
+/// Deviation and volatility are only updated by Glicko-2
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Rating {
+    #[serde(rename = "r")]
+    pub rating: f64,
+    #[serde(rename = "d")]
+    pub deviation: f64,
+    #[serde(rename = "v")]
+    pub volatility: f64,
+}
+
+impl Default for Rating {
+    fn default() -> Self {
+        Self {
+            rating: DEFAULT_RATING,
+            deviation: DEFAULT_DEVIATION,
+            volatility: DEFAULT_VOLATILITY,
+        }
+    }
+}
+
+impl Rating {
+    /// Rating used as MMR by the matchmaking
+    pub fn to_mmr(&self) -> u32 {
+        self.rating.round().max(0.) as u32
+    }
+}
+
+/// Team as rated at the end of a match
+#[derive(Clone)]
+pub struct RatedTeam {
+    /// 1 for the winner, equal placements for a draw
+    pub placement: u32,
+    pub ratings: Vec<Rating>,
+}
+
+/// Compute the ratings of the players after a match.
+/// Each team plays against every other team, represented by the average of its players
+///
+/// # Return
+/// new ratings in the same order as the teams and their players
+pub fn update_ratings(config: RatingSystemConfig, teams: &[RatedTeam]) -> Vec<Vec<Rating>> {
+    let team_ratings = teams.iter().map(average_rating).collect::<Vec<_>>();
+
+    teams
+        .iter()
+        .enumerate()
+        .map(|(team_index, team)| {
+            let results = teams
+                .iter()
+                .zip(&team_ratings)
+                .enumerate()
+                .filter(|(opponent_index, _)| *opponent_index != team_index)
+                .map(|(_, (opponent, opponent_rating))| {
+                    (*opponent_rating, score(team.placement, opponent.placement))
+                })
+                .collect::<Vec<_>>();
+
+            team.ratings
+                .iter()
+                .map(|rating| match config {
+                    RatingSystemConfig::Elo { k_factor } => update_elo(rating, &results, k_factor),
+                    RatingSystemConfig::Glicko2 { tau } => update_glicko2(rating, &results, tau),
+                })
+                .collect()
+        })
+        .collect()
+}
+
+//-------------------------------------------------------------------------------------------------
+fn average_rating(team: &RatedTeam) -> Rating {
+    if team.ratings.is_empty() {
+        return Rating::default();
+    }
+
+    let num_players = team.ratings.len() as f64;
+    Rating {
+        rating: team.ratings.iter().map(|r| r.rating).sum::<f64>() / num_players,
+        // deviations are averaged as variances
+        deviation: (team
+            .ratings
+            .iter()
+            .map(|r| r.deviation * r.deviation)
+            .sum::<f64>()
+            / num_players)
+            .sqrt(),
+        volatility: team.ratings.iter().map(|r| r.volatility).sum::<f64>() / num_players,
+    }
+}
+
+//-------------------------------------------------------------------------------------------------
+fn score(placement: u32, opponent_placement: u32) -> f64 {
+    match placement.cmp(&opponent_placement) {
+        std::cmp::Ordering::Less => 1.,
+        std::cmp::Ordering::Equal => 0.5,
+        std::cmp::Ordering::Greater => 0.,
+    }
+}
+
+//-------------------------------------------------------------------------------------------------
+fn update_elo(rating: &Rating, results: &[(Rating, f64)], k_factor: f64) -> Rating {
+    if results.is_empty() {
+        return *rating;
+    }
+
+    let delta = results
+        .iter()
+        .map(|(opponent, score)| {
+            let expected_score = 1. / (1. + 10f64.powf((opponent.rating - rating.rating) / 400.));
+            score - expected_score
+        })
+        .sum::<f64>();
+
+    Rating {
+        rating: rating.rating + k_factor * delta / results.len() as f64,
+        ..*rating
+    }
+}
+
+//-------------------------------------------------------------------------------------------------
+/// See http://www.glicko.net/glicko/glicko2.pdf
+fn update_glicko2(rating: &Rating, results: &[(Rating, f64)], tau: f64) -> Rating {
+    let mu = (rating.rating - DEFAULT_RATING) / GLICKO2_SCALE;
+    let phi = rating.deviation / GLICKO2_SCALE;
+    let sigma = rating.volatility;
+
+    if results.is_empty() {
+        let phi = (phi * phi + sigma * sigma).sqrt();
+        return Rating {
+            deviation: phi * GLICKO2_SCALE,
+            ..*rating
+        };
+    }
+
+    let opponents = results
+        .iter()
+        .map(|(opponent, score)| {
+            let mu_j = (opponent.rating - DEFAULT_RATING) / GLICKO2_SCALE;
+            let phi_j = opponent.deviation / GLICKO2_SCALE;
+            let g = 1. / (1. + 3. * phi_j * phi_j / (PI * PI)).sqrt();
+            let e = 1. / (1. + (-g * (mu - mu_j)).exp());
+            (g, e, *score)
+        })
+        .collect::<Vec<_>>();
+
+    let v = 1.
+        / opponents
+            .iter()
+            .map(|(g, e, _)| g * g * e * (1. - e))
+            .sum::<f64>();
+    let improvement = opponents
+        .iter()
+        .map(|(g, e, score)| g * (score - e))
+        .sum::<f64>();
+    let delta = v * improvement;
+
+    let sigma = new_volatility(phi, sigma, v, delta, tau);
+
+    let phi_star = (phi * phi + sigma * sigma).sqrt();
+    let phi = 1. / (1. / (phi_star * phi_star) + 1. / v).sqrt();
+    let mu = mu + phi * phi * improvement;
+
+    Rating {
+        rating: mu * GLICKO2_SCALE + DEFAULT_RATING,
+        deviation: phi * GLICKO2_SCALE,
+        volatility: sigma,
+    }
+}
+
+//-------------------------------------------------------------------------------------------------
+/// Illinois algorithm of the Glicko-2 step 5
+fn new_volatility(phi: f64, sigma: f64, v: f64, delta: f64, tau: f64) -> f64 {
+    let a = (sigma * sigma).ln();
+    let f = |x: f64| {
+        let ex = x.exp();
+        let d = phi * phi + v + ex;
+        ex * (delta * delta - d) / (2. * d * d) - (x - a) / (tau * tau)
+    };
+
+    let mut big_a = a;
+    let mut big_b = if delta * delta > phi * phi + v {
+        (delta * delta - phi * phi - v).ln()
+    } else {
+        let mut k = 1.;
+        while f(a - k * tau) < 0. {
+            k += 1.;
+        }
+        a - k * tau
+    };
+
+    let mut f_a = f(big_a);
+    let mut f_b = f(big_b);
+    while (big_b - big_a).abs() > GLICKO2_EPSILON {
+        let big_c = big_a + (big_a - big_b) * f_a / (f_b - f_a);
+        let f_c = f(big_c);
+        if f_c * f_b <= 0. {
+            big_a = big_b;
+            f_a = f_b;
+        } else {
+            f_a /= 2.;
+        }
+        big_b = big_c;
+        f_b = f_c;
+    }
+
+    (big_a / 2.).exp()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn team(placement: u32, ratings: &[Rating]) -> RatedTeam {
+        RatedTeam {
+            placement,
+            ratings: ratings.to_vec(),
+        }
+    }
+
+    #[test]
+    fn elo_winner_gains_what_loser_loses() {
+        let new_ratings = update_ratings(
+            RatingSystemConfig::Elo { k_factor: 32. },
+            &[team(1, &[Rating::default()]), team(2, &[Rating::default()])],
+        );
+
+        assert_eq!(1516., new_ratings[0][0].rating);
+        assert_eq!(1484., new_ratings[1][0].rating);
+    }
+
+    #[test]
+    fn glicko2_matches_reference_example() {
+        // example from the Glicko-2 paper, one rating period with 3 opponents
+        let rating = Rating {
+            rating: 1500.,
+            deviation: 200.,
+            volatility: 0.06,
+        };
+        let opponent = |rating, deviation| Rating {
+            rating,
+            deviation,
+            volatility: 0.06,
+        };
+        let new_rating = update_glicko2(
+            &rating,
+            &[
+                (opponent(1400., 30.), 1.),
+                (opponent(1550., 100.), 0.),
+                (opponent(1700., 300.), 0.),
+            ],
+            0.5,
+        );
+
+        assert!((new_rating.rating - 1464.06).abs() < 0.01);
+        assert!((new_rating.deviation - 151.52).abs() < 0.01);
+        assert!((new_rating.volatility - 0.05999).abs() < 0.0001);
+    }
+}
@@ -0,0 +1,83 @@
+use crate::matchmaking::{build_fencing_token_key, Error};
+use rustis::{
+    client::Client,
+    commands::{CallBuilder, ScriptingCommands},
+};
+
+/// KEYS[1] = fencing token, KEYS[2..] = key of each write,
+/// ARGV[1] = fencing token of the writer,
+/// then for each write: number of arguments, command name and arguments following the key
+const FENCED_WRITES_SCRIPT: &str = r#"
+if tonumber(redis.call('GET', KEYS[1]) or '0') > tonumber(ARGV[1]) then
+    return 0
+end
+local arg_index = 2
+for key_index = 2, #KEYS do
+    local num_args = tonumber(ARGV[arg_index])
+    redis.call(ARGV[arg_index + 1], KEYS[key_index], unpack(ARGV, arg_index + 2, arg_index + num_args))
+    arg_index = arg_index + num_args + 1
+end
+return 1
+"#;
+
+/// Writes to a region, applied all at once by a script, and only if no lease
+/// with a higher fencing token has been acquired on the region since the writer acquired its own.
+/// All the keys must be tagged with the region, to live in the slot of its fencing token
+pub(crate) struct RedisFencedWrites {
+    fencing_token: u64,
+    keys: Vec<String>,
+    args: Vec<String>,
+}
+
+impl RedisFencedWrites {
+    //-------------------------------------------------------------------------------------------------
+    pub fn new(region_system_name: &str, fencing_token: u64) -> Self {
+        Self {
+            fencing_token,
+            keys: vec![build_fencing_token_key(region_system_name)],
+            args: vec![fencing_token.to_string()],
+        }
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    /// Queue a command writing a single key
+    pub fn write<A, AA>(&mut self, command_name: &str, key: String, args: AA)
+    where
+        A: ToString,
+        AA: IntoIterator<Item = A>,
+    {
+        let num_args_index = self.args.len();
+        self.args.push(String::new());
+        self.args.push(command_name.to_owned());
+        self.args.extend(args.into_iter().map(|a| a.to_string()));
+        self.args[num_args_index] = (self.args.len() - num_args_index - 1).to_string();
+        self.keys.push(key);
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    pub fn delete(&mut self, key: String) {
+        self.write("DEL", key, Vec::<String>::new());
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    /// Returns [`Error::StaleFencingToken`] without writing anything
+    /// if the region has been leased again since the writer acquired its lease
+    pub async fn execute(self, client: &Client) -> Result<(), Error> {
+        if self.keys.len() == 1 {
+            return Ok(());
+        }
+
+        let written: u64 = client
+            .eval(
+                CallBuilder::script(FENCED_WRITES_SCRIPT)
+                    .keys(self.keys)
+                    .args(self.args),
+            )
+            .await?;
+        if written == 0 {
+            return Err(Error::StaleFencingToken(self.fencing_token));
+        }
+
+        Ok(())
+    }
+}
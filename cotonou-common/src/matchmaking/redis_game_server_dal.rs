@@ -0,0 +1,177 @@
+use crate::{
+    matchmaking::{redis_fenced_writes::RedisFencedWrites, Error, GameServer, GameServerDAL},
+    redis::RedisConnectionManager,
+    types::GameServerId,
+};
+use futures::future::BoxFuture;
+use rustis::{
+    client::Client,
+    commands::{SetCommands, StringCommands},
+};
+
+#[derive(Clone)]
+pub struct RedisGameServerDAL {
+    client: Client,
+}
+
+impl RedisGameServerDAL {
+    //-------------------------------------------------------------------------------------------------
+    pub fn new(redis_connection_manager: &RedisConnectionManager) -> Self {
+        Self {
+            client: redis_connection_manager.get_client("MATCHMAKING").unwrap(),
+        }
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn set_game_server_values(
+        writes: &mut RedisFencedWrites,
+        region_system_name: &str,
+        game_servers: &[&GameServer],
+    ) -> Result<(), Error> {
+        for game_server in game_servers {
+            writes.write(
+                "SET",
+                build_game_server_key(region_system_name, &game_server.game_server_id),
+                [serde_json::to_string(game_server)?],
+            );
+        }
+        Ok(())
+    }
+}
+
+impl GameServerDAL for RedisGameServerDAL {
+    //-------------------------------------------------------------------------------------------------
+    fn get_game_servers<'a>(
+        &'a self,
+        region_system_name: &'a str,
+    ) -> BoxFuture<'a, Result<Vec<GameServer>, Error>> {
+        Box::pin(async move {
+            let game_server_ids: Vec<GameServerId> = self
+                .client
+                .smembers(build_game_server_set_key(region_system_name))
+                .await?;
+
+            if game_server_ids.is_empty() {
+                return Ok(Vec::new());
+            }
+
+            let keys = game_server_ids
+                .into_iter()
+                .map(|id| build_game_server_key(region_system_name, &id))
+                .collect::<Vec<_>>();
+            let values: Vec<String> = self.client.mget(keys).await?;
+
+            let servers = values
+                .into_iter()
+                .map(|v| serde_json::from_str(&v))
+                .collect::<serde_json::Result<Vec<GameServer>>>()?;
+
+            Ok(servers)
+        })
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn get_game_server<'a>(
+        &'a self,
+        region_system_name: &'a str,
+        game_server_id: &'a GameServerId,
+    ) -> BoxFuture<'a, Result<Option<GameServer>, Error>> {
+        Box::pin(async move {
+            let key = build_game_server_key(region_system_name, game_server_id);
+            let game_server_json: Option<String> = self.client.get(key).await?;
+
+            if let Some(game_server_json) = game_server_json {
+                Ok(Some(serde_json::from_str(&game_server_json)?))
+            } else {
+                Ok(None)
+            }
+        })
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn create_game_servers<'a>(
+        &'a self,
+        region_system_name: &'a str,
+        fencing_token: u64,
+        game_servers: &'a [&'a GameServer],
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        Box::pin(async move {
+            let mut writes = RedisFencedWrites::new(region_system_name, fencing_token);
+            let game_server_set_key = build_game_server_set_key(region_system_name);
+            for game_server in game_servers {
+                writes.write(
+                    "SADD",
+                    game_server_set_key.clone(),
+                    [game_server.game_server_id],
+                );
+            }
+            Self::set_game_server_values(&mut writes, region_system_name, game_servers)?;
+            writes.execute(&self.client).await
+        })
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn update_game_servers<'a>(
+        &'a self,
+        region_system_name: &'a str,
+        fencing_token: u64,
+        game_servers: &'a [&'a GameServer],
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        Box::pin(async move {
+            let mut writes = RedisFencedWrites::new(region_system_name, fencing_token);
+            Self::set_game_server_values(&mut writes, region_system_name, game_servers)?;
+            writes.execute(&self.client).await
+        })
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn delete_game_servers<'a>(
+        &'a self,
+        region_system_name: &'a str,
+        fencing_token: u64,
+        game_server_ids: &'a [GameServerId],
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        Box::pin(async move {
+            let mut writes = RedisFencedWrites::new(region_system_name, fencing_token);
+            let game_server_set_key = build_game_server_set_key(region_system_name);
+            for game_server_id in game_server_ids {
+                writes.write("SREM", game_server_set_key.clone(), [game_server_id]);
+                writes.delete(build_game_server_key(region_system_name, game_server_id));
+            }
+            writes.execute(&self.client).await
+        })
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn reset<'a>(
+        &'a self,
+        region_system_name: &'a str,
+        fencing_token: u64,
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        Box::pin(async move {
+            let game_server_set_key = build_game_server_set_key(region_system_name);
+            let game_server_ids: Vec<GameServerId> =
+                self.client.smembers(game_server_set_key.clone()).await?;
+
+            let mut writes = RedisFencedWrites::new(region_system_name, fencing_token);
+            for key in game_server_ids
+                .iter()
+                .map(|id| build_game_server_key(region_system_name, id))
+                .chain(std::iter::once(game_server_set_key))
+            {
+                writes.delete(key);
+            }
+            writes.execute(&self.client).await
+        })
+    }
+}
+
+//-------------------------------------------------------------------------------------------------
+fn build_game_server_key(region_system_name: &str, game_server_id: &GameServerId) -> String {
+    format!("{{{region_system_name}}}:gs:{game_server_id}")
+}
+
+//-------------------------------------------------------------------------------------------------
+fn build_game_server_set_key(region_system_name: &str) -> String {
+    format!("{{{region_system_name}}}:gss")
+}
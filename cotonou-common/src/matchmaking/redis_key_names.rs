@@ -1,2 +1,31 @@
+//! Region keys are prefixed with a `{region_system_name}` hash tag
+//! so that all the keys of a region live in the same Redis Cluster slot
+
 /// mmcq = matchmaking command queue
 pub const COMMAND_QUEUE: &str = "mmcq";
+
+/// mmcg = matchmaking command consumer group
+pub const COMMAND_CONSUMER_GROUP: &str = "mmcg";
+
+/// mmcdlq = matchmaking command dead letter queue
+pub const COMMAND_DEAD_LETTER_QUEUE: &str = "mmcdlq";
+
+/// mmpc = matchmaking processed commands, scored by processing time
+pub const PROCESSED_COMMANDS: &str = "mmpc";
+
+/// mml = matchmaking lease
+pub const LEASE: &str = "mml";
+
+/// mmlft = matchmaking lease fencing token,
+/// the writes of a region are refused to the holders of a lower one
+pub const LEASE_FENCING_TOKEN: &str = "mmlft";
+
+/// mmlh = matchmaking leases of a holder, not region tagged:
+/// it spans several regions, so it is updated outside of the lease scripts
+pub const LEASE_HOLDER: &str = "mmlh";
+
+/// mmji = matchmaking job instances
+pub const JOB_INSTANCES: &str = "mmji";
+
+/// mmm = matchmaking maintenance
+pub const MAINTENANCE: &str = "mmm";
@@ -0,0 +1,84 @@
+use crate::{
+    matchmaking::{redis_key_names, Error, Maintenance, MaintenanceDAL},
+    redis::RedisConnectionManager,
+};
+use futures::future::BoxFuture;
+use rustis::{
+    client::Client,
+    commands::{GenericCommands, StringCommands},
+};
+
+#[derive(Clone)]
+pub struct RedisMaintenanceDAL {
+    client: Client,
+}
+
+impl RedisMaintenanceDAL {
+    //-------------------------------------------------------------------------------------------------
+    pub fn new(redis_connection_manager: &RedisConnectionManager) -> Self {
+        Self {
+            client: redis_connection_manager.get_client("MATCHMAKING").unwrap(),
+        }
+    }
+}
+
+impl MaintenanceDAL for RedisMaintenanceDAL {
+    //-------------------------------------------------------------------------------------------------
+    fn get_maintenance<'a>(
+        &'a self,
+        region_system_name: Option<&'a str>,
+    ) -> BoxFuture<'a, Result<Option<Maintenance>, Error>> {
+        Box::pin(async move {
+            let maintenance_json: Option<String> = self
+                .client
+                .get(build_maintenance_key(region_system_name))
+                .await?;
+            if let Some(maintenance_json) = maintenance_json {
+                Ok(Some(serde_json::from_str(&maintenance_json)?))
+            } else {
+                Ok(None)
+            }
+        })
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn set_maintenance<'a>(
+        &'a self,
+        region_system_name: Option<&'a str>,
+        maintenance: &'a Maintenance,
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        Box::pin(async move {
+            self.client
+                .set(
+                    build_maintenance_key(region_system_name),
+                    serde_json::to_string(maintenance)?,
+                )
+                .await?;
+            Ok(())
+        })
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn delete_maintenance<'a>(
+        &'a self,
+        region_system_name: Option<&'a str>,
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        Box::pin(async move {
+            self.client
+                .del(build_maintenance_key(region_system_name))
+                .await?;
+            Ok(())
+        })
+    }
+}
+
+//-------------------------------------------------------------------------------------------------
+#[inline]
+fn build_maintenance_key(region_system_name: Option<&str>) -> String {
+    match region_system_name {
+        Some(region_system_name) => {
+            format!("{{{region_system_name}}}:{}", redis_key_names::MAINTENANCE)
+        }
+        None => redis_key_names::MAINTENANCE.to_owned(),
+    }
+}
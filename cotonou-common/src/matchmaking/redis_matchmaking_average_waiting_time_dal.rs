@@ -0,0 +1,100 @@
+use crate::{
+    matchmaking::{Error, MatchmakingWaitingTimeDAL, WaitingTimeStatistics},
+    redis::RedisConnectionManager,
+};
+use futures::future::BoxFuture;
+use rustis::{
+    client::Client,
+    commands::{GenericCommands, StringCommands},
+};
+
+#[derive(Clone)]
+pub struct RedisMatchmakingWaitingTimeDAL {
+    client: Client,
+}
+
+impl RedisMatchmakingWaitingTimeDAL {
+    //-------------------------------------------------------------------------------------------------
+    pub fn new(redis_connection_manager: &RedisConnectionManager) -> Self {
+        Self {
+            client: redis_connection_manager.get_client("MATCHMAKING").unwrap(),
+        }
+    }
+}
+
+impl MatchmakingWaitingTimeDAL for RedisMatchmakingWaitingTimeDAL {
+    //-------------------------------------------------------------------------------------------------
+    fn set_waiting_time_statistics<'a>(
+        &'a self,
+        region_system_name: &'a str,
+        game_mode: &'a str,
+        waiting_time_statistics: &'a WaitingTimeStatistics,
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        Box::pin(async move {
+            let key = build_key(region_system_name, game_mode);
+            let previous_statistics: Option<String> = self
+                .client
+                .set_get_with_options(
+                    key,
+                    serde_json::to_string(waiting_time_statistics)?,
+                    Default::default(),
+                    Default::default(),
+                    false,
+                )
+                .await?;
+
+            // first publication of the statistics, the average waiting time they replace is deleted
+            if previous_statistics.is_none() {
+                self.client
+                    .del(build_legacy_key(region_system_name, game_mode))
+                    .await?;
+            }
+            Ok(())
+        })
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn get_waiting_time_statistics<'a>(
+        &'a self,
+        region_system_name: &'a str,
+        game_mode: &'a str,
+    ) -> BoxFuture<'a, Result<WaitingTimeStatistics, Error>> {
+        Box::pin(async move {
+            let key = build_key(region_system_name, game_mode);
+            let waiting_time_statistics_json: Option<String> = self.client.get(key).await?;
+            match waiting_time_statistics_json {
+                Some(waiting_time_statistics_json) => {
+                    Ok(serde_json::from_str(&waiting_time_statistics_json)?)
+                }
+                None => Ok(WaitingTimeStatistics::default()),
+            }
+        })
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn reset<'a>(
+        &'a self,
+        region_system_name: &'a str,
+        game_mode: &'a str,
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        Box::pin(async move {
+            let keys = [
+                build_key(region_system_name, game_mode),
+                build_legacy_key(region_system_name, game_mode),
+            ];
+            self.client.del(keys).await?;
+            Ok(())
+        })
+    }
+}
+
+//-------------------------------------------------------------------------------------------------
+fn build_key(region_system_name: &str, game_mode: &str) -> String {
+    format!("{{{region_system_name}}}:{game_mode}:mmwts")
+}
+
+//-------------------------------------------------------------------------------------------------
+/// Key of the average waiting time, before it was replaced by the waiting time statistics
+fn build_legacy_key(region_system_name: &str, game_mode: &str) -> String {
+    format!("{{{region_system_name}}}:{game_mode}:mmawt")
+}
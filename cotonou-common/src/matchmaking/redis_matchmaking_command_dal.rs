@@ -0,0 +1,445 @@
+use crate::{
+    matchmaking::{
+        redis_fenced_writes::RedisFencedWrites, redis_key_names, CommandId, DeadLetterCommand,
+        Error, MatchmakingCommand, MatchmakingCommandDAL, QueuedMatchmakingCommand,
+        MAX_COMMAND_DELIVERIES, PROCESSED_COMMAND_RETENTION,
+    },
+    redis::RedisConnectionManager,
+    unix_now,
+};
+use futures::future::BoxFuture;
+use rustis::{
+    client::Client,
+    commands::{
+        SortedSetCommands, StreamCommands, StreamEntry, XAddOptions, XGroupCreateOptions,
+        XPendingMessageResult, XPendingOptions, XReadGroupOptions, XTrimOperator, XTrimOptions,
+    },
+    Error as RedisError, RedisErrorKind,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex},
+};
+
+const MAX_COMMANDS_PER_DEQUEUE: usize = 1000;
+/// the oldest dead letters are trimmed beyond this length
+const MAX_DEAD_LETTER_COMMANDS: i64 = 10_000;
+/// stream entry field holding the command json
+const COMMAND_FIELD: &str = "c";
+/// dead letter stream entry field holding the original command id
+const COMMAND_ID_FIELD: &str = "i";
+/// dead letter stream entry field holding the reason
+const REASON_FIELD: &str = "r";
+
+/// Command queue backed by a Redis stream with a consumer group.
+/// A single matchmaking job consumes the commands of a region
+#[derive(Clone)]
+pub struct RedisMatchmakingCommandDAL {
+    client: Client,
+    /// regions for which the consumer group has been created
+    initialized_regions: Arc<Mutex<HashSet<String>>>,
+}
+
+impl RedisMatchmakingCommandDAL {
+    //-------------------------------------------------------------------------------------------------
+    pub fn new(redis_connection_manager: &RedisConnectionManager) -> Self {
+        Self {
+            client: redis_connection_manager.get_client("MATCHMAKING").unwrap(),
+            initialized_regions: Default::default(),
+        }
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn build_queue_key_name(&self, region_system_name: &str) -> String {
+        format!(
+            "{{{}}}:{}",
+            region_system_name,
+            redis_key_names::COMMAND_QUEUE
+        )
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn build_dead_letter_queue_key_name(&self, region_system_name: &str) -> String {
+        format!(
+            "{{{}}}:{}",
+            region_system_name,
+            redis_key_names::COMMAND_DEAD_LETTER_QUEUE
+        )
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn build_processed_commands_key_name(&self, region_system_name: &str) -> String {
+        format!(
+            "{{{}}}:{}",
+            region_system_name,
+            redis_key_names::PROCESSED_COMMANDS
+        )
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    async fn ensure_consumer_group(&self, region_system_name: &str) -> Result<(), Error> {
+        if self
+            .initialized_regions
+            .lock()
+            .unwrap()
+            .contains(region_system_name)
+        {
+            return Ok(());
+        }
+
+        let key = self.build_queue_key_name(region_system_name);
+        let result = self
+            .client
+            .xgroup_create(
+                key,
+                redis_key_names::COMMAND_CONSUMER_GROUP,
+                "0",
+                XGroupCreateOptions::default().mk_stream(),
+            )
+            .await;
+
+        match result {
+            Ok(_) => (),
+            Err(RedisError::Redis(e)) if e.kind == RedisErrorKind::BusyGroup => (),
+            Err(e) => return Err(e.into()),
+        }
+
+        self.initialized_regions
+            .lock()
+            .unwrap()
+            .insert(region_system_name.to_owned());
+        Ok(())
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    /// Decode stream entries, moving undecodable ones to the dead letter queue
+    async fn decode_entries(
+        &self,
+        region_system_name: &str,
+        entries: Vec<StreamEntry<String>>,
+    ) -> Result<Vec<QueuedMatchmakingCommand>, Error> {
+        let mut commands = Vec::with_capacity(entries.len());
+
+        for mut entry in entries {
+            let command_id = CommandId::new(entry.stream_id);
+            let json = entry.items.remove(COMMAND_FIELD).unwrap_or_default();
+            match serde_json::from_str::<MatchmakingCommand>(&json) {
+                Ok(command) => commands.push(QueuedMatchmakingCommand {
+                    command_id,
+                    command,
+                }),
+                Err(e) => {
+                    log::error!("[{region_system_name}] Cannot decode command {command_id}: {e}");
+                    self.dead_letter(region_system_name, &command_id, &json, &e.to_string())
+                        .await?;
+                }
+            }
+        }
+
+        Ok(commands)
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    async fn dead_letter(
+        &self,
+        region_system_name: &str,
+        command_id: &CommandId,
+        json: &str,
+        reason: &str,
+    ) -> Result<(), Error> {
+        let key = self.build_dead_letter_queue_key_name(region_system_name);
+        let _: String = self
+            .client
+            .xadd(
+                key,
+                "*",
+                [
+                    (COMMAND_ID_FIELD, command_id.as_str()),
+                    (COMMAND_FIELD, json),
+                    (REASON_FIELD, reason),
+                ],
+                XAddOptions::default().trim_options(XTrimOptions::max_len(
+                    XTrimOperator::Approximately,
+                    MAX_DEAD_LETTER_COMMANDS,
+                )),
+            )
+            .await?;
+
+        // moving a command to the dead letter queue is idempotent, it is not fenced
+        let key = self.build_queue_key_name(region_system_name);
+        self.client
+            .xack(
+                key.clone(),
+                redis_key_names::COMMAND_CONSUMER_GROUP,
+                command_id.as_str(),
+            )
+            .await?;
+        self.client.xdel(key, command_id.as_str()).await?;
+        Ok(())
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    /// Claim pending entries, moving the ones delivered too many times to the dead letter queue
+    async fn claim_pending_entries(
+        &self,
+        region_system_name: &str,
+        key: &str,
+        pending: Vec<XPendingMessageResult>,
+    ) -> Result<Vec<QueuedMatchmakingCommand>, Error> {
+        let times_delivered = pending
+            .iter()
+            .map(|p| (p.message_id.clone(), p.times_delivered))
+            .collect::<HashMap<_, _>>();
+
+        // claiming increments the delivery counter
+        let entries: Vec<StreamEntry<String>> = self
+            .client
+            .xclaim(
+                key,
+                redis_key_names::COMMAND_CONSUMER_GROUP,
+                redis_key_names::COMMAND_CONSUMER_GROUP,
+                0,
+                pending
+                    .into_iter()
+                    .map(|p| p.message_id)
+                    .collect::<Vec<_>>(),
+                Default::default(),
+            )
+            .await?;
+
+        let mut entries_to_decode = Vec::with_capacity(entries.len());
+        for mut entry in entries {
+            let num_deliveries = times_delivered
+                .get(&entry.stream_id)
+                .copied()
+                .unwrap_or_default();
+            if num_deliveries >= MAX_COMMAND_DELIVERIES {
+                let command_id = CommandId::new(entry.stream_id);
+                let json = entry.items.remove(COMMAND_FIELD).unwrap_or_default();
+                log::error!(
+                    "[{region_system_name}] Command {command_id} delivered {num_deliveries} times without acknowledgement"
+                );
+                self.dead_letter(
+                    region_system_name,
+                    &command_id,
+                    &json,
+                    &format!("delivered {num_deliveries} times without acknowledgement"),
+                )
+                .await?;
+            } else {
+                entries_to_decode.push(entry);
+            }
+        }
+
+        self.decode_entries(region_system_name, entries_to_decode)
+            .await
+    }
+}
+
+impl MatchmakingCommandDAL for RedisMatchmakingCommandDAL {
+    //-------------------------------------------------------------------------------------------------
+    fn queue_command<'a>(
+        &'a self,
+        region_system_name: &'a str,
+        command: &'a MatchmakingCommand,
+    ) -> BoxFuture<'a, Result<CommandId, Error>> {
+        Box::pin(async move {
+            let key = self.build_queue_key_name(region_system_name);
+            let json = serde_json::to_string(command)?;
+            let stream_id: String = self
+                .client
+                .xadd(key, "*", [(COMMAND_FIELD, json)], Default::default())
+                .await?;
+            Ok(CommandId::new(stream_id))
+        })
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn dequeue_commands<'a>(
+        &'a self,
+        region_system_name: &'a str,
+    ) -> BoxFuture<'a, Result<Vec<QueuedMatchmakingCommand>, Error>> {
+        Box::pin(async move {
+            self.ensure_consumer_group(region_system_name).await?;
+
+            let key = self.build_queue_key_name(region_system_name);
+            let results: Vec<(String, Vec<StreamEntry<String>>)> = self
+                .client
+                .xreadgroup(
+                    redis_key_names::COMMAND_CONSUMER_GROUP,
+                    redis_key_names::COMMAND_CONSUMER_GROUP,
+                    XReadGroupOptions::default().count(MAX_COMMANDS_PER_DEQUEUE),
+                    key,
+                    ">",
+                )
+                .await?;
+
+            let entries = results
+                .into_iter()
+                .flat_map(|(_, entries)| entries)
+                .collect();
+            self.decode_entries(region_system_name, entries).await
+        })
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn recover_commands<'a>(
+        &'a self,
+        region_system_name: &'a str,
+    ) -> BoxFuture<'a, Result<Vec<QueuedMatchmakingCommand>, Error>> {
+        Box::pin(async move {
+            self.ensure_consumer_group(region_system_name).await?;
+
+            let key = self.build_queue_key_name(region_system_name);
+            let mut commands = Vec::new();
+            // page through the whole pending entries list, claimed entries stay in it
+            let mut start = "-".to_owned();
+            loop {
+                let pending = self
+                    .client
+                    .xpending_with_options(
+                        key.clone(),
+                        redis_key_names::COMMAND_CONSUMER_GROUP,
+                        XPendingOptions::default()
+                            .start(start.clone())
+                            .end("+")
+                            .count(MAX_COMMANDS_PER_DEQUEUE),
+                    )
+                    .await?;
+
+                let Some(last_pending) = pending.last() else {
+                    break;
+                };
+                start = format!("({}", last_pending.message_id);
+
+                commands.extend(
+                    self.claim_pending_entries(region_system_name, &key, pending)
+                        .await?,
+                );
+            }
+
+            Ok(commands)
+        })
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn get_processed_commands<'a>(
+        &'a self,
+        region_system_name: &'a str,
+        command_ids: &'a [CommandId],
+    ) -> BoxFuture<'a, Result<HashSet<CommandId>, Error>> {
+        Box::pin(async move {
+            if command_ids.is_empty() {
+                return Ok(HashSet::new());
+            }
+
+            let key = self.build_processed_commands_key_name(region_system_name);
+            let scores: Vec<Option<f64>> = self
+                .client
+                .zmscore(
+                    key,
+                    command_ids.iter().map(|id| id.as_str()).collect::<Vec<_>>(),
+                )
+                .await?;
+
+            Ok(command_ids
+                .iter()
+                .zip(scores)
+                .filter_map(|(id, score)| score.map(|_| id.clone()))
+                .collect())
+        })
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn save_processed_commands<'a>(
+        &'a self,
+        region_system_name: &'a str,
+        fencing_token: u64,
+        command_ids: &'a [CommandId],
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        Box::pin(async move {
+            if command_ids.is_empty() {
+                return Ok(());
+            }
+
+            // scored by processing time, to forget the commands processed before the retention
+            let key = self.build_processed_commands_key_name(region_system_name);
+            let now = unix_now();
+            let retention = PROCESSED_COMMAND_RETENTION.as_secs();
+            let mut writes = RedisFencedWrites::new(region_system_name, fencing_token);
+            writes.write(
+                "ZADD",
+                key.clone(),
+                command_ids
+                    .iter()
+                    .flat_map(|id| [now.to_string(), id.to_string()]),
+            );
+            writes.write(
+                "ZREMRANGEBYSCORE",
+                key.clone(),
+                [
+                    "-inf".to_owned(),
+                    format!("({}", now.saturating_sub(retention)),
+                ],
+            );
+            writes.write("EXPIRE", key, [retention]);
+            writes.execute(&self.client).await
+        })
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn ack_commands<'a>(
+        &'a self,
+        region_system_name: &'a str,
+        fencing_token: u64,
+        command_ids: &'a [CommandId],
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        Box::pin(async move {
+            let key = self.build_queue_key_name(region_system_name);
+            let mut writes = RedisFencedWrites::new(region_system_name, fencing_token);
+            for command_id in command_ids {
+                writes.write(
+                    "XACK",
+                    key.clone(),
+                    [redis_key_names::COMMAND_CONSUMER_GROUP, command_id.as_str()],
+                );
+                writes.write("XDEL", key.clone(), [command_id.as_str()]);
+            }
+            writes.execute(&self.client).await
+        })
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn get_queue_length<'a>(
+        &'a self,
+        region_system_name: &'a str,
+    ) -> BoxFuture<'a, Result<usize, Error>> {
+        Box::pin(async move {
+            // acknowledged commands are deleted from the stream
+            let key = self.build_queue_key_name(region_system_name);
+            Ok(self.client.xlen(key).await?)
+        })
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn get_dead_letter_commands<'a>(
+        &'a self,
+        region_system_name: &'a str,
+    ) -> BoxFuture<'a, Result<Vec<DeadLetterCommand>, Error>> {
+        Box::pin(async move {
+            let key = self.build_dead_letter_queue_key_name(region_system_name);
+            let entries: Vec<StreamEntry<String>> = self.client.xrange(key, "-", "+", None).await?;
+
+            Ok(entries
+                .into_iter()
+                .map(|mut entry| DeadLetterCommand {
+                    command_id: CommandId::new(
+                        entry.items.remove(COMMAND_ID_FIELD).unwrap_or_default(),
+                    ),
+                    command: entry.items.remove(COMMAND_FIELD).unwrap_or_default(),
+                    reason: entry.items.remove(REASON_FIELD).unwrap_or_default(),
+                })
+                .collect())
+        })
+    }
+}
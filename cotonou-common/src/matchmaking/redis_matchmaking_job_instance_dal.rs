@@ -0,0 +1,84 @@
+use crate::{
+    matchmaking::{
+        redis_key_names, Error, MatchmakingJobInstance, MatchmakingJobInstanceDAL,
+        JOB_INSTANCE_TIMEOUT,
+    },
+    redis::RedisConnectionManager,
+    unix_now,
+};
+use futures::future::BoxFuture;
+use rustis::{client::Client, commands::HashCommands};
+use std::collections::HashMap;
+
+#[derive(Clone)]
+pub struct RedisMatchmakingJobInstanceDAL {
+    client: Client,
+}
+
+impl RedisMatchmakingJobInstanceDAL {
+    //-------------------------------------------------------------------------------------------------
+    pub fn new(redis_connection_manager: &RedisConnectionManager) -> Self {
+        Self {
+            client: redis_connection_manager.get_client("MATCHMAKING").unwrap(),
+        }
+    }
+}
+
+impl MatchmakingJobInstanceDAL for RedisMatchmakingJobInstanceDAL {
+    //-------------------------------------------------------------------------------------------------
+    fn report_instance<'a>(
+        &'a self,
+        instance: &'a MatchmakingJobInstance,
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        Box::pin(async move {
+            let json = serde_json::to_string(instance)?;
+            self.client
+                .hset(
+                    redis_key_names::JOB_INSTANCES,
+                    [(instance.instance_id.as_str(), json)],
+                )
+                .await?;
+            Ok(())
+        })
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn get_instances(&self) -> BoxFuture<'_, Result<Vec<MatchmakingJobInstance>, Error>> {
+        Box::pin(async move {
+            let values: HashMap<String, String> =
+                self.client.hgetall(redis_key_names::JOB_INSTANCES).await?;
+
+            let now = unix_now();
+            let mut instances = Vec::with_capacity(values.len());
+            let mut dead_instance_ids = Vec::new();
+
+            for (instance_id, json) in values {
+                match serde_json::from_str::<MatchmakingJobInstance>(&json) {
+                    Ok(instance) if instance.last_heartbeat_time + JOB_INSTANCE_TIMEOUT >= now => {
+                        instances.push(instance)
+                    }
+                    _ => dead_instance_ids.push(instance_id),
+                }
+            }
+
+            if !dead_instance_ids.is_empty() {
+                self.client
+                    .hdel(redis_key_names::JOB_INSTANCES, dead_instance_ids)
+                    .await?;
+            }
+
+            instances.sort_by(|i1, i2| i1.instance_id.cmp(&i2.instance_id));
+            Ok(instances)
+        })
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn remove_instance<'a>(&'a self, instance_id: &'a str) -> BoxFuture<'a, Result<(), Error>> {
+        Box::pin(async move {
+            self.client
+                .hdel(redis_key_names::JOB_INSTANCES, instance_id)
+                .await?;
+            Ok(())
+        })
+    }
+}
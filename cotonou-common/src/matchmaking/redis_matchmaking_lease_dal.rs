@@ -0,0 +1,297 @@
+use crate::{
+    matchmaking::{redis_key_names, Error, MatchmakingLeaseDAL},
+    redis::RedisConnectionManager,
+};
+use futures::future::BoxFuture;
+use rustis::{
+    client::Client,
+    commands::{CallBuilder, GenericCommands, ScriptingCommands, SortedSetCommands},
+};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// KEYS[1] = lease, KEYS[2] = fencing token, ARGV[1] = holder id, ARGV[2] = duration in ms
+const ACQUIRE_LEASE_SCRIPT: &str = r#"
+if redis.call('EXISTS', KEYS[1]) == 1 then
+    return false
+end
+local fencing_token = redis.call('INCR', KEYS[2])
+redis.call('SET', KEYS[1], ARGV[1] .. ':' .. fencing_token, 'PX', ARGV[2])
+return fencing_token
+"#;
+
+/// KEYS[1] = lease, ARGV[1] = lease value, ARGV[2] = duration in ms
+const RENEW_LEASE_SCRIPT: &str = r#"
+if redis.call('GET', KEYS[1]) == ARGV[1] then
+    return redis.call('PEXPIRE', KEYS[1], ARGV[2])
+end
+return 0
+"#;
+
+/// KEYS[1] = lease, ARGV[1] = lease value
+const CHECK_LEASE_SCRIPT: &str = r#"
+if redis.call('GET', KEYS[1]) == ARGV[1] then
+    return 1
+end
+return 0
+"#;
+
+/// KEYS[1] = lease, ARGV[1] = lease value
+const RELEASE_LEASE_SCRIPT: &str = r#"
+if redis.call('GET', KEYS[1]) == ARGV[1] then
+    return redis.call('DEL', KEYS[1])
+end
+return 0
+"#;
+
+#[derive(Clone)]
+pub struct RedisMatchmakingLeaseDAL {
+    client: Client,
+}
+
+impl RedisMatchmakingLeaseDAL {
+    //-------------------------------------------------------------------------------------------------
+    pub fn new(redis_connection_manager: &RedisConnectionManager) -> Self {
+        Self {
+            client: redis_connection_manager.get_client("MATCHMAKING").unwrap(),
+        }
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    /// Number of unexpired leases of a holder.
+    /// The leases of a holder span several regions, so they are not counted in the lease scripts,
+    /// whose keys must live in the slot of their region on a Redis Cluster
+    async fn count_holder_leases(&self, holder_id: &str) -> Result<usize, Error> {
+        let key = build_lease_holder_key(holder_id);
+        self.client
+            .zremrangebyscore(
+                key.clone(),
+                "-inf".to_owned(),
+                unix_now_millis().to_string(),
+            )
+            .await?;
+        Ok(self.client.zcard(key).await?)
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    async fn add_holder_lease(
+        &self,
+        region_system_name: &str,
+        holder_id: &str,
+        duration: Duration,
+    ) -> Result<(), Error> {
+        let key = build_lease_holder_key(holder_id);
+        let duration = duration.as_millis() as u64;
+        self.client
+            .zadd(
+                key.clone(),
+                [((unix_now_millis() + duration) as f64, region_system_name)],
+                Default::default(),
+            )
+            .await?;
+        self.client
+            .pexpire(key, duration, Default::default())
+            .await?;
+        Ok(())
+    }
+}
+
+impl MatchmakingLeaseDAL for RedisMatchmakingLeaseDAL {
+    //-------------------------------------------------------------------------------------------------
+    fn acquire_lease<'a>(
+        &'a self,
+        region_system_name: &'a str,
+        holder_id: &'a str,
+        max_leases: usize,
+        duration: Duration,
+    ) -> BoxFuture<'a, Result<Option<u64>, Error>> {
+        Box::pin(async move {
+            // not atomic with the acquisition: concurrent acquisitions of a holder may exceed
+            // max_leases, the extra regions are then handed over at the next rebalance
+            if self.count_holder_leases(holder_id).await? >= max_leases {
+                return Ok(None);
+            }
+
+            let fencing_token: Option<u64> = self
+                .client
+                .eval(
+                    CallBuilder::script(ACQUIRE_LEASE_SCRIPT)
+                        .keys(build_acquire_lease_keys(region_system_name))
+                        .args([holder_id.to_owned(), duration.as_millis().to_string()]),
+                )
+                .await?;
+
+            if fencing_token.is_some() {
+                self.add_holder_lease(region_system_name, holder_id, duration)
+                    .await?;
+            }
+            Ok(fencing_token)
+        })
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn renew_lease<'a>(
+        &'a self,
+        region_system_name: &'a str,
+        holder_id: &'a str,
+        fencing_token: u64,
+        duration: Duration,
+    ) -> BoxFuture<'a, Result<bool, Error>> {
+        Box::pin(async move {
+            let renewed: u64 = self
+                .client
+                .eval(
+                    CallBuilder::script(RENEW_LEASE_SCRIPT)
+                        .keys(build_renew_lease_keys(region_system_name))
+                        .args([
+                            build_lease_value(holder_id, fencing_token),
+                            duration.as_millis().to_string(),
+                        ]),
+                )
+                .await?;
+
+            if renewed == 1 {
+                self.add_holder_lease(region_system_name, holder_id, duration)
+                    .await?;
+            }
+            Ok(renewed == 1)
+        })
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn check_lease<'a>(
+        &'a self,
+        region_system_name: &'a str,
+        holder_id: &'a str,
+        fencing_token: u64,
+    ) -> BoxFuture<'a, Result<bool, Error>> {
+        Box::pin(async move {
+            let held: u64 = self
+                .client
+                .eval(
+                    CallBuilder::script(CHECK_LEASE_SCRIPT)
+                        .keys(build_check_lease_keys(region_system_name))
+                        .args(build_lease_value(holder_id, fencing_token)),
+                )
+                .await?;
+            Ok(held == 1)
+        })
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn release_lease<'a>(
+        &'a self,
+        region_system_name: &'a str,
+        holder_id: &'a str,
+        fencing_token: u64,
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        Box::pin(async move {
+            let _: u64 = self
+                .client
+                .eval(
+                    CallBuilder::script(RELEASE_LEASE_SCRIPT)
+                        .keys(build_release_lease_keys(region_system_name))
+                        .args(build_lease_value(holder_id, fencing_token)),
+                )
+                .await?;
+
+            self.client
+                .zrem(build_lease_holder_key(holder_id), region_system_name)
+                .await?;
+            Ok(())
+        })
+    }
+}
+
+//-------------------------------------------------------------------------------------------------
+fn build_acquire_lease_keys(region_system_name: &str) -> [String; 2] {
+    [
+        build_lease_key(region_system_name),
+        build_fencing_token_key(region_system_name),
+    ]
+}
+
+//-------------------------------------------------------------------------------------------------
+fn build_renew_lease_keys(region_system_name: &str) -> [String; 1] {
+    [build_lease_key(region_system_name)]
+}
+
+//-------------------------------------------------------------------------------------------------
+fn build_check_lease_keys(region_system_name: &str) -> [String; 1] {
+    [build_lease_key(region_system_name)]
+}
+
+//-------------------------------------------------------------------------------------------------
+fn build_release_lease_keys(region_system_name: &str) -> [String; 1] {
+    [build_lease_key(region_system_name)]
+}
+
+//-------------------------------------------------------------------------------------------------
+#[inline]
+fn build_lease_key(region_system_name: &str) -> String {
+    format!("{{{region_system_name}}}:{}", redis_key_names::LEASE)
+}
+
+//-------------------------------------------------------------------------------------------------
+#[inline]
+pub(crate) fn build_fencing_token_key(region_system_name: &str) -> String {
+    format!(
+        "{{{region_system_name}}}:{}",
+        redis_key_names::LEASE_FENCING_TOKEN
+    )
+}
+
+//-------------------------------------------------------------------------------------------------
+#[inline]
+fn build_lease_holder_key(holder_id: &str) -> String {
+    format!("{}:{holder_id}", redis_key_names::LEASE_HOLDER)
+}
+
+//-------------------------------------------------------------------------------------------------
+#[inline]
+fn build_lease_value(holder_id: &str, fencing_token: u64) -> String {
+    format!("{holder_id}:{fencing_token}")
+}
+
+//-------------------------------------------------------------------------------------------------
+#[inline]
+fn unix_now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Hash slot of a key on a Redis Cluster, from its hash tag if any
+    fn get_hash_slot(key: &str) -> u16 {
+        let hash_tag = key
+            .find('{')
+            .and_then(|start| {
+                key[start + 1..]
+                    .find('}')
+                    .filter(|len| *len > 0)
+                    .map(|len| &key[start + 1..start + 1 + len])
+            })
+            .unwrap_or(key);
+        crc16::State::<crc16::XMODEM>::calculate(hash_tag.as_bytes()) % 16384
+    }
+
+    #[test]
+    fn script_keys_are_in_the_slot_of_their_region() {
+        for region_system_name in ["eu-west-1", "us-east-1"] {
+            let region_slot = get_hash_slot(&format!("{{{region_system_name}}}"));
+            let keys = build_acquire_lease_keys(region_system_name)
+                .into_iter()
+                .chain(build_renew_lease_keys(region_system_name))
+                .chain(build_check_lease_keys(region_system_name))
+                .chain(build_release_lease_keys(region_system_name));
+
+            for key in keys {
+                assert_eq!(region_slot, get_hash_slot(&key), "{key}");
+            }
+        }
+    }
+}
@@ -0,0 +1,356 @@
+use crate::{
+    matchmaking::{
+        redis_fenced_writes::RedisFencedWrites, Error, MatchmakingSession, MatchmakingSessionDAL,
+        SessionId,
+    },
+    redis::RedisConnectionManager,
+    types::ProfileId,
+};
+use futures::future::BoxFuture;
+use rustis::{
+    client::Client,
+    commands::{SortedSetCommands, StringCommands},
+};
+use std::iter::once;
+
+#[derive(Clone)]
+pub struct RedisMatchmakingSessionDAL {
+    client: Client,
+}
+
+impl RedisMatchmakingSessionDAL {
+    //-------------------------------------------------------------------------------------------------
+    pub fn new(redis_connection_manager: &RedisConnectionManager) -> Self {
+        Self {
+            client: redis_connection_manager.get_client("MATCHMAKING").unwrap(),
+        }
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn create_session_ids(
+        writes: &mut RedisFencedWrites,
+        region_system_name: &str,
+        sessions: &[&MatchmakingSession],
+    ) {
+        let key = build_session_queue_key(region_system_name);
+        for session in sessions {
+            writes.write(
+                "ZADD",
+                key.clone(),
+                [
+                    session.creation_time.to_string(),
+                    session.session_id.to_string(),
+                ],
+            );
+        }
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn set_session_values(
+        writes: &mut RedisFencedWrites,
+        region_system_name: &str,
+        sessions: &[&MatchmakingSession],
+    ) -> Result<(), Error> {
+        for session in sessions {
+            writes.write(
+                "SET",
+                build_session_key(region_system_name, &session.session_id),
+                [serde_json::to_string(session)?],
+            );
+        }
+        Ok(())
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    /// Sessions currently stored, read before they are updated or deleted
+    async fn get_session_values(
+        &self,
+        region_system_name: &str,
+        session_ids: &[SessionId],
+    ) -> Result<Vec<MatchmakingSession>, Error> {
+        let keys = session_ids
+            .iter()
+            .map(|id| build_session_key(region_system_name, id))
+            .collect::<Vec<_>>();
+        let values: Vec<Option<String>> = self.client.mget(keys).await?;
+
+        let sessions = values
+            .into_iter()
+            .flatten()
+            .map(|v| serde_json::from_str(&v))
+            .collect::<serde_json::Result<Vec<MatchmakingSession>>>()?;
+        Ok(sessions)
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    /// Index the sessions by player, to find the session of a player without loading all the sessions
+    fn create_player_sessions(
+        writes: &mut RedisFencedWrites,
+        region_system_name: &str,
+        sessions: &[&MatchmakingSession],
+    ) {
+        for session in sessions {
+            for player in &session.players {
+                writes.write(
+                    "SET",
+                    build_player_session_key(region_system_name, player.profile_id),
+                    [session.session_id],
+                );
+            }
+        }
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    /// Remove players from the index, unless they have been matched to another session since
+    async fn delete_player_sessions(
+        &self,
+        writes: &mut RedisFencedWrites,
+        region_system_name: &str,
+        player_sessions: &[(ProfileId, SessionId)],
+    ) -> Result<(), Error> {
+        if player_sessions.is_empty() {
+            return Ok(());
+        }
+
+        let keys = player_sessions
+            .iter()
+            .map(|(profile_id, _)| build_player_session_key(region_system_name, *profile_id))
+            .collect::<Vec<_>>();
+        let current_session_ids: Vec<Option<SessionId>> = self.client.mget(keys.clone()).await?;
+
+        for ((key, (_, session_id)), current_session_id) in keys
+            .into_iter()
+            .zip(player_sessions)
+            .zip(current_session_ids)
+        {
+            if current_session_id == Some(*session_id) {
+                writes.delete(key);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl MatchmakingSessionDAL for RedisMatchmakingSessionDAL {
+    //-------------------------------------------------------------------------------------------------
+    fn get_sessions<'a>(
+        &'a self,
+        region_system_name: &'a str,
+    ) -> BoxFuture<'a, Result<Vec<MatchmakingSession>, Error>> {
+        Box::pin(async move {
+            let session_session_key = build_session_queue_key(region_system_name);
+
+            let session_ids: Vec<SessionId> = self
+                .client
+                .zrange(session_session_key, 0, -1, Default::default())
+                .await?;
+
+            if session_ids.is_empty() {
+                return Ok(Vec::new());
+            }
+
+            let session_keys = session_ids
+                .into_iter()
+                .map(|id| build_session_key(region_system_name, &id))
+                .collect::<Vec<_>>();
+            let values: Vec<String> = self.client.mget(session_keys).await?;
+
+            let sessions = values
+                .into_iter()
+                .map(|v| serde_json::from_str(&v))
+                .collect::<serde_json::Result<Vec<MatchmakingSession>>>()?;
+
+            Ok(sessions)
+        })
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn get_session<'a>(
+        &'a self,
+        region_system_name: &'a str,
+        session_id: &'a SessionId,
+    ) -> BoxFuture<'a, Result<Option<MatchmakingSession>, Error>> {
+        Box::pin(async move {
+            let session_key = build_session_key(region_system_name, session_id);
+
+            let session_json: Option<String> = self.client.get(session_key).await?;
+            if let Some(session_json) = session_json {
+                Ok(Some(serde_json::from_str(&session_json)?))
+            } else {
+                Ok(None)
+            }
+        })
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn get_player_session<'a>(
+        &'a self,
+        region_system_name: &'a str,
+        profile_id: ProfileId,
+    ) -> BoxFuture<'a, Result<Option<MatchmakingSession>, Error>> {
+        Box::pin(async move {
+            let player_session_key = build_player_session_key(region_system_name, profile_id);
+
+            let session_id: Option<SessionId> = self.client.get(player_session_key).await?;
+            let Some(session_id) = session_id else {
+                return Ok(None);
+            };
+
+            let session = self.get_session(region_system_name, &session_id).await?;
+            Ok(session.filter(|s| s.players.iter().any(|p| p.profile_id == profile_id)))
+        })
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn create_sessions<'a>(
+        &'a self,
+        region_system_name: &'a str,
+        fencing_token: u64,
+        sessions: &'a [&'a MatchmakingSession],
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        Box::pin(async move {
+            let mut writes = RedisFencedWrites::new(region_system_name, fencing_token);
+            Self::create_session_ids(&mut writes, region_system_name, sessions);
+            Self::set_session_values(&mut writes, region_system_name, sessions)?;
+            Self::create_player_sessions(&mut writes, region_system_name, sessions);
+            writes.execute(&self.client).await
+        })
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn update_sessions<'a>(
+        &'a self,
+        region_system_name: &'a str,
+        fencing_token: u64,
+        sessions: &'a [&'a MatchmakingSession],
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        Box::pin(async move {
+            if sessions.is_empty() {
+                return Ok(());
+            }
+
+            let session_ids = sessions.iter().map(|s| s.session_id).collect::<Vec<_>>();
+            let removed_players = self
+                .get_session_values(region_system_name, &session_ids)
+                .await?
+                .into_iter()
+                .flat_map(|old_session| {
+                    let session = sessions
+                        .iter()
+                        .find(|s| s.session_id == old_session.session_id);
+                    old_session
+                        .players
+                        .into_iter()
+                        .filter(move |p| {
+                            session.is_some_and(|s| {
+                                s.players.iter().all(|sp| sp.profile_id != p.profile_id)
+                            })
+                        })
+                        .map(move |p| (p.profile_id, old_session.session_id))
+                })
+                .collect::<Vec<_>>();
+
+            // removed players are unindexed before the players of the sessions are indexed again,
+            // in case a player left a session for another one updated in the same batch
+            let mut writes = RedisFencedWrites::new(region_system_name, fencing_token);
+            self.delete_player_sessions(&mut writes, region_system_name, &removed_players)
+                .await?;
+            Self::set_session_values(&mut writes, region_system_name, sessions)?;
+            Self::create_player_sessions(&mut writes, region_system_name, sessions);
+            writes.execute(&self.client).await
+        })
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn delete_sessions<'a>(
+        &'a self,
+        region_system_name: &'a str,
+        fencing_token: u64,
+        session_ids: &'a [SessionId],
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        Box::pin(async move {
+            if session_ids.is_empty() {
+                return Ok(());
+            }
+
+            // the players are read from the sessions, before they are deleted
+            let player_sessions = self
+                .get_session_values(region_system_name, session_ids)
+                .await?
+                .into_iter()
+                .flat_map(|s| {
+                    s.players
+                        .into_iter()
+                        .map(move |p| (p.profile_id, s.session_id))
+                })
+                .collect::<Vec<_>>();
+
+            let mut writes = RedisFencedWrites::new(region_system_name, fencing_token);
+            self.delete_player_sessions(&mut writes, region_system_name, &player_sessions)
+                .await?;
+
+            let session_queue_key = build_session_queue_key(region_system_name);
+            for session_id in session_ids {
+                writes.write("ZREM", session_queue_key.clone(), [session_id]);
+                writes.delete(build_session_key(region_system_name, session_id));
+            }
+
+            writes.execute(&self.client).await
+        })
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn reset<'a>(
+        &'a self,
+        region_system_name: &'a str,
+        fencing_token: u64,
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        Box::pin(async move {
+            let session_queue_key = build_session_queue_key(region_system_name);
+            let session_ids: Vec<SessionId> = self
+                .client
+                .zrange(session_queue_key.clone(), 0, -1, Default::default())
+                .await?;
+
+            let player_session_keys = if session_ids.is_empty() {
+                Vec::new()
+            } else {
+                self.get_session_values(region_system_name, &session_ids)
+                    .await?
+                    .into_iter()
+                    .flat_map(|s| s.players)
+                    .map(|p| build_player_session_key(region_system_name, p.profile_id))
+                    .collect::<Vec<_>>()
+            };
+
+            let mut writes = RedisFencedWrites::new(region_system_name, fencing_token);
+            for key in session_ids
+                .into_iter()
+                .map(|id| build_session_key(region_system_name, &id))
+                .chain(player_session_keys)
+                .chain(once(session_queue_key))
+            {
+                writes.delete(key);
+            }
+            writes.execute(&self.client).await
+        })
+    }
+}
+
+//-------------------------------------------------------------------------------------------------
+#[inline]
+fn build_session_queue_key(region_system_name: &str) -> String {
+    format!("{{{region_system_name}}}:mmsq")
+}
+
+//-------------------------------------------------------------------------------------------------
+#[inline]
+fn build_session_key(region_system_name: &str, session_id: &SessionId) -> String {
+    format!("{{{region_system_name}}}:mms:{session_id}")
+}
+
+//-------------------------------------------------------------------------------------------------
+#[inline]
+fn build_player_session_key(region_system_name: &str, profile_id: ProfileId) -> String {
+    format!("{{{region_system_name}}}:mmps:{profile_id}")
+}
@@ -0,0 +1,297 @@
+use crate::{
+    matchmaking::{
+        redis_fenced_writes::RedisFencedWrites, Error, MatchmakingTicket, MatchmakingTicketDAL,
+    },
+    redis::RedisConnectionManager,
+    types::ProfileId,
+};
+use futures::future::BoxFuture;
+use rustis::{
+    client::Client,
+    commands::{SortedSetCommands, StringCommands},
+};
+use std::{collections::HashSet, iter::once};
+
+#[derive(Clone)]
+pub struct RedisMatchmakingTicketDAL {
+    client: Client,
+}
+
+impl RedisMatchmakingTicketDAL {
+    pub fn new(redis_connection_manager: &RedisConnectionManager) -> Self {
+        Self {
+            client: redis_connection_manager.get_client("MATCHMAKING").unwrap(),
+        }
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn create_ticket_ids(
+        writes: &mut RedisFencedWrites,
+        region_system_name: &str,
+        tickets: &[&MatchmakingTicket],
+    ) {
+        let key = build_ticket_queue_key(region_system_name);
+        for ticket in tickets {
+            writes.write(
+                "ZADD",
+                key.clone(),
+                [
+                    ticket.creation_time.to_string(),
+                    ticket.owner_profile_id.to_string(),
+                ],
+            );
+        }
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn set_ticket_values(
+        writes: &mut RedisFencedWrites,
+        region_system_name: &str,
+        tickets: &[&MatchmakingTicket],
+    ) -> Result<(), Error> {
+        for ticket in tickets {
+            writes.write(
+                "SET",
+                build_ticket_key(region_system_name, ticket.owner_profile_id),
+                [serde_json::to_string(ticket)?],
+            );
+        }
+        Ok(())
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    /// Tickets waiting for a session are also queued by game mode, to get their position in queue
+    fn update_game_mode_ticket_ids(
+        writes: &mut RedisFencedWrites,
+        region_system_name: &str,
+        tickets: &[&MatchmakingTicket],
+    ) {
+        for ticket in tickets {
+            let key = build_game_mode_ticket_queue_key(region_system_name, &ticket.game_mode);
+            if ticket.session_id.is_none() {
+                writes.write(
+                    "ZADD",
+                    key,
+                    [
+                        ticket.creation_time.to_string(),
+                        ticket.owner_profile_id.to_string(),
+                    ],
+                );
+            } else {
+                writes.write("ZREM", key, [ticket.owner_profile_id]);
+            }
+        }
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    /// Must be called before the ticket values are deleted, they hold the game mode of the tickets
+    async fn delete_game_mode_ticket_ids(
+        &self,
+        writes: &mut RedisFencedWrites,
+        region_system_name: &str,
+        ticket_ids: &[ProfileId],
+    ) -> Result<(), Error> {
+        let keys = ticket_ids
+            .iter()
+            .map(|id| build_ticket_key(region_system_name, *id))
+            .collect::<Vec<_>>();
+        let values: Vec<Option<String>> = self.client.mget(keys).await?;
+
+        for value in values.into_iter().flatten() {
+            let ticket: MatchmakingTicket = serde_json::from_str(&value)?;
+            writes.write(
+                "ZREM",
+                build_game_mode_ticket_queue_key(region_system_name, &ticket.game_mode),
+                [ticket.owner_profile_id],
+            );
+        }
+        Ok(())
+    }
+}
+
+impl MatchmakingTicketDAL for RedisMatchmakingTicketDAL {
+    //-------------------------------------------------------------------------------------------------
+    fn get_tickets<'a>(
+        &'a self,
+        region_system_name: &'a str,
+    ) -> BoxFuture<'a, Result<Vec<MatchmakingTicket>, Error>> {
+        Box::pin(async move {
+            let ticket_queue_key = build_ticket_queue_key(region_system_name);
+
+            let profile_ids: Vec<ProfileId> = self
+                .client
+                .zrange(ticket_queue_key, 0, -1, Default::default())
+                .await?;
+
+            if profile_ids.is_empty() {
+                return Ok(Vec::new());
+            }
+
+            let ticket_keys = profile_ids
+                .into_iter()
+                .map(|id| build_ticket_key(region_system_name, id))
+                .collect::<Vec<_>>();
+            let values: Vec<String> = self.client.mget(ticket_keys).await?;
+
+            let tickets = values
+                .into_iter()
+                .map(|v| serde_json::from_str(&v))
+                .collect::<serde_json::Result<Vec<MatchmakingTicket>>>()?;
+
+            Ok(tickets)
+        })
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn get_ticket<'a>(
+        &'a self,
+        region_system_name: &'a str,
+        owner_profile_id: ProfileId,
+    ) -> BoxFuture<'a, Result<Option<MatchmakingTicket>, Error>> {
+        Box::pin(async move {
+            let ticket_key = build_ticket_key(region_system_name, owner_profile_id);
+
+            let ticket_json: Option<String> = self.client.get(ticket_key).await?;
+            if let Some(ticket_json) = ticket_json {
+                Ok(Some(serde_json::from_str(&ticket_json)?))
+            } else {
+                Ok(None)
+            }
+        })
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn get_queue_position<'a>(
+        &'a self,
+        region_system_name: &'a str,
+        ticket: &'a MatchmakingTicket,
+    ) -> BoxFuture<'a, Result<usize, Error>> {
+        Box::pin(async move {
+            let key = build_game_mode_ticket_queue_key(region_system_name, &ticket.game_mode);
+            let num_older_tickets = self
+                .client
+                .zcount(key, "-inf", format!("({}", ticket.creation_time))
+                .await?;
+            Ok(num_older_tickets + 1)
+        })
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn create_tickets<'a>(
+        &'a self,
+        region_system_name: &'a str,
+        fencing_token: u64,
+        tickets: &'a [&'a MatchmakingTicket],
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        Box::pin(async move {
+            let mut writes = RedisFencedWrites::new(region_system_name, fencing_token);
+            Self::create_ticket_ids(&mut writes, region_system_name, tickets);
+            Self::set_ticket_values(&mut writes, region_system_name, tickets)?;
+            Self::update_game_mode_ticket_ids(&mut writes, region_system_name, tickets);
+            writes.execute(&self.client).await
+        })
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn update_tickets<'a>(
+        &'a self,
+        region_system_name: &'a str,
+        fencing_token: u64,
+        tickets: &'a [&'a MatchmakingTicket],
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        Box::pin(async move {
+            let mut writes = RedisFencedWrites::new(region_system_name, fencing_token);
+            Self::set_ticket_values(&mut writes, region_system_name, tickets)?;
+            Self::update_game_mode_ticket_ids(&mut writes, region_system_name, tickets);
+            writes.execute(&self.client).await
+        })
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn delete_tickets<'a>(
+        &'a self,
+        region_system_name: &'a str,
+        fencing_token: u64,
+        ticket_ids: &'a [ProfileId],
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        Box::pin(async move {
+            if ticket_ids.is_empty() {
+                return Ok(());
+            }
+
+            let mut writes = RedisFencedWrites::new(region_system_name, fencing_token);
+            self.delete_game_mode_ticket_ids(&mut writes, region_system_name, ticket_ids)
+                .await?;
+
+            let ticket_queue_key = build_ticket_queue_key(region_system_name);
+            for ticket_id in ticket_ids {
+                writes.write("ZREM", ticket_queue_key.clone(), [ticket_id]);
+                writes.delete(build_ticket_key(region_system_name, *ticket_id));
+            }
+
+            writes.execute(&self.client).await
+        })
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn reset<'a>(
+        &'a self,
+        region_system_name: &'a str,
+        fencing_token: u64,
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        Box::pin(async move {
+            let ticket_queue_key = build_ticket_queue_key(region_system_name);
+            let ticket_ids: Vec<ProfileId> = self
+                .client
+                .zrange(ticket_queue_key.clone(), 0, -1, Default::default())
+                .await?;
+
+            let ticket_keys = ticket_ids
+                .into_iter()
+                .map(|id| build_ticket_key(region_system_name, id))
+                .collect::<Vec<_>>();
+
+            let values: Vec<Option<String>> = if ticket_keys.is_empty() {
+                Vec::new()
+            } else {
+                self.client.mget(ticket_keys.clone()).await?
+            };
+            let game_mode_queue_keys = values
+                .into_iter()
+                .flatten()
+                .filter_map(|value| serde_json::from_str::<MatchmakingTicket>(&value).ok())
+                .map(|ticket| {
+                    build_game_mode_ticket_queue_key(region_system_name, &ticket.game_mode)
+                })
+                .collect::<HashSet<_>>();
+
+            let mut writes = RedisFencedWrites::new(region_system_name, fencing_token);
+            for key in ticket_keys
+                .into_iter()
+                .chain(game_mode_queue_keys)
+                .chain(once(ticket_queue_key))
+            {
+                writes.delete(key);
+            }
+            writes.execute(&self.client).await
+        })
+    }
+}
+
+//-------------------------------------------------------------------------------------------------
+#[inline]
+fn build_ticket_queue_key(region_system_name: &str) -> String {
+    format!("{{{region_system_name}}}:mmtq")
+}
+
+//-------------------------------------------------------------------------------------------------
+#[inline]
+fn build_game_mode_ticket_queue_key(region_system_name: &str, game_mode: &str) -> String {
+    format!("{{{region_system_name}}}:mmtq:{game_mode}")
+}
+
+//-------------------------------------------------------------------------------------------------
+#[inline]
+fn build_ticket_key(region_system_name: &str, profile_id: ProfileId) -> String {
+    format!("{{{region_system_name}}}:mmt:{profile_id}")
+}
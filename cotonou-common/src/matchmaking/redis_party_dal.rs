@@ -0,0 +1,182 @@
+use crate::{
+    matchmaking::{Error, Party, PartyDAL, PartyId},
+    redis::RedisConnectionManager,
+    types::ProfileId,
+};
+use futures::future::BoxFuture;
+use rustis::{
+    client::Client,
+    commands::{CallBuilder, GenericCommands, ScriptingCommands, StringCommands},
+};
+use std::{iter::once, time::Duration};
+
+/// Parties left untouched for this long are forgotten, with the party id of their members
+const PARTY_EXPIRATION: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// KEYS[1] = party, KEYS[2..] = party ids of the members,
+/// ARGV[1] = expected version, ARGV[2] = party json, ARGV[3] = party id, ARGV[4] = expiration in s
+const SAVE_PARTY_SCRIPT: &str = r#"
+local party_json = redis.call('GET', KEYS[1])
+local version = 0
+if party_json then
+    version = cjson.decode(party_json).n or 0
+end
+if version ~= tonumber(ARGV[1]) then
+    return 0
+end
+for i = 2, #KEYS do
+    local party_id = redis.call('GET', KEYS[i])
+    if party_id and party_id ~= ARGV[3] then
+        return 0
+    end
+end
+redis.call('SET', KEYS[1], ARGV[2], 'EX', ARGV[4])
+for i = 2, #KEYS do
+    redis.call('SET', KEYS[i], ARGV[3], 'EX', ARGV[4])
+end
+return 1
+"#;
+
+/// KEYS[1] = party, KEYS[2..] = party ids of the members, ARGV[1] = expected version
+const DELETE_PARTY_SCRIPT: &str = r#"
+local party_json = redis.call('GET', KEYS[1])
+if not party_json or (cjson.decode(party_json).n or 0) ~= tonumber(ARGV[1]) then
+    return 0
+end
+redis.call('DEL', unpack(KEYS))
+return 1
+"#;
+
+#[derive(Clone)]
+pub struct RedisPartyDAL {
+    client: Client,
+}
+
+impl RedisPartyDAL {
+    //-------------------------------------------------------------------------------------------------
+    pub fn new(redis_connection_manager: &RedisConnectionManager) -> Self {
+        Self {
+            client: redis_connection_manager.get_client("MATCHMAKING").unwrap(),
+        }
+    }
+}
+
+impl PartyDAL for RedisPartyDAL {
+    //-------------------------------------------------------------------------------------------------
+    fn get_party<'a>(
+        &'a self,
+        party_id: &'a PartyId,
+    ) -> BoxFuture<'a, Result<Option<Party>, Error>> {
+        Box::pin(async move {
+            let party_json: Option<String> = self.client.get(build_party_key(party_id)).await?;
+            if let Some(party_json) = party_json {
+                Ok(Some(serde_json::from_str(&party_json)?))
+            } else {
+                Ok(None)
+            }
+        })
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn get_party_id<'a>(
+        &'a self,
+        profile_id: ProfileId,
+    ) -> BoxFuture<'a, Result<Option<PartyId>, Error>> {
+        Box::pin(async move {
+            let party_id: Option<String> =
+                self.client.get(build_party_member_key(profile_id)).await?;
+            Ok(party_id.and_then(|id| PartyId::try_parse(&id)))
+        })
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn save_party<'a>(&'a self, party: &'a mut Party) -> BoxFuture<'a, Result<bool, Error>> {
+        Box::pin(async move {
+            let expected_version = party.version;
+            party.version += 1;
+            let party_json = serde_json::to_string(party);
+            party.version = expected_version;
+
+            let keys = once(build_party_key(&party.party_id))
+                .chain(
+                    party
+                        .members
+                        .iter()
+                        .map(|m| build_party_member_key(m.profile_id)),
+                )
+                .collect::<Vec<_>>();
+
+            let saved: u64 = self
+                .client
+                .eval(CallBuilder::script(SAVE_PARTY_SCRIPT).keys(keys).args([
+                    expected_version.to_string(),
+                    party_json?,
+                    party.party_id.to_string(),
+                    PARTY_EXPIRATION.as_secs().to_string(),
+                ]))
+                .await?;
+
+            if saved != 1 {
+                return Ok(false);
+            }
+
+            party.version += 1;
+            Ok(true)
+        })
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn delete_party_members<'a>(
+        &'a self,
+        profile_ids: &'a [ProfileId],
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        Box::pin(async move {
+            if profile_ids.is_empty() {
+                return Ok(());
+            }
+
+            let keys = profile_ids
+                .iter()
+                .map(|id| build_party_member_key(*id))
+                .collect::<Vec<_>>();
+            self.client.del(keys).await?;
+            Ok(())
+        })
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn delete_party<'a>(&'a self, party: &'a Party) -> BoxFuture<'a, Result<bool, Error>> {
+        Box::pin(async move {
+            let keys = once(build_party_key(&party.party_id))
+                .chain(
+                    party
+                        .members
+                        .iter()
+                        .map(|m| build_party_member_key(m.profile_id)),
+                )
+                .collect::<Vec<_>>();
+
+            let deleted: u64 = self
+                .client
+                .eval(
+                    CallBuilder::script(DELETE_PARTY_SCRIPT)
+                        .keys(keys)
+                        .args(party.version.to_string()),
+                )
+                .await?;
+            Ok(deleted == 1)
+        })
+    }
+}
+
+//-------------------------------------------------------------------------------------------------
+#[inline]
+fn build_party_key(party_id: &PartyId) -> String {
+    format!("pty:{party_id}")
+}
+
+//-------------------------------------------------------------------------------------------------
+#[inline]
+fn build_party_member_key(profile_id: ProfileId) -> String {
+    format!("ptym:{profile_id}")
+}
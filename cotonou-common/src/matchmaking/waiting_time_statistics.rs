@@ -0,0 +1,150 @@
+use crate::matchmaking::MatchmakingTicket;
+use serde::{Deserialize, Serialize};
+
+/// Width of the MMR brackets of the waiting times
+pub const MMR_BRACKET_SIZE: u32 = 200;
+
+/// Average waiting time of the tickets of an MMR bracket and a party size
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WaitingTimeBracket {
+    #[serde(rename = "m")]
+    pub mmr_bracket: u32,
+    #[serde(rename = "p")]
+    pub party_size: usize,
+    #[serde(rename = "w")]
+    pub waiting_time: u64,
+}
+
+/// Waiting times of a game mode published by the matchmaking job,
+/// recent waiting times weigh more than older ones
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct WaitingTimeStatistics {
+    #[serde(rename = "b")]
+    pub brackets: Vec<WaitingTimeBracket>,
+    /// Average waiting time of all the tickets
+    #[serde(rename = "w")]
+    pub average_waiting_time: u64,
+    /// Number of tickets waiting for a match
+    #[serde(rename = "q")]
+    pub queue_length: usize,
+    #[serde(rename = "s")]
+    pub num_idle_servers: usize,
+    /// Number of tickets matched per second
+    #[serde(rename = "t")]
+    pub throughput: f64,
+}
+
+impl WaitingTimeStatistics {
+    //-------------------------------------------------------------------------------------------------
+    pub fn get_mmr_bracket(mmr: u32) -> u32 {
+        mmr / MMR_BRACKET_SIZE
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    pub fn get_ticket_mmr_bracket(ticket: &MatchmakingTicket) -> u32 {
+        let num_players = ticket.players.len().max(1) as u32;
+        let mmr = ticket.players.iter().map(|p| p.mmr).sum::<u32>() / num_players;
+        Self::get_mmr_bracket(mmr)
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    /// Estimated waiting time of a ticket from its creation, in seconds
+    ///
+    /// # Arguments
+    /// * `position_in_queue` - 1 for the next ticket to match, None for a new ticket
+    pub fn estimate_waiting_time(
+        &self,
+        ticket: &MatchmakingTicket,
+        position_in_queue: Option<usize>,
+    ) -> u64 {
+        let mmr_bracket = Self::get_ticket_mmr_bracket(ticket);
+        let party_size = ticket.players.len();
+
+        // same bracket, then same MMR with another party size, then the whole game mode
+        let match_waiting_time = self
+            .brackets
+            .iter()
+            .find(|b| b.mmr_bracket == mmr_bracket && b.party_size == party_size)
+            .or_else(|| {
+                self.brackets
+                    .iter()
+                    .filter(|b| b.mmr_bracket == mmr_bracket)
+                    .min_by_key(|b| b.party_size.abs_diff(party_size))
+            })
+            .map(|b| b.waiting_time)
+            .unwrap_or(self.average_waiting_time);
+
+        // once all servers are busy, tickets also wait for the tickets ahead of them
+        let server_waiting_time = if self.num_idle_servers == 0 && self.throughput > 0. {
+            let position_in_queue = position_in_queue.unwrap_or(self.queue_length + 1);
+            (position_in_queue as f64 / self.throughput) as u64
+        } else {
+            0
+        };
+
+        match_waiting_time.max(server_waiting_time)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matchmaking::MatchmakingPlayer;
+
+    fn ticket(mmrs: &[u32]) -> MatchmakingTicket {
+        MatchmakingTicket {
+            owner_profile_id: "1".parse().unwrap(),
+            game_mode: "QuickMatch".to_owned(),
+            players: mmrs
+                .iter()
+                .map(|mmr| MatchmakingPlayer {
+                    profile_id: "1".parse().unwrap(),
+                    mmr: *mmr,
+                    ..Default::default()
+                })
+                .collect(),
+            creation_time: 0,
+            session_id: None,
+            servers_full_notification_last_time_sent: 0,
+            attributes: Default::default(),
+            estimated_wait_time: 0,
+        }
+    }
+
+    #[test]
+    fn waiting_time_is_estimated_from_the_closest_bracket() {
+        let mut statistics = WaitingTimeStatistics {
+            brackets: vec![
+                WaitingTimeBracket {
+                    mmr_bracket: 5,
+                    party_size: 1,
+                    waiting_time: 20,
+                },
+                WaitingTimeBracket {
+                    mmr_bracket: 5,
+                    party_size: 3,
+                    waiting_time: 60,
+                },
+            ],
+            average_waiting_time: 30,
+            queue_length: 10,
+            num_idle_servers: 1,
+            throughput: 0.5,
+        };
+
+        assert_eq!(20, statistics.estimate_waiting_time(&ticket(&[1000]), None));
+        assert_eq!(
+            60,
+            statistics.estimate_waiting_time(&ticket(&[1000; 4]), None)
+        );
+        assert_eq!(30, statistics.estimate_waiting_time(&ticket(&[2000]), None));
+
+        // no idle server, 11 tickets ahead matched at 0.5 ticket per second
+        statistics.num_idle_servers = 0;
+        assert_eq!(22, statistics.estimate_waiting_time(&ticket(&[1000]), None));
+        assert_eq!(
+            20,
+            statistics.estimate_waiting_time(&ticket(&[1000]), Some(2))
+        );
+    }
+}
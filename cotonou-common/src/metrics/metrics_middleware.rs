@@ -0,0 +1,52 @@
+use crate::metrics::{MetricsRegistry, DURATION_BUCKETS};
+use axum::{
+    extract::{MatchedPath, State},
+    http::{header, Request},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::{sync::Arc, time::Instant};
+
+/// Count HTTP requests and measure their duration, by route and status code
+pub async fn metrics_middleware<B>(
+    State(metrics_registry): State<Arc<MetricsRegistry>>,
+    request: Request<B>,
+    next: Next<B>,
+) -> Response {
+    let start = Instant::now();
+    let method = request.method().to_string();
+    // route template rather than uri, to bound the number of labels
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_owned())
+        .unwrap_or_default();
+
+    let response = next.run(request).await;
+
+    let status = response.status().as_u16().to_string();
+    metrics_registry.increment_counter(
+        "http_requests_total",
+        "Number of HTTP requests",
+        &[("method", &method), ("route", &route), ("status", &status)],
+    );
+    metrics_registry.observe_histogram(
+        "http_request_duration_seconds",
+        "Duration of HTTP requests",
+        DURATION_BUCKETS,
+        &[("method", &method), ("route", &route)],
+        start.elapsed().as_secs_f64(),
+    );
+
+    response
+}
+
+/// `/metrics` endpoint, scraped by Prometheus
+pub async fn get_metrics(
+    State(metrics_registry): State<Arc<MetricsRegistry>>,
+) -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        metrics_registry.encode(),
+    )
+}
@@ -0,0 +1,296 @@
+use std::{collections::BTreeMap, fmt::Write, sync::Mutex};
+
+/// Default buckets of duration histograms, in seconds
+pub const DURATION_BUCKETS: &[f64] = &[
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1., 2.5, 5., 10., 30., 60., 120., 300., 600.,
+];
+
+#[derive(Clone, Copy)]
+enum MetricType {
+    Counter,
+    Gauge,
+    Histogram,
+}
+
+impl MetricType {
+    fn as_str(&self) -> &'static str {
+        match self {
+            MetricType::Counter => "counter",
+            MetricType::Gauge => "gauge",
+            MetricType::Histogram => "histogram",
+        }
+    }
+}
+
+enum MetricValue {
+    Value(f64),
+    Histogram {
+        /// number of observations in each bucket, not cumulative
+        bucket_counts: Vec<u64>,
+        sum: f64,
+        count: u64,
+    },
+}
+
+struct MetricFamily {
+    help: String,
+    metric_type: MetricType,
+    /// upper bounds of the buckets, for histograms
+    buckets: Vec<f64>,
+    /// values indexed by their encoded labels
+    values: BTreeMap<String, MetricValue>,
+}
+
+/// Counters, gauges and histograms of a process, exported in Prometheus text format
+#[derive(Default)]
+pub struct MetricsRegistry {
+    families: Mutex<BTreeMap<String, MetricFamily>>,
+}
+
+impl MetricsRegistry {
+    //-------------------------------------------------------------------------------------------------
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    pub fn increment_counter(&self, name: &str, help: &str, labels: &[(&str, &str)]) {
+        self.add_counter(name, help, labels, 1.);
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    pub fn add_counter(&self, name: &str, help: &str, labels: &[(&str, &str)], value: f64) {
+        self.update(
+            name,
+            help,
+            MetricType::Counter,
+            &[],
+            labels,
+            |metric_value| {
+                if let MetricValue::Value(total) = metric_value {
+                    *total += value;
+                }
+            },
+        );
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    pub fn set_gauge(&self, name: &str, help: &str, labels: &[(&str, &str)], value: f64) {
+        self.update(name, help, MetricType::Gauge, &[], labels, |metric_value| {
+            *metric_value = MetricValue::Value(value);
+        });
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    /// # Arguments
+    /// * `buckets` - Sorted upper bounds of the buckets, used on the first observation only
+    pub fn observe_histogram(
+        &self,
+        name: &str,
+        help: &str,
+        buckets: &[f64],
+        labels: &[(&str, &str)],
+        value: f64,
+    ) {
+        self.update(
+            name,
+            help,
+            MetricType::Histogram,
+            buckets,
+            labels,
+            |metric_value| {
+                if let MetricValue::Histogram {
+                    bucket_counts,
+                    sum,
+                    count,
+                } = metric_value
+                {
+                    if let Some(index) =
+                        buckets.iter().position(|upper_bound| value <= *upper_bound)
+                    {
+                        bucket_counts[index] += 1;
+                    }
+                    *sum += value;
+                    *count += 1;
+                }
+            },
+        );
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    /// Remove the values of a metric having all the given labels,
+    /// e.g. gauges of a region no longer processed by this process
+    pub fn remove_values(&self, name: &str, labels: &[(&str, &str)]) {
+        let mut families = self.families.lock().unwrap();
+        let Some(family) = families.get_mut(name) else {
+            return;
+        };
+
+        let labels = labels
+            .iter()
+            .map(|label| encode_labels(&[*label]))
+            .collect::<Vec<_>>();
+        family.values.retain(|encoded_labels, _| {
+            !labels.iter().all(|label| has_label(encoded_labels, label))
+        });
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn update(
+        &self,
+        name: &str,
+        help: &str,
+        metric_type: MetricType,
+        buckets: &[f64],
+        labels: &[(&str, &str)],
+        update: impl FnOnce(&mut MetricValue),
+    ) {
+        let mut families = self.families.lock().unwrap();
+        let family = families
+            .entry(name.to_owned())
+            .or_insert_with(|| MetricFamily {
+                help: help.to_owned(),
+                metric_type,
+                buckets: buckets.to_vec(),
+                values: BTreeMap::new(),
+            });
+
+        let num_buckets = family.buckets.len();
+        let metric_value = family
+            .values
+            .entry(encode_labels(labels))
+            .or_insert_with(|| match metric_type {
+                MetricType::Counter | MetricType::Gauge => MetricValue::Value(0.),
+                MetricType::Histogram => MetricValue::Histogram {
+                    bucket_counts: vec![0; num_buckets],
+                    sum: 0.,
+                    count: 0,
+                },
+            });
+
+        update(metric_value);
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    /// Prometheus text exposition format
+    pub fn encode(&self) -> String {
+        let families = self.families.lock().unwrap();
+        let mut text = String::new();
+
+        for (name, family) in families.iter() {
+            let _ = writeln!(text, "# HELP {name} {}", family.help);
+            let _ = writeln!(text, "# TYPE {name} {}", family.metric_type.as_str());
+
+            for (labels, value) in &family.values {
+                match value {
+                    MetricValue::Value(value) => {
+                        let _ = writeln!(text, "{name}{} {value}", wrap_labels(labels));
+                    }
+                    MetricValue::Histogram {
+                        bucket_counts,
+                        sum,
+                        count,
+                    } => {
+                        let mut cumulative_count = 0;
+                        for (upper_bound, bucket_count) in family.buckets.iter().zip(bucket_counts)
+                        {
+                            cumulative_count += bucket_count;
+                            let _ = writeln!(
+                                text,
+                                "{name}_bucket{} {cumulative_count}",
+                                wrap_labels(&join_labels(labels, &format!("le=\"{upper_bound}\"")))
+                            );
+                        }
+                        let _ = writeln!(
+                            text,
+                            "{name}_bucket{} {count}",
+                            wrap_labels(&join_labels(labels, "le=\"+Inf\""))
+                        );
+                        let _ = writeln!(text, "{name}_sum{} {sum}", wrap_labels(labels));
+                        let _ = writeln!(text, "{name}_count{} {count}", wrap_labels(labels));
+                    }
+                }
+            }
+        }
+
+        text
+    }
+}
+
+//-------------------------------------------------------------------------------------------------
+fn encode_labels(labels: &[(&str, &str)]) -> String {
+    labels
+        .iter()
+        .map(|(name, value)| {
+            let value = value
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('\n', "\\n");
+            format!("{name}=\"{value}\"")
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+//-------------------------------------------------------------------------------------------------
+fn has_label(encoded_labels: &str, label: &str) -> bool {
+    encoded_labels == label
+        || encoded_labels.starts_with(&format!("{label},"))
+        || encoded_labels.ends_with(&format!(",{label}"))
+        || encoded_labels.contains(&format!(",{label},"))
+}
+
+//-------------------------------------------------------------------------------------------------
+fn join_labels(labels: &str, label: &str) -> String {
+    if labels.is_empty() {
+        label.to_owned()
+    } else {
+        format!("{labels},{label}")
+    }
+}
+
+//-------------------------------------------------------------------------------------------------
+fn wrap_labels(labels: &str) -> String {
+    if labels.is_empty() {
+        String::new()
+    } else {
+        format!("{{{labels}}}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn metrics_are_encoded_in_prometheus_text_format() {
+        let registry = MetricsRegistry::new();
+        registry.increment_counter("tickets_total", "Tickets", &[("region", "eu")]);
+        registry.increment_counter("tickets_total", "Tickets", &[("region", "eu")]);
+        registry.set_gauge("servers", "Servers", &[], 3.);
+        registry.observe_histogram("wait_seconds", "Wait", &[1., 10.], &[("region", "eu")], 5.);
+
+        let text = registry.encode();
+        assert!(text.contains("# TYPE tickets_total counter\ntickets_total{region=\"eu\"} 2\n"));
+        assert!(text.contains("servers 3\n"));
+        assert!(text.contains("wait_seconds_bucket{region=\"eu\",le=\"1\"} 0\n"));
+        assert!(text.contains("wait_seconds_bucket{region=\"eu\",le=\"10\"} 1\n"));
+        assert!(text.contains("wait_seconds_bucket{region=\"eu\",le=\"+Inf\"} 1\n"));
+        assert!(text.contains("wait_seconds_sum{region=\"eu\"} 5\n"));
+        assert!(text.contains("wait_seconds_count{region=\"eu\"} 1\n"));
+    }
+
+    #[test]
+    fn values_are_removed_by_label() {
+        let registry = MetricsRegistry::new();
+        registry.set_gauge("queue", "Queue", &[("region", "eu"), ("mode", "a")], 1.);
+        registry.set_gauge("queue", "Queue", &[("region", "eu"), ("mode", "b")], 2.);
+        registry.set_gauge("queue", "Queue", &[("region", "us"), ("mode", "a")], 3.);
+
+        registry.remove_values("queue", &[("region", "eu")]);
+
+        let text = registry.encode();
+        assert!(!text.contains("region=\"eu\""));
+        assert!(text.contains("queue{region=\"us\",mode=\"a\"} 3\n"));
+    }
+}
@@ -0,0 +1,5 @@
+mod metrics_middleware;
+mod metrics_registry;
+
+pub use metrics_middleware::*;
+pub use metrics_registry::*;
@@ -0,0 +1,82 @@
+use crate::notifications::{Error, NotificationManager};
+use futures::future::{self, BoxFuture};
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::sync::Notify;
+
+/// In-memory [`NotificationManager`], meant for tests
+#[derive(Clone, Default)]
+pub struct MemoryNotificationManager {
+    /// notification queues indexed by channel name
+    queues: Arc<Mutex<HashMap<String, VecDeque<String>>>>,
+    /// wakes up receivers waiting for new notifications
+    notify: Arc<Notify>,
+}
+
+impl MemoryNotificationManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn pop_notifications(&self, channel_name: &str) -> Vec<String> {
+        self.queues
+            .lock()
+            .unwrap()
+            .remove(channel_name)
+            .map(Vec::from)
+            .unwrap_or_default()
+    }
+}
+
+impl NotificationManager for MemoryNotificationManager {
+    fn send_notification_as_str<'a>(
+        &'a self,
+        channel_name: &'a str,
+        notification: &'a str,
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        // same ordering as the redis implementation (LPUSH + LPOP)
+        self.queues
+            .lock()
+            .unwrap()
+            .entry(channel_name.to_owned())
+            .or_default()
+            .push_front(notification.to_owned());
+        self.notify.notify_waiters();
+        Box::pin(future::ready(Ok(())))
+    }
+
+    fn get_notifications_from_queue<'a>(
+        &'a self,
+        channel_name: &'a str,
+    ) -> BoxFuture<'a, Result<Vec<String>, Error>> {
+        Box::pin(future::ready(Ok(self.pop_notifications(channel_name))))
+    }
+
+    fn get_notifications_from_queue_with_timeout<'a>(
+        &'a self,
+        channel_name: &'a str,
+        timeout: Duration,
+    ) -> BoxFuture<'a, Result<Vec<String>, Error>> {
+        Box::pin(async move {
+            let notified = self.notify.notified();
+            let notifications = self.pop_notifications(channel_name);
+            if timeout.is_zero() || !notifications.is_empty() {
+                return Ok(notifications);
+            }
+
+            let _ = tokio::time::timeout(timeout, notified).await;
+            Ok(self.pop_notifications(channel_name))
+        })
+    }
+
+    fn clear_notification_queue<'a>(
+        &'a self,
+        channel_name: &'a str,
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        self.queues.lock().unwrap().remove(channel_name);
+        Box::pin(future::ready(Ok(())))
+    }
+}
@@ -1,7 +1,13 @@
 mod error;
+#[cfg(feature = "memory")]
+mod memory_notification_manager;
 mod notification;
 mod notification_manager;
+mod redis_notification_manager;
 
 pub use error::*;
+#[cfg(feature = "memory")]
+pub use memory_notification_manager::*;
 pub use notification::*;
 pub use notification_manager::*;
+pub use redis_notification_manager::*;
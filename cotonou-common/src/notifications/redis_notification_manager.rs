@@ -0,0 +1,100 @@
+use crate::{
+    notifications::{Error, NotificationManager},
+    redis::RedisConnectionManager,
+};
+use futures::{future::BoxFuture, StreamExt};
+use rustis::{
+    client::Client,
+    commands::{GenericCommands, ListCommands, PubSubCommands},
+};
+use std::time::Duration;
+
+#[derive(Clone)]
+pub struct RedisNotificationManager {
+    pubsub: Client,
+    regular: Client,
+}
+
+impl RedisNotificationManager {
+    pub fn new(redis_connection_manager: &RedisConnectionManager) -> Self {
+        Self {
+            pubsub: redis_connection_manager
+                .get_client("NOTIFICATIONS_PUBSUB")
+                .unwrap(),
+            regular: redis_connection_manager
+                .get_client("NOTIFICATIONS")
+                .unwrap(),
+        }
+    }
+
+    fn build_channel_queue_key(&self, channel_name: &str) -> String {
+        format!("nq:{channel_name}")
+    }
+}
+
+impl NotificationManager for RedisNotificationManager {
+    fn send_notification_as_str<'a>(
+        &'a self,
+        channel_name: &'a str,
+        notification: &'a str,
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        Box::pin(async move {
+            // data is not sent via pub/sub; the pub/sub API is used only to notify subscriber to check for new notifications
+            // the actual data is pushed into a list used as a queue
+            self.regular
+                .lpush(
+                    self.build_channel_queue_key(channel_name),
+                    notification.to_owned(),
+                )
+                .await?;
+            self.regular.publish(channel_name.to_owned(), "new").await?;
+            Ok(())
+        })
+    }
+
+    fn get_notifications_from_queue<'a>(
+        &'a self,
+        channel_name: &'a str,
+    ) -> BoxFuture<'a, Result<Vec<String>, Error>> {
+        Box::pin(async move {
+            let key = self.build_channel_queue_key(channel_name);
+            Ok(self.regular.lpop(key, i32::MAX as usize).await?)
+        })
+    }
+
+    fn get_notifications_from_queue_with_timeout<'a>(
+        &'a self,
+        channel_name: &'a str,
+        timeout: Duration,
+    ) -> BoxFuture<'a, Result<Vec<String>, Error>> {
+        Box::pin(async move {
+            let notifications = self.get_notifications_from_queue(channel_name).await?;
+            if timeout.is_zero() || !notifications.is_empty() {
+                return Ok(notifications);
+            }
+
+            let mut messages = self.pubsub.subscribe(channel_name.to_owned()).await?;
+            let msg = tokio::time::timeout(timeout, messages.next()).await;
+
+            match msg {
+                // message
+                Ok(Some(_msg)) => self.get_notifications_from_queue(channel_name).await,
+                // stream closed
+                Ok(None) => Ok(Vec::new()),
+                // timeout
+                Err(_e) => Ok(Vec::new()),
+            }
+        })
+    }
+
+    fn clear_notification_queue<'a>(
+        &'a self,
+        channel_name: &'a str,
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        Box::pin(async move {
+            let key = self.build_channel_queue_key(channel_name);
+            self.regular.del(key).await?;
+            Ok(())
+        })
+    }
+}
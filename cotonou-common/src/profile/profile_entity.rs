@@ -2,8 +2,12 @@ pub const TABLE_NAME: &str = "Profile";
 pub const DISPLAY_NAME_PROPERTY: &str = "dn";
 pub const PLATFORM_ID_PROPERTY: &str = "pi";
 pub const ELOS_PROPERTY: &str = "elos";
+/// Matchmaking ratings indexed by game mode, as used by the matchmaking
+pub const MMRS_PROPERTY: &str = "mmrs";
+/// Full ratings (rating, deviation, volatility) indexed by game mode
+pub const RATINGS_PROPERTY: &str = "rtgs";
 pub const NUM_MATCHES_PLAYED_PROPERTY: &str = "nmp";
+/// Sessions of the last match results applied to the ratings, most recent last
+pub const RATED_SESSION_IDS_PROPERTY: &str = "rss";
 
-pub struct ProfileEntity {
-    
-}
+pub struct ProfileEntity {}
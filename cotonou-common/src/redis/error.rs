@@ -6,4 +6,6 @@ pub enum Error {
     Redis(#[from] rustis::Error),
     #[error("BadUriFormat Error: {0}")]
     BadUriFormat(#[from] url::ParseError),
+    #[error("InvalidConfig Error: {0}")]
+    InvalidConfig(String),
 }
@@ -1,6 +1,228 @@
-use std::collections::HashMap;
+use crate::redis::Error;
+use std::{collections::HashMap, str::FromStr};
 
 #[derive(Clone)]
 pub struct RedisConfig {
-    pub connection_strings: HashMap<String, String>,
+    /// connection configurations indexed by client name (e.g. `MATCHMAKING`)
+    pub connections: HashMap<String, RedisConnectionConfig>,
+}
+
+impl RedisConfig {
+    //-------------------------------------------------------------------------------------------------
+    /// Connection string of each client read from `COTONOU_REDIS_<CLIENT NAME>_URL`,
+    /// or the given default connection string if the variable is not set
+    pub fn from_env(connections: &[(&str, &str)]) -> Result<Self, Error> {
+        let connections = connections
+            .iter()
+            .map(|(name, default_connection_string)| {
+                let var = format!("COTONOU_REDIS_{name}_URL");
+                let connection_string =
+                    std::env::var(&var).unwrap_or_else(|_| (*default_connection_string).to_owned());
+                let connection_config = connection_string.parse().map_err(|e| {
+                    Error::InvalidConfig(format!("{var} is not a valid connection string: {e}"))
+                })?;
+                Ok(((*name).to_owned(), connection_config))
+            })
+            .collect::<Result<_, Error>>()?;
+
+        Ok(Self { connections })
+    }
+}
+
+/// Redis deployment a named client connects to
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum RedisTopology {
+    /// Single server (no master-replica, no cluster)
+    Standalone { host: String, port: u16 },
+    /// Master-replica set monitored by Sentinel instances
+    Sentinel {
+        instances: Vec<(String, u16)>,
+        service_name: String,
+    },
+    /// Redis Cluster; only database `0` is available
+    Cluster { nodes: Vec<(String, u16)> },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RedisConnectionConfig {
+    pub topology: RedisTopology,
+    pub database: usize,
+    pub username: Option<String>,
+    pub password: Option<String>,
+}
+
+impl RedisConnectionConfig {
+    //-------------------------------------------------------------------------------------------------
+    pub fn standalone(host: &str, port: u16, database: usize) -> Self {
+        Self {
+            topology: RedisTopology::Standalone {
+                host: host.to_owned(),
+                port,
+            },
+            database,
+            username: None,
+            password: None,
+        }
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    pub fn sentinel(instances: &[(&str, u16)], service_name: &str, database: usize) -> Self {
+        Self {
+            topology: RedisTopology::Sentinel {
+                instances: to_owned_addresses(instances),
+                service_name: service_name.to_owned(),
+            },
+            database,
+            username: None,
+            password: None,
+        }
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    pub fn cluster(nodes: &[(&str, u16)]) -> Self {
+        Self {
+            topology: RedisTopology::Cluster {
+                nodes: to_owned_addresses(nodes),
+            },
+            database: 0,
+            username: None,
+            password: None,
+        }
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    pub(crate) fn to_rustis_config(
+        &self,
+        connection_name: &str,
+    ) -> Result<rustis::client::Config, Error> {
+        let server = match &self.topology {
+            RedisTopology::Standalone { host, port } => rustis::client::ServerConfig::Standalone {
+                host: host.clone(),
+                port: *port,
+            },
+            RedisTopology::Sentinel {
+                instances,
+                service_name,
+            } => rustis::client::ServerConfig::Sentinel(rustis::client::SentinelConfig {
+                instances: instances.clone(),
+                service_name: service_name.clone(),
+                ..Default::default()
+            }),
+            RedisTopology::Cluster { nodes } => {
+                if self.database != 0 {
+                    return Err(Error::InvalidConfig(format!(
+                        "database {} cannot be selected on a Redis cluster",
+                        self.database
+                    )));
+                }
+                rustis::client::ServerConfig::Cluster(rustis::client::ClusterConfig {
+                    nodes: nodes.clone(),
+                })
+            }
+        };
+
+        Ok(rustis::client::Config {
+            server,
+            username: self.username.clone(),
+            password: self.password.clone(),
+            database: self.database,
+            connection_name: connection_name.to_owned(),
+            ..Default::default()
+        })
+    }
+}
+
+impl FromStr for RedisConnectionConfig {
+    type Err = Error;
+
+    /// Parses `redis://`, `redis+sentinel://` and `redis+cluster://` connection strings
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let config = rustis::client::Config::from_str(s)?;
+
+        let topology = match config.server {
+            rustis::client::ServerConfig::Standalone { host, port } => {
+                RedisTopology::Standalone { host, port }
+            }
+            rustis::client::ServerConfig::Sentinel(sentinel_config) => RedisTopology::Sentinel {
+                instances: sentinel_config.instances,
+                service_name: sentinel_config.service_name,
+            },
+            rustis::client::ServerConfig::Cluster(cluster_config) => {
+                if config.database != 0 {
+                    return Err(Error::InvalidConfig(format!(
+                        "database {} cannot be selected on a Redis cluster",
+                        config.database
+                    )));
+                }
+                RedisTopology::Cluster {
+                    nodes: cluster_config.nodes,
+                }
+            }
+        };
+
+        Ok(Self {
+            topology,
+            database: config.database,
+            username: config.username,
+            password: config.password,
+        })
+    }
+}
+
+//-------------------------------------------------------------------------------------------------
+fn to_owned_addresses(addresses: &[(&str, u16)]) -> Vec<(String, u16)> {
+    addresses
+        .iter()
+        .map(|(host, port)| ((*host).to_owned(), *port))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RedisConnectionConfig, RedisTopology};
+
+    #[test]
+    fn parse_standalone() {
+        let config: RedisConnectionConfig = "redis://127.0.0.1:6379/1".parse().unwrap();
+        assert_eq!(
+            RedisConnectionConfig::standalone("127.0.0.1", 6379, 1),
+            config
+        );
+    }
+
+    #[test]
+    fn parse_sentinel() {
+        let config: RedisConnectionConfig = "redis+sentinel://s1:26379,s2:26379/mymaster/1"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            RedisConnectionConfig::sentinel(&[("s1", 26379), ("s2", 26379)], "mymaster", 1),
+            config
+        );
+    }
+
+    #[test]
+    fn parse_cluster() {
+        let config: RedisConnectionConfig = "redis+cluster://n1:7000,n2:7001".parse().unwrap();
+        assert_eq!(
+            RedisTopology::Cluster {
+                nodes: vec![("n1".to_owned(), 7000), ("n2".to_owned(), 7001)]
+            },
+            config.topology
+        );
+    }
+
+    #[test]
+    fn parse_cluster_rejects_database() {
+        assert!("redis+cluster://n1:7000,n2:7001/1"
+            .parse::<RedisConnectionConfig>()
+            .is_err());
+    }
+
+    #[test]
+    fn cluster_rejects_database() {
+        let mut config = RedisConnectionConfig::cluster(&[("n1", 7000)]);
+        config.database = 1;
+        assert!(config.to_rustis_config("MATCHMAKING").is_err());
+    }
 }
@@ -1,28 +1,86 @@
-use crate::redis::{Error, RedisConfig};
-use std::{collections::HashMap, result};
+use crate::{
+    redis::{Error, RedisConfig, RedisConnectionConfig},
+    unix_now,
+};
+use rustis::{client::Client, commands::ConnectionCommands};
+use serde::Serialize;
+use std::{
+    collections::HashMap,
+    result,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tokio::sync::broadcast::error::RecvError;
+
+const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Health of a named Redis client, as reported by [`RedisConnectionManager::check_health`]
+#[derive(Debug, Clone, Serialize)]
+pub struct RedisClientHealth {
+    pub name: String,
+    /// `true` if the client answered a `PING` in time
+    pub is_connected: bool,
+    /// Number of reconnections since the client was created
+    pub num_reconnections: u64,
+    /// Last reconnection time (unix timestamp)
+    pub last_reconnection_time: Option<u64>,
+}
+
+#[derive(Default)]
+struct ReconnectionStats {
+    num_reconnections: AtomicU64,
+    last_reconnection_time: AtomicU64,
+}
+
+struct NamedClient {
+    client: Client,
+    reconnection_stats: Arc<ReconnectionStats>,
+}
 
 pub struct RedisConnectionManager {
-    clients: HashMap<String, rustis::client::Client>,
+    clients: HashMap<String, NamedClient>,
 }
 
 type Result<T> = result::Result<T, Error>;
 
 impl RedisConnectionManager {
     pub async fn initialize(redis_config: RedisConfig) -> Result<Self> {
-        let mut clients_by_connection_string = HashMap::<String, rustis::client::Client>::new();
-        let mut clients_by_name = HashMap::<String, rustis::client::Client>::new();
+        let mut clients_by_connection_config =
+            HashMap::<RedisConnectionConfig, (Client, Arc<ReconnectionStats>)>::new();
+        let mut clients_by_name = HashMap::<String, NamedClient>::new();
+
+        // sort names so that the connection name of a shared client is deterministic
+        let mut names = redis_config.connections.keys().collect::<Vec<_>>();
+        names.sort();
 
-        for (name, connection_string) in &redis_config.connection_strings {
-            let client: rustis::client::Client;
+        for name in names {
+            let connection_config = &redis_config.connections[name];
 
-            if let Some(existing_client) = clients_by_connection_string.get(connection_string) {
-                client = existing_client.clone();
+            let (client, reconnection_stats) = if let Some((existing_client, existing_stats)) =
+                clients_by_connection_config.get(connection_config)
+            {
+                (existing_client.clone(), existing_stats.clone())
             } else {
-                client = rustis::client::Client::connect(connection_string.clone()).await?;
-                clients_by_connection_string.insert(connection_string.clone(), client.clone());
-            }
+                let client = Client::connect(connection_config.to_rustis_config(name)?).await?;
+                let reconnection_stats = Arc::new(ReconnectionStats::default());
+                watch_reconnections(name, &client, reconnection_stats.clone());
+                clients_by_connection_config.insert(
+                    connection_config.clone(),
+                    (client.clone(), reconnection_stats.clone()),
+                );
+                (client, reconnection_stats)
+            };
 
-            clients_by_name.insert(name.clone(), client);
+            clients_by_name.insert(
+                name.clone(),
+                NamedClient {
+                    client,
+                    reconnection_stats,
+                },
+            );
         }
 
         Ok(Self {
@@ -30,7 +88,53 @@ impl RedisConnectionManager {
         })
     }
 
-    pub fn get_client(&self, name: &str) -> Option<rustis::client::Client> {
-        self.clients.get(name).cloned()
+    pub fn get_client(&self, name: &str) -> Option<Client> {
+        self.clients.get(name).map(|c| c.client.clone())
     }
+
+    /// Pings every named client and reports its reconnection statistics
+    pub async fn check_health(&self) -> Vec<RedisClientHealth> {
+        let mut healths = Vec::with_capacity(self.clients.len());
+
+        for (name, named_client) in &self.clients {
+            let ping = named_client.client.ping::<String>(Default::default());
+            let is_connected = matches!(
+                tokio::time::timeout(HEALTH_CHECK_TIMEOUT, ping).await,
+                Ok(Ok(_))
+            );
+
+            let stats = &named_client.reconnection_stats;
+            let last_reconnection_time = stats.last_reconnection_time.load(Ordering::Relaxed);
+
+            healths.push(RedisClientHealth {
+                name: name.clone(),
+                is_connected,
+                num_reconnections: stats.num_reconnections.load(Ordering::Relaxed),
+                last_reconnection_time: (last_reconnection_time != 0)
+                    .then_some(last_reconnection_time),
+            });
+        }
+
+        healths.sort_by(|h1, h2| h1.name.cmp(&h2.name));
+        healths
+    }
+}
+
+//-------------------------------------------------------------------------------------------------
+/// rustis reconnects on its own; this only keeps track of reconnections for health reporting
+fn watch_reconnections(name: &str, client: &Client, reconnection_stats: Arc<ReconnectionStats>) {
+    let name = name.to_owned();
+    let mut on_reconnect = client.on_reconnect();
+
+    tokio::spawn(async move {
+        while let Ok(()) | Err(RecvError::Lagged(_)) = on_reconnect.recv().await {
+            log::warn!("Redis client {name} reconnected");
+            reconnection_stats
+                .num_reconnections
+                .fetch_add(1, Ordering::Relaxed);
+            reconnection_stats
+                .last_reconnection_time
+                .store(unix_now(), Ordering::Relaxed);
+        }
+    });
 }
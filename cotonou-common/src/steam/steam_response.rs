@@ -14,9 +14,9 @@ pub enum SteamResultCode {
 
 #[derive(Deserialize, Debug, Default, thiserror::Error)]
 pub struct SteamError {
-    #[serde(rename="errorcode")]
+    #[serde(rename = "errorcode")]
     pub error_code: u32,
-    #[serde(rename="errordesc")]
+    #[serde(rename = "errordesc")]
     pub error_desc: String,
 }
 
@@ -28,14 +28,15 @@ impl fmt::Display for SteamError {
 
 #[derive(Deserialize, Debug)]
 pub enum SteamParams<T> {
-    #[serde(rename="params")]
+    #[serde(rename = "params")]
     Params(T),
-    #[serde(rename="error")]
+    #[serde(rename = "error")]
     Error(SteamError),
 }
 
 #[derive(Deserialize, Debug)]
 pub struct SteamParamsWithResult<T> {
+    #[allow(dead_code)]
     pub result: SteamResultCode,
     #[serde(flatten)]
     pub params: SteamParams<T>,
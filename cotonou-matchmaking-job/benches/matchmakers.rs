@@ -0,0 +1,101 @@
+use cotonou_common::{
+    matchmaking::{MatchFunctionsConfig, MatchmakerConfig},
+    unix_now,
+};
+use cotonou_matchmaking_job::{
+    match_functions::{CriteriaMatchFunctions, MatchFunctions, MmrMatchFunctions},
+    simulator::{get_ranked_game_mode_config, Simulation, SimulationScenario, TicketGenerator},
+    Clock, SystemClock,
+};
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use std::{hint::black_box, sync::Arc};
+
+/// One simulated minute of steady arrivals.
+/// The multi-threaded matchmaker matches in background threads and is evaluated with the simulator instead
+fn bench_matchmakers(c: &mut Criterion) {
+    let scenario = SimulationScenario {
+        duration: 60,
+        arrival_rate: 20.,
+        ..SimulationScenario::steady()
+    };
+
+    let mut group = c.benchmark_group("matchmakers");
+    group.sample_size(10);
+
+    for (name, matchmaker_type) in [
+        ("simple_list", MatchmakerConfig::SimpleList),
+        ("cut_lists", MatchmakerConfig::CutLists { mmr_range: 100 }),
+    ] {
+        let mut game_mode_config = get_ranked_game_mode_config();
+        game_mode_config.matchmaker_type = matchmaker_type;
+        game_mode_config.match_functions_type = MatchFunctionsConfig::Mmr {
+            max_mmr_distance: 300,
+            waiting_time_weight: 10,
+        };
+
+        group.bench_function(name, |b| {
+            b.iter_batched(
+                || Simulation::new(game_mode_config.clone(), scenario.clone()),
+                |simulation| simulation.run(),
+                BatchSize::PerIteration,
+            )
+        });
+    }
+
+    group.finish();
+}
+
+fn bench_match_functions(c: &mut Criterion) {
+    let game_mode_config = get_ranked_game_mode_config();
+    let scenario = SimulationScenario {
+        arrival_rate: 100.,
+        ..SimulationScenario::parties()
+    };
+    let tickets = TicketGenerator::new(&scenario).generate_tickets(
+        &game_mode_config.name,
+        0,
+        unix_now() - 30,
+    );
+    let (group1, group2) = (&tickets[0].players, &tickets[1].players);
+
+    let MatchFunctionsConfig::Criteria {
+        criteria,
+        select_best_match,
+    } = game_mode_config.match_functions_type.clone()
+    else {
+        unreachable!("Ranked game mode uses criteria")
+    };
+
+    let clock: Arc<dyn Clock> = Arc::new(SystemClock);
+    let match_functions: [(&str, Box<dyn MatchFunctions>); 2] = [
+        (
+            "mmr",
+            Box::new(MmrMatchFunctions::new(300, 10, clock.clone())),
+        ),
+        (
+            "criteria",
+            Box::new(CriteriaMatchFunctions::new(
+                criteria,
+                select_best_match,
+                clock,
+            )),
+        ),
+    ];
+
+    let mut group = c.benchmark_group("match_functions");
+    for (name, match_functions) in match_functions {
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                match_functions.score_match(
+                    black_box(&game_mode_config),
+                    black_box(group1),
+                    black_box(group2),
+                )
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_matchmakers, bench_match_functions);
+criterion_main!(benches);
@@ -0,0 +1,147 @@
+use crate::{can_form_teams, get_num_teams, match_functions::passes_filters};
+use cotonou_common::matchmaking::{BackfillRequest, GameModeConfig, MatchmakingPlayer};
+
+/// Check that a ticket fulfills the constraints of a backfill request
+pub fn can_backfill(
+    game_mode_config: &GameModeConfig,
+    backfill_request: &BackfillRequest,
+    session_players: &[MatchmakingPlayer],
+    ticket_players: &[MatchmakingPlayer],
+) -> bool {
+    let num_missing_players = backfill_request
+        .num_players
+        .saturating_sub(backfill_request.incoming_players.len());
+
+    if ticket_players.len() > num_missing_players
+        || session_players.len() + ticket_players.len() > game_mode_config.max_players
+    {
+        return false;
+    }
+
+    let is_player_valid = |player: &MatchmakingPlayer| {
+        backfill_request.min_mmr.is_none_or(|mmr| player.mmr >= mmr)
+            && backfill_request.max_mmr.is_none_or(|mmr| player.mmr <= mmr)
+            && backfill_request
+                .role
+                .as_ref()
+                .is_none_or(|role| player.roles.contains(role))
+    };
+
+    if !ticket_players.iter().all(is_player_valid) {
+        return false;
+    }
+
+    let players = session_players.iter().chain(ticket_players.iter());
+    if !passes_filters(&game_mode_config.filters, players.clone()) {
+        return false;
+    }
+
+    match backfill_request.team {
+        Some(team) => {
+            let team_player_count = if game_mode_config.team_player_count == 0 {
+                game_mode_config.max_players
+            } else {
+                game_mode_config.team_player_count
+            };
+            let team_size = session_players
+                .iter()
+                .filter(|p| p.team == Some(team))
+                .count();
+
+            team < get_num_teams(game_mode_config)
+                && team_size + ticket_players.len() <= team_player_count
+        }
+        None => can_form_teams(game_mode_config, players),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cotonou_common::{
+        matchmaking::{
+            MatchFunctionsConfig, MatchmakerConfig, MatchmakingPlayerStatus, RatingSystemConfig,
+        },
+        types::ProfileId,
+    };
+
+    fn game_mode_config() -> GameModeConfig {
+        GameModeConfig {
+            name: "test".to_owned(),
+            short_name: "t".to_owned(),
+            min_players: 2,
+            max_players: 4,
+            team_player_count: 2,
+            matchmaker_type: MatchmakerConfig::SimpleList,
+            match_functions_type: MatchFunctionsConfig::FirstComeFirstServed,
+            rating_system: RatingSystemConfig::Elo { k_factor: 32. },
+            filters: Vec::new(),
+            preferences: Vec::new(),
+            ticket_timeout: None,
+            fallback_game_mode: None,
+            latency_limit: None,
+        }
+    }
+
+    fn player(profile_id: i64, mmr: u32, team: Option<usize>, roles: &[&str]) -> MatchmakingPlayer {
+        MatchmakingPlayer {
+            profile_id: ProfileId::try_from(profile_id).unwrap(),
+            display_name: profile_id.to_string(),
+            mmr,
+            status: MatchmakingPlayerStatus::Active,
+            team,
+            roles: roles.iter().map(|r| r.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn backfill_constraints_are_checked() {
+        let backfill_request = BackfillRequest {
+            num_players: 1,
+            team: Some(1),
+            role: Some("healer".to_owned()),
+            min_mmr: Some(1000),
+            max_mmr: Some(1500),
+            expiration_time: 0,
+            incoming_players: Vec::new(),
+        };
+        let session_players = [
+            player(1, 1200, Some(0), &[]),
+            player(2, 1200, Some(0), &[]),
+            player(3, 1200, Some(1), &[]),
+        ];
+        let config = game_mode_config();
+
+        assert!(can_backfill(
+            &config,
+            &backfill_request,
+            &session_players,
+            &[player(4, 1200, None, &["tank", "healer"])]
+        ));
+        // wrong role
+        assert!(!can_backfill(
+            &config,
+            &backfill_request,
+            &session_players,
+            &[player(4, 1200, None, &["tank"])]
+        ));
+        // out of the skill range
+        assert!(!can_backfill(
+            &config,
+            &backfill_request,
+            &session_players,
+            &[player(4, 2000, None, &["healer"])]
+        ));
+        // full team
+        assert!(!can_backfill(
+            &config,
+            &BackfillRequest {
+                team: Some(0),
+                ..backfill_request.clone()
+            },
+            &session_players,
+            &[player(4, 1200, None, &["healer"])]
+        ));
+    }
+}
@@ -0,0 +1,147 @@
+use cotonou_common::matchmaking::{GameModeConfig, MatchFunctionsConfig, MatchmakerConfig};
+use cotonou_matchmaking_job::simulator::{
+    get_ranked_game_mode_config, Simulation, SimulationScenario,
+};
+use std::{process::ExitCode, time::Duration};
+
+const USAGE: &str =
+    "Usage: cargo run --release --features simulator --bin matchmaking_simulator -- [OPTIONS]
+
+Options:
+  --scenario <steady|burst|parties>                  all scenarios if unset
+  --matchmaker <simple-list|cut-lists|mt-cut-lists>  all matchmakers if unset
+  --duration <seconds>                               simulated duration
+  --arrival-rate <tickets per second>
+  --seed <number>
+  --mmr-range <mmr>                                  MMR range of the cut lists [default: 100]
+  --max-mmr-distance <mmr>                           [default: 300]
+  --waiting-time-weight <weight>                     [default: 10]";
+
+/// Time given at each step to the matchmakers processing tickets in background threads
+const BACKGROUND_STEP_DELAY: Duration = Duration::from_millis(20);
+
+struct SimulatorArgs {
+    scenarios: Vec<SimulationScenario>,
+    matchmakers: Vec<MatchmakerConfig>,
+    duration: Option<u64>,
+    arrival_rate: Option<f64>,
+    seed: Option<u64>,
+    max_mmr_distance: u32,
+    waiting_time_weight: u32,
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(error) => {
+            eprintln!("{error}\n\n{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    for scenario in &args.scenarios {
+        let mut scenario = scenario.clone();
+        scenario.duration = args.duration.unwrap_or(scenario.duration);
+        scenario.arrival_rate = args.arrival_rate.unwrap_or(scenario.arrival_rate);
+        scenario.seed = args.seed.unwrap_or(scenario.seed);
+
+        for matchmaker in &args.matchmakers {
+            let game_mode_config = new_game_mode_config(&args, matchmaker.clone());
+            let mut simulation = Simulation::new(game_mode_config, scenario.clone());
+            if let MatchmakerConfig::MultiThreadedCutLists { .. } = matchmaker {
+                simulation = simulation.with_step_delay(BACKGROUND_STEP_DELAY);
+            }
+
+            println!("{}\n", simulation.run());
+        }
+    }
+
+    ExitCode::SUCCESS
+}
+
+/// Ranked game mode with the matchmaker and the MMR match functions to evaluate
+fn new_game_mode_config(args: &SimulatorArgs, matchmaker: MatchmakerConfig) -> GameModeConfig {
+    let mut game_mode_config = get_ranked_game_mode_config();
+
+    game_mode_config.matchmaker_type = matchmaker;
+    game_mode_config.match_functions_type = MatchFunctionsConfig::Mmr {
+        max_mmr_distance: args.max_mmr_distance,
+        waiting_time_weight: args.waiting_time_weight,
+    };
+    game_mode_config
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<SimulatorArgs, String> {
+    let mut scenarios = Vec::new();
+    let mut matchmakers = Vec::new();
+    let mut mmr_range = 100;
+    let mut simulator_args = SimulatorArgs {
+        scenarios: Vec::new(),
+        matchmakers: Vec::new(),
+        duration: None,
+        arrival_rate: None,
+        seed: None,
+        max_mmr_distance: 300,
+        waiting_time_weight: 10,
+    };
+
+    while let Some(arg) = args.next() {
+        let value = args
+            .next()
+            .ok_or_else(|| format!("Missing value for {arg}"))?;
+
+        match arg.as_str() {
+            "--scenario" => scenarios.push(match value.as_str() {
+                "steady" => SimulationScenario::steady(),
+                "burst" => SimulationScenario::burst(),
+                "parties" => SimulationScenario::parties(),
+                _ => return Err(format!("Unknown scenario {value}")),
+            }),
+            "--matchmaker" => matchmakers.push(value),
+            "--duration" => simulator_args.duration = Some(parse_value(&arg, &value)?),
+            "--arrival-rate" => simulator_args.arrival_rate = Some(parse_value(&arg, &value)?),
+            "--seed" => simulator_args.seed = Some(parse_value(&arg, &value)?),
+            "--mmr-range" => mmr_range = parse_value(&arg, &value)?,
+            "--max-mmr-distance" => simulator_args.max_mmr_distance = parse_value(&arg, &value)?,
+            "--waiting-time-weight" => {
+                simulator_args.waiting_time_weight = parse_value(&arg, &value)?
+            }
+            _ => return Err(format!("Unknown option {arg}")),
+        }
+    }
+
+    if scenarios.is_empty() {
+        scenarios = vec![
+            SimulationScenario::steady(),
+            SimulationScenario::burst(),
+            SimulationScenario::parties(),
+        ];
+    }
+
+    if matchmakers.is_empty() {
+        matchmakers = vec![
+            "simple-list".to_owned(),
+            "cut-lists".to_owned(),
+            "mt-cut-lists".to_owned(),
+        ];
+    }
+
+    simulator_args.scenarios = scenarios;
+    simulator_args.matchmakers = matchmakers
+        .iter()
+        .map(|matchmaker| match matchmaker.as_str() {
+            "simple-list" => Ok(MatchmakerConfig::SimpleList),
+            "cut-lists" => Ok(MatchmakerConfig::CutLists { mmr_range }),
+            "mt-cut-lists" => Ok(MatchmakerConfig::MultiThreadedCutLists { mmr_range }),
+            _ => Err(format!("Unknown matchmaker {matchmaker}")),
+        })
+        .collect::<Result<_, _>>()?;
+
+    Ok(simulator_args)
+}
+
+fn parse_value<T: std::str::FromStr>(arg: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("Invalid value {value} for {arg}"))
+}
@@ -3,6 +3,8 @@ use thiserror::Error;
 
 #[derive(Debug, Error)]
 pub enum Error {
+    #[error("Configuration Error: {0}")]
+    Configuration(String),
     #[error("Database Error: {0}")]
     Database(#[from] database::Error),
     #[error("Matchmaking Error: {0}")]
@@ -17,4 +19,6 @@ pub enum Error {
     RecvError(#[from] tokio::sync::watch::error::RecvError),
     #[error("JoinError Error: {0}")]
     JoinError(#[from] tokio::task::JoinError),
+    #[error("Hyper Error: {0}")]
+    Hyper(#[from] hyper::Error),
 }
@@ -1,30 +1,41 @@
-use crate::{Error, ItemCache, QueueMap};
+use crate::{Clock, Error, ItemCache, QueueMap, ServerPlacement};
 use cotonou_common::{
     matchmaking::{GameServer, GameServerDAL, SessionId},
     types::GameServerId,
-    unix_now,
 };
-use std::{collections::HashSet, time::Duration};
+use std::{collections::HashSet, sync::Arc, time::Duration};
 
 pub type ServerQueueMap = QueueMap<GameServerId>;
-pub type ServerCache = ItemCache<GameServerId, GameServer, GameServerDAL>;
+pub type ServerCache = ItemCache<GameServerId, GameServer, Arc<dyn GameServerDAL>>;
 
-const GAME_SERVER_TIMEOUT: Duration = Duration::from_secs(60);
+pub(crate) const GAME_SERVER_TIMEOUT: Duration = Duration::from_secs(60);
+/// Unhealthy servers receive new sessions again after this duration
+pub(crate) const UNHEALTHY_SERVER_COOLDOWN: Duration = Duration::from_secs(300);
 
 pub struct GameServerManager {
     region_system_name: String,
     servers: ServerCache,
     active_servers: ServerQueueMap,
-    idle_servers: HashSet<GameServerId>,
+    /// healthy servers with at least one free slot
+    available_servers: HashSet<GameServerId>,
+    server_placement: Box<dyn ServerPlacement>,
+    clock: Arc<dyn Clock>,
 }
 
 impl GameServerManager {
-    pub fn new(region_system_name: &str, game_server_dal: GameServerDAL) -> Self {
+    pub fn new(
+        region_system_name: &str,
+        game_server_dal: Arc<dyn GameServerDAL>,
+        server_placement: Box<dyn ServerPlacement>,
+        clock: Arc<dyn Clock>,
+    ) -> Self {
         Self {
             region_system_name: region_system_name.to_owned(),
             servers: ServerCache::new(region_system_name, game_server_dal),
             active_servers: ServerQueueMap::new(),
-            idle_servers: HashSet::new(),
+            available_servers: HashSet::new(),
+            server_placement,
+            clock,
         }
     }
 
@@ -33,35 +44,35 @@ impl GameServerManager {
 
         for server in self.servers.iter() {
             self.active_servers.insert(server.game_server_id);
-            if server.session_id.is_none() {
-                self.idle_servers.insert(server.game_server_id);
+            if server.is_available() {
+                self.available_servers.insert(server.game_server_id);
             }
         }
 
         Ok(())
     }
 
-    pub async fn save(&mut self) -> Result<(), Error> {
-        self.servers.save().await
+    pub async fn save(&mut self, fencing_token: u64) -> Result<(), Error> {
+        self.servers.save(fencing_token).await
     }
 
-    pub async fn reset(&mut self) -> Result<(), Error> {
+    pub async fn reset(&mut self, fencing_token: u64) -> Result<(), Error> {
         self.active_servers.clear();
-        self.idle_servers.clear();
-        self.servers.reset().await
+        self.available_servers.clear();
+        self.servers.reset(fencing_token).await
     }
 
     pub fn process_expired_servers(&mut self) -> Vec<SessionId> {
         let keep_alive_timeout = GAME_SERVER_TIMEOUT.as_secs();
 
-        let now = unix_now();
+        let now = self.clock.now();
 
         let expired_servers = self
             .active_servers
             .iter()
             .filter_map(|id| {
                 if let Some(server) = self.servers.get(id) {
-                    Some((*id, server.keep_alive_time, server.session_id))
+                    Some((*id, server.keep_alive_time, server.session_ids.clone()))
                 } else {
                     log::error!("[{}] Cannot find server {id}", self.region_system_name);
                     None
@@ -82,19 +93,81 @@ impl GameServerManager {
 
         expired_servers
             .into_iter()
-            .filter_map(|(_, _, session_id)| session_id)
+            .flat_map(|(_, _, session_ids)| session_ids)
             .collect()
     }
 
-    pub fn has_idle_server(&self) -> bool {
-        !self.idle_servers.is_empty()
+    /// Unhealthy servers get new sessions again once their cooldown is over
+    pub fn process_unhealthy_servers(&mut self) {
+        let now = self.clock.now();
+        let cooldown = UNHEALTHY_SERVER_COOLDOWN.as_secs();
+
+        let recovered_server_ids = self
+            .servers
+            .iter()
+            .filter(|server| !server.is_healthy && server.unhealthy_time + cooldown <= now)
+            .map(|server| server.game_server_id)
+            .collect::<Vec<_>>();
+
+        for server_id in recovered_server_ids {
+            log::info!(
+                "[{}] Game server {} healthy again after cooldown",
+                self.region_system_name,
+                server_id
+            );
+            self.set_server_healthy(server_id);
+        }
+    }
+
+    /// Delete draining servers without sessions left, returns their ids
+    pub fn process_drained_servers(&mut self) -> Vec<GameServerId> {
+        let drained_server_ids = self
+            .servers
+            .iter()
+            .filter(|server| server.is_draining && server.session_ids.is_empty())
+            .map(|server| server.game_server_id)
+            .collect::<Vec<_>>();
+
+        for server_id in &drained_server_ids {
+            log::info!(
+                "[{}] Game server {} drained",
+                self.region_system_name,
+                server_id
+            );
+            self.delete_server(server_id);
+        }
+
+        drained_server_ids
+    }
+
+    /// Number of idle servers and of servers hosting at least one session
+    pub fn count_servers(&self) -> (usize, usize) {
+        self.servers
+            .iter()
+            .fold((0, 0), |(num_idle_servers, num_active_servers), server| {
+                if server.session_ids.is_empty() {
+                    (num_idle_servers + 1, num_active_servers)
+                } else {
+                    (num_idle_servers, num_active_servers + 1)
+                }
+            })
+    }
+
+    pub fn has_available_server(&self) -> bool {
+        !self.available_servers.is_empty()
     }
 
-    pub fn get_idle_server_mut(&mut self) -> Option<&mut GameServer> {
-        self.idle_servers
+    /// Healthy server with a free slot running a game version, chosen by the server placement.
+    /// Any game version if empty
+    pub fn get_available_server_mut(&mut self, game_version: &str) -> Option<&mut GameServer> {
+        let server_id = self
+            .available_servers
             .iter()
-            .next()
-            .and_then(|id| self.servers.get_mut(id))
+            .filter_map(|id| self.servers.get(id))
+            .filter(|server| game_version.is_empty() || server.game_version == game_version)
+            .min_by(|server1, server2| self.server_placement.compare(server1, server2))?
+            .game_server_id;
+        self.servers.get_mut(&server_id)
     }
 
     pub fn get_server(&self, server_id: &GameServerId) -> Option<&GameServer> {
@@ -107,44 +180,74 @@ impl GameServerManager {
 
     pub fn create_server(&mut self, game_server: GameServer) -> bool {
         let server_id = game_server.game_server_id;
-        let session_id = game_server.session_id;
+        let is_available = game_server.is_available();
 
         if !self.servers.create(game_server) {
             return false;
         }
         self.active_servers.insert(server_id);
-        if session_id.is_none() {
-            self.idle_servers.insert(server_id);
+        if is_available {
+            self.available_servers.insert(server_id);
         }
         true
     }
 
     pub fn update_server(&mut self, server_id: GameServerId) {
         let Some(server) = self.servers.get(&server_id) else {
-            log::error!("[{}] Cannot find game server {} to update", self.region_system_name, server_id);
+            log::error!(
+                "[{}] Cannot find game server {} to update",
+                self.region_system_name,
+                server_id
+            );
             return;
         };
-        if server.session_id.is_none() {
-            self.idle_servers.insert(server_id);
+        if server.is_available() {
+            self.available_servers.insert(server_id);
         } else {
-            self.idle_servers.remove(&server_id);
+            self.available_servers.remove(&server_id);
         }
         self.servers.update(server_id);
     }
 
+    pub fn set_server_healthy(&mut self, server_id: GameServerId) {
+        let Some(server) = self.servers.get_mut(&server_id) else {
+            return;
+        };
+        if !server.is_healthy {
+            server.is_healthy = true;
+            server.unhealthy_time = 0;
+            self.update_server(server_id);
+        }
+    }
+
+    /// The server does not receive new sessions until it activates one or its cooldown is over
+    pub fn set_server_unhealthy(&mut self, server_id: GameServerId) {
+        let Some(server) = self.servers.get_mut(&server_id) else {
+            return;
+        };
+        server.is_healthy = false;
+        server.unhealthy_time = self.clock.now();
+        self.update_server(server_id);
+    }
+
     pub fn delete_server(&mut self, server_id: &GameServerId) -> Option<GameServer> {
         self.active_servers.remove(server_id);
-        self.idle_servers.remove(server_id);
+        self.available_servers.remove(server_id);
         self.servers.delete(server_id)
     }
 
-    pub fn keep_alive_server(&mut self, server_id: GameServerId) {
+    pub fn keep_alive_server(&mut self, server_id: GameServerId, load: f32) {
         let Some(server) = self.servers.get_mut(&server_id) else {
-            log::error!("[{}] Cannot find game server {} to keep alive", self.region_system_name, server_id);
+            log::error!(
+                "[{}] Cannot find game server {} to keep alive",
+                self.region_system_name,
+                server_id
+            );
             return;
         };
 
-        server.keep_alive_time = unix_now();
+        server.keep_alive_time = self.clock.now();
+        server.load = load;
         self.active_servers.remove(&server_id);
         self.active_servers.insert(server_id);
         self.servers.update(server_id);
@@ -53,22 +53,26 @@ where
     }
 
     //-------------------------------------------------------------------------------------------------
-    pub async fn save(&mut self) -> Result<(), Error> {
+    pub async fn save(&mut self, fencing_token: u64) -> Result<(), Error> {
+        let items_to_create = self
+            .items_to_create
+            .iter()
+            .map(|id| self.items.get(id).unwrap())
+            .collect::<Vec<_>>();
+        let items_to_update = self
+            .items_to_update
+            .iter()
+            .map(|id| self.items.get(id).unwrap())
+            .collect::<Vec<_>>();
+        let items_to_delete = self.items_to_delete.iter().cloned().collect::<Vec<_>>();
+
         let results = tokio::join!(
-            self.matchmaking_dal.create(
-                &self.region_system_name,
-                self.items_to_create
-                    .iter()
-                    .map(|id| self.items.get(id).unwrap())
-            ),
-            self.matchmaking_dal.update(
-                &self.region_system_name,
-                self.items_to_update
-                    .iter()
-                    .map(|id| self.items.get(id).unwrap())
-            ),
             self.matchmaking_dal
-                .delete(&self.region_system_name, self.items_to_delete.iter())
+                .create(&self.region_system_name, fencing_token, &items_to_create),
+            self.matchmaking_dal
+                .update(&self.region_system_name, fencing_token, &items_to_update),
+            self.matchmaking_dal
+                .delete(&self.region_system_name, fencing_token, &items_to_delete)
         );
 
         results.0?;
@@ -83,13 +87,15 @@ where
     }
 
     //-------------------------------------------------------------------------------------------------
-    pub async fn reset(&mut self) -> Result<(), Error> {
+    pub async fn reset(&mut self, fencing_token: u64) -> Result<(), Error> {
         self.items.clear();
         self.items_to_create.clear();
         self.items_to_update.clear();
         self.items_to_delete.clear();
 
-        self.matchmaking_dal.reset(&self.region_system_name).await?;
+        self.matchmaking_dal
+            .reset(&self.region_system_name, fencing_token)
+            .await?;
 
         Ok(())
     }
@@ -0,0 +1,32 @@
+use crate::{
+    backfill::*, game_server_manager::*, item_cache::*, matchmaking_assembler::*,
+    matchmaking_dal::*, matchmaking_job::*, matchmaking_metrics::*,
+    matchmaking_waiting_time_cache::*, notification_cache::*, queue_map::*, region_assignment::*,
+    region_lease::*, server_placement::*, team_balancer::*, util::*,
+};
+pub use crate::{
+    error::Error, matchmaking_master_job::MatchmakingMasterJob, region_assignment::RegionAssignment,
+};
+pub use cotonou_common::clock::{Clock, ManualClock, SystemClock};
+
+mod backfill;
+mod error;
+mod game_server_manager;
+mod item_cache;
+pub mod match_functions;
+pub mod matchmaker;
+mod matchmaking_assembler;
+mod matchmaking_dal;
+mod matchmaking_job;
+mod matchmaking_master_job;
+mod matchmaking_metrics;
+mod matchmaking_waiting_time_cache;
+mod notification_cache;
+mod queue_map;
+mod region_assignment;
+mod region_lease;
+mod server_placement;
+#[cfg(feature = "simulator")]
+pub mod simulator;
+mod team_balancer;
+mod util;
@@ -1,23 +1,13 @@
-use crate::{
-    error::*, game_server_manager::*, item_cache::*, matchmaking_assembler::*, matchmaking_dal::*,
-    matchmaking_job::*, matchmaking_master_job::*, matchmaking_waiting_time_cache::*,
-    notification_cache::*, queue_map::*, util::*,
-};
+use axum::{routing::get, Router};
+use cotonou_common::metrics::{get_metrics, MetricsRegistry};
+use cotonou_matchmaking_job::{Error, MatchmakingMasterJob, RegionAssignment};
+use hyper::server::{conn::AddrIncoming, Builder};
+use std::{net::SocketAddr, sync::Arc};
 use tokio::sync::watch;
 
-mod error;
-mod game_server_manager;
-mod item_cache;
-mod match_functions;
-mod matchmaker;
-mod matchmaking_assembler;
-mod matchmaking_dal;
-mod matchmaking_job;
-mod matchmaking_master_job;
-mod matchmaking_waiting_time_cache;
-mod notification_cache;
-mod queue_map;
-mod util;
+/// Environment variable holding the port of the `/metrics` endpoint scraped by Prometheus
+const METRICS_PORT_VAR: &str = "COTONOU_METRICS_PORT";
+const DEFAULT_METRICS_PORT: u16 = 9090;
 
 #[tokio::main]
 async fn main() -> Result<(), Error> {
@@ -30,8 +20,24 @@ async fn main() -> Result<(), Error> {
 
     log::info!("Starting cotonou-matchmaking-job...");
 
+    // comma separated list of regions to process, all regions shared among processes if unset
+    let region_assignment = RegionAssignment::from(
+        std::env::var("COTONOU_MATCHMAKING_REGIONS")
+            .unwrap_or_default()
+            .as_str(),
+    );
+    log::info!("Region assignment: {region_assignment:?}");
+
+    // bound before starting, so that an unavailable port stops the job
+    let metrics_port = get_metrics_port()?;
+    let metrics_server = axum::Server::try_bind(&SocketAddr::from(([0, 0, 0, 0], metrics_port)))?;
+    log::info!("Metrics endpoint listening on port {metrics_port}");
+    let metrics_registry = Arc::new(MetricsRegistry::new());
+    tokio::spawn(serve_metrics(metrics_server, metrics_registry.clone()));
+
     let (shutdown_sender, shutown_receiver) = watch::channel(());
-    let matchmaking_master_job = MatchmakingMasterJob::new(shutown_receiver).await?;
+    let matchmaking_master_job =
+        MatchmakingMasterJob::new(region_assignment, metrics_registry, shutown_receiver).await?;
 
     log::info!("cotonou-matchmaking-job started!");
 
@@ -52,3 +58,25 @@ async fn main() -> Result<(), Error> {
 
     Ok(())
 }
+
+fn get_metrics_port() -> Result<u16, Error> {
+    match std::env::var(METRICS_PORT_VAR) {
+        Ok(port) => port
+            .parse()
+            .map_err(|_| Error::Configuration(format!("{METRICS_PORT_VAR} is not a valid port"))),
+        Err(_) => Ok(DEFAULT_METRICS_PORT),
+    }
+}
+
+async fn serve_metrics(
+    metrics_server: Builder<AddrIncoming>,
+    metrics_registry: Arc<MetricsRegistry>,
+) {
+    let app = Router::new()
+        .route("/metrics", get(get_metrics))
+        .with_state(metrics_registry);
+
+    if let Err(e) = metrics_server.serve(app.into_make_service()).await {
+        log::error!("Metrics endpoint stopped: {e:?}");
+    }
+}
@@ -0,0 +1,193 @@
+use crate::{get_average_mmr, get_average_waiting_time, match_functions::MatchFunctions, Clock};
+use cotonou_common::{
+    matchmaking::{MatchCriterionConfig, MatchmakingPlayer},
+    types::ProfileId,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+/// Composition of criteria, each one with its own relaxation curve over the waiting time.
+/// The score of a match is the sum of the ratios between each criterion value and its limit
+#[derive(Clone)]
+pub struct CriteriaMatchFunctions {
+    criteria: Vec<MatchCriterionConfig>,
+    select_best_match: bool,
+    clock: Arc<dyn Clock>,
+}
+
+impl CriteriaMatchFunctions {
+    pub fn new(
+        criteria: Vec<MatchCriterionConfig>,
+        select_best_match: bool,
+        clock: Arc<dyn Clock>,
+    ) -> Self {
+        Self {
+            criteria,
+            select_best_match,
+            clock,
+        }
+    }
+}
+
+impl MatchFunctions for CriteriaMatchFunctions {
+    fn calculate_match(&self, group1: &[MatchmakingPlayer], group2: &[MatchmakingPlayer]) -> bool {
+        self.calculate_score(group1, group2).is_some()
+    }
+
+    fn calculate_score(
+        &self,
+        group1: &[MatchmakingPlayer],
+        group2: &[MatchmakingPlayer],
+    ) -> Option<f64> {
+        // the longest waiting group relaxes the criteria
+        let now = self.clock.now();
+        let waiting_time = [group1, group2]
+            .iter()
+            .filter(|group| !group.is_empty())
+            .map(|group| get_average_waiting_time(group, now) as u64)
+            .max()
+            .unwrap_or_default();
+
+        self.criteria
+            .iter()
+            .map(|criterion| calculate_criterion_score(criterion, waiting_time, group1, group2))
+            .sum()
+    }
+
+    fn select_best_match(&self) -> bool {
+        self.select_best_match
+    }
+
+    fn get_clock(&self) -> &dyn Clock {
+        self.clock.as_ref()
+    }
+}
+
+//-------------------------------------------------------------------------------------------------
+fn calculate_criterion_score(
+    criterion: &MatchCriterionConfig,
+    waiting_time: u64,
+    group1: &[MatchmakingPlayer],
+    group2: &[MatchmakingPlayer],
+) -> Option<f64> {
+    let players = || group1.iter().chain(group2.iter());
+
+    match criterion {
+        MatchCriterionConfig::Mmr { max_distance } => {
+            if group1.is_empty() || group2.is_empty() {
+                return Some(0.);
+            }
+            let distance = get_average_mmr(group1).abs_diff(get_average_mmr(group2));
+            get_ratio(distance as f64, max_distance.get_value(waiting_time))
+        }
+        MatchCriterionConfig::Latency { max_latency } => {
+            let worst_latency = players().map(|p| p.latency).max().unwrap_or_default();
+            get_ratio(worst_latency as f64, max_latency.get_value(waiting_time))
+        }
+        MatchCriterionConfig::PartySize { max_difference } => {
+            let difference =
+                get_biggest_party_size(group1).abs_diff(get_biggest_party_size(group2));
+            get_ratio(difference as f64, max_difference.get_value(waiting_time))
+        }
+        MatchCriterionConfig::Platform { max_platforms } => {
+            let num_platforms = players().map(|p| &p.platform).collect::<HashSet<_>>().len();
+            get_ratio(
+                num_platforms.saturating_sub(1) as f64,
+                max_platforms.get_value(waiting_time) - 1.,
+            )
+        }
+        MatchCriterionConfig::GameVersion => {
+            let mut players = players();
+            let client_version = players.next().map(|p| &p.attributes.client_version);
+            players
+                .all(|p| Some(&p.attributes.client_version) == client_version)
+                .then_some(0.)
+        }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------
+/// Ratio between a value and its limit, `None` if the limit is exceeded
+fn get_ratio(value: f64, limit: f64) -> Option<f64> {
+    if value > limit {
+        None
+    } else if limit <= 0. {
+        Some(0.)
+    } else {
+        Some(value / limit)
+    }
+}
+
+//-------------------------------------------------------------------------------------------------
+fn get_biggest_party_size(players: &[MatchmakingPlayer]) -> usize {
+    let mut party_sizes = HashMap::<ProfileId, usize>::new();
+    for player in players {
+        *party_sizes
+            .entry(player.party_id.unwrap_or(player.profile_id))
+            .or_default() += 1;
+    }
+    party_sizes.into_values().max().unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ManualClock;
+    use cotonou_common::matchmaking::RelaxationCurve;
+
+    const NOW: u64 = 1_000_000;
+
+    fn player(profile_id: i64, mmr: u32, latency: u32, waiting_time: u64) -> MatchmakingPlayer {
+        MatchmakingPlayer {
+            profile_id: ProfileId::try_from(profile_id).unwrap(),
+            mmr,
+            latency,
+            creation_time: NOW - waiting_time,
+            platform: "pc".to_owned(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn criteria_relax_with_waiting_time() {
+        let match_functions = CriteriaMatchFunctions::new(
+            vec![
+                MatchCriterionConfig::Mmr {
+                    max_distance: RelaxationCurve::Linear {
+                        initial: 100.,
+                        increase_per_second: 10.,
+                        max: 300.,
+                    },
+                },
+                MatchCriterionConfig::Latency {
+                    max_latency: RelaxationCurve::Step {
+                        steps: vec![(0, 80.), (30, 200.)],
+                    },
+                },
+                MatchCriterionConfig::GameVersion,
+            ],
+            true,
+            Arc::new(ManualClock::new(NOW)),
+        );
+
+        let closest = [player(2, 1550, 50, 0)];
+        assert_eq!(
+            Some(0.5 + 50. / 80.),
+            match_functions.calculate_score(&[player(1, 1500, 50, 0)], &closest)
+        );
+
+        let far = [player(2, 1700, 50, 0)];
+        assert!(!match_functions.calculate_match(&[player(1, 1500, 50, 0)], &far));
+        assert!(match_functions.calculate_match(&[player(1, 1500, 50, 10)], &far));
+
+        let laggy = [player(2, 1500, 150, 0)];
+        assert!(!match_functions.calculate_match(&[player(1, 1500, 50, 0)], &laggy));
+        assert!(match_functions.calculate_match(&[player(1, 1500, 50, 30)], &laggy));
+
+        let mut other_version = player(2, 1500, 50, 0);
+        other_version.attributes.client_version = "1.1".to_owned();
+        assert!(!match_functions.calculate_match(&[player(1, 1500, 50, 1000)], &[other_version]));
+    }
+}
@@ -1,12 +1,29 @@
-use crate::match_functions::MatchFunctions;
+use crate::{match_functions::MatchFunctions, Clock};
 use cotonou_common::matchmaking::MatchmakingPlayer;
+use std::sync::Arc;
 
 /// First come, first served
 #[derive(Clone)]
-pub struct FcFsMatchFunctions;
+pub struct FcFsMatchFunctions {
+    clock: Arc<dyn Clock>,
+}
+
+impl FcFsMatchFunctions {
+    pub fn new(clock: Arc<dyn Clock>) -> Self {
+        Self { clock }
+    }
+}
 
 impl MatchFunctions for FcFsMatchFunctions {
-    fn calculate_match(&self, _group1: &[MatchmakingPlayer], _group2: &[MatchmakingPlayer]) -> bool {
+    fn calculate_match(
+        &self,
+        _group1: &[MatchmakingPlayer],
+        _group2: &[MatchmakingPlayer],
+    ) -> bool {
         true
     }
+
+    fn get_clock(&self) -> &dyn Clock {
+        self.clock.as_ref()
+    }
 }
@@ -1,17 +1,20 @@
-use crate::{get_average_mmr, get_average_waiting_time, match_functions::MatchFunctions};
+use crate::{get_average_mmr, get_average_waiting_time, match_functions::MatchFunctions, Clock};
 use cotonou_common::matchmaking::MatchmakingPlayer;
+use std::sync::Arc;
 
 #[derive(Clone)]
 pub struct MmrMatchFunctions {
     max_mmr_distance: u32,
     waiting_time_weight: u32,
+    clock: Arc<dyn Clock>,
 }
 
 impl MmrMatchFunctions {
-    pub fn new(max_mmr_distance: u32, waiting_time_weight: u32) -> Self {
+    pub fn new(max_mmr_distance: u32, waiting_time_weight: u32, clock: Arc<dyn Clock>) -> Self {
         Self {
             max_mmr_distance,
             waiting_time_weight,
+            clock,
         }
     }
 }
@@ -20,11 +23,52 @@ impl MatchFunctions for MmrMatchFunctions {
     fn calculate_match(&self, group1: &[MatchmakingPlayer], group2: &[MatchmakingPlayer]) -> bool {
         let distance =
             (get_average_mmr(group1) as i64 - get_average_mmr(group2) as i64).unsigned_abs() as u32;
-        let waiting_time = get_average_waiting_time(group1);
+        let waiting_time = get_average_waiting_time(group1, self.clock.now());
 
         u32::min(
             waiting_time * self.waiting_time_weight,
             self.max_mmr_distance,
         ) > distance
     }
+
+    fn get_clock(&self) -> &dyn Clock {
+        self.clock.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ManualClock;
+    use cotonou_common::types::ProfileId;
+
+    const NOW: u64 = 1_000_000;
+
+    fn player(profile_id: i64, mmr: u32) -> MatchmakingPlayer {
+        MatchmakingPlayer {
+            profile_id: ProfileId::try_from(profile_id).unwrap(),
+            mmr,
+            creation_time: NOW,
+            platform: "pc".to_owned(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn mmr_window_expands_with_waiting_time() {
+        let clock = Arc::new(ManualClock::new(NOW));
+        let match_functions = MmrMatchFunctions::new(300, 10, clock.clone());
+        let group1 = [player(1, 1500)];
+
+        // the window grows by 10 MMR per second of waiting time
+        clock.advance(10);
+        assert!(!match_functions.calculate_match(&group1, &[player(2, 1600)]));
+        clock.advance(1);
+        assert!(match_functions.calculate_match(&group1, &[player(2, 1600)]));
+
+        // up to the max MMR distance
+        clock.advance(3600);
+        assert!(match_functions.calculate_match(&group1, &[player(2, 1799)]));
+        assert!(!match_functions.calculate_match(&group1, &[player(2, 1800)]));
+    }
 }
@@ -1,9 +1,14 @@
-use cotonou_common::matchmaking::{GameModeConfig, MatchmakingPlayer};
+use crate::{get_average_waiting_time, may_form_teams, Clock};
+use cotonou_common::matchmaking::{GameModeConfig, LatencyLimitConfig, MatchmakingPlayer};
+pub use criteria_match_functions::*;
 pub use fcfs_march_functions::*;
 pub use mmr_match_functions::*;
+pub use ticket_filters::*;
 
+mod criteria_match_functions;
 mod fcfs_march_functions;
 mod mmr_match_functions;
+mod ticket_filters;
 
 pub trait MatchFunctions: MatchFunctionsClone + Send {
     fn is_match(
@@ -12,14 +17,65 @@ pub trait MatchFunctions: MatchFunctionsClone + Send {
         group1: &[MatchmakingPlayer],
         group2: &[MatchmakingPlayer],
     ) -> bool {
+        self.score_match(game_mode_config, group1, group2).is_some()
+    }
+
+    /// Score of a match, lower is better
+    /// # Return
+    /// None if the groups do not match
+    fn score_match(
+        &self,
+        game_mode_config: &GameModeConfig,
+        group1: &[MatchmakingPlayer],
+        group2: &[MatchmakingPlayer],
+    ) -> Option<f64> {
         if !is_in_bounds(game_mode_config, group1.iter().chain(group2.iter())) {
-            return false;
+            return None;
+        }
+
+        if !passes_filters(
+            &game_mode_config.filters,
+            group1.iter().chain(group2.iter()),
+        ) {
+            return None;
+        }
+
+        if let Some(latency_limit) = &game_mode_config.latency_limit {
+            if !is_in_latency_limit(latency_limit, group1, group2, self.get_clock().now()) {
+                return None;
+            }
+        }
+
+        // teams are only balanced when the session is formed
+        if !may_form_teams(game_mode_config, group1.iter().chain(group2.iter())) {
+            return None;
         }
 
-        self.calculate_match(group1, group2)
+        self.calculate_score(group1, group2).map(|score| {
+            score
+                + get_preference_penalty(
+                    &game_mode_config.preferences,
+                    group1.iter().chain(group2.iter()),
+                )
+        })
     }
 
     fn calculate_match(&self, group1: &[MatchmakingPlayer], group2: &[MatchmakingPlayer]) -> bool;
+
+    fn calculate_score(
+        &self,
+        group1: &[MatchmakingPlayer],
+        group2: &[MatchmakingPlayer],
+    ) -> Option<f64> {
+        self.calculate_match(group1, group2).then_some(0.)
+    }
+
+    /// Pick the best scored candidate instead of the first matching one
+    fn select_best_match(&self) -> bool {
+        false
+    }
+
+    fn get_clock(&self) -> &dyn Clock;
 }
 
 pub fn is_in_bounds<'b, I: Iterator<Item = &'b MatchmakingPlayer>>(
@@ -30,6 +86,74 @@ pub fn is_in_bounds<'b, I: Iterator<Item = &'b MatchmakingPlayer>>(
     num_players >= game_mode_config.min_players && num_players <= game_mode_config.max_players
 }
 
+/// The worst latency of the players must not exceed a limit
+/// which relaxes with the waiting time of the longest waiting group
+pub fn is_in_latency_limit(
+    latency_limit: &LatencyLimitConfig,
+    group1: &[MatchmakingPlayer],
+    group2: &[MatchmakingPlayer],
+    now: u64,
+) -> bool {
+    let waiting_time = [group1, group2]
+        .iter()
+        .filter(|group| !group.is_empty())
+        .map(|group| get_average_waiting_time(group, now))
+        .max()
+        .unwrap_or_default();
+    let max_latency = latency_limit
+        .max_latency
+        .saturating_add(waiting_time.saturating_mul(latency_limit.waiting_time_weight))
+        .min(latency_limit.max_relaxed_latency);
+
+    group1
+        .iter()
+        .chain(group2.iter())
+        .all(|p| p.latency <= max_latency)
+}
+
+/// Find a candidate (session or ticket) matching a group of players
+/// # Return
+/// the best scored candidate if the match functions select the best match,
+/// otherwise the first matching candidate meeting the preferences of the game mode,
+/// or the one with the lowest preference penalty if none meets them
+pub fn find_match<'b, T, I: Iterator<Item = (T, &'b [MatchmakingPlayer])>>(
+    match_functions: &dyn MatchFunctions,
+    game_mode_config: &GameModeConfig,
+    players: &[MatchmakingPlayer],
+    candidates: I,
+) -> Option<T> {
+    let matching_candidates = candidates.filter_map(|(candidate, candidate_players)| {
+        match_functions
+            .score_match(game_mode_config, players, candidate_players)
+            .map(|score| (candidate, candidate_players, score))
+    });
+
+    if match_functions.select_best_match() {
+        return matching_candidates
+            .min_by(|(_, _, score1), (_, _, score2)| score1.total_cmp(score2))
+            .map(|(candidate, _, _)| candidate);
+    }
+
+    let mut best_candidate: Option<(T, f64)> = None;
+    for (candidate, candidate_players, _) in matching_candidates {
+        let penalty = get_preference_penalty(
+            &game_mode_config.preferences,
+            players.iter().chain(candidate_players.iter()),
+        );
+        if penalty <= 0. {
+            return Some(candidate);
+        }
+        if best_candidate
+            .as_ref()
+            .is_none_or(|(_, best_penalty)| penalty < *best_penalty)
+        {
+            best_candidate = Some((candidate, penalty));
+        }
+    }
+
+    best_candidate.map(|(candidate, _)| candidate)
+}
+
 /// cf. https://stackoverflow.com/questions/30353462/how-to-clone-a-struct-storing-a-boxed-trait-object
 pub trait MatchFunctionsClone {
     fn clone_box(&self) -> Box<dyn MatchFunctions>;
@@ -49,3 +173,96 @@ impl Clone for Box<dyn MatchFunctions> {
         self.clone_box()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ManualClock;
+    use cotonou_common::{matchmaking::MatchmakingSettingsDAL, types::ProfileId};
+    use std::sync::Arc;
+
+    const NOW: u64 = 1_700_000_000;
+
+    fn player(latency: u32, waiting_time: u64) -> MatchmakingPlayer {
+        MatchmakingPlayer {
+            profile_id: ProfileId::try_from(1).unwrap(),
+            mmr: 1500,
+            latency,
+            creation_time: NOW - waiting_time,
+            platform: "pc".to_owned(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn latency_limit_relaxes_with_waiting_time() {
+        let latency_limit = LatencyLimitConfig {
+            max_latency: 80,
+            waiting_time_weight: 5,
+            max_relaxed_latency: 200,
+        };
+
+        assert!(is_in_latency_limit(
+            &latency_limit,
+            &[player(80, 0)],
+            &[player(20, 0)],
+            NOW
+        ));
+        assert!(!is_in_latency_limit(
+            &latency_limit,
+            &[player(120, 0)],
+            &[player(20, 0)],
+            NOW
+        ));
+        assert!(is_in_latency_limit(
+            &latency_limit,
+            &[player(120, 10)],
+            &[player(20, 0)],
+            NOW
+        ));
+        assert!(!is_in_latency_limit(
+            &latency_limit,
+            &[player(250, 1000)],
+            &[player(20, 0)],
+            NOW
+        ));
+    }
+
+    #[test]
+    fn first_match_meets_preferences() {
+        // first come first served, preferring players sharing a voice language
+        let game_mode_config = MatchmakingSettingsDAL::new()
+            .get_matchmaking_settings()
+            .game_mode_configs[0]
+            .clone();
+        let match_functions = FcFsMatchFunctions::new(Arc::new(ManualClock::new(NOW)));
+
+        let speaker = |voice_language: &str| {
+            let mut player = player(0, 0);
+            player.attributes.voice_languages = vec![voice_language.to_owned()];
+            player
+        };
+        let english = [speaker("en")];
+        let french = [speaker("fr")];
+        let candidates = || [(1, &english[..]), (2, &french[..])].into_iter();
+
+        assert_eq!(
+            Some(2),
+            find_match(
+                &match_functions,
+                &game_mode_config,
+                &[speaker("fr")],
+                candidates()
+            )
+        );
+        assert_eq!(
+            Some(1),
+            find_match(
+                &match_functions,
+                &game_mode_config,
+                &[speaker("de")],
+                candidates()
+            )
+        );
+    }
+}
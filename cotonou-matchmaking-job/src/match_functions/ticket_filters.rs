@@ -0,0 +1,119 @@
+use cotonou_common::matchmaking::{MatchmakingPlayer, TicketFilterConfig, TicketPreferenceConfig};
+use std::collections::HashSet;
+
+/// Check the hard filters of a game mode on the attributes of the players
+pub fn passes_filters<'b, I: Iterator<Item = &'b MatchmakingPlayer> + Clone>(
+    filters: &[TicketFilterConfig],
+    players: I,
+) -> bool {
+    filters.iter().all(|filter| match filter {
+        TicketFilterConfig::ClientVersion => {
+            let mut client_versions = players.clone().map(|p| &p.attributes.client_version);
+            let first_client_version = client_versions.next();
+            client_versions.all(|v| Some(v) == first_client_version)
+        }
+        TicketFilterConfig::CrossPlay => {
+            let num_platforms = players
+                .clone()
+                .map(|p| &p.platform)
+                .collect::<HashSet<_>>()
+                .len();
+            num_platforms <= 1 || players.clone().all(|p| p.attributes.cross_play)
+        }
+        TicketFilterConfig::VoiceLanguage => {
+            share_value(players.clone().map(|p| &p.attributes.voice_languages))
+        }
+        TicketFilterConfig::MapPreference => {
+            share_value(players.clone().map(|p| &p.attributes.map_preferences))
+        }
+    })
+}
+
+/// Sum of the penalties of the soft preferences of a game mode not met by the players
+pub fn get_preference_penalty<'b, I: Iterator<Item = &'b MatchmakingPlayer> + Clone>(
+    preferences: &[TicketPreferenceConfig],
+    players: I,
+) -> f64 {
+    preferences
+        .iter()
+        .map(|preference| match preference {
+            TicketPreferenceConfig::VoiceLanguage { penalty } => {
+                if share_value(players.clone().map(|p| &p.attributes.voice_languages)) {
+                    0.
+                } else {
+                    *penalty
+                }
+            }
+            TicketPreferenceConfig::MapPreference { penalty } => {
+                if share_value(players.clone().map(|p| &p.attributes.map_preferences)) {
+                    0.
+                } else {
+                    *penalty
+                }
+            }
+        })
+        .sum()
+}
+
+//-------------------------------------------------------------------------------------------------
+/// Check that value lists have at least a common value, empty lists accepting any value
+fn share_value<'b, I: Iterator<Item = &'b Vec<String>>>(value_lists: I) -> bool {
+    let mut common_values: Option<HashSet<&String>> = None;
+
+    for values in value_lists.filter(|values| !values.is_empty()) {
+        let values = values.iter().collect::<HashSet<_>>();
+        common_values = Some(match common_values {
+            Some(common_values) => common_values.intersection(&values).copied().collect(),
+            None => values,
+        });
+    }
+
+    common_values.is_none_or(|common_values| !common_values.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cotonou_common::{matchmaking::TicketAttributes, types::ProfileId};
+
+    fn player(platform: &str, cross_play: bool, voice_languages: &[&str]) -> MatchmakingPlayer {
+        MatchmakingPlayer {
+            profile_id: ProfileId::try_from(1).unwrap(),
+            mmr: 1500,
+            platform: platform.to_owned(),
+            attributes: TicketAttributes {
+                client_version: "1.0".to_owned(),
+                cross_play,
+                voice_languages: voice_languages.iter().map(|l| l.to_string()).collect(),
+                map_preferences: Vec::new(),
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn incompatible_attributes_are_filtered() {
+        let filters = [
+            TicketFilterConfig::ClientVersion,
+            TicketFilterConfig::CrossPlay,
+            TicketFilterConfig::VoiceLanguage,
+        ];
+
+        let players = [
+            player("pc", true, &["fr", "en"]),
+            player("ps5", true, &["en"]),
+        ];
+        assert!(passes_filters(&filters, players.iter()));
+
+        let players = [player("pc", false, &[]), player("ps5", true, &[])];
+        assert!(!passes_filters(&filters, players.iter()));
+
+        let players = [player("pc", true, &["fr"]), player("pc", true, &["de"])];
+        assert!(!passes_filters(&filters, players.iter()));
+
+        let mut other_version = player("pc", true, &[]);
+        other_version.attributes.client_version = "1.1".to_owned();
+        let players = [player("pc", true, &[]), other_version];
+        assert!(!passes_filters(&filters, players.iter()));
+    }
+}
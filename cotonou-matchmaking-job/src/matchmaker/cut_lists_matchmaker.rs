@@ -1,6 +1,6 @@
 use crate::{
     get_average_mmr,
-    match_functions::{is_in_bounds, MatchFunctions},
+    match_functions::{find_match, is_in_bounds, MatchFunctions},
     matchmaker::{Matchmaker, MatchmakerContext},
     QueueMap,
 };
@@ -8,7 +8,7 @@ use cotonou_common::{
     matchmaking::{GameModeConfig, MatchmakingSession, MatchmakingTicket, SessionId},
     types::ProfileId,
 };
-use std::iter::repeat;
+use std::iter::repeat_n;
 
 const TICKET_INITIAL_CAPACITY: usize = 10;
 
@@ -55,21 +55,21 @@ impl CutListsMatchmaker {
                     continue;
                 };
 
-                for session_id in self.open_sessions.iter() {
-                    let Some(session) = context.get_session(session_id) else {
-                        continue;
-                    };
-
-                    if self.match_functions.is_match(
-                        &self.game_mode_config,
-                        &ticket.players,
-                        &session.players,
-                    ) {
-                        context.match_ticket_to_existing_session(*ticket_id, *session_id);
-                        break;
+                let session_id = find_match(
+                    self.match_functions.as_ref(),
+                    &self.game_mode_config,
+                    &ticket.players,
+                    self.open_sessions.iter().filter_map(|session_id| {
+                        context
+                            .get_session(session_id)
+                            .map(|session| (*session_id, session.players.as_slice()))
+                    }),
+                );
+
+                if let Some(session_id) = session_id {
+                    if context.match_ticket_to_existing_session(*ticket_id, session_id) {
+                        matched_tickets.push(*ticket_id);
                     }
-
-                    matched_tickets.push(*ticket_id);
                 }
             }
 
@@ -86,45 +86,60 @@ impl CutListsMatchmaker {
             };
 
             // create new sessions
-            for ticket1_id in open_tickets.iter() {
-                for ticket2_id in open_tickets.iter().filter(|id| **id != *ticket1_id) {
-                    let Some(ticket1) = context.get_ticket(ticket1_id) else {
-                        continue;
-                    };
-
-                    let Some(ticket2) = context.get_ticket(ticket2_id) else {
-                        continue;
-                    };
-
-                    if self.match_functions.is_match(
-                        &self.game_mode_config,
-                        &ticket1.players,
-                        &ticket2.players,
-                    ) {
-                        let session_id = SessionId::new();
-                        context
-                            .match_tickets_to_new_session(session_id, &[*ticket1_id, *ticket2_id]);
-
-                        self.open_sessions.insert(session_id);
-
-                        // a new session has been created, restart processing tickets with existing sessions
-                        return true;
-                    }
-                }
+            let mut new_session = None;
 
+            for ticket1_id in open_tickets.iter() {
                 let Some(ticket1) = context.get_ticket(ticket1_id) else {
                     continue;
                 };
 
-                if is_in_bounds(&self.game_mode_config, ticket1.players.iter()) {
+                let ticket2_id = find_match(
+                    self.match_functions.as_ref(),
+                    &self.game_mode_config,
+                    &ticket1.players,
+                    open_tickets
+                        .iter()
+                        .filter(|id| **id != *ticket1_id)
+                        .filter_map(|ticket2_id| {
+                            context
+                                .get_ticket(ticket2_id)
+                                .map(|ticket2| (*ticket2_id, ticket2.players.as_slice()))
+                        }),
+                );
+
+                // checked before the ticket is borrowed mutably by a session creation
+                let is_ticket1_in_bounds =
+                    is_in_bounds(&self.game_mode_config, ticket1.players.iter());
+
+                if let Some(ticket2_id) = ticket2_id {
                     let session_id = SessionId::new();
-                    context.match_tickets_to_new_session(session_id, &[*ticket1_id]);
+                    if context.match_tickets_to_new_session(session_id, &[*ticket1_id, ticket2_id])
+                    {
+                        new_session = Some((session_id, vec![*ticket1_id, ticket2_id]));
+                        break;
+                    }
+                }
+
+                if is_ticket1_in_bounds {
+                    let session_id = SessionId::new();
+                    if context.match_tickets_to_new_session(session_id, &[*ticket1_id]) {
+                        new_session = Some((session_id, vec![*ticket1_id]));
+                        break;
+                    }
+                }
+            }
 
-                    self.open_sessions.insert(session_id);
+            if let Some((session_id, matched_tickets)) = new_session {
+                self.open_sessions.insert(session_id);
 
-                    // a new session has been created, restart processing tickets with existing sessions
-                    return true;
+                if let Some(open_tickets) = self.open_tickets.get_mut(i) {
+                    for ticket_id in matched_tickets {
+                        open_tickets.remove(&ticket_id);
+                    }
                 }
+
+                // a new session has been created, restart processing tickets with existing sessions
+                return true;
             }
         }
 
@@ -138,7 +153,7 @@ impl Matchmaker for CutListsMatchmaker {
         if mmr_index >= self.open_tickets.len() {
             let num_elements_to_add = mmr_index - self.open_tickets.len() + 1;
             self.open_tickets
-                .extend(repeat(QueueMap::new()).take(num_elements_to_add));
+                .extend(repeat_n(QueueMap::new(), num_elements_to_add));
         }
         self.open_tickets[mmr_index].insert(ticket.owner_profile_id);
     }
@@ -1,36 +1,47 @@
-use crate::{MatchmakingAssembler, QueueMap, SessionCache, TicketCache};
+use crate::{
+    balance_teams, can_form_teams, Clock, MatchmakingAssembler, QueueMap, SessionCache, TicketCache,
+};
 use cotonou_common::{
-    matchmaking::{MatchmakingSession, MatchmakingSessionStatus, MatchmakingTicket, SessionId},
+    matchmaking::{
+        GameModeConfig, MatchmakingSession, MatchmakingSessionStatus, MatchmakingTicket,
+        PrivateSession, SessionId,
+    },
     types::ProfileId,
-    unix_now,
 };
 use std::collections::HashMap;
 
 pub struct MatchmakerContext<'a> {
     region_system_name: &'a str,
+    game_mode_config: &'a GameModeConfig,
     tickets: &'a mut TicketCache,
     sessions: &'a mut SessionCache,
     created_sessions: &'a mut QueueMap<SessionId>,
     matched_players: &'a mut HashMap<ProfileId, SessionId>,
     matchmaking_assembler: &'a mut MatchmakingAssembler,
+    clock: &'a dyn Clock,
 }
 
 impl<'a> MatchmakerContext<'a> {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         region_system_name: &'a str,
+        game_mode_config: &'a GameModeConfig,
         tickets: &'a mut TicketCache,
         sessions: &'a mut SessionCache,
         created_sessions: &'a mut QueueMap<SessionId>,
         matched_players: &'a mut HashMap<ProfileId, SessionId>,
         matchmaking_assembler: &'a mut MatchmakingAssembler,
+        clock: &'a dyn Clock,
     ) -> Self {
         Self {
             region_system_name,
+            game_mode_config,
             tickets,
             sessions,
             created_sessions,
             matched_players,
             matchmaking_assembler,
+            clock,
         }
     }
 
@@ -42,11 +53,104 @@ impl<'a> MatchmakerContext<'a> {
         self.sessions.get(session_id)
     }
 
+    /// # Return
+    /// false if the ticket has not been matched, when parties cannot be packed in the teams of the session for instance
     pub fn match_ticket_to_existing_session(
         &mut self,
         ticket_id: ProfileId,
         session_id: SessionId,
+    ) -> bool {
+        let Some(ticket) = self.tickets.get_mut(&ticket_id) else {
+            return false;
+        };
+
+        // already matched, by a backfill request for instance
+        if ticket.session_id.is_some() {
+            return false;
+        }
+
+        let Some(session) = self.sessions.get_mut(&session_id) else {
+            return false;
+        };
+
+        if !can_form_teams(
+            self.game_mode_config,
+            session.players.iter().chain(ticket.players.iter()),
+        ) {
+            log::debug!(
+                "[{}] Cannot form teams with ticket {} in session {}",
+                self.region_system_name,
+                ticket_id,
+                session_id
+            );
+            return false;
+        }
+
+        Self::match_ticket_to_session(
+            self.region_system_name,
+            self.matched_players,
+            self.matchmaking_assembler,
+            ticket,
+            session,
+        );
+        Self::balance_session_teams(self.region_system_name, self.game_mode_config, session);
+
+        self.tickets.update(ticket_id);
+        self.sessions.update(session_id);
+
+        true
+    }
+
+    /// # Return
+    /// false if no session has been created, when parties cannot be packed in the teams for instance
+    pub fn match_tickets_to_new_session(
+        &mut self,
+        session_id: SessionId,
+        tickets_to_match: &[ProfileId],
+    ) -> bool {
+        let players = tickets_to_match
+            .iter()
+            .filter_map(|ticket_id| self.tickets.get(ticket_id))
+            .flat_map(|ticket| ticket.players.iter());
+        if !can_form_teams(self.game_mode_config, players) {
+            log::debug!(
+                "[{}] Cannot form teams with tickets {:?}",
+                self.region_system_name,
+                tickets_to_match
+            );
+            return false;
+        }
+
+        self.create_session(session_id, tickets_to_match, None);
+        self.created_sessions.insert(session_id);
+
+        true
+    }
+
+    /// Create a private session for a ticket, without waiting for a server to be assigned
+    pub fn match_ticket_to_private_session(
+        &mut self,
+        session_id: SessionId,
+        ticket_id: ProfileId,
+        private_session: PrivateSession,
     ) {
+        self.create_session(session_id, &[ticket_id], Some(private_session));
+    }
+
+    /// Add a ticket to a session for a backfill request,
+    /// in the requested team or in balanced teams
+    pub fn match_ticket_to_backfill(
+        &mut self,
+        ticket_id: ProfileId,
+        session_id: SessionId,
+        team: Option<usize>,
+    ) {
+        let Some(team) = team else {
+            // the ticket has been validated against the session already
+            self.match_ticket_to_existing_session(ticket_id, session_id);
+            return;
+        };
+
         let Some(ticket) = self.tickets.get_mut(&ticket_id) else {
             return;
         };
@@ -63,14 +167,24 @@ impl<'a> MatchmakerContext<'a> {
             session,
         );
 
+        for player in session.players.iter_mut().filter(|p| {
+            ticket
+                .players
+                .iter()
+                .any(|tp| tp.profile_id == p.profile_id)
+        }) {
+            player.team = Some(team);
+        }
+
         self.tickets.update(ticket_id);
         self.sessions.update(session_id);
     }
 
-    pub fn match_tickets_to_new_session(
+    fn create_session(
         &mut self,
         session_id: SessionId,
         tickets_to_match: &[ProfileId],
+        private_session: Option<PrivateSession>,
     ) {
         let Some(ticket) = self.tickets.get(&tickets_to_match[0]) else {
             unreachable!()
@@ -80,13 +194,17 @@ impl<'a> MatchmakerContext<'a> {
             session_id,
             game_mode: ticket.game_mode.clone(),
             players: Vec::new(),
-            creation_time: unix_now(),
+            creation_time: self.clock.now(),
             status: MatchmakingSessionStatus::Created,
             is_open: true,
             game_server_id: None,
             ip_address: String::from(""),
             port: 0,
-            encryption_key: String::from(""),
+            encryption_key: new_encryption_key(),
+            private_session,
+            backfill_request: None,
+            activation_time: 0,
+            activation_attempts: 0,
         };
 
         let session_id = session.session_id;
@@ -111,8 +229,23 @@ impl<'a> MatchmakerContext<'a> {
             }
         }
 
+        Self::balance_session_teams(self.region_system_name, self.game_mode_config, &mut session);
+
         self.sessions.create(session);
-        self.created_sessions.insert(session_id);
+    }
+
+    fn balance_session_teams(
+        region_system_name: &str,
+        game_mode_config: &GameModeConfig,
+        session: &mut MatchmakingSession,
+    ) {
+        if !balance_teams(game_mode_config, &mut session.players) {
+            log::error!(
+                "[{}] Cannot form teams for session {}",
+                region_system_name,
+                session.session_id
+            );
+        }
     }
 
     fn match_ticket_to_session(
@@ -146,3 +279,12 @@ impl<'a> MatchmakerContext<'a> {
         matched_players.insert(ticket.owner_profile_id, session.session_id);
     }
 }
+
+/// Random 256 bits key, hex encoded, securing the communication between
+/// the game server and the game clients of a session
+fn new_encryption_key() -> String {
+    rand::random::<[u8; 32]>()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
@@ -3,7 +3,12 @@ mod matchmaker_context;
 mod mt_cut_lists_matchmaker;
 mod simple_list_matchmaker;
 
-use crate::match_functions::{FcFsMatchFunctions, MatchFunctions, MmrMatchFunctions};
+use crate::{
+    match_functions::{
+        CriteriaMatchFunctions, FcFsMatchFunctions, MatchFunctions, MmrMatchFunctions,
+    },
+    Clock,
+};
 use cotonou_common::matchmaking::{
     GameModeConfig, MatchFunctionsConfig, MatchmakerConfig, MatchmakingSession, MatchmakingTicket,
 };
@@ -11,12 +16,14 @@ use cut_lists_matchmaker::*;
 pub use matchmaker_context::*;
 use mt_cut_lists_matchmaker::*;
 use simple_list_matchmaker::*;
+use std::sync::Arc;
 
 pub fn new_matchmaker(
     region_system_name: &str,
     game_mode_config: GameModeConfig,
+    clock: Arc<dyn Clock>,
 ) -> Box<dyn Matchmaker> {
-    let match_functions = new_match_functions(&game_mode_config);
+    let match_functions = new_match_functions(&game_mode_config, clock);
 
     match game_mode_config.matchmaker_type {
         MatchmakerConfig::SimpleList => Box::new(SimpleListMatchmaker::new(
@@ -41,15 +48,27 @@ pub fn new_matchmaker(
     }
 }
 
-fn new_match_functions(game_mode_config: &GameModeConfig) -> Box<dyn MatchFunctions> {
+fn new_match_functions(
+    game_mode_config: &GameModeConfig,
+    clock: Arc<dyn Clock>,
+) -> Box<dyn MatchFunctions> {
     match game_mode_config.match_functions_type {
-        MatchFunctionsConfig::FirstComeFirstServed => Box::new(FcFsMatchFunctions),
+        MatchFunctionsConfig::FirstComeFirstServed => Box::new(FcFsMatchFunctions::new(clock)),
         MatchFunctionsConfig::Mmr {
             max_mmr_distance,
             waiting_time_weight,
         } => Box::new(MmrMatchFunctions::new(
             max_mmr_distance,
             waiting_time_weight,
+            clock,
+        )),
+        MatchFunctionsConfig::Criteria {
+            ref criteria,
+            select_best_match,
+        } => Box::new(CriteriaMatchFunctions::new(
+            criteria.clone(),
+            select_best_match,
+            clock,
         )),
     }
 }
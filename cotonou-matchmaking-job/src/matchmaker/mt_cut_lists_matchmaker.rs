@@ -1,8 +1,8 @@
 use crate::{
-    match_functions::{is_in_bounds, MatchFunctions},
+    get_average_mmr,
+    match_functions::{find_match, is_in_bounds, MatchFunctions},
     matchmaker::{Matchmaker, MatchmakerContext},
     QueueMap,
-    get_average_mmr,
 };
 use cotonou_common::{
     matchmaking::{
@@ -141,6 +141,8 @@ impl Matchmaker for MultiThreadedCutListsMatchmaker {
     }
 
     fn process(&mut self, context: &mut MatchmakerContext) {
+        let mut tickets_to_reinsert = Vec::new();
+
         for cut_list_idx in 0..self.msg_receivers.len() {
             let msg_receiver = &mut self.msg_receivers[cut_list_idx];
 
@@ -156,14 +158,53 @@ impl Matchmaker for MultiThreadedCutListsMatchmaker {
                     MessageFromJob::MatchToExistingSession {
                         ticket_id,
                         session_id,
-                    } => context.match_ticket_to_existing_session(ticket_id, session_id),
+                    } => {
+                        if !context.match_ticket_to_existing_session(ticket_id, session_id) {
+                            tickets_to_reinsert.extend(
+                                context
+                                    .get_ticket(&ticket_id)
+                                    .filter(|ticket| ticket.session_id.is_none())
+                                    .cloned(),
+                            );
+                        }
+                    }
                     MessageFromJob::MatchToNewSession {
                         session_id,
                         tickets_to_match,
-                    } => context.match_tickets_to_new_session(session_id, &tickets_to_match),
+                    } => {
+                        // tickets may have been matched or removed while the job was matching them
+                        let is_valid = tickets_to_match.iter().all(|ticket_id| {
+                            context
+                                .get_ticket(ticket_id)
+                                .is_some_and(|ticket| ticket.session_id.is_none())
+                        });
+
+                        if !is_valid
+                            || !context.match_tickets_to_new_session(session_id, &tickets_to_match)
+                        {
+                            // the job already opened the session on its side
+                            if let Err(e) = self.msg_senders[cut_list_idx]
+                                .send(MessageToJob::RemoveSession { session_id })
+                            {
+                                log::error!("Cannot remove session from MTCutListsMatchmaker: {e}");
+                            }
+
+                            tickets_to_reinsert.extend(
+                                tickets_to_match
+                                    .iter()
+                                    .filter_map(|ticket_id| context.get_ticket(ticket_id))
+                                    .filter(|ticket| ticket.session_id.is_none())
+                                    .cloned(),
+                            );
+                        }
+                    }
                 }
             }
         }
+
+        for ticket in &tickets_to_reinsert {
+            self.insert_ticket(ticket);
+        }
     }
 }
 
@@ -281,29 +322,29 @@ impl Job {
 
         for ticket_id in self.open_tickets.iter() {
             let Some(ticket_players) = self.ticket_players.get(ticket_id) else {
-                    continue;
-                };
-
-            for session_id in self.open_sessions.iter() {
-                let Some(session_players) = self.session_players.get(session_id) else {
-                        continue;
-                    };
-
-                if self.match_functions.is_match(
-                    &self.game_mode_config,
-                    ticket_players,
-                    session_players,
-                ) {
-                    if let Err(e) = self
-                        .msg_sender
-                        .send(MessageFromJob::MatchToExistingSession {
-                            ticket_id: *ticket_id,
-                            session_id: *session_id,
-                        })
-                    {
-                        log::error!("Cannot send MessageFromJob::MatchToExistingSession: {e}");
-                    }
-                    break;
+                continue;
+            };
+
+            let session_id = find_match(
+                self.match_functions.as_ref(),
+                &self.game_mode_config,
+                ticket_players,
+                self.open_sessions.iter().filter_map(|session_id| {
+                    self.session_players
+                        .get(session_id)
+                        .map(|session_players| (*session_id, session_players.as_slice()))
+                }),
+            );
+
+            if let Some(session_id) = session_id {
+                if let Err(e) = self
+                    .msg_sender
+                    .send(MessageFromJob::MatchToExistingSession {
+                        ticket_id: *ticket_id,
+                        session_id,
+                    })
+                {
+                    log::error!("Cannot send MessageFromJob::MatchToExistingSession: {e}");
                 }
 
                 matched_tickets.push(*ticket_id);
@@ -317,66 +358,129 @@ impl Job {
 
         // create new sessions
         for ticket1_id in self.open_tickets.iter() {
-            for ticket2_id in self.open_tickets.iter().filter(|id| **id != *ticket1_id) {
-                let Some(ticket1_players) = self.ticket_players.get(ticket1_id) else {
-                    continue;
-                 };
-
-                let Some(ticket2_players) = self.ticket_players.get(ticket2_id) else {
-                    continue;
-                };
-
-                if self.match_functions.is_match(
-                    &self.game_mode_config,
-                    ticket1_players,
-                    ticket2_players,
-                ) {
-                    let session_id = SessionId::new();
-                    if let Err(e) = self.msg_sender.send(MessageFromJob::MatchToNewSession {
-                        session_id,
-                        tickets_to_match: vec![*ticket1_id, *ticket2_id],
-                    }) {
-                        log::error!("Cannot send MessageFromJob::MatchToNewSession: {e}");
-                    }
+            let Some(ticket1_players) = self.ticket_players.get(ticket1_id) else {
+                continue;
+            };
+
+            let ticket2 = find_match(
+                self.match_functions.as_ref(),
+                &self.game_mode_config,
+                ticket1_players,
+                self.open_tickets
+                    .iter()
+                    .filter(|id| **id != *ticket1_id)
+                    .filter_map(|ticket2_id| {
+                        self.ticket_players.get(ticket2_id).map(|ticket2_players| {
+                            ((*ticket2_id, ticket2_players), ticket2_players.as_slice())
+                        })
+                    }),
+            );
+
+            let tickets_to_match = if let Some((ticket2_id, _)) = ticket2 {
+                vec![*ticket1_id, ticket2_id]
+            } else if is_in_bounds(&self.game_mode_config, ticket1_players.iter()) {
+                vec![*ticket1_id]
+            } else {
+                continue;
+            };
+
+            let session_id = SessionId::new();
+            let session_players = tickets_to_match
+                .iter()
+                .filter_map(|ticket_id| self.ticket_players.get(ticket_id))
+                .flatten()
+                .cloned()
+                .collect();
+
+            if let Err(e) = self.msg_sender.send(MessageFromJob::MatchToNewSession {
+                session_id,
+                tickets_to_match: tickets_to_match.clone(),
+            }) {
+                log::error!("Cannot send MessageFromJob::MatchToNewSession: {e}");
+            }
 
-                    self.open_sessions.insert(session_id);
-                    self.session_players.insert(
-                        session_id,
-                        ticket1_players
-                            .iter()
-                            .chain(ticket2_players.iter())
-                            .cloned()
-                            .collect(),
-                    );
-
-                    // a new session has been created, restart processing tickets with existing sessions
-                    return true;
-                }
+            self.open_sessions.insert(session_id);
+            self.session_players.insert(session_id, session_players);
+
+            for ticket_id in &tickets_to_match {
+                self.open_tickets.remove(ticket_id);
+                self.ticket_players.remove(ticket_id);
             }
 
-            let Some(ticket1_players) = self.ticket_players.get(ticket1_id) else {
-                continue;
-             };
+            // a new session has been created, restart processing tickets with existing sessions
+            return true;
+        }
 
-            if is_in_bounds(&self.game_mode_config, ticket1_players.iter()) {
-                let session_id = SessionId::new();
+        false
+    }
+}
 
-                if let Err(e) = self.msg_sender.send(MessageFromJob::MatchToNewSession {
-                    session_id,
-                    tickets_to_match: vec![*ticket1_id],
-                }) {
-                    log::error!("Cannot send MessageFromJob::MatchToNewSession: {e}");
-                }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{match_functions::MmrMatchFunctions, ManualClock};
+    use cotonou_common::matchmaking::MatchmakingSettingsDAL;
+    use std::sync::Arc;
+
+    const NOW: u64 = 1_000_000;
+
+    fn player(profile_id: i64) -> MatchmakingPlayer {
+        MatchmakingPlayer {
+            profile_id: ProfileId::try_from(profile_id).unwrap(),
+            mmr: 1500,
+            creation_time: NOW,
+            platform: "pc".to_owned(),
+            ..Default::default()
+        }
+    }
 
-                self.open_sessions.insert(session_id);
-                self.session_players
-                    .insert(session_id, ticket1_players.clone());
+    #[test]
+    fn ticket_matched_to_new_session_is_not_matched_again() {
+        let game_mode_config = MatchmakingSettingsDAL::new()
+            .get_matchmaking_settings()
+            .game_mode_configs[0]
+            .clone();
+        let (msg_to_job_sender, msg_to_job_receiver) = mpsc::channel();
+        let (msg_from_job_sender, msg_from_job_receiver) = mpsc::channel();
+        let mut job = Job::new(
+            game_mode_config,
+            Box::new(MmrMatchFunctions::new(
+                300,
+                10,
+                Arc::new(ManualClock::new(NOW + 10)),
+            )),
+            msg_from_job_sender,
+            msg_to_job_receiver,
+        );
+        drop(msg_to_job_sender);
 
-                // a new session has been created, restart processing tickets with existing sessions
-                return true;
-            }
+        for profile_id in 1..=2 {
+            let player = player(profile_id);
+            job.open_tickets.insert(player.profile_id);
+            job.ticket_players.insert(player.profile_id, vec![player]);
         }
 
-        false
+        job.process();
+
+        let mut matched_ticket_ids = Vec::new();
+        while let Ok(message) = msg_from_job_receiver.try_recv() {
+            match message {
+                MessageFromJob::MatchToExistingSession { ticket_id, .. } => {
+                    matched_ticket_ids.push(ticket_id)
+                }
+                MessageFromJob::MatchToNewSession {
+                    tickets_to_match, ..
+                } => matched_ticket_ids.extend(tickets_to_match),
+            }
+        }
+        matched_ticket_ids.sort();
+        assert_eq!(
+            vec![
+                ProfileId::try_from(1).unwrap(),
+                ProfileId::try_from(2).unwrap()
+            ],
+            matched_ticket_ids
+        );
+        assert!(job.open_tickets.iter().next().is_none());
     }
 }
@@ -1,5 +1,5 @@
 use crate::{
-    match_functions::{is_in_bounds, MatchFunctions},
+    match_functions::{find_match, is_in_bounds, MatchFunctions},
     matchmaker::{Matchmaker, MatchmakerContext},
     QueueMap,
 };
@@ -43,19 +43,20 @@ impl SimpleListMatchmaker {
                 continue;
             };
 
-            for session_id in self.open_sessions.iter() {
-                let Some(session) = context.get_session(session_id) else {
-                    continue;
-                };
-
-                if self.match_functions.is_match(
-                    &self.game_mode_config,
-                    &ticket.players,
-                    &session.players,
-                ) {
-                    context.match_ticket_to_existing_session(*ticket_id, *session_id);
+            let session_id = find_match(
+                self.match_functions.as_ref(),
+                &self.game_mode_config,
+                &ticket.players,
+                self.open_sessions.iter().filter_map(|session_id| {
+                    context
+                        .get_session(session_id)
+                        .map(|session| (*session_id, session.players.as_slice()))
+                }),
+            );
+
+            if let Some(session_id) = session_id {
+                if context.match_ticket_to_existing_session(*ticket_id, session_id) {
                     matched_tickets.push(*ticket_id);
-                    break;
                 }
             }
         }
@@ -65,47 +66,59 @@ impl SimpleListMatchmaker {
         }
 
         // create new sessions
-        for ticket1_id in self.open_tickets.iter() {
-            for ticket2_id in self.open_tickets.iter().filter(|id| **id != *ticket1_id) {
-                let Some(ticket1) = context.get_ticket(ticket1_id) else {
-                    continue;
-                };
-
-                let Some(ticket2) = context.get_ticket(ticket2_id) else {
-                    continue;
-                };
-
-                if self.match_functions.is_match(
-                    &self.game_mode_config,
-                    &ticket1.players,
-                    &ticket2.players,
-                ) {
-                    let session_id = SessionId::new();
-                    context.match_tickets_to_new_session(session_id, &[*ticket1_id, *ticket2_id]);
-
-                    self.open_sessions.insert(session_id);
-
-                    // a new session has been created, restart processing tickets with existing sessions
-                    return true;
-                }
-            }
+        let mut new_session = None;
 
+        for ticket1_id in self.open_tickets.iter() {
             let Some(ticket1) = context.get_ticket(ticket1_id) else {
                 continue;
             };
 
-            if is_in_bounds(&self.game_mode_config, ticket1.players.iter()) {
+            let ticket2_id = find_match(
+                self.match_functions.as_ref(),
+                &self.game_mode_config,
+                &ticket1.players,
+                self.open_tickets
+                    .iter()
+                    .filter(|id| **id != *ticket1_id)
+                    .filter_map(|ticket2_id| {
+                        context
+                            .get_ticket(ticket2_id)
+                            .map(|ticket2| (*ticket2_id, ticket2.players.as_slice()))
+                    }),
+            );
+
+            // checked before the ticket is borrowed mutably by a session creation
+            let is_ticket1_in_bounds = is_in_bounds(&self.game_mode_config, ticket1.players.iter());
+
+            if let Some(ticket2_id) = ticket2_id {
                 let session_id = SessionId::new();
-                context.match_tickets_to_new_session(session_id, &[*ticket1_id]);
-
-                self.open_sessions.insert(session_id);
+                if context.match_tickets_to_new_session(session_id, &[*ticket1_id, ticket2_id]) {
+                    new_session = Some((session_id, vec![*ticket1_id, ticket2_id]));
+                    break;
+                }
+            }
 
-                // a new session has been created, restart processing tickets with existing sessions
-                return true;
+            if is_ticket1_in_bounds {
+                let session_id = SessionId::new();
+                if context.match_tickets_to_new_session(session_id, &[*ticket1_id]) {
+                    new_session = Some((session_id, vec![*ticket1_id]));
+                    break;
+                }
             }
         }
 
-        false
+        let Some((session_id, matched_tickets)) = new_session else {
+            return false;
+        };
+
+        self.open_sessions.insert(session_id);
+
+        for ticket_id in matched_tickets {
+            self.open_tickets.remove(&ticket_id);
+        }
+
+        // a new session has been created, restart processing tickets with existing sessions
+        true
     }
 }
 
@@ -1,14 +1,15 @@
-use cotonou_common::{
-    matchmaking::{MatchmakingPlayer, MatchmakingPlayerStatus},
-    unix_now,
-};
+use crate::Clock;
+use cotonou_common::matchmaking::{MatchmakingPlayer, MatchmakingPlayerStatus};
+use std::sync::Arc;
 
 #[derive(Clone)]
-pub struct MatchmakingAssembler {}
+pub struct MatchmakingAssembler {
+    clock: Arc<dyn Clock>,
+}
 
 impl MatchmakingAssembler {
-    pub fn new() -> Self {
-        Self {}
+    pub fn new(clock: Arc<dyn Clock>) -> Self {
+        Self { clock }
     }
 
     pub fn convert_to_matchmaking_player(
@@ -16,13 +17,13 @@ impl MatchmakingAssembler {
         player: &MatchmakingPlayer,
         ticket_creation_time: u64,
     ) -> MatchmakingPlayer {
-        let now = unix_now();
+        let now = self.clock.now();
 
         let mut player = player.clone();
         player.creation_time = ticket_creation_time;
         player.new_status_time = now;
         player.status = MatchmakingPlayerStatus::Matched;
-        player.time_until_open_session = unix_now() - ticket_creation_time;
+        player.time_until_open_session = now - ticket_creation_time;
         player.time_until_close_session = 0;
 
         player
@@ -7,6 +7,7 @@ use cotonou_common::{
     types::{GameServerId, ProfileId},
 };
 use futures_util::future::BoxFuture;
+use std::sync::Arc;
 
 pub trait MatchmakingDAL<Id, Item> {
     //-------------------------------------------------------------------------------------------------
@@ -15,40 +16,38 @@ pub trait MatchmakingDAL<Id, Item> {
         Item: 'a;
 
     //-------------------------------------------------------------------------------------------------
-    fn create<'a, I>(
+    fn create<'a>(
         &'a self,
         region_system_name: &'a str,
-        items: I,
-    ) -> BoxFuture<'a, Result<(), Error>>
-    where
-        Item: 'a,
-        I: ExactSizeIterator<Item = &'a Item> + Clone + Send + 'a;
+        fencing_token: u64,
+        items: &'a [&'a Item],
+    ) -> BoxFuture<'a, Result<(), Error>>;
 
     //-------------------------------------------------------------------------------------------------
-    fn update<'a, I>(
+    fn update<'a>(
         &'a self,
         region_system_name: &'a str,
-        items: I,
-    ) -> BoxFuture<'a, Result<(), Error>>
-    where
-        Item: 'a,
-        I: ExactSizeIterator<Item = &'a Item> + Clone + Send + 'a;
+        fencing_token: u64,
+        items: &'a [&'a Item],
+    ) -> BoxFuture<'a, Result<(), Error>>;
 
     //-------------------------------------------------------------------------------------------------
-    fn delete<'a, I>(
+    fn delete<'a>(
         &'a self,
         region_system_name: &'a str,
-        ids: I,
-    ) -> BoxFuture<'a, Result<(), Error>>
-    where
-        Id: 'a,
-        I: ExactSizeIterator<Item = &'a Id> + Clone + Send + 'a;
+        fencing_token: u64,
+        ids: &'a [Id],
+    ) -> BoxFuture<'a, Result<(), Error>>;
 
     //-------------------------------------------------------------------------------------------------
-    fn reset<'a>(&'a self, region_system_name: &'a str) -> BoxFuture<'a, Result<(), Error>>;
+    fn reset<'a>(
+        &'a self,
+        region_system_name: &'a str,
+        fencing_token: u64,
+    ) -> BoxFuture<'a, Result<(), Error>>;
 }
 
-impl MatchmakingDAL<SessionId, MatchmakingSession> for MatchmakingSessionDAL {
+impl MatchmakingDAL<SessionId, MatchmakingSession> for Arc<dyn MatchmakingSessionDAL> {
     //-------------------------------------------------------------------------------------------------
     fn get<'a>(
         &'a self,
@@ -61,63 +60,63 @@ impl MatchmakingDAL<SessionId, MatchmakingSession> for MatchmakingSessionDAL {
     }
 
     //-------------------------------------------------------------------------------------------------
-    fn create<'a, I>(
+    fn create<'a>(
         &'a self,
         region_system_name: &'a str,
-        items: I,
-    ) -> BoxFuture<'a, Result<(), Error>>
-    where
-        MatchmakingSession: 'a,
-        I: ExactSizeIterator<Item = &'a MatchmakingSession> + Clone + Send + 'a,
-    {
+        fencing_token: u64,
+        items: &'a [&'a MatchmakingSession],
+    ) -> BoxFuture<'a, Result<(), Error>> {
         Box::pin(async move {
-            self.create_sessions(region_system_name, items).await?;
+            self.create_sessions(region_system_name, fencing_token, items)
+                .await?;
             Ok(())
         })
     }
 
     //-------------------------------------------------------------------------------------------------
-    fn update<'a, I>(
+    fn update<'a>(
         &'a self,
         region_system_name: &'a str,
-        items: I,
-    ) -> BoxFuture<'a, Result<(), Error>>
-    where
-        MatchmakingSession: 'a,
-        I: ExactSizeIterator<Item = &'a MatchmakingSession> + Clone + Send + 'a,
-    {
+        fencing_token: u64,
+        items: &'a [&'a MatchmakingSession],
+    ) -> BoxFuture<'a, Result<(), Error>> {
         Box::pin(async move {
-            self.update_sessions(region_system_name, items).await?;
+            self.update_sessions(region_system_name, fencing_token, items)
+                .await?;
             Ok(())
         })
     }
 
     //-------------------------------------------------------------------------------------------------
-    fn delete<'a, I>(
+    fn delete<'a>(
         &'a self,
         region_system_name: &'a str,
-        ids: I,
-    ) -> BoxFuture<'a, Result<(), Error>>
-    where
-        SessionId: 'a,
-        I: ExactSizeIterator<Item = &'a SessionId> + Clone + Send + 'a,
-    {
+        fencing_token: u64,
+        ids: &'a [SessionId],
+    ) -> BoxFuture<'a, Result<(), Error>> {
         Box::pin(async move {
-            self.delete_sessions(region_system_name, ids).await?;
+            self.delete_sessions(region_system_name, fencing_token, ids)
+                .await?;
             Ok(())
         })
     }
 
     //-------------------------------------------------------------------------------------------------
-    fn reset<'a>(&'a self, region_system_name: &'a str) -> BoxFuture<'a, Result<(), Error>> {
+    fn reset<'a>(
+        &'a self,
+        region_system_name: &'a str,
+        fencing_token: u64,
+    ) -> BoxFuture<'a, Result<(), Error>> {
         Box::pin(async move {
-            self.reset(region_system_name).await?;
+            self.as_ref()
+                .reset(region_system_name, fencing_token)
+                .await?;
             Ok(())
         })
     }
 }
 
-impl MatchmakingDAL<ProfileId, MatchmakingTicket> for MatchmakingTicketDAL {
+impl MatchmakingDAL<ProfileId, MatchmakingTicket> for Arc<dyn MatchmakingTicketDAL> {
     //-------------------------------------------------------------------------------------------------
     fn get<'a>(
         &'a self,
@@ -130,63 +129,63 @@ impl MatchmakingDAL<ProfileId, MatchmakingTicket> for MatchmakingTicketDAL {
     }
 
     //-------------------------------------------------------------------------------------------------
-    fn create<'a, I>(
+    fn create<'a>(
         &'a self,
         region_system_name: &'a str,
-        items: I,
-    ) -> BoxFuture<'a, Result<(), Error>>
-    where
-        MatchmakingTicket: 'a,
-        I: ExactSizeIterator<Item = &'a MatchmakingTicket> + Clone + Send + 'a,
-    {
+        fencing_token: u64,
+        items: &'a [&'a MatchmakingTicket],
+    ) -> BoxFuture<'a, Result<(), Error>> {
         Box::pin(async move {
-            self.create_tickets(region_system_name, items).await?;
+            self.create_tickets(region_system_name, fencing_token, items)
+                .await?;
             Ok(())
         })
     }
 
     //-------------------------------------------------------------------------------------------------
-    fn update<'a, I>(
+    fn update<'a>(
         &'a self,
         region_system_name: &'a str,
-        items: I,
-    ) -> BoxFuture<'a, Result<(), Error>>
-    where
-        MatchmakingTicket: 'a,
-        I: ExactSizeIterator<Item = &'a MatchmakingTicket> + Clone + Send + 'a,
-    {
+        fencing_token: u64,
+        items: &'a [&'a MatchmakingTicket],
+    ) -> BoxFuture<'a, Result<(), Error>> {
         Box::pin(async move {
-            self.update_tickets(region_system_name, items).await?;
+            self.update_tickets(region_system_name, fencing_token, items)
+                .await?;
             Ok(())
         })
     }
 
     //-------------------------------------------------------------------------------------------------
-    fn delete<'a, I>(
+    fn delete<'a>(
         &'a self,
         region_system_name: &'a str,
-        ids: I,
-    ) -> BoxFuture<'a, Result<(), Error>>
-    where
-        ProfileId: 'a,
-        I: ExactSizeIterator<Item = &'a ProfileId> + Clone + Send + 'a,
-    {
+        fencing_token: u64,
+        ids: &'a [ProfileId],
+    ) -> BoxFuture<'a, Result<(), Error>> {
         Box::pin(async move {
-            self.delete_tickets(region_system_name, ids).await?;
+            self.delete_tickets(region_system_name, fencing_token, ids)
+                .await?;
             Ok(())
         })
     }
 
     //-------------------------------------------------------------------------------------------------
-    fn reset<'a>(&'a self, region_system_name: &'a str) -> BoxFuture<'a, Result<(), Error>> {
+    fn reset<'a>(
+        &'a self,
+        region_system_name: &'a str,
+        fencing_token: u64,
+    ) -> BoxFuture<'a, Result<(), Error>> {
         Box::pin(async move {
-            self.reset(region_system_name).await?;
+            self.as_ref()
+                .reset(region_system_name, fencing_token)
+                .await?;
             Ok(())
         })
     }
 }
 
-impl MatchmakingDAL<GameServerId, GameServer> for GameServerDAL {
+impl MatchmakingDAL<GameServerId, GameServer> for Arc<dyn GameServerDAL> {
     //-------------------------------------------------------------------------------------------------
     fn get<'a>(
         &'a self,
@@ -199,57 +198,57 @@ impl MatchmakingDAL<GameServerId, GameServer> for GameServerDAL {
     }
 
     //-------------------------------------------------------------------------------------------------
-    fn create<'a, I>(
+    fn create<'a>(
         &'a self,
         region_system_name: &'a str,
-        items: I,
-    ) -> BoxFuture<'a, Result<(), Error>>
-    where
-        GameServer: 'a,
-        I: ExactSizeIterator<Item = &'a GameServer> + Clone + Send + 'a,
-    {
+        fencing_token: u64,
+        items: &'a [&'a GameServer],
+    ) -> BoxFuture<'a, Result<(), Error>> {
         Box::pin(async move {
-            self.create_game_servers(region_system_name, items).await?;
+            self.create_game_servers(region_system_name, fencing_token, items)
+                .await?;
             Ok(())
         })
     }
 
     //-------------------------------------------------------------------------------------------------
-    fn update<'a, I>(
+    fn update<'a>(
         &'a self,
         region_system_name: &'a str,
-        items: I,
-    ) -> BoxFuture<'a, Result<(), Error>>
-    where
-        GameServer: 'a,
-        I: ExactSizeIterator<Item = &'a GameServer> + Clone + Send + 'a,
-    {
+        fencing_token: u64,
+        items: &'a [&'a GameServer],
+    ) -> BoxFuture<'a, Result<(), Error>> {
         Box::pin(async move {
-            self.update_game_servers(region_system_name, items).await?;
+            self.update_game_servers(region_system_name, fencing_token, items)
+                .await?;
             Ok(())
         })
     }
 
     //-------------------------------------------------------------------------------------------------
-    fn delete<'a, I>(
+    fn delete<'a>(
         &'a self,
         region_system_name: &'a str,
-        ids: I,
-    ) -> BoxFuture<'a, Result<(), Error>>
-    where
-        GameServerId: 'a,
-        I: ExactSizeIterator<Item = &'a GameServerId> + Clone + Send + 'a,
-    {
+        fencing_token: u64,
+        ids: &'a [GameServerId],
+    ) -> BoxFuture<'a, Result<(), Error>> {
         Box::pin(async move {
-            self.delete_game_servers(region_system_name, ids).await?;
+            self.delete_game_servers(region_system_name, fencing_token, ids)
+                .await?;
             Ok(())
         })
     }
 
     //-------------------------------------------------------------------------------------------------
-    fn reset<'a>(&'a self, region_system_name: &'a str) -> BoxFuture<'a, Result<(), Error>> {
+    fn reset<'a>(
+        &'a self,
+        region_system_name: &'a str,
+        fencing_token: u64,
+    ) -> BoxFuture<'a, Result<(), Error>> {
         Box::pin(async move {
-            self.reset(region_system_name).await?;
+            self.as_ref()
+                .reset(region_system_name, fencing_token)
+                .await?;
             Ok(())
         })
     }
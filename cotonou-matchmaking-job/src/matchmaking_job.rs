@@ -1,47 +1,58 @@
 use crate::{
-    Error, MatchmakingAssembler,
-    MatchmakingWaitingTimeCache,
-    NotificationCache, ItemCache, 
-    GameServerManager, matchmaker::{Matchmaker, new_matchmaker, MatchmakerContext}, 
-    QueueMap,
+    can_backfill, can_form_teams, get_teams,
+    matchmaker::{new_matchmaker, Matchmaker, MatchmakerContext},
+    new_server_placement, Clock, Error, GameServerManager, ItemCache, MatchmakingAssembler,
+    MatchmakingMetrics, MatchmakingWaitingTimeCache, NotificationCache, QueueMap, RegionLease,
 };
 use cotonou_common::{
     matchmaking::{
-        GameServerDAL,
-        GameServer, GameServerHostType,
-        MatchmakingCommand,
-        MatchmakingCommandDAL,
-        MatchmakingTicket, MatchmakingPlayerStatus, MatchmakingPlayer,
-        MatchmakingFailedNotification, MatchmakingFailureReason, 
-        MatchmakingSessionStatus, MatchmakingSession, SessionId, 
-        MatchmakingCompletedNotification, 
-        MatchmakingActivateSessionNotification, 
-        MatchmakingServersFullNotification, 
-        MatchmakingWaitingTimeDAL, 
-        MatchmakingSessionDAL, 
-        MatchmakingSettingsDAL, 
-        MatchmakingTicketDAL
+        self, BackfillPlayer, BackfillRequest, CommandId, ConnectTokenClaims, GameServer,
+        GameServerDAL, GameServerDrainedNotification, GameServerHostType, JoinCode, Maintenance,
+        MaintenanceDAL, MatchmakingActivateSessionNotification,
+        MatchmakingBackfillCompletedNotification, MatchmakingBackfillTimeoutNotification,
+        MatchmakingCommand, MatchmakingCommandDAL, MatchmakingCompletedNotification,
+        MatchmakingFailedNotification, MatchmakingFailureReason, MatchmakingPlayerStatus,
+        MatchmakingServersFullNotification, MatchmakingSession,
+        MatchmakingSessionCanceledNotification, MatchmakingSessionDAL, MatchmakingSessionStatus,
+        MatchmakingSettingsDAL, MatchmakingTicket, MatchmakingTicketDAL, MatchmakingWaitingTimeDAL,
+        PrivateSession, QueuedMatchmakingCommand, SessionId, WaitingTimeStatistics,
     },
+    metrics::MetricsRegistry,
     notifications::NotificationManager,
-    unix_now,
     types::{GameServerId, ProfileId},
 };
-use std::{time::Duration, collections::HashMap};
+use std::{collections::HashMap, sync::Arc, time::Duration};
 use tokio::time::Instant;
 
 const LOOP_DURATION: Duration = Duration::from_secs(1);
+/// Delay between two samplings of the command queue length, matching the scrape interval of the metrics
+const COMMAND_QUEUE_SAMPLING_DURATION: Duration = Duration::from_secs(15);
 
-pub type TicketCache = ItemCache<ProfileId, MatchmakingTicket, MatchmakingTicketDAL>;
-pub type SessionCache = ItemCache<SessionId, MatchmakingSession, MatchmakingSessionDAL>;
+pub type TicketCache = ItemCache<ProfileId, MatchmakingTicket, Arc<dyn MatchmakingTicketDAL>>;
+pub type SessionCache = ItemCache<SessionId, MatchmakingSession, Arc<dyn MatchmakingSessionDAL>>;
 
 pub struct MatchmakingJob {
     region_system_name: String,
     _region_prefix: String,
-    matchmaking_command_dal: MatchmakingCommandDAL,
+    matchmaking_command_dal: Arc<dyn MatchmakingCommandDAL>,
+    maintenance_dal: Arc<dyn MaintenanceDAL>,
+    /// maintenance of the region
+    maintenance: Option<Maintenance>,
+    /// maintenance of all regions
+    global_maintenance: Option<Maintenance>,
+    /// commands delivered before a restart of the job and never acknowledged
+    recovered_commands: Vec<QueuedMatchmakingCommand>,
+    /// processed commands, acknowledged once the cache is saved
+    commands_to_ack: Vec<CommandId>,
+    /// fencing token of the lease the region is processed under, the writes of a stale one are refused
+    fencing_token: u64,
     matchmaking_settings_dal: MatchmakingSettingsDAL,
     waiting_time_cache: MatchmakingWaitingTimeCache,
     matchmaking_assembler: MatchmakingAssembler,
     notification_cache: NotificationCache,
+    metrics: MatchmakingMetrics,
+    /// last sampling of the command queue length (unix timestamp)
+    command_queue_sampling_time: u64,
     servers: GameServerManager,
     tickets: TicketCache,
     sessions: SessionCache,
@@ -51,38 +62,63 @@ pub struct MatchmakingJob {
     activating_players: QueueMap<(ProfileId, SessionId)>,
     shutdown_receiver: tokio::sync::watch::Receiver<()>,
     can_create_new_sessions: bool,
+    clock: Arc<dyn Clock>,
 }
 
 impl MatchmakingJob {
     //-------------------------------------------------------------------------------------------------
-    #[cfg_attr(feature = "cargo-clippy", allow(clippy::too_many_arguments))]
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         region_system_name: &str,
         region_prefix: &str,
-        game_server_dal: GameServerDAL,
-        matchmaking_command_dal: MatchmakingCommandDAL,
-        matchmaking_session_dal: MatchmakingSessionDAL,
-        matchmaking_ticket_dal: MatchmakingTicketDAL,
-        matchmaking_waiting_time_dal: MatchmakingWaitingTimeDAL,
+        game_server_dal: Arc<dyn GameServerDAL>,
+        matchmaking_command_dal: Arc<dyn MatchmakingCommandDAL>,
+        matchmaking_session_dal: Arc<dyn MatchmakingSessionDAL>,
+        matchmaking_ticket_dal: Arc<dyn MatchmakingTicketDAL>,
+        matchmaking_waiting_time_dal: Arc<dyn MatchmakingWaitingTimeDAL>,
+        maintenance_dal: Arc<dyn MaintenanceDAL>,
         matchmaking_assembler: MatchmakingAssembler,
-        notification_manager: NotificationManager,
+        notification_manager: Arc<dyn NotificationManager>,
+        metrics_registry: Arc<MetricsRegistry>,
         matchmaking_settings_dal: MatchmakingSettingsDAL,
         shutdown_receiver: tokio::sync::watch::Receiver<()>,
+        clock: Arc<dyn Clock>,
     ) -> Self {
         Self {
             region_system_name: region_system_name.to_owned(),
             _region_prefix: region_prefix.to_owned(),
             matchmaking_command_dal,
+            maintenance_dal,
+            maintenance: None,
+            global_maintenance: None,
+            recovered_commands: Vec::new(),
+            commands_to_ack: Vec::new(),
+            fencing_token: 0,
             matchmaking_settings_dal: matchmaking_settings_dal.clone(),
             waiting_time_cache: MatchmakingWaitingTimeCache::new(
                 region_system_name,
                 matchmaking_waiting_time_dal,
+                clock.clone(),
             ),
             matchmaking_assembler,
             notification_cache: NotificationCache::new(notification_manager),
+            metrics: MatchmakingMetrics::new(region_system_name, metrics_registry),
+            command_queue_sampling_time: 0,
             shutdown_receiver,
             can_create_new_sessions: true,
-            servers: GameServerManager::new(region_system_name, game_server_dal),
+            servers: GameServerManager::new(
+                region_system_name,
+                game_server_dal,
+                new_server_placement(
+                    &matchmaking_settings_dal
+                        .get_matchmaking_settings()
+                        .server_placement,
+                    &matchmaking_settings_dal
+                        .get_matchmaking_settings()
+                        .preferred_host_providers,
+                ),
+                clock.clone(),
+            ),
             tickets: ItemCache::new(region_system_name, matchmaking_ticket_dal),
             sessions: ItemCache::new(region_system_name, matchmaking_session_dal),
             created_sessions: QueueMap::new(),
@@ -90,20 +126,27 @@ impl MatchmakingJob {
                 .get_matchmaking_settings()
                 .game_mode_configs
                 .iter()
-                .map(|c|
+                .map(|c| {
                     (
-                        c.name.clone(), 
-                        new_matchmaker(region_system_name, c.clone())
-                    ))
+                        c.name.clone(),
+                        new_matchmaker(region_system_name, c.clone(), clock.clone()),
+                    )
+                })
                 .collect(),
             matched_players: HashMap::new(),
             activating_players: QueueMap::new(),
+            clock,
         }
     }
 
     //-------------------------------------------------------------------------------------------------
-    pub async fn job_loop(&mut self) -> Result<(), Error> {
-        log::info!("[{}] MatchmakingJob started", self.region_system_name);
+    /// Process the region until shutdown or until the lease is lost
+    pub async fn job_loop(&mut self, lease: &RegionLease) -> Result<(), Error> {
+        log::info!(
+            "[{}] MatchmakingJob started with fencing token {}",
+            self.region_system_name,
+            lease.fencing_token()
+        );
 
         if let Err(e) = self.load_cache().await {
             log::error!(
@@ -117,22 +160,27 @@ impl MatchmakingJob {
         while !self.shutdown_receiver.has_changed()? {
             let start = Instant::now();
 
-            self.process_commands().await?;
-            self.process_servers();
-            self.process_matchmakers();
-            self.process_sessions();
-            self.process_players();
+            // another job takes over the region
+            if !lease.renew().await? {
+                log::warn!(
+                    "[{}] Lease with fencing token {} lost or handed over",
+                    self.region_system_name,
+                    lease.fencing_token()
+                );
+                return Ok(());
+            }
 
-            if let Err(e) = self.save_cache().await {
-                log::error!(
-                    "[{}] Error while saving cache: {:?}",
+            if !self.process(lease).await? {
+                log::warn!(
+                    "[{}] Lease with fencing token {} lost before saving, changes discarded",
                     self.region_system_name,
-                    e
+                    lease.fencing_token()
                 );
-                return Err(e);
+                return Ok(());
             }
 
             let elapsed = start.elapsed();
+            self.metrics.loop_processed(elapsed, LOOP_DURATION);
             if elapsed < LOOP_DURATION {
                 tokio::time::sleep(LOOP_DURATION - elapsed).await;
             }
@@ -143,21 +191,138 @@ impl MatchmakingJob {
         Ok(())
     }
 
+    //-------------------------------------------------------------------------------------------------
+    /// One iteration of the job loop.
+    /// Returns `false` without saving anything if the lease was lost during the iteration
+    async fn process(&mut self, lease: &RegionLease) -> Result<bool, Error> {
+        self.fencing_token = lease.fencing_token();
+
+        self.process_commands().await?;
+        self.process_servers();
+        self.process_maintenance();
+        self.process_expired_tickets();
+        self.process_matchmakers();
+        self.process_sessions();
+        self.process_activating_sessions();
+        self.process_players();
+        self.update_metrics().await;
+
+        if !lease.is_held().await? {
+            return Ok(false);
+        }
+
+        // the lease may still be lost between the check and the writes, which are then refused
+        match self.save_cache().await {
+            Ok(()) => {}
+            Err(Error::Matchmaking(matchmaking::Error::StaleFencingToken(_))) => return Ok(false),
+            Err(e) => {
+                log::error!(
+                    "[{}] Error while saving cache: {:?}",
+                    self.region_system_name,
+                    e
+                );
+                return Err(e);
+            }
+        }
+
+        // commands are acknowledged only once their effects are saved
+        if !lease.is_held().await? {
+            return Ok(false);
+        }
+
+        match self.ack_commands().await {
+            Ok(()) => {}
+            Err(Error::Matchmaking(matchmaking::Error::StaleFencingToken(_))) => return Ok(false),
+            Err(e) => log::error!(
+                "[{}] Error while acknowledging commands: {:?}",
+                self.region_system_name,
+                e
+            ),
+        }
+
+        Ok(true)
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    async fn update_metrics(&mut self) {
+        let (num_idle_servers, num_active_servers) = self.servers.count_servers();
+        self.metrics
+            .set_game_servers(num_idle_servers, num_active_servers);
+
+        for game_mode_config in &self
+            .matchmaking_settings_dal
+            .get_matchmaking_settings()
+            .game_mode_configs
+        {
+            let queue_length = self
+                .tickets
+                .iter()
+                .filter(|t| t.session_id.is_none() && t.game_mode == game_mode_config.name)
+                .count();
+            self.metrics
+                .set_queue_length(&game_mode_config.name, queue_length);
+            self.waiting_time_cache.update_queue(
+                &game_mode_config.name,
+                queue_length,
+                num_idle_servers,
+            );
+        }
+
+        let now = self.clock.now();
+        if now < self.command_queue_sampling_time + COMMAND_QUEUE_SAMPLING_DURATION.as_secs() {
+            return;
+        }
+        self.command_queue_sampling_time = now;
+
+        match self
+            .matchmaking_command_dal
+            .get_queue_length(&self.region_system_name)
+            .await
+        {
+            Ok(queue_length) => self.metrics.set_command_queue_length(queue_length),
+            Err(e) => log::warn!(
+                "[{}] Cannot get command queue length: {:?}",
+                self.region_system_name,
+                e
+            ),
+        }
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    /// Stop reporting the gauges of the region, once another job processes it
+    pub fn remove_metrics(&self) {
+        self.metrics.remove_gauges();
+    }
+
     //-------------------------------------------------------------------------------------------------
     async fn load_cache(&mut self) -> Result<(), Error> {
         let results = tokio::join!(
             self.servers.load(),
             self.tickets.load(),
-            self.sessions.load()
+            self.sessions.load(),
+            self.maintenance_dal
+                .get_maintenance(Some(&self.region_system_name)),
+            self.maintenance_dal.get_maintenance(None)
         );
 
         results.0?;
         results.1?;
         results.2?;
+        self.maintenance = results.3?;
+        self.global_maintenance = results.4?;
+
+        self.recovered_commands = self
+            .matchmaking_command_dal
+            .recover_commands(&self.region_system_name)
+            .await?;
 
         for ticket in self.tickets.iter() {
             let Some(matchmaker) = self.matchmakers.get_mut(&ticket.game_mode) else {
-                log::error!("[{}] Cannot find matchmaker for game mode {}", self.region_system_name, ticket.game_mode);
+                log::error!(
+                    "[{}] Cannot find matchmaker for game mode {}",
+                    self.region_system_name,
+                    ticket.game_mode
+                );
                 continue;
             };
 
@@ -169,14 +334,28 @@ impl MatchmakingJob {
 
         for session in self.sessions.iter() {
             let Some(matchmaker) = self.matchmakers.get_mut(&session.game_mode) else {
-                log::error!("[{}] Cannot find matchmaker for game mode {}", self.region_system_name, session.game_mode);
+                log::error!(
+                    "[{}] Cannot find matchmaker for game mode {}",
+                    self.region_system_name,
+                    session.game_mode
+                );
                 continue;
             };
 
-            // session to match
-            if session.is_open {
+            // session to match, private sessions are only joined with their join code
+            if session.is_open && session.private_session.is_none() {
                 matchmaker.insert_session(session);
             }
+
+            // players waiting for the activation of their session
+            for player in session
+                .players
+                .iter()
+                .filter(|p| p.status == MatchmakingPlayerStatus::Matched)
+            {
+                self.matched_players
+                    .insert(player.profile_id, session.session_id);
+            }
         }
 
         Ok(())
@@ -185,36 +364,86 @@ impl MatchmakingJob {
     //-------------------------------------------------------------------------------------------------
     async fn save_cache(&mut self) -> Result<(), Error> {
         let results = tokio::join!(
-            self.servers.save(),
-            self.tickets.save(),
-            self.sessions.save(),
-            self.waiting_time_cache.save_cache(),
-            self.notification_cache.send_notifications()
+            self.servers.save(self.fencing_token),
+            self.tickets.save(self.fencing_token),
+            self.sessions.save(self.fencing_token),
+            self.waiting_time_cache.save_cache()
         );
 
         results.0?;
         results.1?;
         results.2?;
         results.3?;
-        results.4?;
+
+        // saved after their effects, a command whose effects were not saved is processed again
+        self.matchmaking_command_dal
+            .save_processed_commands(
+                &self.region_system_name,
+                self.fencing_token,
+                &self.commands_to_ack,
+            )
+            .await?;
+
+        // players are notified only of the changes that have been saved
+        self.notification_cache.send_notifications().await?;
 
         Ok(())
     }
 
     //-------------------------------------------------------------------------------------------------
     async fn process_commands(&mut self) -> Result<(), Error> {
-        let commands = self
+        let mut commands = std::mem::take(&mut self.recovered_commands);
+        commands.extend(
+            self.matchmaking_command_dal
+                .dequeue_commands(&self.region_system_name)
+                .await?,
+        );
+
+        let command_ids = commands
+            .iter()
+            .map(|c| c.command_id.clone())
+            .collect::<Vec<_>>();
+        let processed_commands = self
             .matchmaking_command_dal
-            .dequeue_commands(&self.region_system_name)
+            .get_processed_commands(&self.region_system_name, &command_ids)
             .await?;
 
-        for command in commands {
+        for QueuedMatchmakingCommand {
+            command_id,
+            command,
+        } in commands
+        {
+            // already processed, only the acknowledgement failed
+            if self.commands_to_ack.contains(&command_id) {
+                continue;
+            }
+
+            // already processed by a previous leader, which could not acknowledge it
+            if processed_commands.contains(&command_id) {
+                self.commands_to_ack.push(command_id);
+                continue;
+            }
+
             self.process_command(command).await?;
+            self.commands_to_ack.push(command_id);
         }
 
         Ok(())
     }
 
+    //-------------------------------------------------------------------------------------------------
+    async fn ack_commands(&mut self) -> Result<(), Error> {
+        self.matchmaking_command_dal
+            .ack_commands(
+                &self.region_system_name,
+                self.fencing_token,
+                &self.commands_to_ack,
+            )
+            .await?;
+        self.commands_to_ack.clear();
+        Ok(())
+    }
+
     //-------------------------------------------------------------------------------------------------
     async fn process_command(&mut self, command: MatchmakingCommand) -> Result<(), Error> {
         match command {
@@ -224,9 +453,23 @@ impl MatchmakingJob {
             } => {
                 self.activate_player_session(session_id, profile_id);
             }
-            MatchmakingCommand::ActivateSession { session_id } => self.activate_session(session_id),
+            MatchmakingCommand::ActivateSession {
+                session_id,
+                game_server_id,
+            } => self.activate_session(session_id, game_server_id),
+            MatchmakingCommand::CancelTicket { owner_profile_id } => {
+                self.delete_ticket(owner_profile_id, None);
+            }
+            MatchmakingCommand::CreatePrivateSession {
+                session_id,
+                join_code,
+                expiration_time,
+                ticket,
+            } => self.create_private_session(session_id, join_code, expiration_time, ticket),
             MatchmakingCommand::CreateTicket { ticket } => self.create_ticket(ticket),
-            MatchmakingCommand::DeleteSession { session_id } => self.command_delete_session(session_id),
+            MatchmakingCommand::DeleteSession { session_id } => {
+                self.command_delete_session(session_id)
+            }
             MatchmakingCommand::DeletePlayerSession {
                 session_id,
                 profile_id,
@@ -235,7 +478,10 @@ impl MatchmakingJob {
                 owner_profile_id,
                 player_who_canceled_profile_id,
             } => {
-                self.delete_ticket(owner_profile_id, player_who_canceled_profile_id);
+                self.delete_ticket(owner_profile_id, Some(player_who_canceled_profile_id));
+            }
+            MatchmakingCommand::DrainGameServer { game_server_id } => {
+                self.drain_game_server(game_server_id)
             }
             MatchmakingCommand::InitializeGameServer {
                 game_server_id,
@@ -247,6 +493,7 @@ impl MatchmakingJob {
                 process_id,
                 ip_address,
                 port,
+                capacity,
             } => self.initialize_game_server(
                 game_server_id,
                 host_name,
@@ -257,11 +504,24 @@ impl MatchmakingJob {
                 process_id,
                 ip_address,
                 port,
+                capacity,
             ),
-            MatchmakingCommand::KeepAliveGameServer { game_server_id } => {
-                self.keep_alive_game_server(game_server_id)
+            MatchmakingCommand::JoinPrivateSession { join_code, ticket } => {
+                self.join_private_session(join_code, ticket)
             }
+            MatchmakingCommand::KeepAliveGameServer {
+                game_server_id,
+                load,
+            } => self.keep_alive_game_server(game_server_id, load),
+            MatchmakingCommand::RequestBackfill {
+                session_id,
+                backfill_request,
+            } => self.request_backfill(session_id, backfill_request),
             MatchmakingCommand::ResetMatchmaking => self.reset_matchmaking().await?,
+            MatchmakingCommand::SetMaintenance {
+                is_global,
+                maintenance,
+            } => self.set_maintenance(is_global, maintenance),
             MatchmakingCommand::ShutdownGameServer { game_server_id } => {
                 self.shutdown_server(game_server_id)
             }
@@ -277,56 +537,336 @@ impl MatchmakingJob {
     //-------------------------------------------------------------------------------------------------
     fn activate_player_session(&mut self, session_id: SessionId, profile_id: ProfileId) {
         let Some(session) = self.sessions.get_mut(&session_id) else {
-            log::error!("[{}] Cannot find session {session_id} while activating player {profile_id}", self.region_system_name);
+            log::error!(
+                "[{}] Cannot find session {session_id} while activating player {profile_id}",
+                self.region_system_name
+            );
             return;
         };
 
-        let Some(player) = session.players.iter_mut().find(|p| p.profile_id == profile_id) else {
-            log::error!("[{}] Cannot find player to activate {profile_id} in session {session_id}", self.region_system_name);
+        let Some(player) = session
+            .players
+            .iter_mut()
+            .find(|p| p.profile_id == profile_id)
+        else {
+            log::error!(
+                "[{}] Cannot find player to activate {profile_id} in session {session_id}",
+                self.region_system_name
+            );
             return;
         };
 
-        player.new_status_time = unix_now();
+        if player.status != MatchmakingPlayerStatus::Activating {
+            log::warn!(
+                "[{}] Player {profile_id} is not activating in session {session_id}",
+                self.region_system_name
+            );
+            return;
+        }
+
+        player.new_status_time = self.clock.now();
         player.status = MatchmakingPlayerStatus::Active;
 
-        log::trace!("[{}] Player activated {profile_id} in session {session_id}", self.region_system_name);       
+        log::trace!(
+            "[{}] Player activated {profile_id} in session {session_id}",
+            self.region_system_name
+        );
 
         self.sessions.update(session_id);
-        self.tickets.delete(&profile_id);     
+        self.tickets.delete(&profile_id);
     }
 
     //-------------------------------------------------------------------------------------------------
-    fn activate_session(&mut self, session_id: SessionId) {
+    fn activate_session(&mut self, session_id: SessionId, game_server_id: GameServerId) {
         let Some(session) = self.sessions.get_mut(&session_id) else {
-            log::error!("[{}] Cannot find session to activate {session_id}", self.region_system_name);
+            log::error!(
+                "[{}] Cannot find session to activate {session_id}",
+                self.region_system_name
+            );
             return;
         };
 
+        // the session may have moved to another server since
+        if session.game_server_id != Some(game_server_id) {
+            log::warn!("[{}] Session {session_id} cannot be activated by server {game_server_id}, it is sent to server {:?}", self.region_system_name, session.game_server_id);
+            return;
+        }
+
         if session.status == MatchmakingSessionStatus::Active {
-            log::warn!("[{}] Session {session_id} is already active", self.region_system_name);
+            log::warn!(
+                "[{}] Session {session_id} is already active",
+                self.region_system_name
+            );
             return;
         }
 
-        log::trace!("[{}] Session {session_id} activated on server {:?}", self.region_system_name, session.game_server_id);     
+        log::trace!(
+            "[{}] Session {session_id} activated on server {:?}",
+            self.region_system_name,
+            session.game_server_id
+        );
 
         session.status = MatchmakingSessionStatus::Active;
-        self.sessions.update(session_id);      
+        self.sessions.update(session_id);
+
+        // a server activating a session is healthy again
+        self.servers.set_server_healthy(game_server_id);
     }
 
     //-------------------------------------------------------------------------------------------------
-    fn create_ticket(&mut self, ticket: MatchmakingTicket) {
+    fn create_private_session(
+        &mut self,
+        session_id: SessionId,
+        join_code: JoinCode,
+        expiration_time: u64,
+        ticket: MatchmakingTicket,
+    ) {
+        // command delivered again
+        if self.sessions.get(&session_id).is_some() {
+            log::warn!(
+                "[{}] Private session {session_id} already created",
+                self.region_system_name
+            );
+            return;
+        }
+
+        if self.find_private_session(&join_code).is_some() {
+            log::warn!(
+                "[{}] Join code {join_code} is already used by another private session",
+                self.region_system_name
+            );
+            self.fail_ticket(&ticket, MatchmakingFailureReason::InvalidInvitation);
+            return;
+        }
+
+        // servers only host sessions of their game version
         if self
-            .tickets
-            .delete(&ticket.owner_profile_id)
-            .is_some()
+            .servers
+            .get_available_server_mut(&ticket.attributes.client_version)
+            .is_none()
+        {
+            log::warn!(
+                "[{}] Cannot find a server for private session {session_id}",
+                self.region_system_name
+            );
+            self.fail_ticket(&ticket, MatchmakingFailureReason::PrivateServerNotFound);
+            return;
+        }
+
+        let Some(game_mode_config) = self
+            .matchmaking_settings_dal
+            .get_matchmaking_settings()
+            .game_mode_configs
+            .iter()
+            .find(|config| config.name == ticket.game_mode)
+        else {
+            log::error!(
+                "[{}] Cannot find game mode config for game mode {}",
+                self.region_system_name,
+                ticket.game_mode
+            );
+            return;
+        };
+
+        let owner_profile_id = ticket.owner_profile_id;
+        self.tickets.delete(&owner_profile_id);
+        self.metrics.ticket_created(&ticket.game_mode);
+        self.tickets.create(ticket);
+
+        MatchmakerContext::new(
+            &self.region_system_name,
+            game_mode_config,
+            &mut self.tickets,
+            &mut self.sessions,
+            &mut self.created_sessions,
+            &mut self.matched_players,
+            &mut self.matchmaking_assembler,
+            self.clock.as_ref(),
+        )
+        .match_ticket_to_private_session(
+            session_id,
+            owner_profile_id,
+            PrivateSession {
+                join_code,
+                expiration_time,
+                owner_profile_id,
+            },
+        );
+
+        log::trace!(
+            "[{}] Private session {session_id} created by player {owner_profile_id}",
+            self.region_system_name
+        );
+
+        // the server is reserved right away, before matchmakers can use it
+        self.start_session_on_server(&session_id);
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn join_private_session(&mut self, join_code: JoinCode, ticket: MatchmakingTicket) {
+        if self.is_ticket_already_created(&ticket) {
+            log::warn!(
+                "[{}] Ticket of player {} already joined private session {join_code}",
+                self.region_system_name,
+                ticket.owner_profile_id
+            );
+            return;
+        }
+
+        let session_id = match self.validate_private_session_join(&join_code, &ticket) {
+            Ok(session_id) => session_id,
+            Err(failure_reason) => {
+                self.fail_ticket(&ticket, failure_reason);
+                return;
+            }
+        };
+
+        let Some(game_mode_config) = self
+            .matchmaking_settings_dal
+            .get_matchmaking_settings()
+            .game_mode_configs
+            .iter()
+            .find(|config| config.name == ticket.game_mode)
+        else {
+            log::error!(
+                "[{}] Cannot find game mode config for game mode {}",
+                self.region_system_name,
+                ticket.game_mode
+            );
+            return;
+        };
+
+        let owner_profile_id = ticket.owner_profile_id;
+        self.tickets.delete(&owner_profile_id);
+        self.metrics.ticket_created(&ticket.game_mode);
+        self.tickets.create(ticket);
+
+        MatchmakerContext::new(
+            &self.region_system_name,
+            game_mode_config,
+            &mut self.tickets,
+            &mut self.sessions,
+            &mut self.created_sessions,
+            &mut self.matched_players,
+            &mut self.matchmaking_assembler,
+            self.clock.as_ref(),
+        )
+        .match_ticket_to_existing_session(owner_profile_id, session_id);
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn validate_private_session_join(
+        &self,
+        join_code: &JoinCode,
+        ticket: &MatchmakingTicket,
+    ) -> Result<SessionId, MatchmakingFailureReason> {
+        let Some(session) = self.find_private_session(join_code) else {
+            return Err(MatchmakingFailureReason::InvalidInvitation);
+        };
+
+        if session.game_mode != ticket.game_mode {
+            return Err(MatchmakingFailureReason::InvalidInvitation);
+        }
+
+        if session
+            .private_session
+            .as_ref()
+            .is_some_and(|p| self.clock.now() > p.expiration_time)
+        {
+            return Err(MatchmakingFailureReason::ExpiredInvitation);
+        }
+
+        if session.game_server_id.is_none() {
+            return Err(MatchmakingFailureReason::PrivateServerNotFound);
+        }
+
+        if !session.is_open {
+            return Err(MatchmakingFailureReason::PrivateServerClosed);
+        }
+
+        let Some(game_mode_config) = self
+            .matchmaking_settings_dal
+            .get_matchmaking_settings()
+            .game_mode_configs
+            .iter()
+            .find(|config| config.name == session.game_mode)
+        else {
+            return Err(MatchmakingFailureReason::PrivateServerNotFound);
+        };
+
+        if session.players.len() + ticket.players.len() > game_mode_config.max_players
+            || !can_form_teams(
+                game_mode_config,
+                session.players.iter().chain(ticket.players.iter()),
+            )
         {
+            return Err(MatchmakingFailureReason::PrivateServerFull);
+        }
+
+        Ok(session.session_id)
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn find_private_session(&self, join_code: &JoinCode) -> Option<&MatchmakingSession> {
+        self.sessions.iter().find(|s| {
+            s.private_session
+                .as_ref()
+                .is_some_and(|p| p.join_code == *join_code)
+        })
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn fail_ticket(
+        &mut self,
+        ticket: &MatchmakingTicket,
+        failure_reason: MatchmakingFailureReason,
+    ) {
+        log::trace!(
+            "[{}] Ticket of player {} failed: {:?}",
+            self.region_system_name,
+            ticket.owner_profile_id,
+            failure_reason
+        );
+
+        for player in &ticket.players {
+            self.notification_cache.queue_player_notification(
+                player.profile_id,
+                MatchmakingFailedNotification {
+                    onwer_profile_id: ticket.owner_profile_id,
+                    failure_reason: failure_reason.clone(),
+                    fallback_game_mode: None,
+                },
+            );
+        }
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    /// A command delivered again must not replace a ticket which may already be matched
+    fn is_ticket_already_created(&self, ticket: &MatchmakingTicket) -> bool {
+        self.tickets.get(&ticket.owner_profile_id).is_some_and(|t| {
+            t.creation_time == ticket.creation_time && t.game_mode == ticket.game_mode
+        })
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn create_ticket(&mut self, ticket: MatchmakingTicket) {
+        if self.is_ticket_already_created(&ticket) {
+            log::warn!(
+                "[{}] Ticket for player {} already created",
+                self.region_system_name,
+                ticket.owner_profile_id
+            );
+            return;
+        }
+
+        if self.tickets.delete(&ticket.owner_profile_id).is_some() {
             log::warn!(
                 "[{}] Ticket for player {} already existed and was replaced.",
                 self.region_system_name,
                 ticket.owner_profile_id
             );
         }
-        
+
         log::trace!(
             "[{}] Ticket created for players {} with requested game mode {}.",
             self.region_system_name,
@@ -340,50 +880,81 @@ impl MatchmakingJob {
         );
 
         let Some(matchmaker) = self.matchmakers.get_mut(&ticket.game_mode) else {
-            log::error!("[{}] Cannot find matchmaker for game mode {}", self.region_system_name, ticket.game_mode);
+            log::error!(
+                "[{}] Cannot find matchmaker for game mode {}",
+                self.region_system_name,
+                ticket.game_mode
+            );
             return;
         };
 
         matchmaker.insert_ticket(&ticket);
+        self.metrics.ticket_created(&ticket.game_mode);
         self.tickets.create(ticket);
     }
 
     //-------------------------------------------------------------------------------------------------
     fn command_delete_session(&mut self, session_id: SessionId) {
-        let Some(session) = self.sessions.get(&session_id) else {
-            log::error!("[{}] Cannot find session to delete {session_id}", self.region_system_name);
+        if self.sessions.get(&session_id).is_none() {
+            log::error!(
+                "[{}] Cannot find session to delete {session_id}",
+                self.region_system_name
+            );
             return;
         };
 
-        if let Some(server_id) = session.game_server_id {
-            if let Some(server) = self.servers.get_server_mut(&server_id) {
-                server.session_id = None;
-                let game_server_id = server.game_server_id;
-                self.servers.update_server(game_server_id);
-            } else {
-                log::warn!("[{}] Cannot find server {} while deleting session {session_id}", self.region_system_name, server_id);
-            };
-        }
-
+        self.release_session_server(&session_id);
         self.delete_session(&session_id);
     }
 
+    //-------------------------------------------------------------------------------------------------
+    /// Free the slot of a session on its game server
+    fn release_session_server(&mut self, session_id: &SessionId) {
+        let Some(session) = self.sessions.get(session_id) else {
+            return;
+        };
+
+        let Some(server_id) = session.game_server_id else {
+            return;
+        };
+
+        if let Some(server) = self.servers.get_server_mut(&server_id) {
+            server.session_ids.retain(|id| id != session_id);
+            self.servers.update_server(server_id);
+        } else {
+            log::warn!(
+                "[{}] Cannot find server {} while releasing session {session_id}",
+                self.region_system_name,
+                server_id
+            );
+        };
+    }
+
     //-------------------------------------------------------------------------------------------------
     fn delete_player_session(&mut self, session_id: SessionId, profile_id: ProfileId) {
         let Some(session) = self.sessions.get_mut(&session_id) else {
-            log::error!("[{}] Cannot find session {session_id} while deleting player {profile_id}", self.region_system_name);
+            log::error!(
+                "[{}] Cannot find session {session_id} while deleting player {profile_id}",
+                self.region_system_name
+            );
             return;
         };
 
         if self.tickets.delete(&profile_id).is_some() {
-            log::trace!("[{}] Ticket deleted for player {profile_id}", self.region_system_name);
+            log::trace!(
+                "[{}] Ticket deleted for player {profile_id}",
+                self.region_system_name
+            );
         }
 
         let old_num_players = session.players.len();
         session.players.retain(|p| p.profile_id != profile_id);
         let new_num_players = session.players.len();
         if new_num_players != old_num_players {
-            log::error!("[{}] Cannot find player to delete {profile_id} in session {session_id}", self.region_system_name);
+            log::error!(
+                "[{}] Cannot find player to delete {profile_id} in session {session_id}",
+                self.region_system_name
+            );
             return;
         }
 
@@ -392,33 +963,52 @@ impl MatchmakingJob {
     }
 
     //-------------------------------------------------------------------------------------------------
+    /// # Arguments
+    /// * `player_who_canceled_profile_id` - None if cancelled by the matchmaking service
     fn delete_ticket(
         &mut self,
         owner_profile_id: ProfileId,
-        player_who_canceled_profile_id: ProfileId,
+        player_who_canceled_profile_id: Option<ProfileId>,
     ) {
         let Some(ticket) = self.tickets.get(&owner_profile_id) else {
-            log::warn!("[{}] Cannot find ticket to delete for player {owner_profile_id}", self.region_system_name);
+            log::warn!(
+                "[{}] Cannot find ticket to delete for player {owner_profile_id}",
+                self.region_system_name
+            );
             return;
         };
 
         let Some(matchmaker) = self.matchmakers.get_mut(&ticket.game_mode) else {
-            log::error!("[{}] Cannot find matchmaker for game mode {}", self.region_system_name, ticket.game_mode);
+            log::error!(
+                "[{}] Cannot find matchmaker for game mode {}",
+                self.region_system_name,
+                ticket.game_mode
+            );
             return;
         };
 
         if let Some(session_id) = ticket.session_id {
             if let Some(session) = self.sessions.get_mut(&session_id) {
-                let players_to_delete = session.players
+                let players_to_delete = session
+                    .players
                     .iter()
-                    .filter_map(|p| if ticket.players.iter().any(|tp| tp.profile_id == p.profile_id) {
-                        Some(p.profile_id) 
-                    } else {
-                        None
+                    .filter_map(|p| {
+                        if ticket
+                            .players
+                            .iter()
+                            .any(|tp| tp.profile_id == p.profile_id)
+                        {
+                            Some(p.profile_id)
+                        } else {
+                            None
+                        }
                     })
                     .collect::<Vec<_>>();
-                session.players.retain(|p| !players_to_delete.contains(&p.profile_id));
-                log::trace!("[{}] Players deleted {} from session {}. Session now contains {} player(s)", 
+                session
+                    .players
+                    .retain(|p| !players_to_delete.contains(&p.profile_id));
+                log::trace!(
+                    "[{}] Players deleted {} from session {}. Session now contains {} player(s)",
                     self.region_system_name,
                     players_to_delete
                         .iter()
@@ -431,16 +1021,31 @@ impl MatchmakingJob {
             }
         }
 
-        for player in ticket.players.iter().filter(|p| p.profile_id != player_who_canceled_profile_id) {
-            self.notification_cache.queue_player_notification(player.profile_id, MatchmakingFailedNotification {
-                onwer_profile_id: ticket.owner_profile_id,
-                failure_reason: MatchmakingFailureReason::CancelledByFriend,
-            });
+        let failure_reason = match player_who_canceled_profile_id {
+            Some(_) => MatchmakingFailureReason::CancelledByFriend,
+            None => MatchmakingFailureReason::CancelledByMatchmakingService,
+        };
+
+        for player in ticket
+            .players
+            .iter()
+            .filter(|p| Some(p.profile_id) != player_who_canceled_profile_id)
+        {
+            self.notification_cache.queue_player_notification(
+                player.profile_id,
+                MatchmakingFailedNotification {
+                    onwer_profile_id: ticket.owner_profile_id,
+                    failure_reason: failure_reason.clone(),
+                    fallback_game_mode: None,
+                },
+            );
         }
 
-        log::trace!("[{}] Ticket deleted for players {}", 
+        log::trace!(
+            "[{}] Ticket deleted for players {}",
             self.region_system_name,
-            ticket.players
+            ticket
+                .players
                 .iter()
                 .map(|p| p.profile_id.to_string())
                 .collect::<Vec<String>>()
@@ -448,9 +1053,29 @@ impl MatchmakingJob {
         );
 
         matchmaker.remove_ticket(ticket);
+        self.metrics.ticket_cancelled(&ticket.game_mode);
         self.tickets.delete(&owner_profile_id);
     }
 
+    //-------------------------------------------------------------------------------------------------
+    fn drain_game_server(&mut self, game_server_id: GameServerId) {
+        let Some(server) = self.servers.get_server_mut(&game_server_id) else {
+            log::warn!(
+                "[{}] Cannot find game server {game_server_id} to drain",
+                self.region_system_name
+            );
+            return;
+        };
+
+        server.is_draining = true;
+        self.servers.update_server(game_server_id);
+
+        log::trace!(
+            "[{}] Game server {game_server_id} draining",
+            self.region_system_name
+        );
+    }
+
     //-------------------------------------------------------------------------------------------------
     #[allow(clippy::too_many_arguments)]
     fn initialize_game_server(
@@ -464,6 +1089,7 @@ impl MatchmakingJob {
         process_id: u32,
         ip_address: String,
         port: u16,
+        capacity: usize,
     ) {
         if self.servers.get_server(&game_server_id).is_some() {
             log::trace!(
@@ -471,6 +1097,8 @@ impl MatchmakingJob {
                 self.region_system_name,
                 game_server_id
             );
+            // a restarted server gets a new chance to activate sessions
+            self.servers.set_server_healthy(game_server_id);
             return;
         }
 
@@ -484,39 +1112,99 @@ impl MatchmakingJob {
             process_id,
             ip_address,
             port,
-            session_id: None,
-            keep_alive_time: unix_now(),
+            session_ids: Vec::new(),
+            capacity,
+            load: 0.,
+            is_healthy: true,
+            unhealthy_time: 0,
+            is_draining: false,
+            keep_alive_time: self.clock.now(),
         };
 
-        log::trace!("[{}] Game server id={}, ip_address={}, port={}, host_name={}, host_boot_time={}, host_provider={}, host_type={}, process_id={}, game_version={} initialized",
-            self.region_system_name, 
-            server.game_server_id, 
-            server.ip_address, 
-            server.port, 
-            server.host_name, 
-            server.host_boot_time, 
+        log::trace!(
+            "[{}] Game server id={}, ip_address={}, port={}, host_name={}, host_boot_time={}, host_provider={}, host_type={}, process_id={}, game_version={}, capacity={} initialized",
+            self.region_system_name,
+            server.game_server_id,
+            server.ip_address,
+            server.port,
+            server.host_name,
+            server.host_boot_time,
             server.host_provider,
-            server.host_type, 
-            server.process_id, 
-            server.game_version);
+            server.host_type,
+            server.process_id,
+            server.game_version,
+            server.capacity
+        );
 
         self.servers.create_server(server);
     }
 
     //-------------------------------------------------------------------------------------------------
-    fn keep_alive_game_server(&mut self, game_server_id: GameServerId) {
-        self.servers.keep_alive_server(game_server_id);
+    fn keep_alive_game_server(&mut self, game_server_id: GameServerId, load: f32) {
+        self.servers.keep_alive_server(game_server_id, load);
     }
 
     //-------------------------------------------------------------------------------------------------
-    async fn reset_matchmaking(&mut self) -> Result<(), Error>  {
+    fn request_backfill(&mut self, session_id: SessionId, backfill_request: BackfillRequest) {
+        let Some(session) = self.sessions.get_mut(&session_id) else {
+            log::error!(
+                "[{}] Cannot find session {session_id} to backfill",
+                self.region_system_name
+            );
+            return;
+        };
+
+        if session.game_server_id.is_none() {
+            log::warn!(
+                "[{}] Cannot backfill session {session_id} without server",
+                self.region_system_name
+            );
+            return;
+        }
+
+        if let Some(current_request) = &session.backfill_request {
+            // command delivered again, incoming players must be kept
+            if current_request.expiration_time == backfill_request.expiration_time
+                && current_request.num_players == backfill_request.num_players
+            {
+                log::warn!(
+                    "[{}] Backfill of session {session_id} already requested",
+                    self.region_system_name
+                );
+                return;
+            }
+            log::warn!(
+                "[{}] Backfill request of session {session_id} replaced",
+                self.region_system_name
+            );
+        }
+
+        log::trace!(
+            "[{}] Backfill of {} player(s) requested for session {session_id}",
+            self.region_system_name,
+            backfill_request.num_players
+        );
+
+        session.backfill_request = Some(BackfillRequest {
+            incoming_players: Vec::new(),
+            ..backfill_request
+        });
+        self.sessions.update(session_id);
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    async fn reset_matchmaking(&mut self) -> Result<(), Error> {
         // Cancel tickets to match
         for ticket in self.tickets.iter().filter(|t| t.session_id.is_none()) {
             for player in &ticket.players {
-                self.notification_cache.queue_player_notification(player.profile_id, MatchmakingFailedNotification {
-                    onwer_profile_id: ticket.owner_profile_id,
-                    failure_reason: MatchmakingFailureReason::CancelledByMatchmakingService,
-                })
+                self.notification_cache.queue_player_notification(
+                    player.profile_id,
+                    MatchmakingFailedNotification {
+                        onwer_profile_id: ticket.owner_profile_id,
+                        failure_reason: MatchmakingFailureReason::CancelledByMatchmakingService,
+                        fallback_game_mode: None,
+                    },
+                )
             }
         }
 
@@ -524,13 +1212,12 @@ impl MatchmakingJob {
     }
 
     //-------------------------------------------------------------------------------------------------
-    async fn reset_cache(&mut self) -> Result<(), Error> 
-    {
+    async fn reset_cache(&mut self) -> Result<(), Error> {
         // Reset caches. Caches will reset the data they own in Redis
         let results = tokio::join!(
-            self.servers.reset(),
-            self.tickets.reset(),
-            self.sessions.reset(),
+            self.servers.reset(self.fencing_token),
+            self.tickets.reset(self.fencing_token),
+            self.sessions.reset(self.fencing_token),
             self.waiting_time_cache.reset(),
         );
 
@@ -542,36 +1229,65 @@ impl MatchmakingJob {
         Ok(())
     }
 
+    //-------------------------------------------------------------------------------------------------
+    fn set_maintenance(&mut self, is_global: bool, maintenance: Option<Maintenance>) {
+        log::info!(
+            "[{}] {} maintenance set to {:?}",
+            self.region_system_name,
+            if is_global { "Global" } else { "Region" },
+            maintenance
+        );
+
+        if is_global {
+            self.global_maintenance = maintenance;
+        } else {
+            self.maintenance = maintenance;
+        }
+    }
+
     //-------------------------------------------------------------------------------------------------
     fn shutdown_server(&mut self, game_server_id: GameServerId) {
         let Some(server) = self.servers.get_server(&game_server_id) else {
-            log::warn!("[{}] Cannot find game server {game_server_id} to shutdown", self.region_system_name);
+            log::warn!(
+                "[{}] Cannot find game server {game_server_id} to shutdown",
+                self.region_system_name
+            );
             return;
         };
 
-        if let Some(session_id) = server.session_id {
+        for session_id in server.session_ids.clone() {
             self.delete_session(&session_id)
         }
 
         self.servers.delete_server(&game_server_id);
 
-        log::trace!("[{}] Game server {game_server_id} shut down", self.region_system_name);
+        log::trace!(
+            "[{}] Game server {game_server_id} shut down",
+            self.region_system_name
+        );
     }
 
     //-------------------------------------------------------------------------------------------------
     fn update_session(&mut self, session_id: SessionId, is_open: bool) {
         let Some(session) = self.sessions.get_mut(&session_id) else {
-            log::error!("[{}] Cannot find session to update {session_id}", self.region_system_name);
+            log::error!(
+                "[{}] Cannot find session to update {session_id}",
+                self.region_system_name
+            );
             return;
         };
 
         if session.status != MatchmakingSessionStatus::Active {
-            log::warn!("[{}] Cannot update session {session_id} because it is not active (status={:?})", self.region_system_name, session.status);
+            log::warn!(
+                "[{}] Cannot update session {session_id} because it is not active (status={:?})",
+                self.region_system_name,
+                session.status
+            );
             return;
         }
 
         if session.is_open && !is_open {
-            let now = unix_now();
+            let now = self.clock.now();
 
             for player in &mut session.players {
                 player.time_until_close_session = now - player.creation_time;
@@ -580,18 +1296,25 @@ impl MatchmakingJob {
 
         session.is_open = is_open;
 
-        if is_open
-        {
-            log::trace!("[{}] Session {session_id} is now open", self.region_system_name);
-        }
-        else
-        {
-            log::trace!("[{}] Session {session_id} is now closed", self.region_system_name);
+        if is_open {
+            log::trace!(
+                "[{}] Session {session_id} is now open",
+                self.region_system_name
+            );
+        } else {
+            log::trace!(
+                "[{}] Session {session_id} is now closed",
+                self.region_system_name
+            );
         }
 
-        if !session.is_open {
+        if !session.is_open && session.private_session.is_none() {
             let Some(matchmaker) = self.matchmakers.get_mut(&session.game_mode) else {
-                log::error!("[{}] Cannot find matchmaker for game mode {}", self.region_system_name, session.game_mode);
+                log::error!(
+                    "[{}] Cannot find matchmaker for game mode {}",
+                    self.region_system_name,
+                    session.game_mode
+                );
                 return;
             };
 
@@ -608,55 +1331,119 @@ impl MatchmakingJob {
 
     //-------------------------------------------------------------------------------------------------
     fn process_matched_players(&mut self) {
-        let now = unix_now();
+        let now = self.clock.now();
+        let matchmaking_settings = self.matchmaking_settings_dal.get_matchmaking_settings();
 
-        for (profile_id, session_id) in &self.matched_players {
+        self.matched_players.retain(|profile_id, session_id| {
             let Some(session) = self.sessions.get_mut(session_id) else {
-                log::error!("[{}] Cannot find session {session_id} for matched player {profile_id}", self.region_system_name);
-                continue;
+                log::error!(
+                    "[{}] Cannot find session {session_id} for matched player {profile_id}",
+                    self.region_system_name
+                );
+                return false;
             };
 
-            let Some(player) = session.players.iter_mut().find(|p| p.profile_id == *profile_id) else {
-                log::error!("[{}] Cannot find matched player {profile_id} in session {session_id}", self.region_system_name);
-                continue;
+            // players are notified once the game server has activated their session
+            if session.status != MatchmakingSessionStatus::Active {
+                return true;
+            }
+
+            let Some(player) = session
+                .players
+                .iter_mut()
+                .find(|p| p.profile_id == *profile_id)
+            else {
+                log::error!(
+                    "[{}] Cannot find matched player {profile_id} in session {session_id}",
+                    self.region_system_name
+                );
+                return false;
             };
 
             player.new_status_time = now;
             player.status = MatchmakingPlayerStatus::Activating;
-            Self::update_waiting_times(player, &self.tickets, &mut self.waiting_time_cache);
 
-            self.notification_cache.queue_player_notification(player.profile_id, MatchmakingCompletedNotification {
-                matchmaking_session_id: session.session_id.to_owned(),
-                game_mode: session.game_mode.clone(),
-                ip_address: session.ip_address.to_owned(),
-                port: session.port,
-                encryption_key: session.encryption_key.to_owned(),
-            });
+            // tickets are indexed by their owner
+            if let Some(ticket) = self.tickets.get(profile_id) {
+                let waiting_time = now.saturating_sub(ticket.creation_time);
+                self.metrics.ticket_matched(&ticket.game_mode, waiting_time);
+
+                // private sessions do not wait for other players
+                if session.private_session.is_none() {
+                    self.waiting_time_cache.update_cache(ticket);
+                    self.metrics.observe_estimate_error(
+                        &ticket.game_mode,
+                        ticket.estimated_wait_time,
+                        waiting_time,
+                    );
+                }
+            }
 
-            self.sessions.update(session_id.to_owned());
-            self.activating_players.insert((profile_id.to_owned(), session_id.to_owned()));
-        }
+            let connect_token = match ConnectTokenClaims::new(
+                player.profile_id,
+                session.session_id,
+                now + matchmaking_settings.connect_token_duration,
+            )
+            .encode(&matchmaking_settings.connect_token_secret)
+            {
+                Ok(connect_token) => connect_token,
+                Err(e) => {
+                    log::error!(
+                        "[{}] Cannot sign connect token of player {profile_id}: {e:?}",
+                        self.region_system_name
+                    );
+                    return false;
+                }
+            };
+
+            self.notification_cache.queue_player_notification(
+                player.profile_id,
+                MatchmakingCompletedNotification {
+                    matchmaking_session_id: session.session_id.to_owned(),
+                    game_mode: session.game_mode.clone(),
+                    ip_address: session.ip_address.to_owned(),
+                    port: session.port,
+                    encryption_key: session.encryption_key.to_owned(),
+                    connect_token,
+                },
+            );
 
-        self.matched_players.clear();
+            self.sessions.update(session_id.to_owned());
+            self.activating_players
+                .insert((profile_id.to_owned(), session_id.to_owned()));
+            false
+        });
     }
 
     //-------------------------------------------------------------------------------------------------
     fn process_activating_players(&mut self) {
-        let now = unix_now();
-        let timeout = self.matchmaking_settings_dal.get_matchmaking_settings().reserved_player_session_timeout;
-
-        let players_to_delete = self.activating_players
+        let now = self.clock.now();
+        let timeout = self
+            .matchmaking_settings_dal
+            .get_matchmaking_settings()
+            .reserved_player_session_timeout;
+
+        let players_to_delete = self
+            .activating_players
             .iter()
             .take_while(|(profile_id, session_id)| {
                 let Some(session) = self.sessions.get_mut(session_id) else {
-                    log::error!("[{}] Cannot find session {session_id} for matched player {profile_id}", self.region_system_name);
+                    log::error!(
+                        "[{}] Cannot find session {session_id} for matched player {profile_id}",
+                        self.region_system_name
+                    );
                     return false;
                 };
-    
-                let Some(player) = session.players
+
+                let Some(player) = session
+                    .players
                     .iter_mut()
-                    .find(|p| p.profile_id == *profile_id) else {
-                    log::error!("[{}] Cannot find matched player {profile_id} in session {session_id}", self.region_system_name);
+                    .find(|p| p.profile_id == *profile_id)
+                else {
+                    log::error!(
+                        "[{}] Cannot find matched player {profile_id} in session {session_id}",
+                        self.region_system_name
+                    );
                     return false;
                 };
 
@@ -667,11 +1454,19 @@ impl MatchmakingJob {
 
         for (profile_id, session_id) in players_to_delete {
             let Some(session) = self.sessions.get_mut(&session_id) else {
-                log::error!("[{}] Cannot find session {session_id} for matched player {profile_id}", self.region_system_name);
+                log::error!(
+                    "[{}] Cannot find session {session_id} for matched player {profile_id}",
+                    self.region_system_name
+                );
                 continue;
             };
 
-            log::trace!("[{}] Player {} timed out in session {}", self.region_system_name, profile_id, session.session_id);
+            log::trace!(
+                "[{}] Player {} timed out in session {}",
+                self.region_system_name,
+                profile_id,
+                session.session_id
+            );
 
             self.tickets.delete(&profile_id);
 
@@ -681,28 +1476,13 @@ impl MatchmakingJob {
         }
     }
 
-    //-------------------------------------------------------------------------------------------------
-    fn update_waiting_times(
-        player: &mut MatchmakingPlayer, 
-        tickets: &TicketCache, 
-        waiting_time_cache: &mut MatchmakingWaitingTimeCache
-    ) {
-        let Some(ticket) = tickets.get(&player.profile_id) else {
-            return;
-        };
-
-        // no estimated waiting time for friend parties
-        if ticket.players.len() != 1 {
-            return;
-        }
-
-        waiting_time_cache.update_cache(ticket, player);
-    }
-
     //-------------------------------------------------------------------------------------------------
     fn delete_session(&mut self, session_id: &SessionId) {
         let Some(session) = self.sessions.get_mut(session_id) else {
-            log::error!("[{}] Cannot find session to delete {session_id}", self.region_system_name);
+            log::error!(
+                "[{}] Cannot find session to delete {session_id}",
+                self.region_system_name
+            );
             return;
         };
 
@@ -713,55 +1493,100 @@ impl MatchmakingJob {
 
             match player.status {
                 MatchmakingPlayerStatus::Created => (),
+                // tickets of private sessions are never matched with other tickets
+                MatchmakingPlayerStatus::Matched if session.private_session.is_some() => {
+                    self.tickets.delete(&player.profile_id);
+                    log::trace!(
+                        "[{}] Ticket deleted for player {}",
+                        self.region_system_name,
+                        player.profile_id
+                    );
+                }
                 MatchmakingPlayerStatus::Matched => {
                     ticket.session_id = None;
                     let owner_profile_id = ticket.owner_profile_id;
                     let Some(matchmaker) = self.matchmakers.get_mut(&ticket.game_mode) else {
-                        log::error!("[{}] Cannot find matchmaker for game mode {}", self.region_system_name, ticket.game_mode);
+                        log::error!(
+                            "[{}] Cannot find matchmaker for game mode {}",
+                            self.region_system_name,
+                            ticket.game_mode
+                        );
                         return;
                     };
                     matchmaker.insert_ticket(ticket);
                     self.tickets.update(owner_profile_id);
-                    log::trace!("[{}] Ticket reset for player {}", self.region_system_name, player.profile_id);
-                },
+                    log::trace!(
+                        "[{}] Ticket reset for player {}",
+                        self.region_system_name,
+                        player.profile_id
+                    );
+                }
                 MatchmakingPlayerStatus::Activating | MatchmakingPlayerStatus::Active => {
                     self.tickets.delete(&player.profile_id);
-                    log::trace!("[{}] Ticket deleted for player {}", self.region_system_name, player.profile_id);
-                },
+                    log::trace!(
+                        "[{}] Ticket deleted for player {}",
+                        self.region_system_name,
+                        player.profile_id
+                    );
+                }
             }
         }
 
         let Some(matchmaker) = self.matchmakers.get_mut(&session.game_mode) else {
-            log::error!("[{}] Cannot find matchmaker for game mode {}", self.region_system_name, session.game_mode);
+            log::error!(
+                "[{}] Cannot find matchmaker for game mode {}",
+                self.region_system_name,
+                session.game_mode
+            );
             return;
         };
 
-        if session.is_open {
+        if session.is_open && session.private_session.is_none() {
             matchmaker.remove_session(session);
         }
+        self.matched_players.retain(|_, id| id != session_id);
         self.sessions.delete(session_id);
     }
 
     //-------------------------------------------------------------------------------------------------
     fn process_servers(&mut self) {
         let expired_session_ids = self.servers.process_expired_servers();
-        
+        self.servers.process_unhealthy_servers();
+
         for session_id in expired_session_ids {
             self.delete_session(&session_id);
         }
 
-        self.can_create_new_sessions = self.servers.has_idle_server();
+        // drained servers can now shut down
+        for game_server_id in self.servers.process_drained_servers() {
+            self.notification_cache.queue_gamer_server_notification(
+                game_server_id,
+                GameServerDrainedNotification { game_server_id },
+            );
+        }
+
+        self.can_create_new_sessions = self.servers.has_available_server();
 
         // Notify all remaining tickets that server are full
         if !self.can_create_new_sessions {
-            let now = unix_now();
+            let now = self.clock.now();
+            // statistics of each game mode, built once for all its tickets
+            let mut waiting_time_statistics =
+                HashMap::<String, Option<WaitingTimeStatistics>>::new();
 
-            for (position_in_queue, owner_profile_id) in self.tickets
+            for (position_in_queue, owner_profile_id) in self
+                .tickets
                 .iter()
-                .filter_map(|t| if t.session_id.is_none() { Some(t.owner_profile_id) } else { None })
+                .filter_map(|t| {
+                    if t.session_id.is_none() {
+                        Some(t.owner_profile_id)
+                    } else {
+                        None
+                    }
+                })
                 .enumerate()
-                .collect::<Vec<_>>() {
-
+                .collect::<Vec<_>>()
+            {
                 let Some(ticket) = self.tickets.get_mut(&owner_profile_id) else {
                     continue;
                 };
@@ -771,14 +1596,27 @@ impl MatchmakingJob {
                     continue;
                 }
 
+                if !waiting_time_statistics.contains_key(&ticket.game_mode) {
+                    waiting_time_statistics.insert(
+                        ticket.game_mode.clone(),
+                        self.waiting_time_cache.get_statistics(&ticket.game_mode),
+                    );
+                }
+
+                let estimated_wait_time = waiting_time_statistics[&ticket.game_mode]
+                    .as_ref()
+                    .map(|statistics| {
+                        statistics.estimate_waiting_time(ticket, Some(position_in_queue + 1))
+                    })
+                    .unwrap_or_default()
+                    .saturating_sub(now.saturating_sub(ticket.creation_time));
+
                 for player in ticket.players.iter() {
                     self.notification_cache.queue_player_notification(
                         player.profile_id,
                         MatchmakingServersFullNotification {
                             position_in_queue: position_in_queue + 1,
-                            estimated_wait_time: self
-                                .waiting_time_cache
-                                .get_average_waiting_time(&ticket.game_mode),
+                            estimated_wait_time,
                         },
                     );
                 }
@@ -790,76 +1628,1580 @@ impl MatchmakingJob {
     }
 
     //-------------------------------------------------------------------------------------------------
-    fn process_matchmakers(&mut self) {
-        if !self.can_create_new_sessions {
+    fn is_in_maintenance(&self, now: u64) -> bool {
+        self.maintenance.as_ref().is_some_and(|m| m.is_active(now))
+            || self
+                .global_maintenance
+                .as_ref()
+                .is_some_and(|m| m.is_active(now))
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    /// Cancel tickets waiting for a match during a maintenance,
+    /// players already in a session keep playing
+    fn process_maintenance(&mut self) {
+        if !self.is_in_maintenance(self.clock.now()) {
             return;
         }
 
-        for matchmaker in self.matchmakers.values_mut() {
-            matchmaker.process(
-                &mut MatchmakerContext::new(
-                    &self.region_system_name,
-                    &mut self.tickets,
-                    &mut self.sessions,
-                    &mut self.created_sessions,
-                    &mut self.matched_players,
-                    &mut self.matchmaking_assembler,
-                ));
+        let queued_tickets = self
+            .tickets
+            .iter()
+            .filter(|t| t.session_id.is_none())
+            .map(|t| t.owner_profile_id)
+            .collect::<Vec<_>>();
+
+        for owner_profile_id in queued_tickets {
+            let Some(ticket) = self.tickets.delete(&owner_profile_id) else {
+                continue;
+            };
+
+            if let Some(matchmaker) = self.matchmakers.get_mut(&ticket.game_mode) {
+                matchmaker.remove_ticket(&ticket);
+            }
+
+            log::trace!(
+                "[{}] Ticket of player {owner_profile_id} cancelled by maintenance",
+                self.region_system_name
+            );
+            self.metrics.ticket_cancelled(&ticket.game_mode);
+
+            self.fail_ticket(&ticket, MatchmakingFailureReason::Maintenance);
         }
     }
 
     //-------------------------------------------------------------------------------------------------
-    fn process_sessions(&mut self) {
+    /// Fail tickets waiting for longer than the ticket timeout of their game mode
+    fn process_expired_tickets(&mut self) {
+        let now = self.clock.now();
+        let game_mode_configs = &self
+            .matchmaking_settings_dal
+            .get_matchmaking_settings()
+            .game_mode_configs;
+
+        let expired_tickets = self
+            .tickets
+            .iter()
+            .filter(|t| t.session_id.is_none())
+            .filter_map(|t| {
+                let config = game_mode_configs
+                    .iter()
+                    .find(|config| config.name == t.game_mode)?;
+                let ticket_timeout = config.ticket_timeout?;
+                if now < t.creation_time + ticket_timeout {
+                    return None;
+                }
+                Some((t.owner_profile_id, config.fallback_game_mode.clone()))
+            })
+            .collect::<Vec<_>>();
+
+        for (owner_profile_id, fallback_game_mode) in expired_tickets {
+            let Some(ticket) = self.tickets.delete(&owner_profile_id) else {
+                continue;
+            };
+
+            if let Some(matchmaker) = self.matchmakers.get_mut(&ticket.game_mode) {
+                matchmaker.remove_ticket(&ticket);
+            }
+
+            log::trace!(
+                "[{}] Ticket of player {owner_profile_id} timed out in game mode {}",
+                self.region_system_name,
+                ticket.game_mode
+            );
+            self.metrics.ticket_expired(&ticket.game_mode);
+
+            for player in &ticket.players {
+                self.notification_cache.queue_player_notification(
+                    player.profile_id,
+                    MatchmakingFailedNotification {
+                        onwer_profile_id: owner_profile_id,
+                        failure_reason: MatchmakingFailureReason::Timeout,
+                        fallback_game_mode: fallback_game_mode.clone(),
+                    },
+                );
+            }
+        }
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn process_matchmakers(&mut self) {
+        // backfill running sessions before creating new ones
+        self.process_backfills();
+
         if !self.can_create_new_sessions {
             return;
         }
 
-        let mut sessions_to_delete = Vec::new();
+        for (game_mode, matchmaker) in self.matchmakers.iter_mut() {
+            let Some(game_mode_config) = self
+                .matchmaking_settings_dal
+                .get_matchmaking_settings()
+                .game_mode_configs
+                .iter()
+                .find(|config| config.name == *game_mode)
+            else {
+                log::error!(
+                    "[{}] Cannot find game mode config for game mode {game_mode}",
+                    self.region_system_name
+                );
+                continue;
+            };
 
-        for session_id in self.created_sessions.iter() {
-            if let Some(game_server) = self.servers.get_idle_server_mut() {
-                let Some(session) = self.sessions.get_mut(session_id) else {
-                    log::error!("[{}] Cannot find created session {session_id}", self.region_system_name);
-                    continue;
-                };
+            matchmaker.process(&mut MatchmakerContext::new(
+                &self.region_system_name,
+                game_mode_config,
+                &mut self.tickets,
+                &mut self.sessions,
+                &mut self.created_sessions,
+                &mut self.matched_players,
+                &mut self.matchmaking_assembler,
+                self.clock.as_ref(),
+            ));
+        }
+    }
 
-                let Some(game_config) = self.matchmaking_settings_dal
-                    .get_matchmaking_settings()
-                    .game_mode_configs
-                    .iter()
-                    .find(|config| config.name == session.game_mode) else {
-                    log::error!("[{}] Cannot find game mode config for game mode {}", self.region_system_name, session.game_mode);
-                    continue;
-                };
+    //-------------------------------------------------------------------------------------------------
+    fn process_backfills(&mut self) {
+        let session_ids = self
+            .sessions
+            .iter()
+            .filter(|s| s.backfill_request.is_some())
+            .map(|s| s.session_id)
+            .collect::<Vec<_>>();
+
+        for session_id in session_ids {
+            self.process_backfill(session_id);
+        }
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn process_backfill(&mut self, session_id: SessionId) {
+        let Some(session) = self.sessions.get(&session_id) else {
+            return;
+        };
+
+        let Some(game_mode_config) = self
+            .matchmaking_settings_dal
+            .get_matchmaking_settings()
+            .game_mode_configs
+            .iter()
+            .find(|config| config.name == session.game_mode)
+        else {
+            log::error!(
+                "[{}] Cannot find game mode config for game mode {}",
+                self.region_system_name,
+                session.game_mode
+            );
+            return;
+        };
+
+        // oldest tickets first
+        let mut ticket_ids = self
+            .tickets
+            .iter()
+            .filter(|t| t.session_id.is_none() && t.game_mode == session.game_mode)
+            .map(|t| (t.creation_time, t.owner_profile_id))
+            .collect::<Vec<_>>();
+        ticket_ids.sort();
 
-                let server_id = game_server.game_server_id;
+        for (_, ticket_id) in ticket_ids {
+            let (Some(session), Some(ticket)) =
+                (self.sessions.get(&session_id), self.tickets.get(&ticket_id))
+            else {
+                continue;
+            };
 
-                self.notification_cache.queue_gamer_server_notification(server_id, 
-                    MatchmakingActivateSessionNotification {
-                        matchmaking_session_id: session.session_id,
-                        game_mode: session.game_mode.clone(),
-                        encryption_key: session.encryption_key.clone(),
-                        max_players: game_config.max_players,
-                        team_player_count: game_config.team_player_count,
-                    });
+            let Some(backfill_request) = &session.backfill_request else {
+                break;
+            };
 
-                game_server.session_id = Some(session.session_id);
-                self.servers.update_server(server_id);
+            if backfill_request.incoming_players.len() >= backfill_request.num_players {
+                break;
+            }
 
-                session.game_server_id = Some(server_id);
-                self.sessions.update(*session_id);
-            } else {
-                sessions_to_delete.push(*session_id);
+            if !can_backfill(
+                game_mode_config,
+                backfill_request,
+                &session.players,
+                &ticket.players,
+            ) {
+                continue;
             }
-        }
 
-        self.created_sessions.clear();
+            let team = backfill_request.team;
+            let profile_ids = ticket
+                .players
+                .iter()
+                .map(|p| p.profile_id)
+                .collect::<Vec<_>>();
 
-        for session_id in sessions_to_delete {
-            // delete new sessions without a server
-            // tickets will be put back into matchmakers
-            self.delete_session(&session_id);
-        }
-    }
-}
+            if let Some(matchmaker) = self.matchmakers.get_mut(&ticket.game_mode) {
+                matchmaker.remove_ticket(ticket);
+            }
 
+            MatchmakerContext::new(
+                &self.region_system_name,
+                game_mode_config,
+                &mut self.tickets,
+                &mut self.sessions,
+                &mut self.created_sessions,
+                &mut self.matched_players,
+                &mut self.matchmaking_assembler,
+                self.clock.as_ref(),
+            )
+            .match_ticket_to_backfill(ticket_id, session_id, team);
+
+            if let Some(backfill_request) = self
+                .sessions
+                .get_mut(&session_id)
+                .and_then(|s| s.backfill_request.as_mut())
+            {
+                backfill_request.incoming_players.extend(profile_ids);
+            }
+        }
+
+        let Some(session) = self.sessions.get_mut(&session_id) else {
+            return;
+        };
+
+        let Some(backfill_request) = &session.backfill_request else {
+            return;
+        };
+
+        let is_completed = backfill_request.incoming_players.len() >= backfill_request.num_players;
+        let is_expired = self.clock.now() > backfill_request.expiration_time;
+        if !is_completed && !is_expired {
+            return;
+        }
+
+        let Some(game_server_id) = session.game_server_id else {
+            return;
+        };
+
+        let players = session
+            .players
+            .iter()
+            .filter(|p| backfill_request.incoming_players.contains(&p.profile_id))
+            .map(|p| BackfillPlayer {
+                profile_id: p.profile_id,
+                team: p.team,
+            })
+            .collect::<Vec<_>>();
+
+        if is_completed {
+            log::trace!(
+                "[{}] Backfill of session {session_id} completed",
+                self.region_system_name
+            );
+            self.notification_cache.queue_gamer_server_notification(
+                game_server_id,
+                MatchmakingBackfillCompletedNotification {
+                    matchmaking_session_id: session_id,
+                    players,
+                },
+            );
+        } else {
+            log::trace!(
+                "[{}] Backfill of session {session_id} timed out with {} player(s) found",
+                self.region_system_name,
+                players.len()
+            );
+            self.notification_cache.queue_gamer_server_notification(
+                game_server_id,
+                MatchmakingBackfillTimeoutNotification {
+                    matchmaking_session_id: session_id,
+                    players,
+                },
+            );
+        }
+
+        session.backfill_request = None;
+        self.sessions.update(session_id);
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn process_sessions(&mut self) {
+        if !self.can_create_new_sessions {
+            return;
+        }
+
+        let mut sessions_to_delete = Vec::new();
+
+        for session_id in self.created_sessions.iter().copied().collect::<Vec<_>>() {
+            if !self.start_session_on_server(&session_id) {
+                sessions_to_delete.push(session_id);
+            } else if let Some(session) = self.sessions.get(&session_id) {
+                self.metrics.session_created(&session.game_mode);
+            }
+        }
+
+        self.created_sessions.clear();
+
+        for session_id in sessions_to_delete {
+            // delete new sessions without a server
+            // tickets will be put back into matchmakers
+            self.delete_session(&session_id);
+        }
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    /// Reserve a slot on a game server for a session and ask it to activate the session
+    /// # Return
+    /// false if no server can host the session
+    fn start_session_on_server(&mut self, session_id: &SessionId) -> bool {
+        let Some(session) = self.sessions.get_mut(session_id) else {
+            log::error!(
+                "[{}] Cannot find created session {session_id}",
+                self.region_system_name
+            );
+            return true;
+        };
+
+        // servers only host sessions of their game version
+        let client_version = session
+            .players
+            .first()
+            .map(|p| p.attributes.client_version.as_str())
+            .unwrap_or_default();
+
+        let Some(game_server) = self.servers.get_available_server_mut(client_version) else {
+            return false;
+        };
+
+        let Some(game_config) = self
+            .matchmaking_settings_dal
+            .get_matchmaking_settings()
+            .game_mode_configs
+            .iter()
+            .find(|config| config.name == session.game_mode)
+        else {
+            log::error!(
+                "[{}] Cannot find game mode config for game mode {}",
+                self.region_system_name,
+                session.game_mode
+            );
+            return true;
+        };
+
+        let server_id = game_server.game_server_id;
+
+        self.notification_cache.queue_gamer_server_notification(
+            server_id,
+            MatchmakingActivateSessionNotification {
+                matchmaking_session_id: session.session_id,
+                game_mode: session.game_mode.clone(),
+                encryption_key: session.encryption_key.clone(),
+                max_players: game_config.max_players,
+                team_player_count: game_config.team_player_count,
+                teams: get_teams(game_config, &session.players),
+            },
+        );
+
+        game_server.session_ids.push(session.session_id);
+        session.game_server_id = Some(server_id);
+        session.ip_address = game_server.ip_address.clone();
+        session.port = game_server.port;
+        self.servers.update_server(server_id);
+
+        session.status = MatchmakingSessionStatus::Activating;
+        session.activation_time = self.clock.now();
+        session.activation_attempts += 1;
+        self.sessions.update(*session_id);
+
+        true
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    /// Move sessions not activated in time to another game server,
+    /// fail their tickets once all activation attempts are exhausted
+    fn process_activating_sessions(&mut self) {
+        let now = self.clock.now();
+        let matchmaking_settings = self.matchmaking_settings_dal.get_matchmaking_settings();
+        let timeout = matchmaking_settings.session_activation_timeout;
+        let max_attempts = matchmaking_settings.max_session_activation_attempts;
+
+        let expired_sessions = self
+            .sessions
+            .iter()
+            .filter(|s| {
+                s.status == MatchmakingSessionStatus::Activating
+                    && now > s.activation_time + timeout
+            })
+            .map(|s| (s.session_id, s.game_server_id, s.activation_attempts))
+            .collect::<Vec<_>>();
+
+        for (session_id, game_server_id, activation_attempts) in expired_sessions {
+            log::warn!(
+                "[{}] Session {session_id} was not activated in time by server {:?}",
+                self.region_system_name,
+                game_server_id
+            );
+
+            // the server must not start the session if it activates it late
+            if let Some(game_server_id) = game_server_id {
+                self.servers.set_server_unhealthy(game_server_id);
+                self.notification_cache.queue_gamer_server_notification(
+                    game_server_id,
+                    MatchmakingSessionCanceledNotification {
+                        matchmaking_session_id: session_id,
+                    },
+                );
+            }
+            self.release_session_server(&session_id);
+
+            if activation_attempts < max_attempts && self.start_session_on_server(&session_id) {
+                continue;
+            }
+
+            log::warn!("[{}] Session {session_id} failed after {activation_attempts} activation attempt(s)", self.region_system_name);
+
+            let tickets = self
+                .tickets
+                .iter()
+                .filter(|t| t.session_id == Some(session_id))
+                .cloned()
+                .collect::<Vec<_>>();
+
+            for ticket in tickets {
+                self.fail_ticket(&ticket, MatchmakingFailureReason::SessionActivationTimeout);
+                self.tickets.delete(&ticket.owner_profile_id);
+            }
+
+            self.delete_session(&session_id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ManualClock, GAME_SERVER_TIMEOUT, UNHEALTHY_SERVER_COOLDOWN};
+    use cotonou_common::{
+        matchmaking::{
+            MatchmakingLeaseDAL, MatchmakingPlayer, MemoryGameServerDAL, MemoryMaintenanceDAL,
+            MemoryMatchmakingCommandDAL, MemoryMatchmakingLeaseDAL, MemoryMatchmakingSessionDAL,
+            MemoryMatchmakingTicketDAL, MemoryMatchmakingWaitingTimeDAL, TicketAttributes,
+        },
+        notifications::MemoryNotificationManager,
+    };
+
+    const REGION: &str = "eu-central-1";
+    const NOW: u64 = 1_700_000_000;
+
+    struct TestContext {
+        job: MatchmakingJob,
+        game_server_dal: Arc<MemoryGameServerDAL>,
+        matchmaking_command_dal: Arc<MemoryMatchmakingCommandDAL>,
+        matchmaking_session_dal: Arc<MemoryMatchmakingSessionDAL>,
+        matchmaking_ticket_dal: Arc<MemoryMatchmakingTicketDAL>,
+        notification_manager: Arc<MemoryNotificationManager>,
+        matchmaking_lease_dal: Arc<MemoryMatchmakingLeaseDAL>,
+        lease: RegionLease,
+        clock: Arc<ManualClock>,
+        _shutdown_sender: tokio::sync::watch::Sender<()>,
+    }
+
+    async fn new_context() -> TestContext {
+        let game_server_dal = Arc::new(MemoryGameServerDAL::new());
+        let matchmaking_command_dal = Arc::new(MemoryMatchmakingCommandDAL::new());
+        let matchmaking_session_dal = Arc::new(MemoryMatchmakingSessionDAL::new());
+        let matchmaking_ticket_dal = Arc::new(MemoryMatchmakingTicketDAL::new());
+        let notification_manager = Arc::new(MemoryNotificationManager::new());
+        let (shutdown_sender, shutdown_receiver) = tokio::sync::watch::channel(());
+        let clock = Arc::new(ManualClock::new(NOW));
+
+        let job = MatchmakingJob::new(
+            REGION,
+            "eu",
+            game_server_dal.clone(),
+            matchmaking_command_dal.clone(),
+            matchmaking_session_dal.clone(),
+            matchmaking_ticket_dal.clone(),
+            Arc::new(MemoryMatchmakingWaitingTimeDAL::new()),
+            Arc::new(MemoryMaintenanceDAL::new()),
+            MatchmakingAssembler::new(clock.clone()),
+            notification_manager.clone(),
+            Arc::new(MetricsRegistry::new()),
+            MatchmakingSettingsDAL::new(),
+            shutdown_receiver,
+            clock.clone(),
+        );
+
+        let matchmaking_lease_dal = Arc::new(MemoryMatchmakingLeaseDAL::new());
+        let lease = RegionLease::acquire(REGION, "job", 1, matchmaking_lease_dal.clone())
+            .await
+            .unwrap()
+            .unwrap();
+
+        TestContext {
+            job,
+            game_server_dal,
+            matchmaking_command_dal,
+            matchmaking_session_dal,
+            matchmaking_ticket_dal,
+            notification_manager,
+            matchmaking_lease_dal,
+            lease,
+            clock,
+            _shutdown_sender: shutdown_sender,
+        }
+    }
+
+    fn new_ticket(profile_id: ProfileId, game_mode: &str) -> MatchmakingTicket {
+        MatchmakingTicket {
+            owner_profile_id: profile_id,
+            game_mode: game_mode.to_owned(),
+            players: vec![MatchmakingPlayer {
+                profile_id,
+                display_name: profile_id.to_string(),
+                mmr: 1000,
+                new_status_time: NOW,
+                creation_time: NOW,
+                party_id: Some(profile_id),
+                attributes: TicketAttributes {
+                    client_version: "1.0".to_owned(),
+                    ..Default::default()
+                },
+                ..Default::default()
+            }],
+            creation_time: NOW,
+            session_id: None,
+            servers_full_notification_last_time_sent: 0,
+            attributes: TicketAttributes {
+                client_version: "1.0".to_owned(),
+                ..Default::default()
+            },
+            estimated_wait_time: 0,
+        }
+    }
+
+    fn initialize_game_server_command(game_server_id: GameServerId) -> MatchmakingCommand {
+        MatchmakingCommand::InitializeGameServer {
+            game_server_id,
+            host_name: "localhost".to_owned(),
+            host_type: GameServerHostType::Static,
+            host_boot_time: NOW,
+            host_provider: "test".to_owned(),
+            game_version: "1.0".to_owned(),
+            process_id: 1,
+            ip_address: "127.0.0.1".to_owned(),
+            port: 7777,
+            capacity: 1,
+        }
+    }
+
+    #[tokio::test]
+    async fn quick_match_creates_session_on_idle_server() -> Result<(), Error> {
+        let mut context = new_context().await;
+        let game_server_id = GameServerId::new();
+        let profile_id1: ProfileId = "1".parse().unwrap();
+        let profile_id2: ProfileId = "2".parse().unwrap();
+
+        for command in [
+            initialize_game_server_command(game_server_id),
+            MatchmakingCommand::CreateTicket {
+                ticket: new_ticket(profile_id1, "QuickMatch"),
+            },
+            MatchmakingCommand::CreateTicket {
+                ticket: new_ticket(profile_id2, "QuickMatch"),
+            },
+        ] {
+            context
+                .matchmaking_command_dal
+                .queue_command(REGION, &command)
+                .await?;
+        }
+
+        context.job.process(&context.lease).await?;
+
+        let sessions = context.matchmaking_session_dal.get_sessions(REGION).await?;
+        assert_eq!(1, sessions.len());
+        let session = &sessions[0];
+        assert_eq!(Some(game_server_id), session.game_server_id);
+        assert_eq!(2, session.players.len());
+        assert_eq!(64, session.encryption_key.len());
+        assert_eq!("127.0.0.1", session.ip_address);
+        assert_eq!(7777, session.port);
+
+        let server_notifications = context
+            .notification_manager
+            .get_notifications_from_queue(&game_server_id.to_string())
+            .await?;
+        assert_eq!(1, server_notifications.len());
+        assert!(server_notifications[0].contains("MatchmakingActivateSessionNotification"));
+
+        // players are notified once the server has activated the session
+        let player_notifications = context
+            .notification_manager
+            .get_notifications_from_queue(&profile_id1.to_string())
+            .await?;
+        assert!(player_notifications.is_empty());
+
+        context
+            .matchmaking_command_dal
+            .queue_command(
+                REGION,
+                &MatchmakingCommand::ActivateSession {
+                    session_id: session.session_id,
+                    game_server_id,
+                },
+            )
+            .await?;
+        context.job.process(&context.lease).await?;
+
+        for profile_id in [profile_id1, profile_id2] {
+            let player_notifications = context
+                .notification_manager
+                .get_notifications_from_queue(&profile_id.to_string())
+                .await?;
+            assert_eq!(1, player_notifications.len());
+            assert!(player_notifications[0].contains("MatchmakingCompletedNotification"));
+            assert!(player_notifications[0].contains("127.0.0.1"));
+            assert!(player_notifications[0].contains("connect_token"));
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn quick_match_without_server_keeps_tickets() -> Result<(), Error> {
+        let mut context = new_context().await;
+
+        for profile_id in ["1", "2"] {
+            let command = MatchmakingCommand::CreateTicket {
+                ticket: new_ticket(profile_id.parse().unwrap(), "QuickMatch"),
+            };
+            context
+                .matchmaking_command_dal
+                .queue_command(REGION, &command)
+                .await?;
+        }
+
+        context.job.process(&context.lease).await?;
+
+        let sessions = context.matchmaking_session_dal.get_sessions(REGION).await?;
+        assert!(sessions.is_empty());
+        assert_eq!(2, context.job.tickets.iter().len());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn expired_ticket_fails_with_fallback_game_mode() -> Result<(), Error> {
+        let mut context = new_context().await;
+        let profile_id: ProfileId = "1".parse().unwrap();
+
+        let mut ticket = new_ticket(profile_id, "Ranked");
+        ticket.creation_time -= 601;
+
+        for command in [
+            initialize_game_server_command(GameServerId::new()),
+            MatchmakingCommand::CreateTicket { ticket },
+        ] {
+            context
+                .matchmaking_command_dal
+                .queue_command(REGION, &command)
+                .await?;
+        }
+
+        context.job.process(&context.lease).await?;
+
+        assert!(context.job.tickets.get(&profile_id).is_none());
+
+        let player_notifications = context
+            .notification_manager
+            .get_notifications_from_queue(&profile_id.to_string())
+            .await?;
+        assert_eq!(1, player_notifications.len());
+        assert!(player_notifications[0].contains("Timeout"));
+        assert!(player_notifications[0].contains("QuickMatch"));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn processed_commands_are_acknowledged_and_malformed_ones_dead_lettered(
+    ) -> Result<(), Error> {
+        let mut context = new_context().await;
+
+        context
+            .matchmaking_command_dal
+            .queue_raw_command(REGION, "{\"Unknown\":{}}");
+        let command = MatchmakingCommand::CreateTicket {
+            ticket: new_ticket("1".parse().unwrap(), "QuickMatch"),
+        };
+        context
+            .matchmaking_command_dal
+            .queue_command(REGION, &command)
+            .await?;
+
+        context.job.process(&context.lease).await?;
+
+        assert_eq!(1, context.job.tickets.iter().len());
+        assert!(context
+            .matchmaking_command_dal
+            .recover_commands(REGION)
+            .await?
+            .is_empty());
+        let dead_letters = context
+            .matchmaking_command_dal
+            .get_dead_letter_commands(REGION)
+            .await?;
+        assert_eq!(1, dead_letters.len());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn redelivered_commands_are_applied_once() -> Result<(), Error> {
+        let mut context = new_context().await;
+        let profile_id1: ProfileId = "1".parse().unwrap();
+        let profile_id2: ProfileId = "2".parse().unwrap();
+        let profile_id3: ProfileId = "3".parse().unwrap();
+        let private_session_id = SessionId::new();
+        let join_code = JoinCode::new();
+
+        context
+            .matchmaking_command_dal
+            .queue_command(REGION, &initialize_game_server_command(GameServerId::new()))
+            .await?;
+        context
+            .matchmaking_command_dal
+            .queue_command(REGION, &initialize_game_server_command(GameServerId::new()))
+            .await?;
+
+        let commands = || {
+            [
+                MatchmakingCommand::CreateTicket {
+                    ticket: new_ticket(profile_id1, "QuickMatch"),
+                },
+                MatchmakingCommand::CreateTicket {
+                    ticket: new_ticket(profile_id2, "QuickMatch"),
+                },
+                MatchmakingCommand::CreatePrivateSession {
+                    session_id: private_session_id,
+                    join_code: join_code.clone(),
+                    expiration_time: NOW + 60,
+                    ticket: new_ticket(profile_id3, "QuickMatch"),
+                },
+            ]
+        };
+
+        // commands applied, then delivered again as if they had not been acknowledged
+        for _ in 0..2 {
+            for command in commands() {
+                context
+                    .matchmaking_command_dal
+                    .queue_command(REGION, &command)
+                    .await?;
+            }
+            context.job.process(&context.lease).await?;
+        }
+
+        let sessions = context.matchmaking_session_dal.get_sessions(REGION).await?;
+        assert_eq!(2, sessions.len());
+        for profile_id in [profile_id1, profile_id2, profile_id3] {
+            assert!(context
+                .job
+                .tickets
+                .get(&profile_id)
+                .unwrap()
+                .session_id
+                .is_some());
+            let player_notifications = context
+                .notification_manager
+                .get_notifications_from_queue(&profile_id.to_string())
+                .await?;
+            assert!(player_notifications
+                .iter()
+                .all(|n| !n.contains("MatchmakingFailedNotification")));
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn commands_saved_but_not_acknowledged_are_not_processed_again() -> Result<(), Error> {
+        let mut context = new_context().await;
+        let profile_id: ProfileId = "1".parse().unwrap();
+        let create_ticket_command = MatchmakingCommand::CreateTicket {
+            ticket: new_ticket(profile_id, "QuickMatch"),
+        };
+
+        context
+            .matchmaking_command_dal
+            .queue_command(REGION, &create_ticket_command)
+            .await?;
+        context.job.process(&context.lease).await?;
+
+        // the cancellation is saved, but the leader stops before acknowledging it
+        context
+            .matchmaking_command_dal
+            .queue_command(
+                REGION,
+                &MatchmakingCommand::CancelTicket {
+                    owner_profile_id: profile_id,
+                },
+            )
+            .await?;
+        context.job.process_commands().await?;
+        context.job.save_cache().await?;
+        assert!(context.job.tickets.get(&profile_id).is_none());
+        context.job.commands_to_ack.clear();
+
+        // the player matchmakes again, then the cancellation is redelivered
+        context
+            .matchmaking_command_dal
+            .queue_command(REGION, &create_ticket_command)
+            .await?;
+        context.job.process(&context.lease).await?;
+        context.job.recovered_commands = context
+            .matchmaking_command_dal
+            .recover_commands(REGION)
+            .await?;
+        assert_eq!(1, context.job.recovered_commands.len());
+        context.job.process(&context.lease).await?;
+
+        assert!(context.job.tickets.get(&profile_id).is_some());
+        assert!(context
+            .matchmaking_command_dal
+            .recover_commands(REGION)
+            .await?
+            .is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn stale_leader_writes_are_refused() -> Result<(), Error> {
+        let mut context = new_context().await;
+        context.job.process(&context.lease).await?;
+
+        let command_id = context
+            .matchmaking_command_dal
+            .queue_command(REGION, &MatchmakingCommand::ResetMatchmaking)
+            .await?;
+        context
+            .matchmaking_command_dal
+            .dequeue_commands(REGION)
+            .await?;
+
+        // another job takes over the region and saves it,
+        // right after this one checked its lease before saving
+        context
+            .matchmaking_lease_dal
+            .release_lease(REGION, "job", context.lease.fencing_token())
+            .await?;
+        let standby_fencing_token = context
+            .matchmaking_lease_dal
+            .acquire_lease(REGION, "standby", 1, Duration::from_secs(10))
+            .await?
+            .unwrap();
+        context
+            .matchmaking_ticket_dal
+            .update_tickets(REGION, standby_fencing_token, &[])
+            .await?;
+        context
+            .matchmaking_command_dal
+            .ack_commands(REGION, standby_fencing_token, &[])
+            .await?;
+
+        context
+            .job
+            .tickets
+            .create(new_ticket("1".parse().unwrap(), "QuickMatch"));
+        assert!(matches!(
+            context.job.save_cache().await,
+            Err(Error::Matchmaking(matchmaking::Error::StaleFencingToken(_)))
+        ));
+        assert!(context
+            .matchmaking_ticket_dal
+            .get_tickets(REGION)
+            .await?
+            .is_empty());
+
+        context.job.commands_to_ack.push(command_id.clone());
+        assert!(matches!(
+            context.job.ack_commands().await,
+            Err(Error::Matchmaking(matchmaking::Error::StaleFencingToken(_)))
+        ));
+        let recovered_commands = context
+            .matchmaking_command_dal
+            .recover_commands(REGION)
+            .await?;
+        assert_eq!(1, recovered_commands.len());
+        assert_eq!(command_id, recovered_commands[0].command_id);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn stale_leader_neither_saves_nor_acknowledges() -> Result<(), Error> {
+        let mut context = new_context().await;
+
+        let command = MatchmakingCommand::CreateTicket {
+            ticket: new_ticket("1".parse().unwrap(), "QuickMatch"),
+        };
+        context
+            .matchmaking_command_dal
+            .queue_command(REGION, &command)
+            .await?;
+
+        // another job takes over the region while this one is processing
+        context
+            .matchmaking_lease_dal
+            .release_lease(REGION, "job", context.lease.fencing_token())
+            .await?;
+        context
+            .matchmaking_lease_dal
+            .acquire_lease(REGION, "standby", 1, Duration::from_secs(10))
+            .await?;
+
+        assert!(!context.job.process(&context.lease).await?);
+
+        assert!(context
+            .matchmaking_ticket_dal
+            .get_tickets(REGION)
+            .await?
+            .is_empty());
+        assert_eq!(
+            1,
+            context
+                .matchmaking_command_dal
+                .recover_commands(REGION)
+                .await?
+                .len()
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn session_not_activated_in_time_moves_to_another_server() -> Result<(), Error> {
+        let mut context = new_context().await;
+        let game_server_id1 = GameServerId::new();
+        let game_server_id2 = GameServerId::new();
+        let profile_id1: ProfileId = "1".parse().unwrap();
+
+        for command in [
+            initialize_game_server_command(game_server_id1),
+            MatchmakingCommand::CreateTicket {
+                ticket: new_ticket(profile_id1, "QuickMatch"),
+            },
+            MatchmakingCommand::CreateTicket {
+                ticket: new_ticket("2".parse().unwrap(), "QuickMatch"),
+            },
+        ] {
+            context
+                .matchmaking_command_dal
+                .queue_command(REGION, &command)
+                .await?;
+        }
+
+        context.job.process(&context.lease).await?;
+
+        let session_id = context.matchmaking_session_dal.get_sessions(REGION).await?[0].session_id;
+        context.clock.advance(31);
+        context
+            .matchmaking_command_dal
+            .queue_command(REGION, &initialize_game_server_command(game_server_id2))
+            .await?;
+
+        context.job.process(&context.lease).await?;
+
+        let session = context
+            .matchmaking_session_dal
+            .get_session(REGION, &session_id)
+            .await?
+            .unwrap();
+        assert_eq!(Some(game_server_id2), session.game_server_id);
+        assert_eq!(2, session.activation_attempts);
+        let server1 = context
+            .game_server_dal
+            .get_game_server(REGION, &game_server_id1)
+            .await?
+            .unwrap();
+        assert!(!server1.is_healthy);
+        assert!(server1.session_ids.is_empty());
+        let server1_notifications = context
+            .notification_manager
+            .get_notifications_from_queue(&game_server_id1.to_string())
+            .await?;
+        assert!(server1_notifications
+            .iter()
+            .any(|n| n.contains("MatchmakingSessionCanceledNotification")));
+
+        // late activation by the former server
+        context
+            .matchmaking_command_dal
+            .queue_command(
+                REGION,
+                &MatchmakingCommand::ActivateSession {
+                    session_id,
+                    game_server_id: game_server_id1,
+                },
+            )
+            .await?;
+        context.job.process(&context.lease).await?;
+        assert_eq!(
+            MatchmakingSessionStatus::Activating,
+            context.job.sessions.get(&session_id).unwrap().status
+        );
+        assert!(
+            !context
+                .job
+                .servers
+                .get_server(&game_server_id1)
+                .unwrap()
+                .is_healthy
+        );
+
+        // attempts exhausted
+        context
+            .job
+            .sessions
+            .get_mut(&session_id)
+            .unwrap()
+            .activation_attempts = 3;
+        context.clock.advance(31);
+
+        context.job.process(&context.lease).await?;
+
+        assert!(context
+            .matchmaking_session_dal
+            .get_sessions(REGION)
+            .await?
+            .is_empty());
+        assert!(context.job.tickets.get(&profile_id1).is_none());
+        let player_notifications = context
+            .notification_manager
+            .get_notifications_from_queue(&profile_id1.to_string())
+            .await?;
+        assert_eq!(1, player_notifications.len());
+        assert!(player_notifications[0].contains("SessionActivationTimeout"));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn unhealthy_server_recovers_after_cooldown_or_restart() -> Result<(), Error> {
+        let mut context = new_context().await;
+        let game_server_id1 = GameServerId::new();
+        let game_server_id2 = GameServerId::new();
+
+        for command in [
+            initialize_game_server_command(game_server_id1),
+            initialize_game_server_command(game_server_id2),
+        ] {
+            context
+                .matchmaking_command_dal
+                .queue_command(REGION, &command)
+                .await?;
+        }
+        context.job.process(&context.lease).await?;
+
+        context.job.servers.set_server_unhealthy(game_server_id1);
+        context.job.servers.set_server_unhealthy(game_server_id2);
+
+        // restarted server
+        context
+            .matchmaking_command_dal
+            .queue_command(REGION, &initialize_game_server_command(game_server_id1))
+            .await?;
+        context.job.process(&context.lease).await?;
+        assert!(
+            context
+                .job
+                .servers
+                .get_server(&game_server_id1)
+                .unwrap()
+                .is_healthy
+        );
+        assert!(
+            !context
+                .job
+                .servers
+                .get_server(&game_server_id2)
+                .unwrap()
+                .is_healthy
+        );
+
+        // keep alive during the cooldown, so that the server does not expire
+        let cooldown = UNHEALTHY_SERVER_COOLDOWN.as_secs();
+        let keep_alive_interval = GAME_SERVER_TIMEOUT.as_secs() - 1;
+        let mut elapsed = 0;
+        while elapsed < cooldown {
+            let step = keep_alive_interval.min(cooldown - elapsed);
+            context.clock.advance(step);
+            elapsed += step;
+            context
+                .matchmaking_command_dal
+                .queue_command(
+                    REGION,
+                    &MatchmakingCommand::KeepAliveGameServer {
+                        game_server_id: game_server_id2,
+                        load: 0.,
+                    },
+                )
+                .await?;
+            context.job.process(&context.lease).await?;
+        }
+
+        let server2 = context
+            .game_server_dal
+            .get_game_server(REGION, &game_server_id2)
+            .await?
+            .unwrap();
+        assert!(server2.is_healthy);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn matched_players_not_joining_in_time_leave_their_session() -> Result<(), Error> {
+        let mut context = new_context().await;
+        let game_server_id = GameServerId::new();
+        let profile_id1: ProfileId = "1".parse().unwrap();
+        let profile_id2: ProfileId = "2".parse().unwrap();
+
+        for command in [
+            initialize_game_server_command(game_server_id),
+            MatchmakingCommand::CreateTicket {
+                ticket: new_ticket(profile_id1, "QuickMatch"),
+            },
+            MatchmakingCommand::CreateTicket {
+                ticket: new_ticket(profile_id2, "QuickMatch"),
+            },
+        ] {
+            context
+                .matchmaking_command_dal
+                .queue_command(REGION, &command)
+                .await?;
+        }
+
+        context.job.process(&context.lease).await?;
+
+        let session_id = context.matchmaking_session_dal.get_sessions(REGION).await?[0].session_id;
+        context
+            .matchmaking_command_dal
+            .queue_command(
+                REGION,
+                &MatchmakingCommand::ActivateSession {
+                    session_id,
+                    game_server_id,
+                },
+            )
+            .await?;
+        context.job.process(&context.lease).await?;
+
+        let timeout = MatchmakingSettingsDAL::new()
+            .get_matchmaking_settings()
+            .reserved_player_session_timeout;
+        context.clock.advance(timeout);
+        context.job.process(&context.lease).await?;
+
+        assert_eq!(
+            2,
+            context.job.sessions.get(&session_id).unwrap().players.len()
+        );
+
+        context.clock.advance(1);
+        context.job.process(&context.lease).await?;
+
+        assert!(context
+            .job
+            .sessions
+            .get(&session_id)
+            .unwrap()
+            .players
+            .is_empty());
+        assert!(context.job.tickets.get(&profile_id1).is_none());
+        assert!(context.job.tickets.get(&profile_id2).is_none());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn game_server_not_kept_alive_expires() -> Result<(), Error> {
+        let mut context = new_context().await;
+        let game_server_id = GameServerId::new();
+
+        context
+            .matchmaking_command_dal
+            .queue_command(REGION, &initialize_game_server_command(game_server_id))
+            .await?;
+        context.job.process(&context.lease).await?;
+
+        // last second before the timeout
+        let keep_alive_timeout = GAME_SERVER_TIMEOUT.as_secs() - 1;
+
+        context.clock.advance(keep_alive_timeout);
+        context
+            .matchmaking_command_dal
+            .queue_command(
+                REGION,
+                &MatchmakingCommand::KeepAliveGameServer {
+                    game_server_id,
+                    load: 0.,
+                },
+            )
+            .await?;
+        context.job.process(&context.lease).await?;
+
+        context.clock.advance(keep_alive_timeout);
+        context.job.process(&context.lease).await?;
+
+        assert!(context
+            .game_server_dal
+            .get_game_server(REGION, &game_server_id)
+            .await?
+            .is_some());
+
+        context.clock.advance(1);
+        context.job.process(&context.lease).await?;
+
+        assert!(context
+            .game_server_dal
+            .get_game_server(REGION, &game_server_id)
+            .await?
+            .is_none());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn maintenance_cancels_queued_tickets() -> Result<(), Error> {
+        let mut context = new_context().await;
+        let profile_id1: ProfileId = "1".parse().unwrap();
+
+        for command in [
+            initialize_game_server_command(GameServerId::new()),
+            MatchmakingCommand::CreateTicket {
+                ticket: new_ticket(profile_id1, "QuickMatch"),
+            },
+            MatchmakingCommand::SetMaintenance {
+                is_global: true,
+                maintenance: Some(Maintenance {
+                    start_time: NOW,
+                    end_time: NOW + 3600,
+                    message: "Server update".to_owned(),
+                }),
+            },
+        ] {
+            context
+                .matchmaking_command_dal
+                .queue_command(REGION, &command)
+                .await?;
+        }
+
+        context.job.process(&context.lease).await?;
+
+        assert!(context.job.tickets.get(&profile_id1).is_none());
+        let player_notifications = context
+            .notification_manager
+            .get_notifications_from_queue(&profile_id1.to_string())
+            .await?;
+        assert_eq!(1, player_notifications.len());
+        assert!(player_notifications[0].contains("Maintenance"));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn draining_server_is_notified_once_its_sessions_are_over() -> Result<(), Error> {
+        let mut context = new_context().await;
+        let game_server_id = GameServerId::new();
+
+        for command in [
+            initialize_game_server_command(game_server_id),
+            MatchmakingCommand::CreateTicket {
+                ticket: new_ticket("1".parse().unwrap(), "QuickMatch"),
+            },
+            MatchmakingCommand::CreateTicket {
+                ticket: new_ticket("2".parse().unwrap(), "QuickMatch"),
+            },
+        ] {
+            context
+                .matchmaking_command_dal
+                .queue_command(REGION, &command)
+                .await?;
+        }
+
+        context.job.process(&context.lease).await?;
+
+        let session_id = context.matchmaking_session_dal.get_sessions(REGION).await?[0].session_id;
+        context
+            .matchmaking_command_dal
+            .queue_command(
+                REGION,
+                &MatchmakingCommand::DrainGameServer { game_server_id },
+            )
+            .await?;
+
+        context.job.process(&context.lease).await?;
+
+        assert!(context
+            .notification_manager
+            .get_notifications_from_queue(&game_server_id.to_string())
+            .await?
+            .iter()
+            .all(|n| !n.contains("GameServerDrainedNotification")));
+
+        context
+            .matchmaking_command_dal
+            .queue_command(REGION, &MatchmakingCommand::DeleteSession { session_id })
+            .await?;
+
+        context.job.process(&context.lease).await?;
+
+        let server_notifications = context
+            .notification_manager
+            .get_notifications_from_queue(&game_server_id.to_string())
+            .await?;
+        assert!(server_notifications
+            .iter()
+            .any(|n| n.contains("GameServerDrainedNotification")));
+        assert!(context
+            .game_server_dal
+            .get_game_server(REGION, &game_server_id)
+            .await?
+            .is_none());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn private_session_is_joined_with_its_join_code() -> Result<(), Error> {
+        let mut context = new_context().await;
+        let game_server_id = GameServerId::new();
+        let profile_id1: ProfileId = "1".parse().unwrap();
+        let profile_id2: ProfileId = "2".parse().unwrap();
+        let profile_id3: ProfileId = "3".parse().unwrap();
+        let profile_id4: ProfileId = "4".parse().unwrap();
+        let join_code = JoinCode::new();
+
+        for command in [
+            initialize_game_server_command(game_server_id),
+            MatchmakingCommand::CreatePrivateSession {
+                session_id: SessionId::new(),
+                join_code: join_code.clone(),
+                expiration_time: NOW + 60,
+                ticket: new_ticket(profile_id1, "QuickMatch"),
+            },
+            // no server left
+            MatchmakingCommand::CreatePrivateSession {
+                session_id: SessionId::new(),
+                join_code: JoinCode::new(),
+                expiration_time: NOW + 60,
+                ticket: new_ticket(profile_id4, "QuickMatch"),
+            },
+            MatchmakingCommand::JoinPrivateSession {
+                join_code: join_code.clone(),
+                ticket: new_ticket(profile_id2, "QuickMatch"),
+            },
+            MatchmakingCommand::JoinPrivateSession {
+                join_code: JoinCode::new(),
+                ticket: new_ticket(profile_id3, "QuickMatch"),
+            },
+        ] {
+            context
+                .matchmaking_command_dal
+                .queue_command(REGION, &command)
+                .await?;
+        }
+
+        context.job.process(&context.lease).await?;
+
+        let sessions = context.matchmaking_session_dal.get_sessions(REGION).await?;
+        assert_eq!(1, sessions.len());
+        let session = &sessions[0];
+        assert_eq!(Some(game_server_id), session.game_server_id);
+        assert_eq!(2, session.players.len());
+
+        context
+            .matchmaking_command_dal
+            .queue_command(
+                REGION,
+                &MatchmakingCommand::ActivateSession {
+                    session_id: session.session_id,
+                    game_server_id,
+                },
+            )
+            .await?;
+        context.job.process(&context.lease).await?;
+
+        for (profile_id, expected_notification) in [
+            (profile_id2, "MatchmakingCompletedNotification"),
+            (profile_id3, "InvalidInvitation"),
+            (profile_id4, "PrivateServerNotFound"),
+        ] {
+            let player_notifications = context
+                .notification_manager
+                .get_notifications_from_queue(&profile_id.to_string())
+                .await?;
+            assert_eq!(1, player_notifications.len());
+            assert!(player_notifications[0].contains(expected_notification));
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn backfill_is_processed_before_new_sessions() -> Result<(), Error> {
+        let mut context = new_context().await;
+        let game_server_id1 = GameServerId::new();
+        let game_server_id2 = GameServerId::new();
+        let profile_id3: ProfileId = "3".parse().unwrap();
+
+        for command in [
+            initialize_game_server_command(game_server_id1),
+            MatchmakingCommand::CreateTicket {
+                ticket: new_ticket("1".parse().unwrap(), "QuickMatch"),
+            },
+            MatchmakingCommand::CreateTicket {
+                ticket: new_ticket("2".parse().unwrap(), "QuickMatch"),
+            },
+        ] {
+            context
+                .matchmaking_command_dal
+                .queue_command(REGION, &command)
+                .await?;
+        }
+
+        context.job.process(&context.lease).await?;
+
+        let session_id = context.matchmaking_session_dal.get_sessions(REGION).await?[0].session_id;
+
+        for command in [
+            initialize_game_server_command(game_server_id2),
+            MatchmakingCommand::ActivateSession {
+                session_id,
+                game_server_id: game_server_id1,
+            },
+            MatchmakingCommand::UpdateSession {
+                session_id,
+                is_open: false,
+            },
+            MatchmakingCommand::RequestBackfill {
+                session_id,
+                backfill_request: BackfillRequest {
+                    num_players: 1,
+                    team: Some(0),
+                    role: None,
+                    min_mmr: None,
+                    max_mmr: None,
+                    expiration_time: NOW + 60,
+                    incoming_players: Vec::new(),
+                },
+            },
+            MatchmakingCommand::CreateTicket {
+                ticket: new_ticket(profile_id3, "QuickMatch"),
+            },
+            MatchmakingCommand::CreateTicket {
+                ticket: new_ticket("4".parse().unwrap(), "QuickMatch"),
+            },
+        ] {
+            context
+                .matchmaking_command_dal
+                .queue_command(REGION, &command)
+                .await?;
+        }
+
+        context.job.process(&context.lease).await?;
+
+        let session = context
+            .matchmaking_session_dal
+            .get_session(REGION, &session_id)
+            .await?
+            .unwrap();
+        assert_eq!(3, session.players.len());
+        let player3 = session
+            .players
+            .iter()
+            .find(|p| p.profile_id == profile_id3)
+            .unwrap();
+        assert_eq!(Some(0), player3.team);
+        assert!(session.backfill_request.is_none());
+
+        let server_notifications = context
+            .notification_manager
+            .get_notifications_from_queue(&game_server_id1.to_string())
+            .await?;
+        assert!(server_notifications
+            .iter()
+            .any(|n| n.contains("MatchmakingBackfillCompletedNotification")));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn game_server_hosts_sessions_up_to_its_capacity() -> Result<(), Error> {
+        let mut context = new_context().await;
+        let game_server_id = GameServerId::new();
+
+        let mut initialize_command = initialize_game_server_command(game_server_id);
+        if let MatchmakingCommand::InitializeGameServer { capacity, .. } = &mut initialize_command {
+            *capacity = 2;
+        }
+
+        for command in [
+            initialize_command,
+            MatchmakingCommand::CreateTicket {
+                ticket: new_ticket("1".parse().unwrap(), "QuickMatch"),
+            },
+            MatchmakingCommand::CreateTicket {
+                ticket: new_ticket("2".parse().unwrap(), "QuickMatch"),
+            },
+        ] {
+            context
+                .matchmaking_command_dal
+                .queue_command(REGION, &command)
+                .await?;
+        }
+
+        context.job.process(&context.lease).await?;
+
+        let session_id = context.matchmaking_session_dal.get_sessions(REGION).await?[0].session_id;
+
+        for command in [
+            MatchmakingCommand::ActivateSession {
+                session_id,
+                game_server_id,
+            },
+            MatchmakingCommand::UpdateSession {
+                session_id,
+                is_open: false,
+            },
+            MatchmakingCommand::CreateTicket {
+                ticket: new_ticket("3".parse().unwrap(), "QuickMatch"),
+            },
+            MatchmakingCommand::CreateTicket {
+                ticket: new_ticket("4".parse().unwrap(), "QuickMatch"),
+            },
+        ] {
+            context
+                .matchmaking_command_dal
+                .queue_command(REGION, &command)
+                .await?;
+        }
+
+        context.job.process(&context.lease).await?;
+
+        let sessions = context.matchmaking_session_dal.get_sessions(REGION).await?;
+        assert_eq!(2, sessions.len());
+        assert!(sessions
+            .iter()
+            .all(|session| session.game_server_id == Some(game_server_id)));
+
+        let server = context
+            .game_server_dal
+            .get_game_server(REGION, &game_server_id)
+            .await?
+            .unwrap();
+        assert_eq!(2, server.session_ids.len());
+        assert_eq!(0, server.get_free_slots());
+
+        Ok(())
+    }
+}
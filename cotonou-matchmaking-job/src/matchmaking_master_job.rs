@@ -1,84 +1,240 @@
-use crate::{Error, MatchmakingAssembler, MatchmakingJob};
-use common_macros::hash_map;
+use crate::{
+    Clock, Error, MatchmakingAssembler, MatchmakingJob, RegionAssignment, RegionClaims,
+    RegionLease, SystemClock,
+};
 use cotonou_common::{
     matchmaking::{
-        GameServerDAL, MatchmakingCommandDAL, MatchmakingSessionDAL, MatchmakingSettingsDAL,
-        MatchmakingTicketDAL, MatchmakingWaitingTimeDAL,
+        GameRegion, GameServerDAL, MaintenanceDAL, MatchmakingCommandDAL, MatchmakingJobInstance,
+        MatchmakingJobInstanceDAL, MatchmakingLeaseDAL, MatchmakingSessionDAL,
+        MatchmakingSettingsDAL, MatchmakingTicketDAL, MatchmakingWaitingTimeDAL,
+        RedisGameServerDAL, RedisMaintenanceDAL, RedisMatchmakingCommandDAL,
+        RedisMatchmakingJobInstanceDAL, RedisMatchmakingLeaseDAL, RedisMatchmakingSessionDAL,
+        RedisMatchmakingTicketDAL, RedisMatchmakingWaitingTimeDAL,
     },
-    notifications::NotificationManager,
+    metrics::MetricsRegistry,
+    notifications::{NotificationManager, RedisNotificationManager},
     redis::{RedisConfig, RedisConnectionManager},
+    types::UniqueId,
 };
+use std::{sync::Arc, time::Duration};
 use tokio::task::JoinSet;
 
+/// Delay between two attempts of a standby job to acquire a region lease
+const LEASE_RETRY_DURATION: Duration = Duration::from_secs(1);
+/// Delay between two reports of this process to the other ones
+const HEARTBEAT_DURATION: Duration = Duration::from_secs(2);
+
+#[derive(Clone)]
 pub struct MatchmakingMasterJob {
+    /// identifies this process as a lease holder and among the job instances
+    instance_id: String,
+    region_claims: Arc<RegionClaims>,
     matchmaking_settings_dal: MatchmakingSettingsDAL,
-    game_server_dal: GameServerDAL,
-    matchmaking_command_dal: MatchmakingCommandDAL,
-    matchmaking_session_dal: MatchmakingSessionDAL,
-    matchmaking_ticket_dal: MatchmakingTicketDAL,
-    matchmaking_waiting_time_dal: MatchmakingWaitingTimeDAL,
+    game_server_dal: Arc<dyn GameServerDAL>,
+    maintenance_dal: Arc<dyn MaintenanceDAL>,
+    matchmaking_command_dal: Arc<dyn MatchmakingCommandDAL>,
+    matchmaking_job_instance_dal: Arc<dyn MatchmakingJobInstanceDAL>,
+    matchmaking_lease_dal: Arc<dyn MatchmakingLeaseDAL>,
+    matchmaking_session_dal: Arc<dyn MatchmakingSessionDAL>,
+    matchmaking_ticket_dal: Arc<dyn MatchmakingTicketDAL>,
+    matchmaking_waiting_time_dal: Arc<dyn MatchmakingWaitingTimeDAL>,
     matchmaking_assembler: MatchmakingAssembler,
-    notification_manager: NotificationManager,
+    notification_manager: Arc<dyn NotificationManager>,
+    metrics_registry: Arc<MetricsRegistry>,
     shutdown_receiver: tokio::sync::watch::Receiver<()>,
+    clock: Arc<dyn Clock>,
 }
 
 impl MatchmakingMasterJob {
-    pub async fn new(shutdown_receiver: tokio::sync::watch::Receiver<()>) -> Result<Self, Error> {
-        let redis_host = "127.0.0.1";
-        let redis_connection_manager = RedisConnectionManager::initialize(RedisConfig {
-            connection_strings: hash_map! {
-                "NOTIFICATIONS".to_owned() => format!("redis://{redis_host}:6379/0"),
-                "NOTIFICATIONS_PUBSUB".to_owned() => format!("redis://{redis_host}:6379/0"),
-                "MATCHMAKING".to_owned() => format!("redis://{redis_host}:6379/1"),
-            },
-        })
-        .await?;
-
-        let game_server_dal = GameServerDAL::new(&redis_connection_manager);
-        let matchmaking_command_dal = MatchmakingCommandDAL::new(&redis_connection_manager);
-        let matchmaking_settings_dal = MatchmakingSettingsDAL::new();
-        let matchmaking_session_dal = MatchmakingSessionDAL::new(&redis_connection_manager);
-        let matchmaking_ticket_dal = MatchmakingTicketDAL::new(&redis_connection_manager);
-        let matchmaking_waiting_time_dal =
-            MatchmakingWaitingTimeDAL::new(&redis_connection_manager);
-        let matchmaking_assembler = MatchmakingAssembler::new();
-        let notification_manager = NotificationManager::new(&redis_connection_manager);
+    //-------------------------------------------------------------------------------------------------
+    pub async fn new(
+        region_assignment: RegionAssignment,
+        metrics_registry: Arc<MetricsRegistry>,
+        shutdown_receiver: tokio::sync::watch::Receiver<()>,
+    ) -> Result<Self, Error> {
+        let matchmaking_settings_dal = MatchmakingSettingsDAL::from_env()?;
+        let redis_connection_manager =
+            RedisConnectionManager::initialize(RedisConfig::from_env(&[
+                ("NOTIFICATIONS", "redis://127.0.0.1:6379"),
+                ("NOTIFICATIONS_PUBSUB", "redis://127.0.0.1:6379"),
+                ("MATCHMAKING", "redis://127.0.0.1:6379"),
+            ])?)
+            .await?;
+
+        let game_server_dal = Arc::new(RedisGameServerDAL::new(&redis_connection_manager));
+        let maintenance_dal = Arc::new(RedisMaintenanceDAL::new(&redis_connection_manager));
+        let matchmaking_command_dal =
+            Arc::new(RedisMatchmakingCommandDAL::new(&redis_connection_manager));
+        let matchmaking_job_instance_dal = Arc::new(RedisMatchmakingJobInstanceDAL::new(
+            &redis_connection_manager,
+        ));
+        let matchmaking_lease_dal =
+            Arc::new(RedisMatchmakingLeaseDAL::new(&redis_connection_manager));
+        let matchmaking_session_dal =
+            Arc::new(RedisMatchmakingSessionDAL::new(&redis_connection_manager));
+        let matchmaking_ticket_dal =
+            Arc::new(RedisMatchmakingTicketDAL::new(&redis_connection_manager));
+        let matchmaking_waiting_time_dal = Arc::new(RedisMatchmakingWaitingTimeDAL::new(
+            &redis_connection_manager,
+        ));
+        let clock: Arc<dyn Clock> = Arc::new(SystemClock);
+        let matchmaking_assembler = MatchmakingAssembler::new(clock.clone());
+        let notification_manager =
+            Arc::new(RedisNotificationManager::new(&redis_connection_manager));
 
         Ok(Self {
+            instance_id: UniqueId::new().to_string(),
+            region_claims: Arc::new(RegionClaims::new(region_assignment)),
             matchmaking_settings_dal,
             game_server_dal,
+            maintenance_dal,
             matchmaking_command_dal,
+            matchmaking_job_instance_dal,
+            matchmaking_lease_dal,
             matchmaking_session_dal,
             matchmaking_ticket_dal,
             matchmaking_waiting_time_dal,
             matchmaking_assembler,
             notification_manager,
+            metrics_registry,
             shutdown_receiver,
+            clock,
         })
     }
 
+    //-------------------------------------------------------------------------------------------------
     pub async fn initialize(&self) -> Result<(), Error> {
         let mut set = JoinSet::new();
 
-        for region in self.matchmaking_settings_dal.get_supported_regions() {
+        let master_job = self.clone();
+        set.spawn(async move { master_job.instance_loop().await });
+
+        for region in self
+            .matchmaking_settings_dal
+            .get_supported_regions()
+            .iter()
+            .filter(|r| self.region_claims.is_assigned(&r.region_system_name))
+        {
+            let master_job = self.clone();
+            let region = region.clone();
+            set.spawn(async move { master_job.region_loop(region).await });
+        }
+
+        while let Some(_result) = set.join_next().await {}
+
+        Ok(())
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    /// Report this process with its claimed regions and rebalance regions among processes
+    async fn instance_loop(&self) -> Result<(), Error> {
+        let num_regions = self
+            .matchmaking_settings_dal
+            .get_supported_regions()
+            .iter()
+            .filter(|r| self.region_claims.is_assigned(&r.region_system_name))
+            .count();
+
+        while !self.shutdown_receiver.has_changed()? {
+            if let Err(e) = self.heartbeat(num_regions).await {
+                log::error!("Error while reporting job instance: {e:?}");
+            }
+
+            tokio::time::sleep(HEARTBEAT_DURATION).await;
+        }
+
+        self.matchmaking_job_instance_dal
+            .remove_instance(&self.instance_id)
+            .await?;
+
+        Ok(())
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    async fn heartbeat(&self, num_regions: usize) -> Result<(), Error> {
+        let instance = MatchmakingJobInstance {
+            instance_id: self.instance_id.clone(),
+            regions: self.region_claims.get_claimed_regions(),
+            last_heartbeat_time: self.clock.now(),
+            is_static: self.region_claims.is_static(),
+        };
+        self.matchmaking_job_instance_dal
+            .report_instance(&instance)
+            .await?;
+
+        let instances = self.matchmaking_job_instance_dal.get_instances().await?;
+        self.region_claims.rebalance(num_regions, &instances);
+
+        Ok(())
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    /// Wait for the region lease as a standby, then process the region until the lease is lost
+    async fn region_loop(&self, region: GameRegion) -> Result<(), Error> {
+        let region_system_name = &region.region_system_name;
+
+        while !self.shutdown_receiver.has_changed()? {
+            if !self.region_claims.can_claim() {
+                tokio::time::sleep(LEASE_RETRY_DURATION).await;
+                continue;
+            }
+
+            // the number of regions claimed is checked again with the acquisition,
+            // as the regions of this process compete for the same fair share;
+            // regions claimed concurrently beyond it are handed over at the next rebalance
+            let lease = RegionLease::acquire(
+                region_system_name,
+                &self.instance_id,
+                self.region_claims.get_fair_share(),
+                self.matchmaking_lease_dal.clone(),
+            )
+            .await;
+
+            let lease = match lease {
+                Ok(Some(lease)) => lease,
+                Ok(None) => {
+                    tokio::time::sleep(LEASE_RETRY_DURATION).await;
+                    continue;
+                }
+                Err(e) => {
+                    log::error!("[{region_system_name}] Error while acquiring lease: {e:?}");
+                    tokio::time::sleep(LEASE_RETRY_DURATION).await;
+                    continue;
+                }
+            };
+
             let mut matchmaking_job = MatchmakingJob::new(
-                &region.region_system_name,
+                region_system_name,
                 &region.region_prefix,
                 self.game_server_dal.clone(),
                 self.matchmaking_command_dal.clone(),
                 self.matchmaking_session_dal.clone(),
                 self.matchmaking_ticket_dal.clone(),
                 self.matchmaking_waiting_time_dal.clone(),
+                self.maintenance_dal.clone(),
                 self.matchmaking_assembler.clone(),
                 self.notification_manager.clone(),
+                self.metrics_registry.clone(),
                 self.matchmaking_settings_dal.clone(),
                 self.shutdown_receiver.clone(),
+                self.clock.clone(),
             );
 
-            set.spawn(async move { matchmaking_job.job_loop().await });
-        }
+            self.region_claims
+                .claim(region_system_name, lease.handover_requested());
+            let result = matchmaking_job.job_loop(&lease).await;
+            matchmaking_job.remove_metrics();
+            self.region_claims.unclaim(region_system_name);
 
-        while let Some(_result) = set.join_next().await {}
+            if let Err(e) = lease.release().await {
+                log::error!("[{region_system_name}] Error while releasing lease: {e:?}");
+            }
+
+            if let Err(e) = result {
+                log::error!("[{region_system_name}] MatchmakingJob stopped: {e:?}");
+                tokio::time::sleep(LEASE_RETRY_DURATION).await;
+            }
+        }
 
         Ok(())
     }
@@ -0,0 +1,175 @@
+use cotonou_common::metrics::{MetricsRegistry, DURATION_BUCKETS};
+use std::{sync::Arc, time::Duration};
+
+/// Buckets of the time to match histogram, in seconds
+const TIME_TO_MATCH_BUCKETS: &[f64] = &[1., 5., 10., 20., 30., 60., 90., 120., 180., 300., 600.];
+
+const QUEUE_LENGTH_GAUGE: &str = "matchmaking_queue_length";
+const GAME_SERVERS_GAUGE: &str = "matchmaking_game_servers";
+const COMMAND_QUEUE_LENGTH_GAUGE: &str = "matchmaking_command_queue_length";
+
+/// Metrics of the matchmaking job of a region
+pub struct MatchmakingMetrics {
+    region_system_name: String,
+    metrics_registry: Arc<MetricsRegistry>,
+}
+
+impl MatchmakingMetrics {
+    //-------------------------------------------------------------------------------------------------
+    pub fn new(region_system_name: &str, metrics_registry: Arc<MetricsRegistry>) -> Self {
+        Self {
+            region_system_name: region_system_name.to_owned(),
+            metrics_registry,
+        }
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    pub fn ticket_created(&self, game_mode: &str) {
+        self.increment_ticket_counter(game_mode, "created");
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    pub fn ticket_matched(&self, game_mode: &str, time_to_match: u64) {
+        self.increment_ticket_counter(game_mode, "matched");
+        self.metrics_registry.observe_histogram(
+            "matchmaking_time_to_match_seconds",
+            "Time between the creation of a ticket and the activation of its session",
+            TIME_TO_MATCH_BUCKETS,
+            &[
+                ("region", &self.region_system_name),
+                ("game_mode", game_mode),
+            ],
+            time_to_match as f64,
+        );
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    /// Error of the waiting time estimated on the creation of a matched ticket
+    pub fn observe_estimate_error(
+        &self,
+        game_mode: &str,
+        estimated_wait_time: u64,
+        waiting_time: u64,
+    ) {
+        let direction = if estimated_wait_time > waiting_time {
+            "over"
+        } else {
+            "under"
+        };
+        self.metrics_registry.observe_histogram(
+            "matchmaking_wait_time_estimate_error_seconds",
+            "Difference between the estimated and the actual waiting time of matched tickets",
+            TIME_TO_MATCH_BUCKETS,
+            &[
+                ("region", &self.region_system_name),
+                ("game_mode", game_mode),
+                ("direction", direction),
+            ],
+            estimated_wait_time.abs_diff(waiting_time) as f64,
+        );
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    pub fn ticket_cancelled(&self, game_mode: &str) {
+        self.increment_ticket_counter(game_mode, "cancelled");
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    pub fn ticket_expired(&self, game_mode: &str) {
+        self.increment_ticket_counter(game_mode, "expired");
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn increment_ticket_counter(&self, game_mode: &str, event: &str) {
+        self.metrics_registry.increment_counter(
+            "matchmaking_tickets_total",
+            "Number of matchmaking tickets by event",
+            &[
+                ("region", &self.region_system_name),
+                ("game_mode", game_mode),
+                ("event", event),
+            ],
+        );
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    pub fn session_created(&self, game_mode: &str) {
+        self.metrics_registry.increment_counter(
+            "matchmaking_sessions_created_total",
+            "Number of sessions started on a game server",
+            &[
+                ("region", &self.region_system_name),
+                ("game_mode", game_mode),
+            ],
+        );
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    pub fn set_queue_length(&self, game_mode: &str, queue_length: usize) {
+        self.metrics_registry.set_gauge(
+            QUEUE_LENGTH_GAUGE,
+            "Number of tickets waiting for a match",
+            &[
+                ("region", &self.region_system_name),
+                ("game_mode", game_mode),
+            ],
+            queue_length as f64,
+        );
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    pub fn set_game_servers(&self, num_idle_servers: usize, num_active_servers: usize) {
+        for (state, num_servers) in [("idle", num_idle_servers), ("active", num_active_servers)] {
+            self.metrics_registry.set_gauge(
+                GAME_SERVERS_GAUGE,
+                "Number of game servers, active ones host at least one session",
+                &[("region", &self.region_system_name), ("state", state)],
+                num_servers as f64,
+            );
+        }
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    pub fn set_command_queue_length(&self, queue_length: usize) {
+        self.metrics_registry.set_gauge(
+            COMMAND_QUEUE_LENGTH_GAUGE,
+            "Number of commands queued or pending acknowledgement",
+            &[("region", &self.region_system_name)],
+            queue_length as f64,
+        );
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    /// Remove the gauges of the region, so that they are only reported by the job processing it
+    pub fn remove_gauges(&self) {
+        for gauge in [
+            QUEUE_LENGTH_GAUGE,
+            GAME_SERVERS_GAUGE,
+            COMMAND_QUEUE_LENGTH_GAUGE,
+        ] {
+            self.metrics_registry
+                .remove_values(gauge, &[("region", &self.region_system_name)]);
+        }
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    /// # Arguments
+    /// * `loop_duration` - expected duration of an iteration, longer iterations are counted as overruns
+    pub fn loop_processed(&self, duration: Duration, loop_duration: Duration) {
+        self.metrics_registry.observe_histogram(
+            "matchmaking_job_loop_duration_seconds",
+            "Duration of an iteration of the matchmaking job loop",
+            DURATION_BUCKETS,
+            &[("region", &self.region_system_name)],
+            duration.as_secs_f64(),
+        );
+
+        if duration > loop_duration {
+            self.metrics_registry.increment_counter(
+                "matchmaking_job_loop_overruns_total",
+                "Number of iterations of the matchmaking job loop longer than the loop duration",
+                &[("region", &self.region_system_name)],
+            );
+        }
+    }
+}
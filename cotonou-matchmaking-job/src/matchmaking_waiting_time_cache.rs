@@ -1,88 +1,183 @@
-use crate::Error;
-use cotonou_common::{
-    matchmaking::{MatchmakingPlayer, MatchmakingTicket, MatchmakingWaitingTimeDAL},
-    unix_now,
+use crate::{Clock, Error};
+use cotonou_common::matchmaking::{
+    MatchmakingTicket, MatchmakingWaitingTimeDAL, WaitingTimeBracket, WaitingTimeStatistics,
 };
 use futures_util::future;
-use std::collections::VecDeque;
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Arc,
+};
+
+/// Time after which a waiting time weighs half as much, in seconds
+const WAITING_TIME_HALF_LIFE: f64 = 300.;
+/// Brackets weighing less are not published anymore
+const MIN_BRACKET_WEIGHT: f64 = 0.05;
+/// Duration over which the throughput is measured, in seconds
+const THROUGHPUT_WINDOW: u64 = 300;
+
+/// Exponentially time-decayed average
+#[derive(Default)]
+struct DecayedAverage {
+    weighted_sum: f64,
+    total_weight: f64,
+    last_update_time: u64,
+}
+
+impl DecayedAverage {
+    //-------------------------------------------------------------------------------------------------
+    fn add(&mut self, value: u64, now: u64) {
+        let decay = self.get_decay(now);
+        self.weighted_sum = self.weighted_sum * decay + value as f64;
+        self.total_weight = self.total_weight * decay + 1.;
+        self.last_update_time = now;
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn get_decay(&self, now: u64) -> f64 {
+        let elapsed = now.saturating_sub(self.last_update_time) as f64;
+        0.5f64.powf(elapsed / WAITING_TIME_HALF_LIFE)
+    }
 
-const MAX_WAITING_QUEUE_SIZE: usize = 10;
+    //-------------------------------------------------------------------------------------------------
+    fn get_weight(&self, now: u64) -> f64 {
+        self.total_weight * self.get_decay(now)
+    }
 
+    //-------------------------------------------------------------------------------------------------
+    fn get_average(&self) -> u64 {
+        if self.total_weight > 0. {
+            (self.weighted_sum / self.total_weight).round() as u64
+        } else {
+            0
+        }
+    }
+}
+
+#[derive(Default)]
 struct WaitingTimeInfo {
-    pub game_mode: String,
-    pub last_waiting_times: VecDeque<u64>,
-    pub median_waiting_time: u64,
+    /// waiting times indexed by MMR bracket and party size
+    brackets: HashMap<(u32, usize), DecayedAverage>,
+    average_waiting_time: DecayedAverage,
+    /// times at which tickets were matched during the throughput window
+    match_times: VecDeque<u64>,
+    queue_length: usize,
+    num_idle_servers: usize,
+}
+
+impl WaitingTimeInfo {
+    //-------------------------------------------------------------------------------------------------
+    fn add_waiting_time(&mut self, ticket: &MatchmakingTicket, now: u64) {
+        let waiting_time = now.saturating_sub(ticket.creation_time);
+        let key = (
+            WaitingTimeStatistics::get_ticket_mmr_bracket(ticket),
+            ticket.players.len(),
+        );
+
+        self.brackets.entry(key).or_default().add(waiting_time, now);
+        self.average_waiting_time.add(waiting_time, now);
+        self.match_times.push_back(now);
+        self.remove_old_match_times(now);
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn remove_old_match_times(&mut self, now: u64) {
+        while self
+            .match_times
+            .front()
+            .is_some_and(|time| time + THROUGHPUT_WINDOW < now)
+        {
+            self.match_times.pop_front();
+        }
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn get_statistics(&self, now: u64) -> WaitingTimeStatistics {
+        let mut brackets = self
+            .brackets
+            .iter()
+            .filter(|(_, average)| average.get_weight(now) >= MIN_BRACKET_WEIGHT)
+            .map(|((mmr_bracket, party_size), average)| WaitingTimeBracket {
+                mmr_bracket: *mmr_bracket,
+                party_size: *party_size,
+                waiting_time: average.get_average(),
+            })
+            .collect::<Vec<_>>();
+        brackets.sort_by_key(|b| (b.mmr_bracket, b.party_size));
+
+        let num_recent_matches = self
+            .match_times
+            .iter()
+            .filter(|time| *time + THROUGHPUT_WINDOW >= now)
+            .count();
+
+        WaitingTimeStatistics {
+            brackets,
+            average_waiting_time: self.average_waiting_time.get_average(),
+            queue_length: self.queue_length,
+            num_idle_servers: self.num_idle_servers,
+            throughput: num_recent_matches as f64 / THROUGHPUT_WINDOW as f64,
+        }
+    }
 }
 
 pub struct MatchmakingWaitingTimeCache {
     region_system_name: String,
-    matchmaking_waiting_time_dal: MatchmakingWaitingTimeDAL,
-    waiting_time_infos: Vec<WaitingTimeInfo>,
+    matchmaking_waiting_time_dal: Arc<dyn MatchmakingWaitingTimeDAL>,
+    /// waiting times indexed by game mode
+    waiting_time_infos: HashMap<String, WaitingTimeInfo>,
+    clock: Arc<dyn Clock>,
 }
 
 impl MatchmakingWaitingTimeCache {
     //-------------------------------------------------------------------------------------------------
     pub fn new(
         region_system_name: &str,
-        matchmaking_waiting_time_dal: MatchmakingWaitingTimeDAL,
+        matchmaking_waiting_time_dal: Arc<dyn MatchmakingWaitingTimeDAL>,
+        clock: Arc<dyn Clock>,
     ) -> Self {
         Self {
             region_system_name: region_system_name.to_owned(),
             matchmaking_waiting_time_dal,
-            waiting_time_infos: Vec::new(),
+            waiting_time_infos: HashMap::new(),
+            clock,
         }
     }
 
     //-------------------------------------------------------------------------------------------------
-    pub fn update_cache(&mut self, ticket: &MatchmakingTicket, _player: &MatchmakingPlayer) {
-        let game_mode = ticket.game_mode.as_str();
+    /// Record the waiting time of a matched ticket
+    pub fn update_cache(&mut self, ticket: &MatchmakingTicket) {
+        self.waiting_time_infos
+            .entry(ticket.game_mode.clone())
+            .or_default()
+            .add_waiting_time(ticket, self.clock.now());
+    }
 
+    //-------------------------------------------------------------------------------------------------
+    /// Record the current load of a game mode
+    pub fn update_queue(&mut self, game_mode: &str, queue_length: usize, num_idle_servers: usize) {
         let info = self
             .waiting_time_infos
-            .iter_mut()
-            .find(|i| i.game_mode == game_mode);
-
-        let info = if let Some(info) = info {
-            info
-        } else {
-            let info = WaitingTimeInfo {
-                game_mode: game_mode.to_owned(),
-                last_waiting_times: VecDeque::new(),
-                median_waiting_time: 0,
-            };
-            self.waiting_time_infos.push(info);
-            self.waiting_time_infos.last_mut().unwrap()
-        };
-
-        while info.last_waiting_times.len() >= MAX_WAITING_QUEUE_SIZE {
-            info.last_waiting_times.pop_back();
-        }
-
-        info.last_waiting_times
-            .push_front(unix_now() - ticket.creation_time);
-
-        let count = info.last_waiting_times.len();
-
-        info.median_waiting_time = if count > 0 {
-            let mut times = info.last_waiting_times.iter().copied().collect::<Vec<_>>();
-            times.sort();
-            times[count / 2]
-        } else {
-            0u64
-        };
+            .entry(game_mode.to_owned())
+            .or_default();
+        info.queue_length = queue_length;
+        info.num_idle_servers = num_idle_servers;
+        info.remove_old_match_times(self.clock.now());
     }
 
     //-------------------------------------------------------------------------------------------------
     pub async fn save_cache(&self) -> Result<(), Error> {
-        let tasks = self
+        let now = self.clock.now();
+        let statistics = self
             .waiting_time_infos
             .iter()
-            .map(|i| {
-                self.matchmaking_waiting_time_dal.set_average_waiting_time(
-                    &self.region_system_name,
-                    &i.game_mode,
-                    i.median_waiting_time,
-                )
+            .map(|(game_mode, info)| (game_mode, info.get_statistics(now)))
+            .collect::<Vec<_>>();
+
+        let tasks = statistics
+            .iter()
+            .map(|(game_mode, statistics)| {
+                self.matchmaking_waiting_time_dal
+                    .set_waiting_time_statistics(&self.region_system_name, game_mode, statistics)
             })
             .collect::<Vec<_>>();
         let results = future::join_all(tasks).await;
@@ -97,10 +192,10 @@ impl MatchmakingWaitingTimeCache {
     pub async fn reset(&mut self) -> Result<(), Error> {
         let tasks = self
             .waiting_time_infos
-            .iter()
-            .map(|i| {
+            .keys()
+            .map(|game_mode| {
                 self.matchmaking_waiting_time_dal
-                    .reset(&self.region_system_name, &i.game_mode)
+                    .reset(&self.region_system_name, game_mode)
             })
             .collect::<Vec<_>>();
         let results = future::join_all(tasks).await;
@@ -114,11 +209,30 @@ impl MatchmakingWaitingTimeCache {
     }
 
     //-------------------------------------------------------------------------------------------------
-    pub fn get_average_waiting_time(&self, game_mode: &str) -> u64 {
+    /// Waiting time statistics of a game mode, to estimate the waiting times of its tickets.
+    /// They are built on each call, callers estimating several tickets should reuse them
+    pub fn get_statistics(&self, game_mode: &str) -> Option<WaitingTimeStatistics> {
         self.waiting_time_infos
-            .iter()
-            .find(|i| i.game_mode == game_mode)
-            .map(|i| i.median_waiting_time)
-            .unwrap_or_default()
+            .get(game_mode)
+            .map(|info| info.get_statistics(self.clock.now()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recent_waiting_times_weigh_more() {
+        let mut average = DecayedAverage::default();
+        average.add(100, 1000);
+        average.add(10, 1000 + WAITING_TIME_HALF_LIFE as u64);
+
+        // the first waiting time weighs half as much as the second one
+        assert_eq!(40, average.get_average());
+        assert_eq!(
+            1.5,
+            average.get_weight(1000 + WAITING_TIME_HALF_LIFE as u64)
+        );
     }
 }
@@ -3,7 +3,7 @@ use cotonou_common::{
     notifications::{Notification, NotificationManager},
     types::{GameServerId, ProfileId},
 };
-use std::collections::VecDeque;
+use std::{collections::VecDeque, sync::Arc};
 
 struct NotificationInfo {
     channel: String,
@@ -11,13 +11,13 @@ struct NotificationInfo {
 }
 
 pub struct NotificationCache {
-    notification_manager: NotificationManager,
+    notification_manager: Arc<dyn NotificationManager>,
     notification_queue: VecDeque<NotificationInfo>,
 }
 
 impl NotificationCache {
     //-------------------------------------------------------------------------------------------------
-    pub fn new(notification_manager: NotificationManager) -> Self {
+    pub fn new(notification_manager: Arc<dyn NotificationManager>) -> Self {
         Self {
             notification_manager,
             notification_queue: VecDeque::new(),
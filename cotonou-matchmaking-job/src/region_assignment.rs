@@ -0,0 +1,214 @@
+use cotonou_common::matchmaking::MatchmakingJobInstance;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+};
+
+/// Which regions a matchmaking job process competes for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RegionAssignment {
+    /// the given regions only
+    Static(Vec<String>),
+    /// a fair share of all the supported regions, rebalanced when processes join or leave
+    Dynamic,
+}
+
+impl From<&str> for RegionAssignment {
+    /// Comma separated list of region system names, empty for a dynamic assignment
+    fn from(s: &str) -> Self {
+        let regions = s
+            .split(',')
+            .map(str::trim)
+            .filter(|r| !r.is_empty())
+            .map(str::to_owned)
+            .collect::<Vec<_>>();
+
+        if regions.is_empty() {
+            Self::Dynamic
+        } else {
+            Self::Static(regions)
+        }
+    }
+}
+
+/// Regions claimed by this process
+pub struct RegionClaims {
+    assignment: RegionAssignment,
+    /// handover flags indexed by claimed region
+    claimed_regions: Mutex<HashMap<String, Arc<AtomicBool>>>,
+    /// maximum number of regions this process should claim,
+    /// none for a dynamic assignment until the other processes are known
+    fair_share: AtomicUsize,
+}
+
+impl RegionClaims {
+    //-------------------------------------------------------------------------------------------------
+    pub fn new(assignment: RegionAssignment) -> Self {
+        let fair_share = match assignment {
+            RegionAssignment::Static(_) => usize::MAX,
+            RegionAssignment::Dynamic => 0,
+        };
+
+        Self {
+            assignment,
+            claimed_regions: Mutex::new(HashMap::new()),
+            fair_share: AtomicUsize::new(fair_share),
+        }
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    pub fn is_assigned(&self, region_system_name: &str) -> bool {
+        match &self.assignment {
+            RegionAssignment::Static(regions) => regions.iter().any(|r| r == region_system_name),
+            RegionAssignment::Dynamic => true,
+        }
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    pub fn is_static(&self) -> bool {
+        matches!(self.assignment, RegionAssignment::Static(_))
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    pub fn get_fair_share(&self) -> usize {
+        self.fair_share.load(Ordering::Relaxed)
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    pub fn can_claim(&self) -> bool {
+        self.claimed_regions.lock().unwrap().len() < self.get_fair_share()
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    pub fn claim(&self, region_system_name: &str, handover_requested: Arc<AtomicBool>) {
+        self.claimed_regions
+            .lock()
+            .unwrap()
+            .insert(region_system_name.to_owned(), handover_requested);
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    pub fn unclaim(&self, region_system_name: &str) {
+        self.claimed_regions
+            .lock()
+            .unwrap()
+            .remove(region_system_name);
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    pub fn get_claimed_regions(&self) -> Vec<String> {
+        let mut regions = self
+            .claimed_regions
+            .lock()
+            .unwrap()
+            .keys()
+            .cloned()
+            .collect::<Vec<_>>();
+        regions.sort();
+        regions
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    /// Update the fair share from the live processes and hand over the regions claimed beyond it.
+    /// The regions of statically assigned processes are shared by the other processes only
+    pub fn rebalance(&self, num_regions: usize, instances: &[MatchmakingJobInstance]) {
+        if self.assignment != RegionAssignment::Dynamic {
+            return;
+        }
+
+        let static_regions = instances
+            .iter()
+            .filter(|i| i.is_static)
+            .flat_map(|i| i.regions.iter())
+            .collect::<HashSet<_>>();
+        let num_dynamic_instances = instances.iter().filter(|i| !i.is_static).count();
+        let fair_share = num_regions
+            .saturating_sub(static_regions.len())
+            .div_ceil(num_dynamic_instances.max(1));
+        self.fair_share.store(fair_share, Ordering::Relaxed);
+
+        let claimed_regions = self.claimed_regions.lock().unwrap();
+        let mut regions = claimed_regions.iter().collect::<Vec<_>>();
+        regions.sort_by_key(|(r, _)| *r);
+
+        for (region_system_name, handover_requested) in regions.into_iter().skip(fair_share) {
+            if !handover_requested.swap(true, Ordering::Relaxed) {
+                log::info!("[{region_system_name}] Handing region over to another instance");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_assignment() {
+        assert_eq!(RegionAssignment::Dynamic, RegionAssignment::from(""));
+        assert_eq!(
+            RegionAssignment::Static(vec!["eu-central-1".to_owned(), "us-east-1".to_owned()]),
+            RegionAssignment::from("eu-central-1, us-east-1")
+        );
+    }
+
+    fn instance(instance_id: &str, regions: &[&str], is_static: bool) -> MatchmakingJobInstance {
+        MatchmakingJobInstance {
+            instance_id: instance_id.to_owned(),
+            regions: regions.iter().map(|r| (*r).to_owned()).collect(),
+            last_heartbeat_time: 0,
+            is_static,
+        }
+    }
+
+    #[test]
+    fn rebalance_hands_over_extra_regions() {
+        let claims = RegionClaims::new(RegionAssignment::Dynamic);
+        // nothing is claimed until the other processes are known
+        assert!(!claims.can_claim());
+
+        let flags = ["eu-central-1", "us-east-1", "us-west-1"]
+            .into_iter()
+            .map(|region| {
+                let flag = Arc::new(AtomicBool::new(false));
+                claims.claim(region, flag.clone());
+                flag
+            })
+            .collect::<Vec<_>>();
+
+        // a second instance joins
+        claims.rebalance(
+            3,
+            &[
+                instance("1", &["eu-central-1", "us-east-1", "us-west-1"], false),
+                instance("2", &[], false),
+            ],
+        );
+
+        assert!(!claims.can_claim());
+        assert!(!flags[0].load(Ordering::Relaxed));
+        assert!(!flags[1].load(Ordering::Relaxed));
+        assert!(flags[2].load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn rebalance_ignores_static_instances() {
+        let claims = RegionClaims::new(RegionAssignment::Dynamic);
+
+        // the static instance processes one region, the two dynamic ones share the two others
+        claims.rebalance(
+            3,
+            &[
+                instance("1", &[], false),
+                instance("2", &[], false),
+                instance("3", &["eu-central-1"], true),
+            ],
+        );
+        assert_eq!(1, claims.get_fair_share());
+
+        assert!(RegionClaims::new(RegionAssignment::from("eu-central-1")).can_claim());
+    }
+}
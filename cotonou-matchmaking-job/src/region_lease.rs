@@ -0,0 +1,100 @@
+use crate::Error;
+use cotonou_common::matchmaking::MatchmakingLeaseDAL;
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+/// A lease not renewed during this duration can be acquired by a standby job
+pub const LEASE_DURATION: Duration = Duration::from_secs(10);
+
+/// Lease held by this process on a region
+pub struct RegionLease {
+    region_system_name: String,
+    holder_id: String,
+    fencing_token: u64,
+    lease_dal: Arc<dyn MatchmakingLeaseDAL>,
+    /// set when the region must be handed over to another process
+    handover_requested: Arc<AtomicBool>,
+}
+
+impl RegionLease {
+    //-------------------------------------------------------------------------------------------------
+    /// Returns `None` if the region is already leased by another job
+    /// or if the holder already holds `max_leases` leases
+    pub async fn acquire(
+        region_system_name: &str,
+        holder_id: &str,
+        max_leases: usize,
+        lease_dal: Arc<dyn MatchmakingLeaseDAL>,
+    ) -> Result<Option<Self>, Error> {
+        let fencing_token = lease_dal
+            .acquire_lease(region_system_name, holder_id, max_leases, LEASE_DURATION)
+            .await?;
+
+        Ok(fencing_token.map(|fencing_token| Self {
+            region_system_name: region_system_name.to_owned(),
+            holder_id: holder_id.to_owned(),
+            fencing_token,
+            lease_dal,
+            handover_requested: Arc::new(AtomicBool::new(false)),
+        }))
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    pub fn fencing_token(&self) -> u64 {
+        self.fencing_token
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    pub fn handover_requested(&self) -> Arc<AtomicBool> {
+        self.handover_requested.clone()
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    /// Returns `false` if the lease has been lost or must be handed over
+    pub async fn renew(&self) -> Result<bool, Error> {
+        if self.handover_requested.load(Ordering::Relaxed) {
+            return Ok(false);
+        }
+
+        Ok(self
+            .lease_dal
+            .renew_lease(
+                &self.region_system_name,
+                &self.holder_id,
+                self.fencing_token,
+                LEASE_DURATION,
+            )
+            .await?)
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    /// Returns `false` if another job may have taken over the region since the last renewal,
+    /// checked right before writing to the region so that a stale leader does not overwrite it
+    pub async fn is_held(&self) -> Result<bool, Error> {
+        Ok(self
+            .lease_dal
+            .check_lease(
+                &self.region_system_name,
+                &self.holder_id,
+                self.fencing_token,
+            )
+            .await?)
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    pub async fn release(self) -> Result<(), Error> {
+        self.lease_dal
+            .release_lease(
+                &self.region_system_name,
+                &self.holder_id,
+                self.fencing_token,
+            )
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,110 @@
+use cotonou_common::matchmaking::{GameServer, ServerPlacementConfig};
+use std::cmp::Ordering;
+
+/// Choose the game server hosting a new session
+pub trait ServerPlacement: Send {
+    /// Compare two servers with free slots, the lowest one is selected
+    fn compare(&self, server1: &GameServer, server2: &GameServer) -> Ordering;
+}
+
+pub fn new_server_placement(
+    config: &ServerPlacementConfig,
+    preferred_host_providers: &[String],
+) -> Box<dyn ServerPlacement> {
+    let preferred_host_providers = preferred_host_providers.to_vec();
+    match config {
+        ServerPlacementConfig::Pack => Box::new(PackServerPlacement {
+            preferred_host_providers,
+        }),
+        ServerPlacementConfig::Spread => Box::new(SpreadServerPlacement {
+            preferred_host_providers,
+        }),
+    }
+}
+
+/// Servers with the fewest free slots first
+pub struct PackServerPlacement {
+    preferred_host_providers: Vec<String>,
+}
+
+impl ServerPlacement for PackServerPlacement {
+    fn compare(&self, server1: &GameServer, server2: &GameServer) -> Ordering {
+        get_host_provider_rank(&self.preferred_host_providers, server1)
+            .cmp(&get_host_provider_rank(
+                &self.preferred_host_providers,
+                server2,
+            ))
+            .then(server1.get_free_slots().cmp(&server2.get_free_slots()))
+            .then(server2.load.total_cmp(&server1.load))
+    }
+}
+
+/// Least loaded servers first
+pub struct SpreadServerPlacement {
+    preferred_host_providers: Vec<String>,
+}
+
+impl ServerPlacement for SpreadServerPlacement {
+    fn compare(&self, server1: &GameServer, server2: &GameServer) -> Ordering {
+        get_host_provider_rank(&self.preferred_host_providers, server1)
+            .cmp(&get_host_provider_rank(
+                &self.preferred_host_providers,
+                server2,
+            ))
+            .then(server1.load.total_cmp(&server2.load))
+            .then(server2.get_free_slots().cmp(&server1.get_free_slots()))
+    }
+}
+
+//-------------------------------------------------------------------------------------------------
+fn get_host_provider_rank(preferred_host_providers: &[String], server: &GameServer) -> usize {
+    preferred_host_providers
+        .iter()
+        .position(|p| *p == server.host_provider)
+        .unwrap_or(preferred_host_providers.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cotonou_common::{
+        matchmaking::{GameServerHostType, SessionId},
+        types::GameServerId,
+    };
+
+    fn server(host_provider: &str, capacity: usize, num_sessions: usize, load: f32) -> GameServer {
+        GameServer {
+            game_server_id: GameServerId::new(),
+            host_name: String::new(),
+            host_type: GameServerHostType::Static,
+            host_boot_time: 0,
+            host_provider: host_provider.to_owned(),
+            game_version: String::new(),
+            process_id: 0,
+            ip_address: String::new(),
+            port: 0,
+            session_ids: (0..num_sessions).map(|_| SessionId::new()).collect(),
+            capacity,
+            load,
+            is_healthy: true,
+            unhealthy_time: 0,
+            is_draining: false,
+            keep_alive_time: 0,
+        }
+    }
+
+    #[test]
+    fn pack_fills_servers_and_spread_balances_load() {
+        let busy = server("ovh", 4, 3, 0.8);
+        let idle = server("ovh", 4, 0, 0.);
+        let pack = new_server_placement(&ServerPlacementConfig::Pack, &[]);
+        let spread = new_server_placement(&ServerPlacementConfig::Spread, &[]);
+
+        assert_eq!(Ordering::Less, pack.compare(&busy, &idle));
+        assert_eq!(Ordering::Greater, spread.compare(&busy, &idle));
+
+        let preferred = server("static", 4, 0, 0.);
+        let pack = new_server_placement(&ServerPlacementConfig::Pack, &["static".to_owned()]);
+        assert_eq!(Ordering::Less, pack.compare(&preferred, &busy));
+    }
+}
@@ -0,0 +1,7 @@
+mod simulation;
+mod simulation_report;
+mod simulation_scenario;
+
+pub use simulation::*;
+pub use simulation_report::*;
+pub use simulation_scenario::*;
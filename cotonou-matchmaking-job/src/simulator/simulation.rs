@@ -0,0 +1,250 @@
+use crate::{
+    matchmaker::{new_matchmaker, Matchmaker, MatchmakerContext},
+    simulator::{SimulationReport, SimulationScenario, TicketGenerator},
+    Clock, ItemCache, ManualClock, MatchmakingAssembler, QueueMap, SessionCache, TicketCache,
+};
+use cotonou_common::{
+    matchmaking::{
+        GameModeConfig, MatchmakingSessionDAL, MatchmakingSettingsDAL, MatchmakingTicketDAL,
+        MemoryMatchmakingSessionDAL, MemoryMatchmakingTicketDAL, SessionId,
+    },
+    types::ProfileId,
+    unix_now,
+};
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    thread,
+    time::{Duration, Instant},
+};
+
+const REGION: &str = "simulation";
+
+/// Ranked game mode of the default settings, evaluated by the simulator binary and the benchmarks
+pub fn get_ranked_game_mode_config() -> GameModeConfig {
+    MatchmakingSettingsDAL::new()
+        .get_matchmaking_settings()
+        .game_mode_configs
+        .iter()
+        .find(|c| c.name == "Ranked")
+        .expect("Ranked game mode")
+        .clone()
+}
+
+/// Drives a matchmaker with the tickets of a scenario, one simulated second per step
+pub struct Simulation {
+    game_mode_config: GameModeConfig,
+    scenario: SimulationScenario,
+    matchmaker: Box<dyn Matchmaker>,
+    ticket_generator: TicketGenerator,
+    tickets: TicketCache,
+    sessions: SessionCache,
+    created_sessions: QueueMap<SessionId>,
+    matched_players: HashMap<ProfileId, SessionId>,
+    matchmaking_assembler: MatchmakingAssembler,
+    /// moved forward by a second at each step
+    clock: Arc<ManualClock>,
+    /// real time given to matchmakers processing in the background at each step
+    step_delay: Duration,
+    /// simulated time, in seconds
+    time: u64,
+    /// simulated creation times of the queued tickets
+    queued_tickets: HashMap<ProfileId, u64>,
+    /// simulated creation times of the sessions accepting new players
+    open_sessions: HashMap<SessionId, u64>,
+    num_tickets: usize,
+    num_matched_players: usize,
+    waiting_times: Vec<u64>,
+    mmr_spreads: Vec<u64>,
+    processing_time: Duration,
+}
+
+impl Simulation {
+    //-------------------------------------------------------------------------------------------------
+    pub fn new(game_mode_config: GameModeConfig, scenario: SimulationScenario) -> Self {
+        let ticket_dal: Arc<dyn MatchmakingTicketDAL> = Arc::new(MemoryMatchmakingTicketDAL::new());
+        let session_dal: Arc<dyn MatchmakingSessionDAL> =
+            Arc::new(MemoryMatchmakingSessionDAL::new());
+        let clock = Arc::new(ManualClock::new(unix_now()));
+
+        Self {
+            matchmaker: new_matchmaker(REGION, game_mode_config.clone(), clock.clone()),
+            ticket_generator: TicketGenerator::new(&scenario),
+            game_mode_config,
+            scenario,
+            tickets: ItemCache::new(REGION, ticket_dal),
+            sessions: ItemCache::new(REGION, session_dal),
+            created_sessions: QueueMap::new(),
+            matched_players: HashMap::new(),
+            matchmaking_assembler: MatchmakingAssembler::new(clock.clone()),
+            clock,
+            step_delay: Duration::ZERO,
+            time: 0,
+            queued_tickets: HashMap::new(),
+            open_sessions: HashMap::new(),
+            num_tickets: 0,
+            num_matched_players: 0,
+            waiting_times: Vec::new(),
+            mmr_spreads: Vec::new(),
+            processing_time: Duration::ZERO,
+        }
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    /// Wait at each step for matchmakers processing tickets in background threads
+    pub fn with_step_delay(mut self, step_delay: Duration) -> Self {
+        self.step_delay = step_delay;
+        self
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    pub fn run(mut self) -> SimulationReport {
+        while self.time < self.scenario.duration {
+            self.step();
+        }
+
+        // sessions still open count for the match quality
+        let session_ids = self.open_sessions.keys().copied().collect::<Vec<_>>();
+        for session_id in session_ids {
+            self.close_session(&session_id);
+        }
+
+        SimulationReport::new(
+            &self.scenario.name,
+            &format!("{:?}", self.game_mode_config.matchmaker_type),
+            self.num_tickets,
+            self.waiting_times,
+            self.mmr_spreads,
+            self.num_matched_players,
+            self.scenario.duration,
+            self.processing_time,
+        )
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    /// Simulate one second
+    pub fn step(&mut self) {
+        self.create_tickets();
+
+        let start = Instant::now();
+        self.matchmaker.process(&mut MatchmakerContext::new(
+            REGION,
+            &self.game_mode_config,
+            &mut self.tickets,
+            &mut self.sessions,
+            &mut self.created_sessions,
+            &mut self.matched_players,
+            &mut self.matchmaking_assembler,
+            self.clock.as_ref(),
+        ));
+        self.processing_time += start.elapsed();
+
+        if !self.step_delay.is_zero() {
+            thread::sleep(self.step_delay);
+        }
+
+        self.process_matched_tickets();
+        self.process_open_sessions();
+
+        self.time += 1;
+        self.clock.advance(1);
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn create_tickets(&mut self) {
+        let tickets = self.ticket_generator.generate_tickets(
+            &self.game_mode_config.name,
+            self.time,
+            self.clock.now(),
+        );
+
+        for ticket in tickets {
+            self.num_tickets += 1;
+            self.queued_tickets
+                .insert(ticket.owner_profile_id, self.time);
+            self.matchmaker.insert_ticket(&ticket);
+            self.tickets.create(ticket);
+        }
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn process_matched_tickets(&mut self) {
+        for session_id in self.created_sessions.iter() {
+            self.open_sessions.insert(*session_id, self.time);
+        }
+        self.created_sessions.clear();
+
+        for (owner_profile_id, _session_id) in self.matched_players.drain() {
+            let Some(creation_time) = self.queued_tickets.remove(&owner_profile_id) else {
+                continue;
+            };
+
+            if let Some(ticket) = self.tickets.delete(&owner_profile_id) {
+                self.matchmaker.remove_ticket(&ticket);
+                self.num_matched_players += ticket.players.len();
+                self.waiting_times.push(self.time - creation_time);
+            }
+        }
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    /// Close full sessions and sessions open for too long
+    fn process_open_sessions(&mut self) {
+        let session_ids = self
+            .open_sessions
+            .iter()
+            .filter(|(session_id, creation_time)| {
+                self.time >= *creation_time + self.scenario.session_open_duration
+                    || self.sessions.get(session_id).is_some_and(|session| {
+                        session.players.len() >= self.game_mode_config.max_players
+                    })
+            })
+            .map(|(session_id, _)| *session_id)
+            .collect::<Vec<_>>();
+
+        for session_id in session_ids {
+            self.close_session(&session_id);
+        }
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn close_session(&mut self, session_id: &SessionId) {
+        self.open_sessions.remove(session_id);
+
+        let Some(session) = self.sessions.delete(session_id) else {
+            return;
+        };
+
+        self.matchmaker.remove_session(&session);
+
+        let mmrs = session.players.iter().map(|p| p.mmr);
+        if let (Some(min_mmr), Some(max_mmr)) = (mmrs.clone().min(), mmrs.max()) {
+            self.mmr_spreads.push((max_mmr - min_mmr) as u64);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn simulation_matches_steady_arrivals() {
+        let game_mode_config = MatchmakingSettingsDAL::new()
+            .get_matchmaking_settings()
+            .game_mode_configs[0]
+            .clone();
+        let scenario = SimulationScenario {
+            duration: 60,
+            ..SimulationScenario::steady()
+        };
+
+        let report = Simulation::new(game_mode_config, scenario).run();
+
+        assert!(report.num_tickets > 0);
+        assert!(report.num_matched_tickets > report.num_tickets / 2);
+        assert!(report.num_sessions > 0);
+        assert!(report.waiting_time_p50 <= report.waiting_time_p90);
+        assert!(report.waiting_time_p90 <= report.waiting_time_p99);
+    }
+}
@@ -0,0 +1,92 @@
+use std::{fmt, time::Duration};
+
+/// Outcome of a simulation
+#[derive(Debug, Clone)]
+pub struct SimulationReport {
+    pub scenario: String,
+    pub matchmaker: String,
+    pub num_tickets: usize,
+    pub num_matched_tickets: usize,
+    pub num_sessions: usize,
+    /// Waiting times of the matched tickets, in seconds
+    pub waiting_time_p50: u64,
+    pub waiting_time_p90: u64,
+    pub waiting_time_p99: u64,
+    /// Difference between the highest and the lowest MMR of a session
+    pub mean_mmr_spread: f64,
+    pub mmr_spread_p90: u64,
+    /// Matched players per simulated second
+    pub throughput: f64,
+    /// Time spent in `Matchmaker::process`, background threads excluded
+    pub processing_time: Duration,
+}
+
+impl SimulationReport {
+    //-------------------------------------------------------------------------------------------------
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        scenario: &str,
+        matchmaker: &str,
+        num_tickets: usize,
+        mut waiting_times: Vec<u64>,
+        mut mmr_spreads: Vec<u64>,
+        num_matched_players: usize,
+        duration: u64,
+        processing_time: Duration,
+    ) -> Self {
+        waiting_times.sort_unstable();
+        mmr_spreads.sort_unstable();
+
+        Self {
+            scenario: scenario.to_owned(),
+            matchmaker: matchmaker.to_owned(),
+            num_tickets,
+            num_matched_tickets: waiting_times.len(),
+            num_sessions: mmr_spreads.len(),
+            waiting_time_p50: get_percentile(&waiting_times, 50.),
+            waiting_time_p90: get_percentile(&waiting_times, 90.),
+            waiting_time_p99: get_percentile(&waiting_times, 99.),
+            mean_mmr_spread: if mmr_spreads.is_empty() {
+                0.
+            } else {
+                mmr_spreads.iter().sum::<u64>() as f64 / mmr_spreads.len() as f64
+            },
+            mmr_spread_p90: get_percentile(&mmr_spreads, 90.),
+            throughput: num_matched_players as f64 / duration.max(1) as f64,
+            processing_time,
+        }
+    }
+}
+
+impl fmt::Display for SimulationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} / {}", self.scenario, self.matchmaker)?;
+        writeln!(
+            f,
+            "  tickets: {} created, {} matched in {} sessions",
+            self.num_tickets, self.num_matched_tickets, self.num_sessions
+        )?;
+        writeln!(
+            f,
+            "  waiting time: p50 {}s, p90 {}s, p99 {}s",
+            self.waiting_time_p50, self.waiting_time_p90, self.waiting_time_p99
+        )?;
+        writeln!(
+            f,
+            "  MMR spread: mean {:.0}, p90 {}",
+            self.mean_mmr_spread, self.mmr_spread_p90
+        )?;
+        writeln!(f, "  throughput: {:.2} players/s", self.throughput)?;
+        write!(f, "  processing time: {:?}", self.processing_time)
+    }
+}
+
+/// Nearest-rank percentile of sorted values, 0 if empty
+fn get_percentile(sorted_values: &[u64], percentile: f64) -> u64 {
+    if sorted_values.is_empty() {
+        return 0;
+    }
+
+    let rank = (percentile / 100. * sorted_values.len() as f64).ceil() as usize;
+    sorted_values[rank.clamp(1, sorted_values.len()) - 1]
+}
@@ -0,0 +1,217 @@
+use cotonou_common::{
+    matchmaking::{
+        MatchmakingPlayer, MatchmakingPlayerStatus, MatchmakingTicket, TicketAttributes,
+    },
+    types::ProfileId,
+};
+use rand::{distributions::WeightedIndex, rngs::StdRng, Rng, SeedableRng};
+use std::f64::consts::PI;
+
+/// Above this arrival rate, the number of arrivals is drawn from a normal distribution
+const MAX_POISSON_ARRIVAL_RATE: f64 = 30.;
+
+/// Temporary increase of the arrival rate
+#[derive(Debug, Clone)]
+pub struct ArrivalBurst {
+    /// Simulated time at which the burst starts, in seconds
+    pub start_time: u64,
+    /// Duration of the burst, in seconds
+    pub duration: u64,
+    pub rate_multiplier: f64,
+}
+
+/// Synthetic ticket arrival pattern
+#[derive(Debug, Clone)]
+pub struct SimulationScenario {
+    pub name: String,
+    /// Simulated duration, in seconds
+    pub duration: u64,
+    /// Average number of tickets created per second
+    pub arrival_rate: f64,
+    pub burst: Option<ArrivalBurst>,
+    pub mmr_mean: f64,
+    pub mmr_standard_deviation: f64,
+    /// MMR standard deviation of the members of a party around the party MMR
+    pub party_mmr_standard_deviation: f64,
+    /// Relative weights of the party sizes, starting with solo players
+    pub party_size_weights: Vec<f64>,
+    /// Duration during which sessions accept new players, in seconds
+    pub session_open_duration: u64,
+    pub seed: u64,
+}
+
+impl SimulationScenario {
+    //-------------------------------------------------------------------------------------------------
+    /// Solo players arriving at a constant rate
+    pub fn steady() -> Self {
+        Self {
+            name: "steady".to_owned(),
+            duration: 600,
+            arrival_rate: 5.,
+            burst: None,
+            mmr_mean: 1500.,
+            mmr_standard_deviation: 300.,
+            party_mmr_standard_deviation: 100.,
+            party_size_weights: vec![1.],
+            session_open_duration: 30,
+            seed: 0,
+        }
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    /// Solo players with ten times more arrivals for one minute
+    pub fn burst() -> Self {
+        Self {
+            name: "burst".to_owned(),
+            burst: Some(ArrivalBurst {
+                start_time: 120,
+                duration: 60,
+                rate_multiplier: 10.,
+            }),
+            ..Self::steady()
+        }
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    /// Mix of solo players and parties
+    pub fn parties() -> Self {
+        Self {
+            name: "parties".to_owned(),
+            party_size_weights: vec![0.6, 0.25, 0.1, 0.05],
+            ..Self::steady()
+        }
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    pub fn get_arrival_rate(&self, time: u64) -> f64 {
+        match &self.burst {
+            Some(burst) if time >= burst.start_time && time < burst.start_time + burst.duration => {
+                self.arrival_rate * burst.rate_multiplier
+            }
+            _ => self.arrival_rate,
+        }
+    }
+}
+
+/// Random tickets following a scenario, reproducible with its seed
+pub struct TicketGenerator {
+    scenario: SimulationScenario,
+    rng: StdRng,
+    party_sizes: WeightedIndex<f64>,
+    next_profile_id: u32,
+}
+
+impl TicketGenerator {
+    //-------------------------------------------------------------------------------------------------
+    pub fn new(scenario: &SimulationScenario) -> Self {
+        Self {
+            scenario: scenario.clone(),
+            rng: StdRng::seed_from_u64(scenario.seed),
+            party_sizes: WeightedIndex::new(&scenario.party_size_weights)
+                .expect("party size weights must be positive"),
+            next_profile_id: 1,
+        }
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    /// Tickets created during a second of the simulation
+    ///
+    /// # Arguments
+    /// * `time` - simulated time, in seconds
+    /// * `creation_time` - creation time of the tickets (unix timestamp)
+    pub fn generate_tickets(
+        &mut self,
+        game_mode: &str,
+        time: u64,
+        creation_time: u64,
+    ) -> Vec<MatchmakingTicket> {
+        let num_tickets = self.sample_num_arrivals(self.scenario.get_arrival_rate(time));
+        (0..num_tickets)
+            .map(|_| self.generate_ticket(game_mode, creation_time))
+            .collect()
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn generate_ticket(&mut self, game_mode: &str, creation_time: u64) -> MatchmakingTicket {
+        let party_size = self.rng.sample(&self.party_sizes) + 1;
+        let party_mmr =
+            self.sample_normal(self.scenario.mmr_mean, self.scenario.mmr_standard_deviation);
+        let owner_profile_id = self.new_profile_id();
+        let attributes = TicketAttributes {
+            client_version: "1.0".to_owned(),
+            ..Default::default()
+        };
+
+        let players = (0..party_size)
+            .map(|i| MatchmakingPlayer {
+                profile_id: if i == 0 {
+                    owner_profile_id
+                } else {
+                    self.new_profile_id()
+                },
+                display_name: String::new(),
+                mmr: self
+                    .sample_normal(party_mmr, self.scenario.party_mmr_standard_deviation)
+                    .max(0.) as u32,
+                latency: self.rng.gen_range(20..100),
+                new_status_time: 0,
+                status: MatchmakingPlayerStatus::Created,
+                creation_time,
+                time_until_open_session: 0,
+                time_until_close_session: 0,
+                party_id: Some(owner_profile_id),
+                team: None,
+                platform: "pc".to_owned(),
+                roles: Vec::new(),
+                attributes: attributes.clone(),
+            })
+            .collect();
+
+        MatchmakingTicket {
+            owner_profile_id,
+            game_mode: game_mode.to_owned(),
+            players,
+            creation_time,
+            session_id: None,
+            servers_full_notification_last_time_sent: 0,
+            attributes,
+            estimated_wait_time: 0,
+        }
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    fn new_profile_id(&mut self) -> ProfileId {
+        let profile_id = ProfileId::try_from(self.next_profile_id as i64).unwrap();
+        self.next_profile_id += 1;
+        profile_id
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    /// Poisson distributed number of arrivals during a second
+    fn sample_num_arrivals(&mut self, arrival_rate: f64) -> usize {
+        if arrival_rate > MAX_POISSON_ARRIVAL_RATE {
+            return self
+                .sample_normal(arrival_rate, arrival_rate.sqrt())
+                .round()
+                .max(0.) as usize;
+        }
+
+        // Knuth's algorithm
+        let limit = (-arrival_rate).exp();
+        let mut num_arrivals = 0;
+        let mut product = self.rng.gen::<f64>();
+        while product > limit {
+            num_arrivals += 1;
+            product *= self.rng.gen::<f64>();
+        }
+        num_arrivals
+    }
+
+    //-------------------------------------------------------------------------------------------------
+    /// Box-Muller transform
+    fn sample_normal(&mut self, mean: f64, standard_deviation: f64) -> f64 {
+        let u1 = self.rng.gen_range(f64::EPSILON..1.);
+        let u2 = self.rng.gen::<f64>();
+        mean + standard_deviation * (-2. * u1.ln()).sqrt() * (2. * PI * u2).cos()
+    }
+}
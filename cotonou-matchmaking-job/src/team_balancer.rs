@@ -0,0 +1,355 @@
+use cotonou_common::{
+    matchmaking::{GameModeConfig, MatchmakingPlayer, MatchmakingPlayerStatus},
+    types::ProfileId,
+};
+use std::collections::HashMap;
+
+/// Above this number of parties to place, teams are formed greedily
+const MAX_PARTIES_FOR_EXHAUSTIVE_SEARCH: usize = 10;
+
+struct Party {
+    player_indices: Vec<usize>,
+    mmr_sum: u64,
+    /// team of a party already sent to a server, which cannot be moved anymore
+    fixed_team: Option<usize>,
+}
+
+struct Teams {
+    sizes: Vec<usize>,
+    mmr_sums: Vec<u64>,
+}
+
+impl Teams {
+    fn add(&mut self, team: usize, party: &Party) {
+        self.sizes[team] += party.player_indices.len();
+        self.mmr_sums[team] += party.mmr_sum;
+    }
+
+    fn remove(&mut self, team: usize, party: &Party) {
+        self.sizes[team] -= party.player_indices.len();
+        self.mmr_sums[team] -= party.mmr_sum;
+    }
+
+    /// (difference of team sizes, difference of team average MMRs), lower is better
+    fn score(&self) -> (usize, f64) {
+        let max_size = self.sizes.iter().max().copied().unwrap_or_default();
+        let min_size = self.sizes.iter().min().copied().unwrap_or_default();
+
+        let averages = self
+            .sizes
+            .iter()
+            .zip(&self.mmr_sums)
+            .filter(|(size, _)| **size > 0)
+            .map(|(size, mmr_sum)| *mmr_sum as f64 / *size as f64)
+            .collect::<Vec<_>>();
+        let max_average = averages.iter().copied().fold(f64::MIN, f64::max);
+        let min_average = averages.iter().copied().fold(f64::MAX, f64::min);
+
+        (max_size - min_size, (max_average - min_average).max(0.))
+    }
+}
+
+/// Number of teams of a session of this game mode
+pub fn get_num_teams(game_mode_config: &GameModeConfig) -> usize {
+    if game_mode_config.team_player_count == 0 {
+        1
+    } else {
+        game_mode_config
+            .max_players
+            .div_ceil(game_mode_config.team_player_count)
+            .max(1)
+    }
+}
+
+/// Maximum number of players of a team
+fn get_team_player_count(game_mode_config: &GameModeConfig) -> usize {
+    if game_mode_config.team_player_count == 0 {
+        game_mode_config.max_players
+    } else {
+        game_mode_config.team_player_count
+    }
+}
+
+/// Profile ids of the players of each team
+pub fn get_teams(
+    game_mode_config: &GameModeConfig,
+    players: &[MatchmakingPlayer],
+) -> Vec<Vec<ProfileId>> {
+    let mut teams = vec![Vec::new(); get_num_teams(game_mode_config)];
+    for player in players {
+        if let Some(team) = player.team.and_then(|team| teams.get_mut(team)) {
+            team.push(player.profile_id);
+        }
+    }
+    teams
+}
+
+/// Cheap check that players may be split into teams without breaking their parties:
+/// they fit in the teams and no party is bigger than a team.
+/// Only [`can_form_teams`] tells whether the parties can actually be packed
+pub fn may_form_teams<'b, I: Iterator<Item = &'b MatchmakingPlayer>>(
+    game_mode_config: &GameModeConfig,
+    players: I,
+) -> bool {
+    let team_player_count = get_team_player_count(game_mode_config);
+
+    let mut party_sizes = HashMap::<ProfileId, usize>::new();
+    for player in players {
+        *party_sizes
+            .entry(player.party_id.unwrap_or(player.profile_id))
+            .or_default() += 1;
+    }
+
+    party_sizes.values().sum::<usize>() <= get_num_teams(game_mode_config) * team_player_count
+        && party_sizes.values().all(|size| *size <= team_player_count)
+}
+
+/// Check that players can be split into teams without breaking their parties
+pub fn can_form_teams<'b, I: Iterator<Item = &'b MatchmakingPlayer>>(
+    game_mode_config: &GameModeConfig,
+    players: I,
+) -> bool {
+    let mut players = players.cloned().collect::<Vec<_>>();
+    balance_teams(game_mode_config, &mut players)
+}
+
+/// Assign a team to each player, keeping parties together,
+/// and minimizing the difference of average MMR between teams.
+/// Players already sent to a server keep their team.
+/// # Return
+/// false if parties cannot fit in the teams, players are left untouched
+pub fn balance_teams(game_mode_config: &GameModeConfig, players: &mut [MatchmakingPlayer]) -> bool {
+    let num_teams = get_num_teams(game_mode_config);
+    let team_player_count = get_team_player_count(game_mode_config);
+
+    let parties = get_parties(players);
+
+    let mut teams = Teams {
+        sizes: vec![0; num_teams],
+        mmr_sums: vec![0; num_teams],
+    };
+    for party in &parties {
+        if let Some(team) = party.fixed_team {
+            if team >= num_teams {
+                return false;
+            }
+            teams.add(team, party);
+        }
+    }
+
+    let mut free_parties = parties
+        .iter()
+        .filter(|p| p.fixed_team.is_none())
+        .collect::<Vec<_>>();
+    // biggest parties first, they are the hardest to place
+    free_parties.sort_by_key(|p| std::cmp::Reverse(p.player_indices.len()));
+
+    let assignment = if free_parties.len() <= MAX_PARTIES_FOR_EXHAUSTIVE_SEARCH {
+        let mut best = None;
+        search_best_assignment(
+            &free_parties,
+            team_player_count,
+            &mut teams,
+            &mut Vec::with_capacity(free_parties.len()),
+            &mut best,
+        );
+        best.map(|(assignment, _)| assignment)
+    } else {
+        greedy_assignment(&free_parties, team_player_count, &mut teams)
+    };
+
+    let Some(assignment) = assignment else {
+        return false;
+    };
+
+    for (party, team) in free_parties.iter().zip(assignment) {
+        for player_index in &party.player_indices {
+            players[*player_index].team = Some(team);
+        }
+    }
+
+    true
+}
+
+//-------------------------------------------------------------------------------------------------
+fn get_parties(players: &[MatchmakingPlayer]) -> Vec<Party> {
+    let mut party_indices = HashMap::<ProfileId, usize>::new();
+    let mut parties = Vec::<Party>::new();
+
+    for (player_index, player) in players.iter().enumerate() {
+        let party_id = player.party_id.unwrap_or(player.profile_id);
+        let party_index = *party_indices.entry(party_id).or_insert_with(|| {
+            parties.push(Party {
+                player_indices: Vec::new(),
+                mmr_sum: 0,
+                fixed_team: None,
+            });
+            parties.len() - 1
+        });
+
+        let party = &mut parties[party_index];
+        party.player_indices.push(player_index);
+        party.mmr_sum += player.mmr as u64;
+        if matches!(
+            player.status,
+            MatchmakingPlayerStatus::Activating | MatchmakingPlayerStatus::Active
+        ) {
+            party.fixed_team = party.fixed_team.or(player.team);
+        }
+    }
+
+    parties
+}
+
+//-------------------------------------------------------------------------------------------------
+fn search_best_assignment(
+    parties: &[&Party],
+    team_player_count: usize,
+    teams: &mut Teams,
+    assignment: &mut Vec<usize>,
+    best: &mut Option<(Vec<usize>, (usize, f64))>,
+) {
+    let Some(party) = parties.get(assignment.len()) else {
+        let score = teams.score();
+        if best
+            .as_ref()
+            .is_none_or(|(_, best_score)| score < *best_score)
+        {
+            *best = Some((assignment.clone(), score));
+        }
+        return;
+    };
+
+    let mut tried_empty_team = false;
+    for team in 0..teams.sizes.len() {
+        if teams.sizes[team] + party.player_indices.len() > team_player_count {
+            continue;
+        }
+
+        // empty teams are interchangeable
+        if teams.sizes[team] == 0 {
+            if tried_empty_team {
+                continue;
+            }
+            tried_empty_team = true;
+        }
+
+        teams.add(team, party);
+        assignment.push(team);
+        search_best_assignment(parties, team_player_count, teams, assignment, best);
+        assignment.pop();
+        teams.remove(team, party);
+    }
+}
+
+//-------------------------------------------------------------------------------------------------
+fn greedy_assignment(
+    parties: &[&Party],
+    team_player_count: usize,
+    teams: &mut Teams,
+) -> Option<Vec<usize>> {
+    parties
+        .iter()
+        .map(|party| {
+            // smallest team first, then weakest one
+            let team = (0..teams.sizes.len())
+                .filter(|team| teams.sizes[*team] + party.player_indices.len() <= team_player_count)
+                .min_by_key(|team| (teams.sizes[*team], teams.mmr_sums[*team]))?;
+            teams.add(team, party);
+            Some(team)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cotonou_common::matchmaking::{MatchFunctionsConfig, MatchmakerConfig, RatingSystemConfig};
+
+    fn game_mode_config() -> GameModeConfig {
+        GameModeConfig {
+            name: "test".to_owned(),
+            short_name: "t".to_owned(),
+            min_players: 2,
+            max_players: 4,
+            team_player_count: 2,
+            matchmaker_type: MatchmakerConfig::SimpleList,
+            match_functions_type: MatchFunctionsConfig::FirstComeFirstServed,
+            rating_system: RatingSystemConfig::Elo { k_factor: 32. },
+            filters: Vec::new(),
+            preferences: Vec::new(),
+            ticket_timeout: None,
+            fallback_game_mode: None,
+            latency_limit: None,
+        }
+    }
+
+    fn player(profile_id: i64, party_id: i64, mmr: u32) -> MatchmakingPlayer {
+        MatchmakingPlayer {
+            profile_id: ProfileId::try_from(profile_id).unwrap(),
+            display_name: profile_id.to_string(),
+            mmr,
+            status: MatchmakingPlayerStatus::Matched,
+            party_id: Some(ProfileId::try_from(party_id).unwrap()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn teams_are_balanced_by_mmr() {
+        let mut players = vec![
+            player(1, 1, 2000),
+            player(2, 2, 1900),
+            player(3, 3, 1100),
+            player(4, 4, 1000),
+        ];
+
+        assert!(balance_teams(&game_mode_config(), &mut players));
+
+        assert_eq!(players[0].team, players[3].team);
+        assert_eq!(players[1].team, players[2].team);
+        assert_ne!(players[0].team, players[1].team);
+    }
+
+    #[test]
+    fn parties_stay_together() {
+        let mut players = vec![
+            player(1, 1, 2000),
+            player(2, 1, 1900),
+            player(3, 3, 1100),
+            player(4, 4, 1000),
+        ];
+
+        assert!(balance_teams(&game_mode_config(), &mut players));
+        assert_eq!(players[0].team, players[1].team);
+        assert_eq!(players[2].team, players[3].team);
+
+        let too_big_party = [player(1, 1, 0), player(2, 1, 0), player(3, 1, 0)];
+        assert!(!can_form_teams(&game_mode_config(), too_big_party.iter()));
+    }
+
+    #[test]
+    fn feasibility_check_does_not_pack_parties() {
+        let config = GameModeConfig {
+            max_players: 8,
+            team_player_count: 4,
+            ..game_mode_config()
+        };
+        let players = [
+            player(1, 1, 0),
+            player(2, 1, 0),
+            player(3, 1, 0),
+            player(4, 4, 0),
+            player(5, 4, 0),
+            player(6, 4, 0),
+            player(7, 7, 0),
+            player(8, 7, 0),
+        ];
+
+        assert!(may_form_teams(&config, players.iter()));
+        assert!(!can_form_teams(&config, players.iter()));
+
+        let too_big_party = [player(1, 1, 0), player(2, 1, 0), player(3, 1, 0)];
+        assert!(!may_form_teams(&game_mode_config(), too_big_party.iter()));
+    }
+}
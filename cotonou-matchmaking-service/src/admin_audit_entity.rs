@@ -0,0 +1,32 @@
+use bson::DateTime;
+use cotonou_common::database::MongoDbCollection;
+use serde::{Deserialize, Serialize};
+
+const TABLE_NAME: &str = "AdminAudit";
+
+/// Operation of an admin on the live matchmaking state
+#[derive(Serialize, Deserialize)]
+pub struct AdminAuditEntity {
+    /// unique id of the audit entry (uuid)
+    #[serde(rename = "_id")]
+    pub id: String,
+    /// subject of the admin jwt
+    #[serde(rename = "a")]
+    pub admin: String,
+    /// e.g. CancelTicket
+    #[serde(rename = "o")]
+    pub operation: String,
+    #[serde(rename = "r")]
+    pub region_system_name: String,
+    /// id of the ticket, session or game server, empty for region wide operations
+    #[serde(rename = "t")]
+    pub target: String,
+    #[serde(rename = "cd")]
+    pub creation_date: DateTime,
+}
+
+impl MongoDbCollection for AdminAuditEntity {
+    fn get_collection_name() -> &'static str {
+        TABLE_NAME
+    }
+}
@@ -0,0 +1,42 @@
+use crate::{AdminAuditEntity, Error};
+use bson::DateTime;
+use cotonou_common::{authentication::User, database::GenericDAL, types::UniqueId};
+use std::result;
+
+#[derive(Clone)]
+pub struct AdminAuditManager {
+    generic_dal: GenericDAL,
+}
+
+type Result<T> = result::Result<T, Error>;
+
+impl AdminAuditManager {
+    pub fn new(generic_dal: GenericDAL) -> AdminAuditManager {
+        AdminAuditManager { generic_dal }
+    }
+
+    pub async fn audit(
+        &self,
+        admin: &User,
+        operation: &str,
+        region_system_name: &str,
+        target: &str,
+    ) -> Result<()> {
+        log::info!(
+            "[{region_system_name}] Admin {} {operation} {target}",
+            admin.subject
+        );
+
+        self.generic_dal
+            .save_entity(&mut AdminAuditEntity {
+                id: UniqueId::new().to_string(),
+                admin: admin.subject.clone(),
+                operation: operation.to_owned(),
+                region_system_name: region_system_name.to_owned(),
+                target: target.to_owned(),
+                creation_date: DateTime::now(),
+            })
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,471 @@
+#[cfg(debug_assertions)]
+use crate::AppState;
+use crate::{validate_region, AdminAuditManager, Error};
+use axum::{
+    extract::{Path, State},
+    Extension, Json,
+};
+use cotonou_common::{
+    authentication::{JwtRole, User},
+    matchmaking::{
+        GameServer, GameServerDAL, Maintenance, MaintenanceDAL, MatchmakingCommand,
+        MatchmakingCommandDAL, MatchmakingSession, MatchmakingSessionDAL, MatchmakingSettingsDAL,
+        MatchmakingTicket, MatchmakingTicketDAL, SessionId,
+    },
+    types::{GameServerId, ProfileId},
+};
+use serde::Deserialize;
+use std::sync::Arc;
+
+#[derive(Deserialize)]
+pub struct SetMaintenanceRequest {
+    /// Start of the maintenance (unix timestamp)
+    pub start_time: u64,
+    /// End of the maintenance (unix timestamp)
+    pub end_time: u64,
+    /// Message displayed to players
+    pub message: String,
+}
+
+/// List the matchmaking tickets of a region (admin only)
+pub async fn list_tickets(
+    State(matchmaking_settings_dal): State<Arc<MatchmakingSettingsDAL>>,
+    State(matchmaking_ticket_dal): State<Arc<dyn MatchmakingTicketDAL>>,
+    State(admin_audit_manager): State<Arc<AdminAuditManager>>,
+    Extension(user): Extension<User>,
+    Path(region_system_name): Path<String>,
+) -> Result<Json<Vec<MatchmakingTicket>>, Error> {
+    validate_admin(&user)?;
+    validate_region(&matchmaking_settings_dal, &region_system_name)?;
+
+    let tickets = matchmaking_ticket_dal
+        .get_tickets(&region_system_name)
+        .await?;
+
+    admin_audit_manager
+        .audit(&user, "ListTickets", &region_system_name, "")
+        .await?;
+
+    Ok(Json(tickets))
+}
+
+/// Cancel a matchmaking ticket, its players are notified (admin only)
+pub async fn cancel_ticket(
+    State(matchmaking_settings_dal): State<Arc<MatchmakingSettingsDAL>>,
+    State(matchmaking_command_dal): State<Arc<dyn MatchmakingCommandDAL>>,
+    State(admin_audit_manager): State<Arc<AdminAuditManager>>,
+    Extension(user): Extension<User>,
+    Path((region_system_name, owner_profile_id)): Path<(String, ProfileId)>,
+) -> Result<(), Error> {
+    queue_audited_command(
+        &matchmaking_settings_dal,
+        matchmaking_command_dal.as_ref(),
+        &admin_audit_manager,
+        &user,
+        &region_system_name,
+        "CancelTicket",
+        &owner_profile_id.to_string(),
+        MatchmakingCommand::CancelTicket { owner_profile_id },
+    )
+    .await
+}
+
+/// List the matchmaking sessions of a region (admin only)
+pub async fn list_sessions(
+    State(matchmaking_settings_dal): State<Arc<MatchmakingSettingsDAL>>,
+    State(matchmaking_session_dal): State<Arc<dyn MatchmakingSessionDAL>>,
+    State(admin_audit_manager): State<Arc<AdminAuditManager>>,
+    Extension(user): Extension<User>,
+    Path(region_system_name): Path<String>,
+) -> Result<Json<Vec<MatchmakingSession>>, Error> {
+    validate_admin(&user)?;
+    validate_region(&matchmaking_settings_dal, &region_system_name)?;
+
+    let sessions = matchmaking_session_dal
+        .get_sessions(&region_system_name)
+        .await?;
+
+    admin_audit_manager
+        .audit(&user, "ListSessions", &region_system_name, "")
+        .await?;
+
+    Ok(Json(sessions))
+}
+
+/// Close a matchmaking session, no player can join it anymore (admin only)
+pub async fn close_session(
+    State(matchmaking_settings_dal): State<Arc<MatchmakingSettingsDAL>>,
+    State(matchmaking_command_dal): State<Arc<dyn MatchmakingCommandDAL>>,
+    State(admin_audit_manager): State<Arc<AdminAuditManager>>,
+    Extension(user): Extension<User>,
+    Path((region_system_name, session_id)): Path<(String, SessionId)>,
+) -> Result<(), Error> {
+    queue_audited_command(
+        &matchmaking_settings_dal,
+        matchmaking_command_dal.as_ref(),
+        &admin_audit_manager,
+        &user,
+        &region_system_name,
+        "CloseSession",
+        &session_id.to_string(),
+        MatchmakingCommand::UpdateSession {
+            session_id,
+            is_open: false,
+        },
+    )
+    .await
+}
+
+/// Delete a matchmaking session and free its game server slot (admin only)
+pub async fn force_delete_session(
+    State(matchmaking_settings_dal): State<Arc<MatchmakingSettingsDAL>>,
+    State(matchmaking_command_dal): State<Arc<dyn MatchmakingCommandDAL>>,
+    State(admin_audit_manager): State<Arc<AdminAuditManager>>,
+    Extension(user): Extension<User>,
+    Path((region_system_name, session_id)): Path<(String, SessionId)>,
+) -> Result<(), Error> {
+    queue_audited_command(
+        &matchmaking_settings_dal,
+        matchmaking_command_dal.as_ref(),
+        &admin_audit_manager,
+        &user,
+        &region_system_name,
+        "DeleteSession",
+        &session_id.to_string(),
+        MatchmakingCommand::DeleteSession { session_id },
+    )
+    .await
+}
+
+/// List the game servers of a region (admin only)
+pub async fn list_game_servers(
+    State(matchmaking_settings_dal): State<Arc<MatchmakingSettingsDAL>>,
+    State(game_server_dal): State<Arc<dyn GameServerDAL>>,
+    State(admin_audit_manager): State<Arc<AdminAuditManager>>,
+    Extension(user): Extension<User>,
+    Path(region_system_name): Path<String>,
+) -> Result<Json<Vec<GameServer>>, Error> {
+    validate_admin(&user)?;
+    validate_region(&matchmaking_settings_dal, &region_system_name)?;
+
+    let game_servers = game_server_dal
+        .get_game_servers(&region_system_name)
+        .await?;
+
+    admin_audit_manager
+        .audit(&user, "ListGameServers", &region_system_name, "")
+        .await?;
+
+    Ok(Json(game_servers))
+}
+
+/// Stop sending new sessions to a game server, its current sessions are not affected (admin only)
+pub async fn drain_game_server(
+    State(matchmaking_settings_dal): State<Arc<MatchmakingSettingsDAL>>,
+    State(matchmaking_command_dal): State<Arc<dyn MatchmakingCommandDAL>>,
+    State(admin_audit_manager): State<Arc<AdminAuditManager>>,
+    Extension(user): Extension<User>,
+    Path((region_system_name, game_server_id)): Path<(String, GameServerId)>,
+) -> Result<(), Error> {
+    queue_audited_command(
+        &matchmaking_settings_dal,
+        matchmaking_command_dal.as_ref(),
+        &admin_audit_manager,
+        &user,
+        &region_system_name,
+        "DrainGameServer",
+        &game_server_id.to_string(),
+        MatchmakingCommand::DrainGameServer { game_server_id },
+    )
+    .await
+}
+
+/// Remove a game server from the matchmaking, deleting its sessions (admin only)
+pub async fn evict_game_server(
+    State(matchmaking_settings_dal): State<Arc<MatchmakingSettingsDAL>>,
+    State(matchmaking_command_dal): State<Arc<dyn MatchmakingCommandDAL>>,
+    State(admin_audit_manager): State<Arc<AdminAuditManager>>,
+    Extension(user): Extension<User>,
+    Path((region_system_name, game_server_id)): Path<(String, GameServerId)>,
+) -> Result<(), Error> {
+    queue_audited_command(
+        &matchmaking_settings_dal,
+        matchmaking_command_dal.as_ref(),
+        &admin_audit_manager,
+        &user,
+        &region_system_name,
+        "EvictGameServer",
+        &game_server_id.to_string(),
+        MatchmakingCommand::ShutdownGameServer { game_server_id },
+    )
+    .await
+}
+
+/// Cancel all tickets and reset the matchmaking state of a region (admin only)
+#[axum::debug_handler(state = AppState)]
+pub async fn reset_matchmaking(
+    State(matchmaking_settings_dal): State<Arc<MatchmakingSettingsDAL>>,
+    State(matchmaking_command_dal): State<Arc<dyn MatchmakingCommandDAL>>,
+    State(admin_audit_manager): State<Arc<AdminAuditManager>>,
+    Extension(user): Extension<User>,
+    Path(region_system_name): Path<String>,
+) -> Result<(), Error> {
+    queue_audited_command(
+        &matchmaking_settings_dal,
+        matchmaking_command_dal.as_ref(),
+        &admin_audit_manager,
+        &user,
+        &region_system_name,
+        "ResetMatchmaking",
+        "",
+        MatchmakingCommand::ResetMatchmaking,
+    )
+    .await
+}
+
+/// Schedule a maintenance of all regions, queued tickets are cancelled once it starts (admin only)
+pub async fn set_global_maintenance(
+    State(matchmaking_settings_dal): State<Arc<MatchmakingSettingsDAL>>,
+    State(maintenance_dal): State<Arc<dyn MaintenanceDAL>>,
+    State(matchmaking_command_dal): State<Arc<dyn MatchmakingCommandDAL>>,
+    State(admin_audit_manager): State<Arc<AdminAuditManager>>,
+    Extension(user): Extension<User>,
+    Json(request): Json<SetMaintenanceRequest>,
+) -> Result<(), Error> {
+    update_maintenance(
+        &matchmaking_settings_dal,
+        maintenance_dal.as_ref(),
+        matchmaking_command_dal.as_ref(),
+        &admin_audit_manager,
+        &user,
+        None,
+        Some(validate_maintenance_request(request)?),
+    )
+    .await
+}
+
+/// Cancel the maintenance of all regions (admin only)
+pub async fn delete_global_maintenance(
+    State(matchmaking_settings_dal): State<Arc<MatchmakingSettingsDAL>>,
+    State(maintenance_dal): State<Arc<dyn MaintenanceDAL>>,
+    State(matchmaking_command_dal): State<Arc<dyn MatchmakingCommandDAL>>,
+    State(admin_audit_manager): State<Arc<AdminAuditManager>>,
+    Extension(user): Extension<User>,
+) -> Result<(), Error> {
+    update_maintenance(
+        &matchmaking_settings_dal,
+        maintenance_dal.as_ref(),
+        matchmaking_command_dal.as_ref(),
+        &admin_audit_manager,
+        &user,
+        None,
+        None,
+    )
+    .await
+}
+
+/// Schedule a maintenance of a region, queued tickets are cancelled once it starts (admin only)
+pub async fn set_region_maintenance(
+    State(matchmaking_settings_dal): State<Arc<MatchmakingSettingsDAL>>,
+    State(maintenance_dal): State<Arc<dyn MaintenanceDAL>>,
+    State(matchmaking_command_dal): State<Arc<dyn MatchmakingCommandDAL>>,
+    State(admin_audit_manager): State<Arc<AdminAuditManager>>,
+    Extension(user): Extension<User>,
+    Path(region_system_name): Path<String>,
+    Json(request): Json<SetMaintenanceRequest>,
+) -> Result<(), Error> {
+    update_maintenance(
+        &matchmaking_settings_dal,
+        maintenance_dal.as_ref(),
+        matchmaking_command_dal.as_ref(),
+        &admin_audit_manager,
+        &user,
+        Some(&region_system_name),
+        Some(validate_maintenance_request(request)?),
+    )
+    .await
+}
+
+/// Cancel the maintenance of a region (admin only)
+pub async fn delete_region_maintenance(
+    State(matchmaking_settings_dal): State<Arc<MatchmakingSettingsDAL>>,
+    State(maintenance_dal): State<Arc<dyn MaintenanceDAL>>,
+    State(matchmaking_command_dal): State<Arc<dyn MatchmakingCommandDAL>>,
+    State(admin_audit_manager): State<Arc<AdminAuditManager>>,
+    Extension(user): Extension<User>,
+    Path(region_system_name): Path<String>,
+) -> Result<(), Error> {
+    update_maintenance(
+        &matchmaking_settings_dal,
+        maintenance_dal.as_ref(),
+        matchmaking_command_dal.as_ref(),
+        &admin_audit_manager,
+        &user,
+        Some(&region_system_name),
+        None,
+    )
+    .await
+}
+
+fn validate_admin(user: &User) -> Result<(), Error> {
+    if matches!(user.role, JwtRole::Admin) {
+        Ok(())
+    } else {
+        Err(Error::Unauthorized)
+    }
+}
+
+/// Queue a command for the matchmaking job, then audit the operation
+#[allow(clippy::too_many_arguments)]
+async fn queue_audited_command(
+    matchmaking_settings_dal: &MatchmakingSettingsDAL,
+    matchmaking_command_dal: &dyn MatchmakingCommandDAL,
+    admin_audit_manager: &AdminAuditManager,
+    user: &User,
+    region_system_name: &str,
+    operation: &str,
+    target: &str,
+    command: MatchmakingCommand,
+) -> Result<(), Error> {
+    validate_admin(user)?;
+    validate_region(matchmaking_settings_dal, region_system_name)?;
+
+    matchmaking_command_dal
+        .queue_command(region_system_name, &command)
+        .await?;
+
+    // only operations which actually happened are audited
+    admin_audit_manager
+        .audit(user, operation, region_system_name, target)
+        .await?;
+
+    Ok(())
+}
+
+fn validate_maintenance_request(request: SetMaintenanceRequest) -> Result<Maintenance, Error> {
+    if request.start_time >= request.end_time {
+        return Err(Error::InvalidParameter("body.end_time".to_owned()));
+    }
+
+    Ok(Maintenance {
+        start_time: request.start_time,
+        end_time: request.end_time,
+        message: request.message,
+    })
+}
+
+/// Save the maintenance of a region, or of all regions if `region_system_name` is None,
+/// and notify the matchmaking jobs of the affected regions. A None maintenance cancels it
+async fn update_maintenance(
+    matchmaking_settings_dal: &MatchmakingSettingsDAL,
+    maintenance_dal: &dyn MaintenanceDAL,
+    matchmaking_command_dal: &dyn MatchmakingCommandDAL,
+    admin_audit_manager: &AdminAuditManager,
+    user: &User,
+    region_system_name: Option<&str>,
+    maintenance: Option<Maintenance>,
+) -> Result<(), Error> {
+    validate_admin(user)?;
+
+    let region_system_names = match region_system_name {
+        Some(region_system_name) => {
+            validate_region(matchmaking_settings_dal, region_system_name)?;
+            vec![region_system_name]
+        }
+        None => matchmaking_settings_dal
+            .get_supported_regions()
+            .iter()
+            .map(|r| r.region_system_name.as_str())
+            .collect(),
+    };
+
+    let (operation, target) = match &maintenance {
+        Some(maintenance) => (
+            "SetMaintenance",
+            format!("{}-{}", maintenance.start_time, maintenance.end_time),
+        ),
+        None => ("DeleteMaintenance", String::new()),
+    };
+    match &maintenance {
+        Some(maintenance) => {
+            maintenance_dal
+                .set_maintenance(region_system_name, maintenance)
+                .await?
+        }
+        None => {
+            maintenance_dal
+                .delete_maintenance(region_system_name)
+                .await?
+        }
+    }
+
+    for region in region_system_names {
+        matchmaking_command_dal
+            .queue_command(
+                region,
+                &MatchmakingCommand::SetMaintenance {
+                    is_global: region_system_name.is_none(),
+                    maintenance: maintenance.clone(),
+                },
+            )
+            .await?;
+    }
+
+    admin_audit_manager
+        .audit(user, operation, region_system_name.unwrap_or("*"), &target)
+        .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cotonou_common::{
+        database::{GenericDAL, MemoryDocumentStore},
+        matchmaking::MemoryMatchmakingCommandDAL,
+    };
+
+    const REGION: &str = "eu-central-1";
+
+    fn user(role: JwtRole) -> User {
+        User {
+            subject: "admin".to_owned(),
+            role,
+            country: "FR".to_owned(),
+            currency: "EUR".to_owned(),
+        }
+    }
+
+    #[tokio::test]
+    async fn only_admins_can_reset_matchmaking() {
+        let matchmaking_settings_dal = Arc::new(MatchmakingSettingsDAL::new());
+        let matchmaking_command_dal = Arc::new(MemoryMatchmakingCommandDAL::new());
+        let admin_audit_manager = Arc::new(AdminAuditManager::new(GenericDAL::new(Arc::new(
+            MemoryDocumentStore::new(),
+        ))));
+
+        for role in [JwtRole::Player, JwtRole::Admin] {
+            let is_admin = role == JwtRole::Admin;
+            let result = reset_matchmaking(
+                State(matchmaking_settings_dal.clone()),
+                State(matchmaking_command_dal.clone()),
+                State(admin_audit_manager.clone()),
+                Extension(user(role)),
+                Path(REGION.to_owned()),
+            )
+            .await;
+            assert_eq!(is_admin, result.is_ok());
+        }
+
+        let commands = matchmaking_command_dal
+            .dequeue_commands(REGION)
+            .await
+            .unwrap();
+        assert_eq!(1, commands.len());
+        assert!(matches!(
+            commands[0].command,
+            MatchmakingCommand::ResetMatchmaking
+        ));
+    }
+}
@@ -1,14 +1,21 @@
-use crate::{Error, MatchmakingAssembler, ProfileForMatchmakingManager};
+use crate::{
+    AdminAuditManager, Error, MatchResultManager, MatchmakingAssembler,
+    ProfileForMatchmakingManager,
+};
 use axum::extract::FromRef;
-use common_macros::hash_map;
 use cotonou_common::{
+    clock::{Clock, SystemClock},
     database::GenericDAL,
     matchmaking::{
-        GameServerDAL, MatchmakingCommandDAL, MatchmakingSessionDAL, MatchmakingSettingsDAL,
-        MatchmakingTicketDAL, MatchmakingWaitingTimeDAL,
+        GameServerDAL, MaintenanceDAL, MatchmakingCommandDAL, MatchmakingSessionDAL,
+        MatchmakingSettingsDAL, MatchmakingTicketDAL, MatchmakingWaitingTimeDAL, PartyDAL,
+        RedisGameServerDAL, RedisMaintenanceDAL, RedisMatchmakingCommandDAL,
+        RedisMatchmakingSessionDAL, RedisMatchmakingTicketDAL, RedisMatchmakingWaitingTimeDAL,
+        RedisPartyDAL,
     },
+    metrics::MetricsRegistry,
     mongo_db::MongoDbConfig,
-    notifications::NotificationManager,
+    notifications::{NotificationManager, RedisNotificationManager},
     redis::{RedisConfig, RedisConnectionManager},
 };
 use std::sync::Arc;
@@ -16,52 +23,68 @@ use std::sync::Arc;
 #[derive(Clone, FromRef)]
 pub struct AppState {
     pub profile_for_matchmaking_manager: Arc<ProfileForMatchmakingManager>,
+    pub match_result_manager: Arc<MatchResultManager>,
+    pub admin_audit_manager: Arc<AdminAuditManager>,
     pub matchmaking_assembler: Arc<MatchmakingAssembler>,
-    pub matchmaking_command_dal: Arc<MatchmakingCommandDAL>,
-    pub notification_manager: Arc<NotificationManager>,
+    pub matchmaking_command_dal: Arc<dyn MatchmakingCommandDAL>,
+    pub notification_manager: Arc<dyn NotificationManager>,
     pub matchmaking_settings_dal: Arc<MatchmakingSettingsDAL>,
-    pub game_server_dal: Arc<GameServerDAL>,
-    pub matchmaking_ticket_dal: Arc<MatchmakingTicketDAL>,
-    pub matchmaking_session_dal: Arc<MatchmakingSessionDAL>,
-    pub matchmaking_waiting_time_dal: Arc<MatchmakingWaitingTimeDAL>,
+    pub game_server_dal: Arc<dyn GameServerDAL>,
+    pub matchmaking_ticket_dal: Arc<dyn MatchmakingTicketDAL>,
+    pub matchmaking_session_dal: Arc<dyn MatchmakingSessionDAL>,
+    pub matchmaking_waiting_time_dal: Arc<dyn MatchmakingWaitingTimeDAL>,
+    pub party_dal: Arc<dyn PartyDAL>,
+    pub maintenance_dal: Arc<dyn MaintenanceDAL>,
+    pub redis_connection_manager: Arc<RedisConnectionManager>,
+    pub metrics_registry: Arc<MetricsRegistry>,
+    pub clock: Arc<dyn Clock>,
 }
 
 impl AppState {
     pub async fn new() -> Result<AppState, Error> {
+        let matchmaking_settings_dal = Arc::new(MatchmakingSettingsDAL::from_env()?);
         let mongo_host = "mongo";
-        let redis_host = "redis";
 
         let generic_dal = GenericDAL::initialize(&MongoDbConfig {
             connection_string: format!("mongodb://{mongo_host}:27017/test"),
         })
         .await?;
 
-        let redis_connection_manager = RedisConnectionManager::initialize(RedisConfig {
-            connection_strings: hash_map! {
-                "NOTIFICATIONS".to_owned() => format!("redis://{redis_host}:6379/0"),
-                "NOTIFICATIONS_PUBSUB".to_owned() => format!("redis://{redis_host}:6379/0"),
-                "MATCHMAKING".to_owned() => format!("redis://{redis_host}:6379/1"),
-            },
-        })
-        .await
-        .unwrap();
+        let redis_connection_manager =
+            RedisConnectionManager::initialize(RedisConfig::from_env(&[
+                ("NOTIFICATIONS", "redis://redis:6379"),
+                ("NOTIFICATIONS_PUBSUB", "redis://redis:6379"),
+                ("MATCHMAKING", "redis://redis:6379"),
+            ])?)
+            .await?;
 
         let profile_for_matchmaking_manager =
             Arc::new(ProfileForMatchmakingManager::new(generic_dal.clone()));
+        let match_result_manager = Arc::new(MatchResultManager::new(generic_dal.clone()));
+        let admin_audit_manager = Arc::new(AdminAuditManager::new(generic_dal.clone()));
         let matchmaking_assembler = Arc::new(MatchmakingAssembler);
         let matchmaking_command_dal =
-            Arc::new(MatchmakingCommandDAL::new(&redis_connection_manager));
-        let notification_manager = Arc::new(NotificationManager::new(&redis_connection_manager));
-        let matchmaking_settings_dal = Arc::new(MatchmakingSettingsDAL::new());
-        let game_server_dal = Arc::new(GameServerDAL::new(&redis_connection_manager));
-        let matchmaking_ticket_dal = Arc::new(MatchmakingTicketDAL::new(&redis_connection_manager));
+            Arc::new(RedisMatchmakingCommandDAL::new(&redis_connection_manager));
+        let notification_manager =
+            Arc::new(RedisNotificationManager::new(&redis_connection_manager));
+        let game_server_dal = Arc::new(RedisGameServerDAL::new(&redis_connection_manager));
+        let matchmaking_ticket_dal =
+            Arc::new(RedisMatchmakingTicketDAL::new(&redis_connection_manager));
         let matchmaking_session_dal =
-            Arc::new(MatchmakingSessionDAL::new(&redis_connection_manager));
-        let matchmaking_waiting_time_dal =
-            Arc::new(MatchmakingWaitingTimeDAL::new(&redis_connection_manager));
+            Arc::new(RedisMatchmakingSessionDAL::new(&redis_connection_manager));
+        let matchmaking_waiting_time_dal = Arc::new(RedisMatchmakingWaitingTimeDAL::new(
+            &redis_connection_manager,
+        ));
+        let party_dal = Arc::new(RedisPartyDAL::new(&redis_connection_manager));
+        let maintenance_dal = Arc::new(RedisMaintenanceDAL::new(&redis_connection_manager));
+        let redis_connection_manager = Arc::new(redis_connection_manager);
+        let metrics_registry = Arc::new(MetricsRegistry::new());
+        let clock = Arc::new(SystemClock);
 
         Ok(Self {
             profile_for_matchmaking_manager,
+            match_result_manager,
+            admin_audit_manager,
             matchmaking_assembler,
             matchmaking_command_dal,
             notification_manager,
@@ -70,6 +93,11 @@ impl AppState {
             matchmaking_ticket_dal,
             matchmaking_session_dal,
             matchmaking_waiting_time_dal,
+            party_dal,
+            maintenance_dal,
+            redis_connection_manager,
+            metrics_registry,
+            clock,
         })
     }
 }
@@ -1,5 +1,5 @@
 use axum::response::{IntoResponse, Response};
-use cotonou_common::{database, matchmaking, notifications, profile};
+use cotonou_common::{database, matchmaking, notifications, profile, redis};
 use hyper::StatusCode;
 use thiserror::Error;
 
@@ -13,12 +13,20 @@ pub enum Error {
     Notification(#[from] notifications::Error),
     #[error("Profile Error: {0}")]
     Profile(#[from] profile::Error),
+    #[error("Redis Error: {0}")]
+    Redis(#[from] redis::Error),
     #[error("Unauthorized Error")]
     Unauthorized,
     #[error("MissingParameter Error: {0}")]
     MissingParameter(String),
     #[error("InvalidParameter Error: {0}")]
     InvalidParameter(String),
+    #[error("Conflict Error: {0}")]
+    Conflict(String),
+    #[error("NotFound Error: {0}")]
+    NotFound(String),
+    #[error("Maintenance Error: {0}")]
+    Maintenance(String),
     #[error("Hyper Error: {0}")]
     Hyper(#[from] hyper::Error),
 }
@@ -31,6 +39,7 @@ impl IntoResponse for Error {
             Error::Matchmaking(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
             Error::Notification(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
             Error::Profile(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+            Error::Redis(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
             Error::Unauthorized => StatusCode::UNAUTHORIZED.into_response(),
             Error::MissingParameter(parameter) => (
                 StatusCode::BAD_REQUEST,
@@ -42,6 +51,13 @@ impl IntoResponse for Error {
                 format!("Invalid parameter: {}", parameter),
             )
                 .into_response(),
+            Error::Conflict(reason) => (StatusCode::CONFLICT, reason).into_response(),
+            Error::NotFound(item) => {
+                (StatusCode::NOT_FOUND, format!("Not found: {}", item)).into_response()
+            }
+            Error::Maintenance(message) => {
+                (StatusCode::SERVICE_UNAVAILABLE, message).into_response()
+            }
             Error::Hyper(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
         }
     }
@@ -2,14 +2,14 @@
 use crate::AppState;
 use crate::Error;
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     Extension, Json,
 };
 use cotonou_common::{
     authentication::User,
     matchmaking::{
-        GameServerDAL, GameServerHostType, MatchmakingCommand, MatchmakingCommandDAL,
-        MatchmakingSettingsDAL,
+        default_capacity, GameServerDAL, GameServerHostType, MatchmakingCommand,
+        MatchmakingCommandDAL, MatchmakingSettingsDAL,
     },
     types::GameServerId,
 };
@@ -26,6 +26,16 @@ pub struct InitializeGameServerRequest {
     pub process_id: u32,
     pub ip_address: String,
     pub port: u16,
+    /// Number of sessions the game server can host at the same time
+    #[serde(default = "default_capacity")]
+    pub capacity: usize,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct KeepAliveGameServerQuery {
+    /// Current load of the game server, between 0 and 1
+    #[serde(default)]
+    pub load: f32,
 }
 
 /// Called by a game server to register itself in the matchmaking (server only)
@@ -36,7 +46,7 @@ pub struct InitializeGameServerRequest {
 /// * `request` - Details of the game server registration
 #[axum::debug_handler(state = AppState)]
 pub async fn initialize_game_server(
-    State(matchmaking_command_dal): State<Arc<MatchmakingCommandDAL>>,
+    State(matchmaking_command_dal): State<Arc<dyn MatchmakingCommandDAL>>,
     State(matchmaking_settings_dal): State<Arc<MatchmakingSettingsDAL>>,
     Extension(user): Extension<User>,
     Path((region_system_name, game_server_id)): Path<(String, GameServerId)>,
@@ -59,6 +69,7 @@ pub async fn initialize_game_server(
                 process_id: request.process_id,
                 ip_address: request.ip_address,
                 port: request.port,
+                capacity: request.capacity,
             },
         )
         .await?;
@@ -70,11 +81,13 @@ pub async fn initialize_game_server(
 /// # Arguments
 /// * `region_system_name` - e.g. us-east-1
 /// * `game_server_id` - id as passed to the game server registration
+/// * `load` - current load of the game server
 pub async fn keep_alive_game_server(
     State(matchmaking_settings_dal): State<Arc<MatchmakingSettingsDAL>>,
-    State(matchmaking_command_dal): State<Arc<MatchmakingCommandDAL>>,
-    State(game_server_dal): State<Arc<GameServerDAL>>,
+    State(matchmaking_command_dal): State<Arc<dyn MatchmakingCommandDAL>>,
+    State(game_server_dal): State<Arc<dyn GameServerDAL>>,
     Path((region_system_name, game_server_id)): Path<(String, GameServerId)>,
+    Query(query): Query<KeepAliveGameServerQuery>,
 ) -> Result<(), Error> {
     validate_region(matchmaking_settings_dal, &region_system_name)?;
 
@@ -89,7 +102,10 @@ pub async fn keep_alive_game_server(
     matchmaking_command_dal
         .queue_command(
             &region_system_name,
-            &MatchmakingCommand::KeepAliveGameServer { game_server_id },
+            &MatchmakingCommand::KeepAliveGameServer {
+                game_server_id,
+                load: query.load,
+            },
         )
         .await?;
     Ok(())
@@ -102,8 +118,8 @@ pub async fn keep_alive_game_server(
 /// * `game_server_id` - id as passed to the game server registration
 pub async fn shutdown_game_server(
     State(matchmaking_settings_dal): State<Arc<MatchmakingSettingsDAL>>,
-    State(matchmaking_command_dal): State<Arc<MatchmakingCommandDAL>>,
-    State(game_server_dal): State<Arc<GameServerDAL>>,
+    State(matchmaking_command_dal): State<Arc<dyn MatchmakingCommandDAL>>,
+    State(game_server_dal): State<Arc<dyn GameServerDAL>>,
     Path((region_system_name, game_server_id)): Path<(String, GameServerId)>,
 ) -> Result<(), Error> {
     validate_region(matchmaking_settings_dal, &region_system_name)?;
@@ -125,6 +141,37 @@ pub async fn shutdown_game_server(
     Ok(())
 }
 
+/// Stop receiving new sessions before shutting down (server only).
+/// A `GameServerDrainedNotification` is sent once the current sessions are over
+///
+/// # Arguments
+/// * `region_system_name` - e.g. us-east-1
+/// * `game_server_id` - id as passed to the game server registration
+pub async fn start_game_server_drain(
+    State(matchmaking_settings_dal): State<Arc<MatchmakingSettingsDAL>>,
+    State(matchmaking_command_dal): State<Arc<dyn MatchmakingCommandDAL>>,
+    State(game_server_dal): State<Arc<dyn GameServerDAL>>,
+    Path((region_system_name, game_server_id)): Path<(String, GameServerId)>,
+) -> Result<(), Error> {
+    validate_region(matchmaking_settings_dal, &region_system_name)?;
+
+    let game_server = game_server_dal
+        .get_game_server(&region_system_name, &game_server_id)
+        .await?;
+
+    if game_server.is_none() {
+        return Err(Error::InvalidParameter("game_server_id".to_owned()));
+    };
+
+    matchmaking_command_dal
+        .queue_command(
+            &region_system_name,
+            &MatchmakingCommand::DrainGameServer { game_server_id },
+        )
+        .await?;
+    Ok(())
+}
+
 fn validate_region(
     matchmaking_settings_dal: Arc<MatchmakingSettingsDAL>,
     region_system_name: &str,
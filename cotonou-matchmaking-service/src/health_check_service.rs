@@ -1,3 +1,25 @@
-pub async fn health_check() -> &'static str {
-   "OK"
-}
\ No newline at end of file
+use axum::{extract::State, Json};
+use cotonou_common::redis::{RedisClientHealth, RedisConnectionManager};
+use hyper::StatusCode;
+use serde::Serialize;
+use std::sync::Arc;
+
+#[derive(Serialize)]
+pub struct HealthCheckResponse {
+    pub redis: Vec<RedisClientHealth>,
+}
+
+/// Reports the health of the Redis clients; unhealthy (503) if one of them is not connected
+pub async fn health_check(
+    State(redis_connection_manager): State<Arc<RedisConnectionManager>>,
+) -> (StatusCode, Json<HealthCheckResponse>) {
+    let redis = redis_connection_manager.check_health().await;
+
+    let status_code = if redis.iter().all(|h| h.is_connected) {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    (status_code, Json(HealthCheckResponse { redis }))
+}
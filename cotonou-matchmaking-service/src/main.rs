@@ -1,23 +1,35 @@
 use crate::{
-    app_state::*, error::*, game_server_service::*, health_check_service::*,
-    matchmaking_assembler::*, matchmaking_service::*, matchmaking_started_notification::*,
+    admin_audit_entity::*, admin_audit_manager::*, admin_service::*, app_state::*, error::*,
+    game_server_service::*, health_check_service::*, match_result_entity::*,
+    match_result_manager::*, match_result_service::*, matchmaking_assembler::*,
+    matchmaking_service::*, matchmaking_started_notification::*, party_service::*,
     profile_for_matchmaking_entity::*, profile_for_matchmaking_manager::*,
 };
 use axum::{
     middleware,
-    routing::{get, post, put},
+    routing::{delete, get, post, put},
     Router,
 };
-use cotonou_common::authentication::{jwt_auth_middleware, JwtSecret};
+use cotonou_common::{
+    authentication::{jwt_auth_middleware, JwtSecret},
+    metrics::{get_metrics, metrics_middleware},
+};
 use std::{net::SocketAddr, result::Result};
 
+mod admin_audit_entity;
+mod admin_audit_manager;
+mod admin_service;
 mod app_state;
 mod error;
 mod game_server_service;
 mod health_check_service;
+mod match_result_entity;
+mod match_result_manager;
+mod match_result_service;
 mod matchmaking_assembler;
 mod matchmaking_service;
 mod matchmaking_started_notification;
+mod party_service;
 mod profile_for_matchmaking_entity;
 mod profile_for_matchmaking_manager;
 
@@ -38,15 +50,45 @@ async fn main() -> Result<(), Error> {
     // build our application with a route
     let app = Router::new()
         .route("/healthcheck", get(health_check))
+        .route("/metrics", get(get_metrics))
+        .route(
+            "/matchmaking/regions",
+            post(suggest_matchmaking_region).route_layer(middleware::from_fn_with_state(
+                jwt_secret.clone(),
+                jwt_auth_middleware,
+            )),
+        )
         .route(
             "/matchmaking/:region_system_name/tickets/:owner_profile_id",
             post(create_matchmaking_ticket)
+                .get(get_matchmaking_ticket)
                 .delete(delete_matchmaking_ticket)
                 .route_layer(middleware::from_fn_with_state(
                     jwt_secret.clone(),
                     jwt_auth_middleware,
                 )),
         )
+        .route(
+            "/matchmaking/:region_system_name/players/:profile_id/session",
+            get(get_player_session).route_layer(middleware::from_fn_with_state(
+                jwt_secret.clone(),
+                jwt_auth_middleware,
+            )),
+        )
+        .route(
+            "/matchmaking/:region_system_name/private-sessions/:owner_profile_id",
+            post(create_private_session).route_layer(middleware::from_fn_with_state(
+                jwt_secret.clone(),
+                jwt_auth_middleware,
+            )),
+        )
+        .route(
+            "/matchmaking/:region_system_name/private-sessions/:join_code/:owner_profile_id",
+            post(join_private_session).route_layer(middleware::from_fn_with_state(
+                jwt_secret.clone(),
+                jwt_auth_middleware,
+            )),
+        )
         .route(
             "/matchmaking/:region_system_name/sessions/:session_id",
             put(activate_session)
@@ -66,16 +108,176 @@ async fn main() -> Result<(), Error> {
                     jwt_auth_middleware,
                 )),
         )
+        .route(
+            "/matchmaking/:region_system_name/sessions/:session_id/backfill",
+            post(request_backfill).route_layer(middleware::from_fn_with_state(
+                jwt_secret.clone(),
+                jwt_auth_middleware,
+            )),
+        )
+        .route(
+            "/matchmaking/:region_system_name/sessions/:session_id/result",
+            post(report_match_result).route_layer(middleware::from_fn_with_state(
+                jwt_secret.clone(),
+                jwt_auth_middleware,
+            )),
+        )
+        .route(
+            "/parties",
+            post(create_party).route_layer(middleware::from_fn_with_state(
+                jwt_secret.clone(),
+                jwt_auth_middleware,
+            )),
+        )
+        .route(
+            "/parties/:party_id",
+            get(get_party).route_layer(middleware::from_fn_with_state(
+                jwt_secret.clone(),
+                jwt_auth_middleware,
+            )),
+        )
+        .route(
+            "/parties/:party_id/invitations/:profile_id",
+            post(invite_party_member)
+                .put(accept_party_invitation)
+                .delete(decline_party_invitation)
+                .route_layer(middleware::from_fn_with_state(
+                    jwt_secret.clone(),
+                    jwt_auth_middleware,
+                )),
+        )
+        .route(
+            "/parties/:party_id/leader/:profile_id",
+            put(transfer_party_leadership).route_layer(middleware::from_fn_with_state(
+                jwt_secret.clone(),
+                jwt_auth_middleware,
+            )),
+        )
+        .route(
+            "/parties/:party_id/members/:profile_id",
+            put(set_party_member_ready)
+                .delete(remove_party_member)
+                .route_layer(middleware::from_fn_with_state(
+                    jwt_secret.clone(),
+                    jwt_auth_middleware,
+                )),
+        )
+        .route(
+            "/parties/:party_id/tickets",
+            delete(delete_party_ticket).route_layer(middleware::from_fn_with_state(
+                jwt_secret.clone(),
+                jwt_auth_middleware,
+            )),
+        )
+        .route(
+            "/parties/:party_id/tickets/:region_system_name",
+            post(create_party_ticket).route_layer(middleware::from_fn_with_state(
+                jwt_secret.clone(),
+                jwt_auth_middleware,
+            )),
+        )
+        .route(
+            "/admin/matchmaking/:region_system_name/tickets",
+            get(list_tickets).route_layer(middleware::from_fn_with_state(
+                jwt_secret.clone(),
+                jwt_auth_middleware,
+            )),
+        )
+        .route(
+            "/admin/matchmaking/:region_system_name/tickets/:owner_profile_id",
+            delete(cancel_ticket).route_layer(middleware::from_fn_with_state(
+                jwt_secret.clone(),
+                jwt_auth_middleware,
+            )),
+        )
+        .route(
+            "/admin/matchmaking/:region_system_name/sessions",
+            get(list_sessions).route_layer(middleware::from_fn_with_state(
+                jwt_secret.clone(),
+                jwt_auth_middleware,
+            )),
+        )
+        .route(
+            "/admin/matchmaking/:region_system_name/sessions/:session_id",
+            delete(force_delete_session).route_layer(middleware::from_fn_with_state(
+                jwt_secret.clone(),
+                jwt_auth_middleware,
+            )),
+        )
+        .route(
+            "/admin/matchmaking/:region_system_name/sessions/:session_id/close",
+            put(close_session).route_layer(middleware::from_fn_with_state(
+                jwt_secret.clone(),
+                jwt_auth_middleware,
+            )),
+        )
+        .route(
+            "/admin/matchmaking/:region_system_name/gameservers",
+            get(list_game_servers).route_layer(middleware::from_fn_with_state(
+                jwt_secret.clone(),
+                jwt_auth_middleware,
+            )),
+        )
+        .route(
+            "/admin/matchmaking/:region_system_name/gameservers/:game_server_id",
+            delete(evict_game_server).route_layer(middleware::from_fn_with_state(
+                jwt_secret.clone(),
+                jwt_auth_middleware,
+            )),
+        )
+        .route(
+            "/admin/matchmaking/:region_system_name/gameservers/:game_server_id/drain",
+            put(drain_game_server).route_layer(middleware::from_fn_with_state(
+                jwt_secret.clone(),
+                jwt_auth_middleware,
+            )),
+        )
+        .route(
+            "/admin/matchmaking/maintenance",
+            put(set_global_maintenance)
+                .delete(delete_global_maintenance)
+                .route_layer(middleware::from_fn_with_state(
+                    jwt_secret.clone(),
+                    jwt_auth_middleware,
+                )),
+        )
+        .route(
+            "/admin/matchmaking/:region_system_name/maintenance",
+            put(set_region_maintenance)
+                .delete(delete_region_maintenance)
+                .route_layer(middleware::from_fn_with_state(
+                    jwt_secret.clone(),
+                    jwt_auth_middleware,
+                )),
+        )
+        .route(
+            "/admin/matchmaking/:region_system_name/reset",
+            post(reset_matchmaking).route_layer(middleware::from_fn_with_state(
+                jwt_secret.clone(),
+                jwt_auth_middleware,
+            )),
+        )
         .route(
             "/gameserver/:region_system_name/:game_server_id",
             post(initialize_game_server)
                 .put(keep_alive_game_server)
                 .delete(shutdown_game_server)
                 .route_layer(middleware::from_fn_with_state(
-                    jwt_secret,
+                    jwt_secret.clone(),
                     jwt_auth_middleware,
                 )),
         )
+        .route(
+            "/gameserver/:region_system_name/:game_server_id/drain",
+            put(start_game_server_drain).route_layer(middleware::from_fn_with_state(
+                jwt_secret,
+                jwt_auth_middleware,
+            )),
+        )
+        .layer(middleware::from_fn_with_state(
+            app_state.metrics_registry.clone(),
+            metrics_middleware,
+        ))
         .with_state(app_state);
 
     println!("cotonou-matchmaking-service started!");
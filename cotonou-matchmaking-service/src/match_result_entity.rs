@@ -0,0 +1,25 @@
+use bson::DateTime;
+use cotonou_common::database::MongoDbCollection;
+use serde::{Deserialize, Serialize};
+
+const TABLE_NAME: &str = "MatchResult";
+
+/// Reported result of a matchmaking session, its id guards against duplicate reports
+#[derive(Serialize, Deserialize)]
+pub struct MatchResultEntity {
+    /// matchmaking session id
+    #[serde(rename = "_id")]
+    pub session_id: String,
+    #[serde(rename = "r")]
+    pub region_system_name: String,
+    #[serde(rename = "m")]
+    pub game_mode: String,
+    #[serde(rename = "cd")]
+    pub creation_date: DateTime,
+}
+
+impl MongoDbCollection for MatchResultEntity {
+    fn get_collection_name() -> &'static str {
+        TABLE_NAME
+    }
+}
@@ -0,0 +1,30 @@
+use crate::{Error, MatchResultEntity};
+use cotonou_common::database::{self, GenericDAL};
+use std::result;
+
+#[derive(Clone)]
+pub struct MatchResultManager {
+    generic_dal: GenericDAL,
+}
+
+type Result<T> = result::Result<T, Error>;
+
+impl MatchResultManager {
+    pub fn new(generic_dal: GenericDAL) -> MatchResultManager {
+        MatchResultManager { generic_dal }
+    }
+
+    pub async fn get_match_result(&self, session_id: &str) -> Result<Option<MatchResultEntity>> {
+        Ok(self.generic_dal.get_entity(session_id).await?)
+    }
+
+    /// # Return
+    /// false if a result has already been reported for the same session
+    pub async fn create_match_result(&self, match_result: &mut MatchResultEntity) -> Result<bool> {
+        match self.generic_dal.save_entity(match_result).await {
+            Ok(()) => Ok(true),
+            Err(database::Error::DuplicateKey) => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
@@ -0,0 +1,403 @@
+#[cfg(debug_assertions)]
+use crate::AppState;
+use crate::{
+    validate_region, Error, MatchResultEntity, MatchResultManager, ProfileForMatchmakingEntity,
+    ProfileForMatchmakingManager,
+};
+use axum::{
+    extract::{Path, State},
+    Extension, Json,
+};
+use bson::DateTime;
+use cotonou_common::{
+    authentication::{JwtRole, User},
+    matchmaking::{
+        update_ratings, MatchmakingSessionDAL, MatchmakingSettingsDAL, RatedTeam, Rating,
+        RatingSystemConfig, SessionId,
+    },
+    types::ProfileId,
+};
+use serde::Deserialize;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+/// Maximum number of attempts to update a rating modified concurrently
+const MAX_RATING_UPDATE_ATTEMPTS: usize = 3;
+
+#[derive(Deserialize)]
+pub struct MatchResultPlayer {
+    pub profile_id: ProfileId,
+    /// A player who left before the end of the match is rated as if their team finished last
+    #[serde(default)]
+    pub has_left: bool,
+}
+
+#[derive(Deserialize)]
+pub struct MatchResultTeam {
+    /// 1 for the winner, equal placements for a draw
+    pub placement: u32,
+    pub players: Vec<MatchResultPlayer>,
+}
+
+#[derive(Deserialize)]
+pub struct ReportMatchResultRequest {
+    pub teams: Vec<MatchResultTeam>,
+}
+
+/// Report the result of a matchmaking session and update the ratings of its players (server only).
+/// Should be called before deleting the session. A result can only be reported once per session,
+/// a report which failed halfway can be retried: players already rated for the session are skipped
+#[axum::debug_handler(state = AppState)]
+pub async fn report_match_result(
+    State(matchmaking_settings_dal): State<Arc<MatchmakingSettingsDAL>>,
+    State(matchmaking_session_dal): State<Arc<dyn MatchmakingSessionDAL>>,
+    State(profile_for_matchmaking_manager): State<Arc<ProfileForMatchmakingManager>>,
+    State(match_result_manager): State<Arc<MatchResultManager>>,
+    Extension(user): Extension<User>,
+    Path((region_system_name, session_id)): Path<(String, SessionId)>,
+    Json(request): Json<ReportMatchResultRequest>,
+) -> Result<(), Error> {
+    if !matches!(user.role, JwtRole::Server) {
+        return Err(Error::Unauthorized);
+    }
+
+    validate_region(&matchmaking_settings_dal, &region_system_name)?;
+
+    let Some(session) = matchmaking_session_dal
+        .get_session(&region_system_name, &session_id)
+        .await?
+    else {
+        return Err(Error::InvalidParameter("session_id".to_owned()));
+    };
+
+    let Some(game_mode_config) = matchmaking_settings_dal
+        .get_matchmaking_settings()
+        .game_mode_configs
+        .iter()
+        .find(|gmc| gmc.name == session.game_mode)
+    else {
+        return Err(Error::InvalidParameter("session_id".to_owned()));
+    };
+
+    // each reported player must belong to the session, once
+    let mut profile_ids = HashSet::new();
+    for player in request.teams.iter().flat_map(|t| &t.players) {
+        if !session
+            .players
+            .iter()
+            .any(|p| p.profile_id == player.profile_id)
+            || !profile_ids.insert(player.profile_id)
+        {
+            return Err(Error::InvalidParameter("body.teams.players".to_owned()));
+        }
+    }
+
+    if request.teams.len() < 2 {
+        return Err(Error::InvalidParameter("body.teams".to_owned()));
+    }
+
+    let session_id = session_id.to_string();
+    if match_result_manager
+        .get_match_result(&session_id)
+        .await?
+        .is_some()
+    {
+        return Err(Error::Conflict(format!(
+            "Result already reported for session {session_id}"
+        )));
+    }
+
+    let game_mode = &session.game_mode;
+    let profile_ids = profile_ids.into_iter().collect::<Vec<_>>();
+    let mut ratings = profile_for_matchmaking_manager
+        .get_profiles_for_matchmaking(&profile_ids)
+        .await?
+        .into_iter()
+        .map(|p| (p.id, (get_rating(&p, game_mode), p)))
+        .collect::<HashMap<_, _>>();
+    let mut profile_ids_to_update = ratings
+        .values()
+        .filter(|(_, p)| !is_rated(p, &session_id))
+        .map(|(_, p)| p.id)
+        .collect::<Vec<_>>();
+
+    for _ in 0..MAX_RATING_UPDATE_ATTEMPTS {
+        let new_ratings = compute_new_ratings(game_mode_config.rating_system, &request, &ratings);
+
+        let mut failed_profile_ids = Vec::new();
+        for profile_id in &profile_ids_to_update {
+            let (_, profile) = &ratings[profile_id];
+            if !profile_for_matchmaking_manager
+                .update_rating(profile, game_mode, &new_ratings[profile_id], &session_id)
+                .await?
+            {
+                failed_profile_ids.push(*profile_id);
+            }
+        }
+
+        // another match of these players has been rated meanwhile,
+        // their new ratings are computed from their current ratings against the same opponents
+        profile_ids_to_update.clear();
+        for profile in profile_for_matchmaking_manager
+            .get_profiles_for_matchmaking(&failed_profile_ids)
+            .await?
+        {
+            // rated by a concurrent report of the same result
+            if !is_rated(&profile, &session_id) {
+                profile_ids_to_update.push(profile.id);
+            }
+            ratings.insert(profile.id, (get_rating(&profile, game_mode), profile));
+        }
+
+        if profile_ids_to_update.is_empty() {
+            // the result is saved once all the ratings are updated, so that a failed report can be retried
+            if !match_result_manager
+                .create_match_result(&mut MatchResultEntity {
+                    session_id: session_id.clone(),
+                    region_system_name,
+                    game_mode: session.game_mode.clone(),
+                    creation_date: DateTime::now(),
+                })
+                .await?
+            {
+                return Err(Error::Conflict(format!(
+                    "Result already reported for session {session_id}"
+                )));
+            }
+            return Ok(());
+        }
+    }
+
+    log::error!(
+        "[{region_system_name}] Cannot update ratings of {profile_ids_to_update:?} for session {session_id}"
+    );
+    Err(Error::Conflict("Ratings updated concurrently".to_owned()))
+}
+
+/// Has the result of the session already been applied to the rating of the profile
+fn is_rated(profile: &ProfileForMatchmakingEntity, session_id: &str) -> bool {
+    profile.rated_session_ids.iter().any(|id| id == session_id)
+}
+
+/// Rating of a game mode, initialized from the MMR for profiles rated before full ratings existed
+fn get_rating(profile: &ProfileForMatchmakingEntity, game_mode: &str) -> Rating {
+    match (profile.ratings.get(game_mode), profile.mmrs.get(game_mode)) {
+        (Some(rating), _) => *rating,
+        (None, Some(mmr)) => get_mmr_rating(*mmr),
+        (None, None) => Rating::default(),
+    }
+}
+
+/// Rating of a player rated before full ratings existed, starting from their MMR
+fn get_mmr_rating(mmr: u32) -> Rating {
+    Rating {
+        rating: mmr as f64,
+        ..Default::default()
+    }
+}
+
+fn compute_new_ratings(
+    rating_system: RatingSystemConfig,
+    request: &ReportMatchResultRequest,
+    ratings: &HashMap<ProfileId, (Rating, ProfileForMatchmakingEntity)>,
+) -> HashMap<ProfileId, Rating> {
+    let teams = request
+        .teams
+        .iter()
+        .map(|t| RatedTeam {
+            placement: t.placement,
+            ratings: t
+                .players
+                .iter()
+                .map(|p| {
+                    ratings
+                        .get(&p.profile_id)
+                        .map(|(r, _)| *r)
+                        .unwrap_or_default()
+                })
+                .collect(),
+        })
+        .collect::<Vec<_>>();
+    let last_placement = teams.iter().map(|t| t.placement).max().unwrap_or_default() + 1;
+
+    let mut new_ratings = HashMap::new();
+    for (team_index, (team, team_ratings)) in request
+        .teams
+        .iter()
+        .zip(update_ratings(rating_system, &teams))
+        .enumerate()
+    {
+        // players who left are rated as if their team finished last
+        let leaver_ratings = team.players.iter().any(|p| p.has_left).then(|| {
+            let mut teams = teams.clone();
+            teams[team_index].placement = last_placement;
+            update_ratings(rating_system, &teams).swap_remove(team_index)
+        });
+
+        for (player_index, player) in team.players.iter().enumerate() {
+            let rating = match &leaver_ratings {
+                Some(leaver_ratings) if player.has_left => leaver_ratings[player_index],
+                _ => team_ratings[player_index],
+            };
+            new_ratings.insert(player.profile_id, rating);
+        }
+    }
+
+    new_ratings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cotonou_common::{
+        database::{GenericDAL, MemoryDocumentStore},
+        matchmaking::{
+            MatchmakingPlayer, MatchmakingPlayerStatus, MatchmakingSession,
+            MatchmakingSessionStatus, MemoryMatchmakingSessionDAL, DEFAULT_RATING,
+        },
+    };
+
+    const REGION: &str = "eu-central-1";
+
+    fn profile_id(id: i64) -> ProfileId {
+        ProfileId::try_from(id).unwrap()
+    }
+
+    fn player(id: i64, has_left: bool) -> MatchResultPlayer {
+        MatchResultPlayer {
+            profile_id: profile_id(id),
+            has_left,
+        }
+    }
+
+    #[test]
+    fn leaver_of_winning_team_is_rated_as_loser() {
+        let request = ReportMatchResultRequest {
+            teams: vec![
+                MatchResultTeam {
+                    placement: 1,
+                    players: vec![player(1, false), player(2, true)],
+                },
+                MatchResultTeam {
+                    placement: 2,
+                    players: vec![player(3, false), player(4, false)],
+                },
+            ],
+        };
+
+        let new_ratings = compute_new_ratings(
+            RatingSystemConfig::Elo { k_factor: 32. },
+            &request,
+            &HashMap::new(),
+        );
+
+        assert_eq!(1516., new_ratings[&profile_id(1)].rating);
+        assert_eq!(1484., new_ratings[&profile_id(2)].rating);
+        assert_eq!(1484., new_ratings[&profile_id(3)].rating);
+    }
+
+    fn session_player(id: i64) -> MatchmakingPlayer {
+        MatchmakingPlayer {
+            profile_id: profile_id(id),
+            display_name: id.to_string(),
+            status: MatchmakingPlayerStatus::Active,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn report_failed_halfway_is_retried_without_rating_players_twice() {
+        let generic_dal = GenericDAL::new(Arc::new(MemoryDocumentStore::new()));
+        let profile_for_matchmaking_manager =
+            Arc::new(ProfileForMatchmakingManager::new(generic_dal.clone()));
+        let match_result_manager = Arc::new(MatchResultManager::new(generic_dal.clone()));
+        let matchmaking_session_dal = Arc::new(MemoryMatchmakingSessionDAL::new());
+        let session = MatchmakingSession {
+            session_id: SessionId::new(),
+            game_mode: "QuickMatch".to_owned(),
+            players: vec![session_player(1), session_player(2)],
+            creation_time: 0,
+            status: MatchmakingSessionStatus::Active,
+            is_open: false,
+            game_server_id: None,
+            ip_address: String::new(),
+            port: 0,
+            encryption_key: String::new(),
+            private_session: None,
+            backfill_request: None,
+            activation_time: 0,
+            activation_attempts: 1,
+        };
+        matchmaking_session_dal
+            .create_sessions(REGION, 1, &[&session])
+            .await
+            .unwrap();
+
+        // the first report only rated the winner
+        let mut winner_ratings = HashMap::new();
+        winner_ratings.insert("QuickMatch".to_owned(), get_mmr_rating(1516));
+        for (id, ratings, num_matches_played, rated_session_ids) in [
+            (1, winner_ratings, 1, vec![session.session_id.to_string()]),
+            (2, HashMap::new(), 0, Vec::new()),
+        ] {
+            generic_dal
+                .save_entity(&mut ProfileForMatchmakingEntity {
+                    id: profile_id(id),
+                    display_name: id.to_string(),
+                    mmrs: HashMap::new(),
+                    ratings,
+                    num_matches_played,
+                    rated_session_ids,
+                })
+                .await
+                .unwrap();
+        }
+
+        let report = || async {
+            report_match_result(
+                State(Arc::new(MatchmakingSettingsDAL::new())),
+                State(matchmaking_session_dal.clone() as Arc<dyn MatchmakingSessionDAL>),
+                State(profile_for_matchmaking_manager.clone()),
+                State(match_result_manager.clone()),
+                Extension(User {
+                    subject: "server".to_owned(),
+                    role: JwtRole::Server,
+                    country: String::new(),
+                    currency: String::new(),
+                }),
+                Path((REGION.to_owned(), session.session_id)),
+                Json(ReportMatchResultRequest {
+                    teams: vec![
+                        MatchResultTeam {
+                            placement: 1,
+                            players: vec![player(1, false)],
+                        },
+                        MatchResultTeam {
+                            placement: 2,
+                            players: vec![player(2, false)],
+                        },
+                    ],
+                }),
+            )
+            .await
+        };
+
+        report().await.unwrap();
+
+        let profiles = profile_for_matchmaking_manager
+            .get_profiles_for_matchmaking(&[profile_id(1), profile_id(2)])
+            .await
+            .unwrap();
+        for profile in &profiles {
+            assert_eq!(1, profile.num_matches_played);
+        }
+        let winner = profiles.iter().find(|p| p.id == profile_id(1)).unwrap();
+        assert_eq!(1516., winner.ratings["QuickMatch"].rating);
+        let loser = profiles.iter().find(|p| p.id == profile_id(2)).unwrap();
+        assert!(loser.ratings["QuickMatch"].rating < DEFAULT_RATING);
+
+        assert!(matches!(report().await, Err(Error::Conflict(_))));
+    }
+}
@@ -1,6 +1,9 @@
 use crate::{CreateMatchmakingTicketRequest, ProfileForMatchmakingEntity};
 use cotonou_common::{
-    matchmaking::{MatchmakingPlayer, MatchmakingPlayerStatus, MatchmakingTicket},
+    matchmaking::{
+        MatchmakingPlayer, MatchmakingPlayerStatus, MatchmakingTicket, TicketAttributes,
+        DEFAULT_MMR,
+    },
     types::ProfileId,
     unix_now,
 };
@@ -12,9 +15,17 @@ impl MatchmakingAssembler {
     pub fn convert_to_matchmaking_ticket(
         &self,
         owner_online_id: ProfileId,
+        region_system_name: &str,
         request: &CreateMatchmakingTicketRequest,
         profiles_for_matchmaking: &[ProfileForMatchmakingEntity],
     ) -> MatchmakingTicket {
+        let attributes = TicketAttributes {
+            client_version: request.client_version.clone(),
+            cross_play: request.cross_play,
+            voice_languages: request.voice_languages.clone(),
+            map_preferences: request.map_preferences.clone(),
+        };
+
         MatchmakingTicket {
             owner_profile_id: owner_online_id,
             game_mode: request.game_mode.clone(),
@@ -32,24 +43,31 @@ impl MatchmakingAssembler {
                         mmr: match profile_for_matchmaking {
                             Some(profile_for_matchmaking) => {
                                 match profile_for_matchmaking.mmrs.get(&request.game_mode) {
-                                    Some(elo) => *elo,
-                                    None => 0u32,
+                                    Some(mmr) => *mmr,
+                                    None => DEFAULT_MMR,
                                 }
                             }
-                            None => 0u32,
+                            None => DEFAULT_MMR,
                         },
-                        latency: 0u32,
+                        latency: p.get_latency(region_system_name),
                         new_status_time: 0u64,
                         status: MatchmakingPlayerStatus::Created,
                         creation_time: 0u64,
                         time_until_open_session: 0u64,
                         time_until_close_session: 0u64,
+                        party_id: Some(owner_online_id),
+                        team: None,
+                        platform: p.platform.clone(),
+                        roles: p.roles.clone(),
+                        attributes: attributes.clone(),
                     }
                 })
                 .collect(),
             creation_time: unix_now(),
             session_id: None,
             servers_full_notification_last_time_sent: 0u64,
+            attributes,
+            estimated_wait_time: 0,
         }
     }
 }
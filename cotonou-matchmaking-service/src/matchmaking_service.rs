@@ -1,8 +1,7 @@
 #[cfg(debug_assertions)]
 use crate::AppState;
 use crate::{
-    Error, MatchmakingAssembler,
-    MatchmakingStartedNotification,
+    validate_party_members, Error, MatchmakingAssembler, MatchmakingStartedNotification,
     ProfileForMatchmakingManager,
 };
 use axum::{
@@ -11,15 +10,19 @@ use axum::{
 };
 use cotonou_common::{
     authentication::{JwtRole, User},
+    clock::Clock,
     matchmaking::{
+        BackfillRequest, ConnectTokenClaims, GameRegion, JoinCode, MaintenanceDAL,
         MatchmakingCommand, MatchmakingCommandDAL, MatchmakingPlayerStatus, MatchmakingSessionDAL,
-        MatchmakingSettingsDAL, MatchmakingTicketDAL, MatchmakingWaitingTimeDAL, SessionId,
+        MatchmakingSessionStatus, MatchmakingSettingsDAL, MatchmakingTicket, MatchmakingTicketDAL,
+        MatchmakingWaitingTimeDAL, PartyDAL, SessionId,
     },
     notifications::NotificationManager,
-    types::ProfileId,
+    types::{GameServerId, ProfileId},
+    unix_now,
 };
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
 #[derive(Serialize)]
 pub struct CreateMatchmakingTicketResponse {
@@ -29,7 +32,26 @@ pub struct CreateMatchmakingTicketResponse {
 #[derive(Deserialize)]
 pub struct MatchmakingTicketPlayer {
     pub profile_id: ProfileId,
+    /// Latency (ms) to the region of the ticket, when `latencies` does not contain it
+    #[serde(default)]
     pub latency: u32,
+    /// Latencies (ms) measured by the client, indexed by region system name
+    #[serde(default)]
+    pub latencies: HashMap<String, u32>,
+    #[serde(default)]
+    pub platform: String,
+    /// Roles the player accepts to play, any role if empty
+    #[serde(default)]
+    pub roles: Vec<String>,
+}
+
+impl MatchmakingTicketPlayer {
+    pub fn get_latency(&self, region_system_name: &str) -> u32 {
+        self.latencies
+            .get(region_system_name)
+            .copied()
+            .unwrap_or(self.latency)
+    }
 }
 
 #[derive(Deserialize)]
@@ -37,6 +59,15 @@ pub struct CreateMatchmakingTicketRequest {
     pub game_mode: String,
     pub players: Vec<MatchmakingTicketPlayer>,
     pub client_version: String,
+    /// Accept players of other platforms
+    #[serde(default)]
+    pub cross_play: bool,
+    /// Voice chat languages (ISO 639-1), any language if empty
+    #[serde(default)]
+    pub voice_languages: Vec<String>,
+    /// Preferred maps, any map if empty
+    #[serde(default)]
+    pub map_preferences: Vec<String>,
 }
 
 /// Create a matchmaking ticket (client only)
@@ -46,13 +77,191 @@ pub async fn create_matchmaking_ticket(
     State(matchmaking_settings_dal): State<Arc<MatchmakingSettingsDAL>>,
     State(profile_for_matchmaking_manager): State<Arc<ProfileForMatchmakingManager>>,
     State(matchmaking_assembler): State<Arc<MatchmakingAssembler>>,
-    State(matchmaking_command_dal): State<Arc<MatchmakingCommandDAL>>,
-    State(notification_manager): State<Arc<NotificationManager>>,
-    State(matchmaking_waiting_time_dal): State<Arc<MatchmakingWaitingTimeDAL>>,
+    State(matchmaking_command_dal): State<Arc<dyn MatchmakingCommandDAL>>,
+    State(party_dal): State<Arc<dyn PartyDAL>>,
+    State(maintenance_dal): State<Arc<dyn MaintenanceDAL>>,
+    State(notification_manager): State<Arc<dyn NotificationManager>>,
+    State(matchmaking_waiting_time_dal): State<Arc<dyn MatchmakingWaitingTimeDAL>>,
     Extension(user): Extension<User>,
     Path((region_system_name, owner_profile_id)): Path<(String, ProfileId)>,
     Json(request): Json<CreateMatchmakingTicketRequest>,
 ) -> Result<Json<CreateMatchmakingTicketResponse>, Error> {
+    let mut ticket = validate_ticket_request(
+        &matchmaking_settings_dal,
+        &profile_for_matchmaking_manager,
+        &matchmaking_assembler,
+        party_dal.as_ref(),
+        maintenance_dal.as_ref(),
+        &user,
+        &region_system_name,
+        owner_profile_id,
+        &request,
+    )
+    .await?;
+
+    let estimated_wait_time = estimate_ticket_waiting_time(
+        matchmaking_waiting_time_dal.as_ref(),
+        &region_system_name,
+        &mut ticket,
+    )
+    .await?;
+
+    matchmaking_command_dal
+        .queue_command(
+            &region_system_name,
+            &MatchmakingCommand::CreateTicket { ticket },
+        )
+        .await?;
+
+    notify_party(
+        notification_manager.as_ref(),
+        &region_system_name,
+        owner_profile_id,
+        &request,
+    )
+    .await?;
+
+    Ok(Json(CreateMatchmakingTicketResponse {
+        estimated_wait_time,
+    }))
+}
+
+#[derive(Serialize)]
+pub struct CreatePrivateSessionResponse {
+    pub session_id: SessionId,
+    /// Code to share with the players invited to the session
+    pub join_code: JoinCode,
+    /// The join code cannot be used after this time (unix timestamp)
+    pub expiration_time: u64,
+}
+
+/// Create a private session on a reserved server, joined by other players with its join code (client only).
+/// Failures are sent with a `MatchmakingFailedNotification`
+#[allow(clippy::too_many_arguments)]
+#[axum::debug_handler(state = AppState)]
+pub async fn create_private_session(
+    State(matchmaking_settings_dal): State<Arc<MatchmakingSettingsDAL>>,
+    State(profile_for_matchmaking_manager): State<Arc<ProfileForMatchmakingManager>>,
+    State(matchmaking_assembler): State<Arc<MatchmakingAssembler>>,
+    State(matchmaking_command_dal): State<Arc<dyn MatchmakingCommandDAL>>,
+    State(party_dal): State<Arc<dyn PartyDAL>>,
+    State(maintenance_dal): State<Arc<dyn MaintenanceDAL>>,
+    State(notification_manager): State<Arc<dyn NotificationManager>>,
+    Extension(user): Extension<User>,
+    Path((region_system_name, owner_profile_id)): Path<(String, ProfileId)>,
+    Json(request): Json<CreateMatchmakingTicketRequest>,
+) -> Result<Json<CreatePrivateSessionResponse>, Error> {
+    let ticket = validate_ticket_request(
+        &matchmaking_settings_dal,
+        &profile_for_matchmaking_manager,
+        &matchmaking_assembler,
+        party_dal.as_ref(),
+        maintenance_dal.as_ref(),
+        &user,
+        &region_system_name,
+        owner_profile_id,
+        &request,
+    )
+    .await?;
+
+    let session_id = SessionId::new();
+    let join_code = JoinCode::new();
+    let expiration_time = unix_now()
+        + matchmaking_settings_dal
+            .get_matchmaking_settings()
+            .private_session_invitation_duration;
+
+    matchmaking_command_dal
+        .queue_command(
+            &region_system_name,
+            &MatchmakingCommand::CreatePrivateSession {
+                session_id,
+                join_code: join_code.clone(),
+                expiration_time,
+                ticket,
+            },
+        )
+        .await?;
+
+    notify_party(
+        notification_manager.as_ref(),
+        &region_system_name,
+        owner_profile_id,
+        &request,
+    )
+    .await?;
+
+    Ok(Json(CreatePrivateSessionResponse {
+        session_id,
+        join_code,
+        expiration_time,
+    }))
+}
+
+/// Join a private session with its join code (client only).
+/// Failures are sent with a `MatchmakingFailedNotification`
+#[allow(clippy::too_many_arguments)]
+#[axum::debug_handler(state = AppState)]
+pub async fn join_private_session(
+    State(matchmaking_settings_dal): State<Arc<MatchmakingSettingsDAL>>,
+    State(profile_for_matchmaking_manager): State<Arc<ProfileForMatchmakingManager>>,
+    State(matchmaking_assembler): State<Arc<MatchmakingAssembler>>,
+    State(matchmaking_command_dal): State<Arc<dyn MatchmakingCommandDAL>>,
+    State(party_dal): State<Arc<dyn PartyDAL>>,
+    State(maintenance_dal): State<Arc<dyn MaintenanceDAL>>,
+    State(notification_manager): State<Arc<dyn NotificationManager>>,
+    Extension(user): Extension<User>,
+    Path((region_system_name, join_code, owner_profile_id)): Path<(String, String, ProfileId)>,
+    Json(request): Json<CreateMatchmakingTicketRequest>,
+) -> Result<(), Error> {
+    let Some(join_code) = JoinCode::try_parse(&join_code) else {
+        return Err(Error::InvalidParameter("join_code".to_owned()));
+    };
+
+    let ticket = validate_ticket_request(
+        &matchmaking_settings_dal,
+        &profile_for_matchmaking_manager,
+        &matchmaking_assembler,
+        party_dal.as_ref(),
+        maintenance_dal.as_ref(),
+        &user,
+        &region_system_name,
+        owner_profile_id,
+        &request,
+    )
+    .await?;
+
+    matchmaking_command_dal
+        .queue_command(
+            &region_system_name,
+            &MatchmakingCommand::JoinPrivateSession { join_code, ticket },
+        )
+        .await?;
+
+    notify_party(
+        notification_manager.as_ref(),
+        &region_system_name,
+        owner_profile_id,
+        &request,
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Check a ticket request and convert it to a ticket
+#[allow(clippy::too_many_arguments)]
+pub async fn validate_ticket_request(
+    matchmaking_settings_dal: &MatchmakingSettingsDAL,
+    profile_for_matchmaking_manager: &ProfileForMatchmakingManager,
+    matchmaking_assembler: &MatchmakingAssembler,
+    party_dal: &dyn PartyDAL,
+    maintenance_dal: &dyn MaintenanceDAL,
+    user: &User,
+    region_system_name: &str,
+    owner_profile_id: ProfileId,
+    request: &CreateMatchmakingTicketRequest,
+) -> Result<MatchmakingTicket, Error> {
     if !matches!(user.role, JwtRole::Player) {
         return Err(Error::Unauthorized);
     }
@@ -61,9 +270,9 @@ pub async fn create_matchmaking_ticket(
         return Err(Error::Unauthorized);
     }
 
-    validate_region(&matchmaking_settings_dal, &region_system_name)?;
+    validate_region(matchmaking_settings_dal, region_system_name)?;
 
-    // TODO check if we are in maintenance mode (server are closed)
+    validate_maintenance(maintenance_dal, region_system_name).await?;
 
     if !request
         .players
@@ -73,11 +282,15 @@ pub async fn create_matchmaking_ticket(
         return Err(Error::InvalidParameter("body.players".to_owned()));
     }
 
+    // players cannot be added to a ticket without joining the party of its owner
+    validate_party_members(party_dal, owner_profile_id, request).await?;
+
     let Some(game_mode_config) = matchmaking_settings_dal
         .get_matchmaking_settings()
         .game_mode_configs
         .iter()
-        .find(|gmc| gmc.name == request.game_mode) else {
+        .find(|gmc| gmc.name == request.game_mode)
+    else {
         return Err(Error::InvalidParameter("body.game_mode".to_owned()));
     };
 
@@ -86,7 +299,9 @@ pub async fn create_matchmaking_ticket(
         return Err(Error::InvalidParameter("body.players.len".to_owned()));
     }
 
-    // TODO check client version
+    if !matchmaking_settings_dal.is_client_version_supported(&request.client_version) {
+        return Err(Error::InvalidParameter("body.client_version".to_owned()));
+    }
 
     let players_profile_ids = request
         .players
@@ -108,29 +323,34 @@ pub async fn create_matchmaking_ticket(
         return Err(Error::InvalidParameter("body.players".to_owned()));
     }
 
-    let ticket = matchmaking_assembler.convert_to_matchmaking_ticket(
+    Ok(matchmaking_assembler.convert_to_matchmaking_ticket(
         owner_profile_id,
-        &request,
+        region_system_name,
+        request,
         &profiles_for_matchmaking,
-    );
-
-    let game_mode = ticket.game_mode.clone();
-    let mut estimated_wait_time: u64 = 0;
-
-    // estimated wait time when no friend party
-    if ticket.players.len() == 1 {
-        estimated_wait_time = matchmaking_waiting_time_dal
-            .get_average_waiting_time(&region_system_name, &game_mode)
-            .await?;
-    }
+    ))
+}
 
-    matchmaking_command_dal
-        .queue_command(
-            &region_system_name,
-            &MatchmakingCommand::CreateTicket { ticket },
-        )
-        .await?;
+/// Estimate the waiting time of a new ticket, kept in the ticket to measure the estimate accuracy
+pub async fn estimate_ticket_waiting_time(
+    matchmaking_waiting_time_dal: &dyn MatchmakingWaitingTimeDAL,
+    region_system_name: &str,
+    ticket: &mut MatchmakingTicket,
+) -> Result<u64, Error> {
+    ticket.estimated_wait_time = matchmaking_waiting_time_dal
+        .get_waiting_time_statistics(region_system_name, &ticket.game_mode)
+        .await?
+        .estimate_waiting_time(ticket, None);
+    Ok(ticket.estimated_wait_time)
+}
 
+/// Notify the other players of a party that the owner started matchmaking
+pub async fn notify_party(
+    notification_manager: &dyn NotificationManager,
+    region_system_name: &str,
+    owner_profile_id: ProfileId,
+    request: &CreateMatchmakingTicketRequest,
+) -> Result<(), Error> {
     for player in request
         .players
         .iter()
@@ -141,27 +361,82 @@ pub async fn create_matchmaking_ticket(
                 &player.profile_id.to_string(),
                 &MatchmakingStartedNotification {
                     owner_profile_id,
-                    region_system_name: region_system_name.clone(),
-                    game_mode: game_mode.clone(),
+                    region_system_name: region_system_name.to_owned(),
+                    game_mode: request.game_mode.clone(),
                 },
             )
             .await?;
     }
 
-    Ok(Json(CreateMatchmakingTicketResponse {
-        estimated_wait_time,
+    Ok(())
+}
+
+#[derive(Deserialize)]
+pub struct SuggestMatchmakingRegionRequest {
+    pub players: Vec<MatchmakingTicketPlayer>,
+}
+
+#[derive(Serialize)]
+pub struct SuggestMatchmakingRegionResponse {
+    pub region_system_name: String,
+    /// Worst latency (ms) of the players to this region
+    pub latency: u32,
+}
+
+/// Suggest the region with the lowest worst latency for a party (client only).
+/// Only regions measured by every player are considered
+pub async fn suggest_matchmaking_region(
+    State(matchmaking_settings_dal): State<Arc<MatchmakingSettingsDAL>>,
+    Extension(user): Extension<User>,
+    Json(request): Json<SuggestMatchmakingRegionRequest>,
+) -> Result<Json<SuggestMatchmakingRegionResponse>, Error> {
+    if !matches!(user.role, JwtRole::Player) {
+        return Err(Error::Unauthorized);
+    }
+
+    let Some((region_system_name, latency)) = find_best_region(
+        matchmaking_settings_dal.get_supported_regions(),
+        &request.players,
+    ) else {
+        return Err(Error::InvalidParameter("body.players.latencies".to_owned()));
+    };
+
+    Ok(Json(SuggestMatchmakingRegionResponse {
+        region_system_name: region_system_name.to_owned(),
+        latency,
     }))
 }
 
+fn find_best_region<'a>(
+    regions: &'a [GameRegion],
+    players: &[MatchmakingTicketPlayer],
+) -> Option<(&'a str, u32)> {
+    regions
+        .iter()
+        .filter_map(|region| {
+            let worst_latency = players
+                .iter()
+                .map(|p| p.latencies.get(&region.region_system_name).copied())
+                .collect::<Option<Vec<_>>>()?
+                .into_iter()
+                .max()?;
+            Some((region.region_system_name.as_str(), worst_latency))
+        })
+        .min_by_key(|(_, latency)| *latency)
+}
+
 /// Delete a matchmaking ticket (client only)
 pub async fn delete_matchmaking_ticket(
     State(matchmaking_settings_dal): State<Arc<MatchmakingSettingsDAL>>,
-    State(matchmaking_ticket_dal): State<Arc<MatchmakingTicketDAL>>,
-    State(matchmaking_command_dal): State<Arc<MatchmakingCommandDAL>>,
+    State(matchmaking_ticket_dal): State<Arc<dyn MatchmakingTicketDAL>>,
+    State(matchmaking_command_dal): State<Arc<dyn MatchmakingCommandDAL>>,
     Extension(user): Extension<User>,
     Path((region_system_name, owner_profile_id)): Path<(String, ProfileId)>,
 ) -> Result<(), Error> {
-    let Some(ticket) = matchmaking_ticket_dal.get_ticket(&region_system_name, owner_profile_id).await? else {
+    let Some(ticket) = matchmaking_ticket_dal
+        .get_ticket(&region_system_name, owner_profile_id)
+        .await?
+    else {
         return Err(Error::InvalidParameter("owner_profile_id".to_owned()));
     };
 
@@ -189,18 +464,161 @@ pub async fn delete_matchmaking_ticket(
     Ok(())
 }
 
+#[derive(Debug, Serialize, PartialEq)]
+pub enum MatchmakingTicketStatus {
+    /// Waiting in the matchmaking queue
+    Queued,
+    /// Matched to a session
+    Matched,
+    /// Session activation has been sent to the game server
+    Activating,
+    /// Game server has confirmed the player
+    Active,
+}
+
+#[derive(Serialize)]
+pub struct GetMatchmakingTicketResponse {
+    pub game_mode: String,
+    pub status: MatchmakingTicketStatus,
+    /// 1 for the next ticket to match, None when not queued
+    pub position_in_queue: Option<usize>,
+    pub estimated_wait_time: u64,
+    pub session_id: Option<SessionId>,
+}
+
+/// Get the status of a matchmaking ticket (client only)
+pub async fn get_matchmaking_ticket(
+    State(matchmaking_settings_dal): State<Arc<MatchmakingSettingsDAL>>,
+    State(matchmaking_ticket_dal): State<Arc<dyn MatchmakingTicketDAL>>,
+    State(matchmaking_waiting_time_dal): State<Arc<dyn MatchmakingWaitingTimeDAL>>,
+    Extension(user): Extension<User>,
+    Path((region_system_name, owner_profile_id)): Path<(String, ProfileId)>,
+) -> Result<Json<GetMatchmakingTicketResponse>, Error> {
+    validate_region(&matchmaking_settings_dal, &region_system_name)?;
+
+    let Some(ticket) = matchmaking_ticket_dal
+        .get_ticket(&region_system_name, owner_profile_id)
+        .await?
+    else {
+        return Err(Error::NotFound("ticket".to_owned()));
+    };
+
+    let current_profile_id = user.get_profile_id();
+    let Some(player) = ticket
+        .players
+        .iter()
+        .find(|p| p.profile_id == current_profile_id)
+    else {
+        return Err(Error::Unauthorized);
+    };
+
+    let status = match player.status {
+        MatchmakingPlayerStatus::Created => MatchmakingTicketStatus::Queued,
+        MatchmakingPlayerStatus::Matched => MatchmakingTicketStatus::Matched,
+        MatchmakingPlayerStatus::Activating => MatchmakingTicketStatus::Activating,
+        MatchmakingPlayerStatus::Active => MatchmakingTicketStatus::Active,
+    };
+
+    let mut position_in_queue = None;
+    let mut estimated_wait_time = 0;
+
+    if status == MatchmakingTicketStatus::Queued {
+        // older tickets of the same game mode are matched first
+        position_in_queue = Some(
+            matchmaking_ticket_dal
+                .get_queue_position(&region_system_name, &ticket)
+                .await?,
+        );
+
+        let waiting_time = matchmaking_waiting_time_dal
+            .get_waiting_time_statistics(&region_system_name, &ticket.game_mode)
+            .await?
+            .estimate_waiting_time(&ticket, position_in_queue);
+        estimated_wait_time =
+            waiting_time.saturating_sub(unix_now().saturating_sub(ticket.creation_time));
+    }
+
+    Ok(Json(GetMatchmakingTicketResponse {
+        game_mode: ticket.game_mode,
+        status,
+        position_in_queue,
+        estimated_wait_time,
+        session_id: ticket.session_id,
+    }))
+}
+
+#[derive(Serialize)]
+pub struct GetPlayerSessionResponse {
+    pub session_id: SessionId,
+    pub game_mode: String,
+    pub ip_address: String,
+    pub port: u16,
+    pub encryption_key: String,
+    pub connect_token: String,
+}
+
+/// Get the active session of a player, to reconnect after a client restart (client only)
+pub async fn get_player_session(
+    State(matchmaking_settings_dal): State<Arc<MatchmakingSettingsDAL>>,
+    State(matchmaking_session_dal): State<Arc<dyn MatchmakingSessionDAL>>,
+    State(clock): State<Arc<dyn Clock>>,
+    Extension(user): Extension<User>,
+    Path((region_system_name, profile_id)): Path<(String, ProfileId)>,
+) -> Result<Json<GetPlayerSessionResponse>, Error> {
+    validate_region(&matchmaking_settings_dal, &region_system_name)?;
+
+    if user.get_profile_id() != profile_id {
+        return Err(Error::Unauthorized);
+    }
+
+    let Some(session) = matchmaking_session_dal
+        .get_player_session(&region_system_name, profile_id)
+        .await?
+        .filter(|s| s.status == MatchmakingSessionStatus::Active)
+    else {
+        return Err(Error::NotFound("session".to_owned()));
+    };
+
+    let matchmaking_settings = matchmaking_settings_dal.get_matchmaking_settings();
+    let connect_token = ConnectTokenClaims::new(
+        profile_id,
+        session.session_id,
+        clock.now() + matchmaking_settings.connect_token_duration,
+    )
+    .encode(&matchmaking_settings.connect_token_secret)?;
+
+    Ok(Json(GetPlayerSessionResponse {
+        session_id: session.session_id,
+        game_mode: session.game_mode,
+        ip_address: session.ip_address,
+        port: session.port,
+        encryption_key: session.encryption_key,
+        connect_token,
+    }))
+}
+
+#[derive(Deserialize)]
+pub struct ActivateSessionQuery {
+    /// Server activating the session
+    pub game_server_id: GameServerId,
+}
+
 /// Active a matchmaking session (server only)
 pub async fn activate_session(
     State(matchmaking_settings_dal): State<Arc<MatchmakingSettingsDAL>>,
-    State(matchmaking_command_dal): State<Arc<MatchmakingCommandDAL>>,
+    State(matchmaking_command_dal): State<Arc<dyn MatchmakingCommandDAL>>,
     Path((region_system_name, session_id)): Path<(String, SessionId)>,
+    Query(query): Query<ActivateSessionQuery>,
 ) -> Result<(), Error> {
     validate_region(&matchmaking_settings_dal, &region_system_name)?;
 
     matchmaking_command_dal
         .queue_command(
             &region_system_name,
-            &MatchmakingCommand::ActivateSession { session_id },
+            &MatchmakingCommand::ActivateSession {
+                session_id,
+                game_server_id: query.game_server_id,
+            },
         )
         .await?;
 
@@ -215,7 +633,7 @@ pub struct UpdateSessionQuery {
 /// Update a matchmaking session (server only)
 pub async fn update_session(
     State(matchmaking_settings_dal): State<Arc<MatchmakingSettingsDAL>>,
-    State(matchmaking_command_dal): State<Arc<MatchmakingCommandDAL>>,
+    State(matchmaking_command_dal): State<Arc<dyn MatchmakingCommandDAL>>,
     Path((region_system_name, session_id)): Path<(String, SessionId)>,
     Query(query): Query<UpdateSessionQuery>,
 ) -> Result<(), Error> {
@@ -234,10 +652,58 @@ pub async fn update_session(
     Ok(())
 }
 
+#[derive(Deserialize)]
+pub struct RequestBackfillRequest {
+    pub num_players: usize,
+    /// Team of the new players, teams are balanced if not set
+    pub team: Option<usize>,
+    /// Role the new players must accept to play
+    pub role: Option<String>,
+    pub min_mmr: Option<u32>,
+    pub max_mmr: Option<u32>,
+    /// Duration (seconds) after which the request is given up
+    pub timeout: u64,
+}
+
+/// Ask for new players to replace players who left a session (server only).
+/// The server is notified with the incoming players, or when the request times out
+pub async fn request_backfill(
+    State(matchmaking_settings_dal): State<Arc<MatchmakingSettingsDAL>>,
+    State(matchmaking_command_dal): State<Arc<dyn MatchmakingCommandDAL>>,
+    Path((region_system_name, session_id)): Path<(String, SessionId)>,
+    Json(request): Json<RequestBackfillRequest>,
+) -> Result<(), Error> {
+    validate_region(&matchmaking_settings_dal, &region_system_name)?;
+
+    if request.num_players == 0 {
+        return Err(Error::InvalidParameter("body.num_players".to_owned()));
+    }
+
+    matchmaking_command_dal
+        .queue_command(
+            &region_system_name,
+            &MatchmakingCommand::RequestBackfill {
+                session_id,
+                backfill_request: BackfillRequest {
+                    num_players: request.num_players,
+                    team: request.team,
+                    role: request.role,
+                    min_mmr: request.min_mmr,
+                    max_mmr: request.max_mmr,
+                    expiration_time: unix_now() + request.timeout,
+                    incoming_players: Vec::new(),
+                },
+            },
+        )
+        .await?;
+
+    Ok(())
+}
+
 /// Delete a matchmaking session (server only).
 pub async fn delete_session(
     State(matchmaking_settings_dal): State<Arc<MatchmakingSettingsDAL>>,
-    State(matchmaking_command_dal): State<Arc<MatchmakingCommandDAL>>,
+    State(matchmaking_command_dal): State<Arc<dyn MatchmakingCommandDAL>>,
     Path((region_system_name, session_id)): Path<(String, SessionId)>,
 ) -> Result<(), Error> {
     validate_region(&matchmaking_settings_dal, &region_system_name)?;
@@ -253,16 +719,20 @@ pub async fn delete_session(
 }
 
 /// Activate a matchmaking player session (server only).
-/// Should be called when a client connects to a server to validate that the client is allowed by the matchmaking
+/// Should be called once a client connected to a server with a valid connect token.
+/// The game server verifies connect tokens offline, see [`ConnectTokenClaims`]
 pub async fn activate_player_session(
     State(matchmaking_settings_dal): State<Arc<MatchmakingSettingsDAL>>,
-    State(matchmaking_session_dal): State<Arc<MatchmakingSessionDAL>>,
-    State(matchmaking_command_dal): State<Arc<MatchmakingCommandDAL>>,
+    State(matchmaking_session_dal): State<Arc<dyn MatchmakingSessionDAL>>,
+    State(matchmaking_command_dal): State<Arc<dyn MatchmakingCommandDAL>>,
     Path((region_system_name, session_id, profile_id)): Path<(String, SessionId, ProfileId)>,
 ) -> Result<(), Error> {
     validate_region(&matchmaking_settings_dal, &region_system_name)?;
 
-    let Some(session) = matchmaking_session_dal.get_session(&region_system_name, &session_id).await? else {
+    let Some(session) = matchmaking_session_dal
+        .get_session(&region_system_name, &session_id)
+        .await?
+    else {
         return Err(Error::InvalidParameter("session_id".to_owned()));
     };
 
@@ -290,7 +760,7 @@ pub async fn activate_player_session(
 /// Delete an existing matchmaking player session (server only).
 pub async fn delete_player_session(
     State(matchmaking_settings_dal): State<Arc<MatchmakingSettingsDAL>>,
-    State(matchmaking_command_dal): State<Arc<MatchmakingCommandDAL>>,
+    State(matchmaking_command_dal): State<Arc<dyn MatchmakingCommandDAL>>,
     Path((region_system_name, session_id, profile_id)): Path<(String, SessionId, ProfileId)>,
 ) -> Result<(), Error> {
     validate_region(&matchmaking_settings_dal, &region_system_name)?;
@@ -308,7 +778,7 @@ pub async fn delete_player_session(
     Ok(())
 }
 
-fn validate_region(
+pub fn validate_region(
     matchmaking_settings_dal: &MatchmakingSettingsDAL,
     region_system_name: &str,
 ) -> Result<(), Error> {
@@ -320,3 +790,374 @@ fn validate_region(
         Ok(())
     }
 }
+
+/// Reject new tickets during a maintenance of the region or of all regions
+pub async fn validate_maintenance(
+    maintenance_dal: &dyn MaintenanceDAL,
+    region_system_name: &str,
+) -> Result<(), Error> {
+    let now = unix_now();
+    for maintenance in [
+        maintenance_dal
+            .get_maintenance(Some(region_system_name))
+            .await?,
+        maintenance_dal.get_maintenance(None).await?,
+    ]
+    .into_iter()
+    .flatten()
+    {
+        if maintenance.is_active(now) {
+            return Err(Error::Maintenance(maintenance.message));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ProfileForMatchmakingEntity;
+    use cotonou_common::{
+        database::{GenericDAL, MemoryDocumentStore},
+        matchmaking::{
+            Maintenance, MemoryMaintenanceDAL, MemoryMatchmakingCommandDAL,
+            MemoryMatchmakingTicketDAL, MemoryMatchmakingWaitingTimeDAL, MemoryPartyDAL, Party,
+            PartyMember, WaitingTimeBracket, WaitingTimeStatistics, DEFAULT_MMR,
+        },
+        notifications::MemoryNotificationManager,
+    };
+
+    const REGION: &str = "eu-central-1";
+
+    struct TestContext {
+        matchmaking_settings_dal: Arc<MatchmakingSettingsDAL>,
+        profile_for_matchmaking_manager: Arc<ProfileForMatchmakingManager>,
+        matchmaking_command_dal: Arc<MemoryMatchmakingCommandDAL>,
+        party_dal: Arc<MemoryPartyDAL>,
+        maintenance_dal: Arc<MemoryMaintenanceDAL>,
+        notification_manager: Arc<MemoryNotificationManager>,
+        matchmaking_waiting_time_dal: Arc<MemoryMatchmakingWaitingTimeDAL>,
+    }
+
+    async fn new_context(profile_ids: &[ProfileId]) -> TestContext {
+        let generic_dal = GenericDAL::new(Arc::new(MemoryDocumentStore::new()));
+        for profile_id in profile_ids {
+            generic_dal
+                .save_entity(&mut ProfileForMatchmakingEntity {
+                    id: *profile_id,
+                    display_name: profile_id.to_string(),
+                    mmrs: HashMap::new(),
+                    ratings: HashMap::new(),
+                    num_matches_played: 0,
+                    rated_session_ids: Vec::new(),
+                })
+                .await
+                .unwrap();
+        }
+
+        TestContext {
+            matchmaking_settings_dal: Arc::new(MatchmakingSettingsDAL::new()),
+            profile_for_matchmaking_manager: Arc::new(ProfileForMatchmakingManager::new(
+                generic_dal,
+            )),
+            matchmaking_command_dal: Arc::new(MemoryMatchmakingCommandDAL::new()),
+            party_dal: Arc::new(MemoryPartyDAL::new()),
+            maintenance_dal: Arc::new(MemoryMaintenanceDAL::new()),
+            notification_manager: Arc::new(MemoryNotificationManager::new()),
+            matchmaking_waiting_time_dal: Arc::new(MemoryMatchmakingWaitingTimeDAL::new()),
+        }
+    }
+
+    fn player(profile_id: ProfileId) -> User {
+        User {
+            subject: profile_id.to_string(),
+            role: JwtRole::Player,
+            country: "FR".to_owned(),
+            currency: "EUR".to_owned(),
+        }
+    }
+
+    async fn create_ticket(
+        context: &TestContext,
+        user: User,
+        owner_profile_id: ProfileId,
+        profile_ids: &[ProfileId],
+    ) -> Result<Json<CreateMatchmakingTicketResponse>, Error> {
+        create_matchmaking_ticket(
+            State(context.matchmaking_settings_dal.clone()),
+            State(context.profile_for_matchmaking_manager.clone()),
+            State(Arc::new(MatchmakingAssembler)),
+            State(context.matchmaking_command_dal.clone()),
+            State(context.party_dal.clone()),
+            State(context.maintenance_dal.clone()),
+            State(context.notification_manager.clone()),
+            State(context.matchmaking_waiting_time_dal.clone()),
+            Extension(user),
+            Path((REGION.to_owned(), owner_profile_id)),
+            Json(CreateMatchmakingTicketRequest {
+                game_mode: "QuickMatch".to_owned(),
+                players: profile_ids
+                    .iter()
+                    .map(|profile_id| MatchmakingTicketPlayer {
+                        profile_id: *profile_id,
+                        latency: 0,
+                        latencies: HashMap::new(),
+                        platform: "pc".to_owned(),
+                        roles: Vec::new(),
+                    })
+                    .collect(),
+                client_version: "1.0".to_owned(),
+                cross_play: false,
+                voice_languages: Vec::new(),
+                map_preferences: Vec::new(),
+            }),
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn create_ticket_queues_command_and_notifies_party() {
+        let profile_id1: ProfileId = "1".parse().unwrap();
+        let profile_id2: ProfileId = "2".parse().unwrap();
+        let context = new_context(&[profile_id1, profile_id2]).await;
+
+        // players of another party are rejected
+        let result = create_ticket(
+            &context,
+            player(profile_id1),
+            profile_id1,
+            &[profile_id1, profile_id2],
+        )
+        .await;
+        assert!(matches!(result, Err(Error::InvalidParameter(_))));
+
+        let mut party = Party::new(profile_id1, 0);
+        party.members.push(PartyMember {
+            profile_id: profile_id2,
+            is_ready: false,
+        });
+        assert!(context.party_dal.save_party(&mut party).await.unwrap());
+
+        // every member but the leader must be ready
+        let result = create_ticket(
+            &context,
+            player(profile_id1),
+            profile_id1,
+            &[profile_id1, profile_id2],
+        )
+        .await;
+        assert!(matches!(result, Err(Error::Conflict(_))));
+
+        party.members[1].is_ready = true;
+        assert!(context.party_dal.save_party(&mut party).await.unwrap());
+
+        // parties are estimated from the waiting times of parties of the same size
+        let mmr_bracket = WaitingTimeStatistics::get_mmr_bracket(DEFAULT_MMR);
+        context
+            .matchmaking_waiting_time_dal
+            .set_waiting_time_statistics(
+                REGION,
+                "QuickMatch",
+                &WaitingTimeStatistics {
+                    brackets: [(1, 20), (2, 50)]
+                        .into_iter()
+                        .map(|(party_size, waiting_time)| WaitingTimeBracket {
+                            mmr_bracket,
+                            party_size,
+                            waiting_time,
+                        })
+                        .collect(),
+                    average_waiting_time: 30,
+                    num_idle_servers: 1,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+
+        let response = create_ticket(
+            &context,
+            player(profile_id1),
+            profile_id1,
+            &[profile_id1, profile_id2],
+        )
+        .await
+        .unwrap();
+        assert_eq!(50, response.estimated_wait_time);
+
+        let commands = context
+            .matchmaking_command_dal
+            .dequeue_commands(REGION)
+            .await
+            .unwrap();
+        assert_eq!(1, commands.len());
+        let MatchmakingCommand::CreateTicket { ticket } = &commands[0].command else {
+            panic!("unexpected command");
+        };
+        assert_eq!(profile_id1, ticket.owner_profile_id);
+        assert_eq!(2, ticket.players.len());
+        assert_eq!(50, ticket.estimated_wait_time);
+
+        let notifications = context
+            .notification_manager
+            .get_notifications_from_queue(&profile_id2.to_string())
+            .await
+            .unwrap();
+        assert_eq!(1, notifications.len());
+        assert!(notifications[0].contains("MatchmakingStartedNotification"));
+    }
+
+    #[tokio::test]
+    async fn create_ticket_for_another_player_is_unauthorized() {
+        let profile_id1: ProfileId = "1".parse().unwrap();
+        let profile_id2: ProfileId = "2".parse().unwrap();
+        let context = new_context(&[profile_id1, profile_id2]).await;
+
+        let result =
+            create_ticket(&context, player(profile_id2), profile_id1, &[profile_id1]).await;
+        assert!(matches!(result, Err(Error::Unauthorized)));
+    }
+
+    #[tokio::test]
+    async fn create_ticket_with_unknown_profile_is_rejected() {
+        let profile_id1: ProfileId = "1".parse().unwrap();
+        let context = new_context(&[]).await;
+
+        let result =
+            create_ticket(&context, player(profile_id1), profile_id1, &[profile_id1]).await;
+        assert!(matches!(result, Err(Error::InvalidParameter(_))));
+    }
+
+    #[tokio::test]
+    async fn create_ticket_during_maintenance_is_rejected() {
+        let profile_id1: ProfileId = "1".parse().unwrap();
+        let context = new_context(&[profile_id1]).await;
+        let now = unix_now();
+
+        // scheduled maintenance
+        context
+            .maintenance_dal
+            .set_maintenance(
+                None,
+                &Maintenance {
+                    start_time: now + 3600,
+                    end_time: now + 7200,
+                    message: "Server update".to_owned(),
+                },
+            )
+            .await
+            .unwrap();
+        assert!(
+            create_ticket(&context, player(profile_id1), profile_id1, &[profile_id1])
+                .await
+                .is_ok()
+        );
+
+        context
+            .maintenance_dal
+            .set_maintenance(
+                Some(REGION),
+                &Maintenance {
+                    start_time: now,
+                    end_time: now + 3600,
+                    message: "Server update".to_owned(),
+                },
+            )
+            .await
+            .unwrap();
+        let result =
+            create_ticket(&context, player(profile_id1), profile_id1, &[profile_id1]).await;
+        assert!(matches!(result, Err(Error::Maintenance(message)) if message == "Server update"));
+    }
+
+    #[tokio::test]
+    async fn queued_ticket_reports_its_position_in_queue() {
+        let profile_id1: ProfileId = "1".parse().unwrap();
+        let profile_id2: ProfileId = "2".parse().unwrap();
+        let context = new_context(&[profile_id1, profile_id2]).await;
+        let matchmaking_ticket_dal = Arc::new(MemoryMatchmakingTicketDAL::new());
+
+        for profile_id in [profile_id1, profile_id2] {
+            assert!(
+                create_ticket(&context, player(profile_id), profile_id, &[profile_id])
+                    .await
+                    .is_ok()
+            );
+        }
+
+        let mut tickets = context
+            .matchmaking_command_dal
+            .dequeue_commands(REGION)
+            .await
+            .unwrap()
+            .into_iter()
+            .filter_map(|c| match c.command {
+                MatchmakingCommand::CreateTicket { ticket } => Some(ticket),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        tickets[1].creation_time += 1;
+        matchmaking_ticket_dal
+            .create_tickets(REGION, 1, &tickets.iter().collect::<Vec<_>>())
+            .await
+            .unwrap();
+
+        let response = get_matchmaking_ticket(
+            State(context.matchmaking_settings_dal.clone()),
+            State(matchmaking_ticket_dal.clone()),
+            State(context.matchmaking_waiting_time_dal.clone()),
+            Extension(player(profile_id2)),
+            Path((REGION.to_owned(), profile_id2)),
+        )
+        .await
+        .unwrap();
+        assert_eq!(MatchmakingTicketStatus::Queued, response.status);
+        assert_eq!(Some(2), response.position_in_queue);
+
+        let result = get_matchmaking_ticket(
+            State(context.matchmaking_settings_dal.clone()),
+            State(matchmaking_ticket_dal),
+            State(context.matchmaking_waiting_time_dal.clone()),
+            Extension(player(profile_id1)),
+            Path((REGION.to_owned(), profile_id2)),
+        )
+        .await;
+        assert!(matches!(result, Err(Error::Unauthorized)));
+    }
+
+    #[test]
+    fn best_region_minimizes_worst_latency_of_the_party() {
+        let region = |name: &str| GameRegion {
+            region_system_name: name.to_owned(),
+            region_prefix: name.to_owned(),
+            region_endpoint: String::new(),
+        };
+        let regions = [
+            region("eu-central-1"),
+            region("us-east-1"),
+            region("ap-south-1"),
+        ];
+        let player = |latencies: &[(&str, u32)]| MatchmakingTicketPlayer {
+            profile_id: "1".parse().unwrap(),
+            latency: 0,
+            latencies: latencies
+                .iter()
+                .map(|(region, latency)| (region.to_string(), *latency))
+                .collect(),
+            platform: "pc".to_owned(),
+            roles: Vec::new(),
+        };
+
+        let players = [
+            player(&[("eu-central-1", 20), ("us-east-1", 90), ("ap-south-1", 10)]),
+            player(&[("eu-central-1", 150), ("us-east-1", 80)]),
+        ];
+
+        assert_eq!(
+            Some(("us-east-1", 90)),
+            find_best_region(&regions, &players)
+        );
+        assert_eq!(None, find_best_region(&regions, &[player(&[])]));
+    }
+}
@@ -0,0 +1,594 @@
+use crate::{
+    estimate_ticket_waiting_time, notify_party, validate_region, validate_ticket_request,
+    CreateMatchmakingTicketRequest, CreateMatchmakingTicketResponse, Error, MatchmakingAssembler,
+    ProfileForMatchmakingManager,
+};
+use axum::{
+    extract::{Path, Query, State},
+    Extension, Json,
+};
+use cotonou_common::{
+    authentication::{JwtRole, User},
+    matchmaking::{
+        MaintenanceDAL, MatchmakingCommand, MatchmakingCommandDAL, MatchmakingSettingsDAL,
+        MatchmakingWaitingTimeDAL, Party, PartyDAL, PartyId, PartyInvitation,
+        PartyInvitationNotification, PartyMember, PartyRemovedNotification,
+        PartyUpdatedNotification,
+    },
+    notifications::NotificationManager,
+    types::ProfileId,
+    unix_now,
+};
+use serde::Deserialize;
+use std::sync::Arc;
+
+/// Create a party led by the current player (client only)
+pub async fn create_party(
+    State(party_dal): State<Arc<dyn PartyDAL>>,
+    Extension(user): Extension<User>,
+) -> Result<Json<Party>, Error> {
+    let profile_id = get_player_profile_id(&user)?;
+
+    if party_dal.get_party_id(profile_id).await?.is_some() {
+        return Err(Error::Conflict("Player is already in a party".to_owned()));
+    }
+
+    let mut party = Party::new(profile_id, unix_now());
+    if !party_dal.save_party(&mut party).await? {
+        return Err(Error::Conflict("Player is already in a party".to_owned()));
+    }
+
+    Ok(Json(party))
+}
+
+/// Get a party (members only)
+pub async fn get_party(
+    State(party_dal): State<Arc<dyn PartyDAL>>,
+    Extension(user): Extension<User>,
+    Path(party_id): Path<PartyId>,
+) -> Result<Json<Party>, Error> {
+    let profile_id = get_player_profile_id(&user)?;
+    let party = get_member_party(party_dal.as_ref(), &party_id, profile_id).await?;
+    Ok(Json(party))
+}
+
+/// Invite a player into a party (leader only)
+pub async fn invite_party_member(
+    State(matchmaking_settings_dal): State<Arc<MatchmakingSettingsDAL>>,
+    State(party_dal): State<Arc<dyn PartyDAL>>,
+    State(notification_manager): State<Arc<dyn NotificationManager>>,
+    Extension(user): Extension<User>,
+    Path((party_id, profile_id)): Path<(PartyId, ProfileId)>,
+) -> Result<(), Error> {
+    let leader_profile_id = get_player_profile_id(&user)?;
+    let mut party = get_leader_party(party_dal.as_ref(), &party_id, leader_profile_id).await?;
+
+    if party.is_member(profile_id) {
+        return Err(Error::Conflict("Player is already a member".to_owned()));
+    }
+
+    let now = unix_now();
+    party.purge_invitations(now);
+
+    let settings = matchmaking_settings_dal.get_matchmaking_settings();
+    if party.members.len() + party.invitations.len() >= settings.max_party_size {
+        return Err(Error::Conflict("Party is full".to_owned()));
+    }
+
+    let expiration_time = now + settings.party_invitation_duration;
+    party.invitations.retain(|i| i.profile_id != profile_id);
+    party.invitations.push(PartyInvitation {
+        profile_id,
+        expiration_time,
+    });
+    save_party(party_dal.as_ref(), &mut party).await?;
+
+    notification_manager
+        .send_notification(
+            &profile_id.to_string(),
+            &PartyInvitationNotification {
+                party_id,
+                leader_profile_id,
+                expiration_time,
+            },
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Accept an invitation into a party (invited player only)
+pub async fn accept_party_invitation(
+    State(party_dal): State<Arc<dyn PartyDAL>>,
+    State(notification_manager): State<Arc<dyn NotificationManager>>,
+    Extension(user): Extension<User>,
+    Path((party_id, profile_id)): Path<(PartyId, ProfileId)>,
+) -> Result<(), Error> {
+    if get_player_profile_id(&user)? != profile_id {
+        return Err(Error::Unauthorized);
+    }
+
+    let Some(mut party) = party_dal.get_party(&party_id).await? else {
+        return Err(Error::InvalidParameter("party_id".to_owned()));
+    };
+
+    party.purge_invitations(unix_now());
+    if party.get_invitation(profile_id).is_none() {
+        return Err(Error::InvalidParameter("profile_id".to_owned()));
+    }
+
+    if party_dal.get_party_id(profile_id).await?.is_some() {
+        return Err(Error::Conflict("Player is already in a party".to_owned()));
+    }
+
+    party.invitations.retain(|i| i.profile_id != profile_id);
+    party.members.push(PartyMember {
+        profile_id,
+        is_ready: false,
+    });
+    party.reset_ready_check();
+    save_party(party_dal.as_ref(), &mut party).await?;
+
+    notify_party_updated(notification_manager.as_ref(), &party).await
+}
+
+/// Decline an invitation (invited player) or cancel it (leader)
+pub async fn decline_party_invitation(
+    State(party_dal): State<Arc<dyn PartyDAL>>,
+    State(notification_manager): State<Arc<dyn NotificationManager>>,
+    Extension(user): Extension<User>,
+    Path((party_id, profile_id)): Path<(PartyId, ProfileId)>,
+) -> Result<(), Error> {
+    let current_profile_id = get_player_profile_id(&user)?;
+
+    let Some(mut party) = party_dal.get_party(&party_id).await? else {
+        return Err(Error::InvalidParameter("party_id".to_owned()));
+    };
+
+    if current_profile_id != profile_id && current_profile_id != party.leader_profile_id {
+        return Err(Error::Unauthorized);
+    }
+
+    if party.get_invitation(profile_id).is_none() {
+        return Err(Error::InvalidParameter("profile_id".to_owned()));
+    }
+
+    party.invitations.retain(|i| i.profile_id != profile_id);
+    save_party(party_dal.as_ref(), &mut party).await?;
+
+    notify_party_updated(notification_manager.as_ref(), &party).await
+}
+
+/// Give the leadership of a party to another member (leader only)
+pub async fn transfer_party_leadership(
+    State(party_dal): State<Arc<dyn PartyDAL>>,
+    State(notification_manager): State<Arc<dyn NotificationManager>>,
+    Extension(user): Extension<User>,
+    Path((party_id, profile_id)): Path<(PartyId, ProfileId)>,
+) -> Result<(), Error> {
+    let leader_profile_id = get_player_profile_id(&user)?;
+    let mut party = get_leader_party(party_dal.as_ref(), &party_id, leader_profile_id).await?;
+
+    if !party.is_member(profile_id) {
+        return Err(Error::InvalidParameter("profile_id".to_owned()));
+    }
+
+    party.leader_profile_id = profile_id;
+    party.reset_ready_check();
+    save_party(party_dal.as_ref(), &mut party).await?;
+
+    notify_party_updated(notification_manager.as_ref(), &party).await
+}
+
+/// Leave a party (member) or kick a member (leader).
+/// The party is deleted when its last member leaves
+pub async fn remove_party_member(
+    State(party_dal): State<Arc<dyn PartyDAL>>,
+    State(notification_manager): State<Arc<dyn NotificationManager>>,
+    Extension(user): Extension<User>,
+    Path((party_id, profile_id)): Path<(PartyId, ProfileId)>,
+) -> Result<(), Error> {
+    let current_profile_id = get_player_profile_id(&user)?;
+    let mut party = get_member_party(party_dal.as_ref(), &party_id, current_profile_id).await?;
+
+    if current_profile_id != profile_id && current_profile_id != party.leader_profile_id {
+        return Err(Error::Unauthorized);
+    }
+
+    let leader_profile_id = party.leader_profile_id;
+    if !party.remove_member(profile_id) {
+        return Err(Error::InvalidParameter("profile_id".to_owned()));
+    }
+
+    if party.members.is_empty() {
+        if !party_dal.delete_party(&party).await? {
+            return Err(concurrent_modification_error());
+        }
+        party_dal.delete_party_members(&[profile_id]).await?;
+        return Ok(());
+    }
+
+    save_party(party_dal.as_ref(), &mut party).await?;
+    party_dal.delete_party_members(&[profile_id]).await?;
+
+    if current_profile_id != profile_id {
+        notification_manager
+            .send_notification(
+                &profile_id.to_string(),
+                &PartyRemovedNotification {
+                    party_id,
+                    leader_profile_id,
+                },
+            )
+            .await?;
+    }
+
+    notify_party_updated(notification_manager.as_ref(), &party).await
+}
+
+#[derive(Deserialize)]
+pub struct SetPartyMemberReadyQuery {
+    pub is_ready: bool,
+}
+
+/// Answer the ready check of a party (member only)
+pub async fn set_party_member_ready(
+    State(party_dal): State<Arc<dyn PartyDAL>>,
+    State(notification_manager): State<Arc<dyn NotificationManager>>,
+    Extension(user): Extension<User>,
+    Path((party_id, profile_id)): Path<(PartyId, ProfileId)>,
+    Query(query): Query<SetPartyMemberReadyQuery>,
+) -> Result<(), Error> {
+    if get_player_profile_id(&user)? != profile_id {
+        return Err(Error::Unauthorized);
+    }
+
+    let mut party = get_member_party(party_dal.as_ref(), &party_id, profile_id).await?;
+    if let Some(member) = party.get_member_mut(profile_id) {
+        member.is_ready = query.is_ready;
+    }
+    save_party(party_dal.as_ref(), &mut party).await?;
+
+    notify_party_updated(notification_manager.as_ref(), &party).await
+}
+
+/// Create a matchmaking ticket for all the members of a party (leader only).
+/// Every other member must be ready
+#[allow(clippy::too_many_arguments)]
+pub async fn create_party_ticket(
+    State(matchmaking_settings_dal): State<Arc<MatchmakingSettingsDAL>>,
+    State(profile_for_matchmaking_manager): State<Arc<ProfileForMatchmakingManager>>,
+    State(matchmaking_assembler): State<Arc<MatchmakingAssembler>>,
+    State(matchmaking_command_dal): State<Arc<dyn MatchmakingCommandDAL>>,
+    State(party_dal): State<Arc<dyn PartyDAL>>,
+    State(maintenance_dal): State<Arc<dyn MaintenanceDAL>>,
+    State(notification_manager): State<Arc<dyn NotificationManager>>,
+    State(matchmaking_waiting_time_dal): State<Arc<dyn MatchmakingWaitingTimeDAL>>,
+    Extension(user): Extension<User>,
+    Path((party_id, region_system_name)): Path<(PartyId, String)>,
+    Json(request): Json<CreateMatchmakingTicketRequest>,
+) -> Result<Json<CreateMatchmakingTicketResponse>, Error> {
+    let leader_profile_id = get_player_profile_id(&user)?;
+    let mut party = get_leader_party(party_dal.as_ref(), &party_id, leader_profile_id).await?;
+
+    if !party.is_ready() {
+        return Err(Error::Conflict("Party members are not ready".to_owned()));
+    }
+
+    let mut ticket = validate_ticket_request(
+        &matchmaking_settings_dal,
+        &profile_for_matchmaking_manager,
+        &matchmaking_assembler,
+        party_dal.as_ref(),
+        maintenance_dal.as_ref(),
+        &user,
+        &region_system_name,
+        leader_profile_id,
+        &request,
+    )
+    .await?;
+
+    let estimated_wait_time = estimate_ticket_waiting_time(
+        matchmaking_waiting_time_dal.as_ref(),
+        &region_system_name,
+        &mut ticket,
+    )
+    .await?;
+
+    // saved first, so that no ticket is created if the party changed since its ready check
+    party.ticket_region_system_name = Some(region_system_name.clone());
+    save_party(party_dal.as_ref(), &mut party).await?;
+
+    matchmaking_command_dal
+        .queue_command(
+            &region_system_name,
+            &MatchmakingCommand::CreateTicket { ticket },
+        )
+        .await?;
+
+    notify_party(
+        notification_manager.as_ref(),
+        &region_system_name,
+        leader_profile_id,
+        &request,
+    )
+    .await?;
+
+    Ok(Json(CreateMatchmakingTicketResponse {
+        estimated_wait_time,
+    }))
+}
+
+/// Cancel the matchmaking ticket of a party (member only)
+pub async fn delete_party_ticket(
+    State(matchmaking_settings_dal): State<Arc<MatchmakingSettingsDAL>>,
+    State(matchmaking_command_dal): State<Arc<dyn MatchmakingCommandDAL>>,
+    State(party_dal): State<Arc<dyn PartyDAL>>,
+    State(notification_manager): State<Arc<dyn NotificationManager>>,
+    Extension(user): Extension<User>,
+    Path(party_id): Path<PartyId>,
+) -> Result<(), Error> {
+    let profile_id = get_player_profile_id(&user)?;
+    let mut party = get_member_party(party_dal.as_ref(), &party_id, profile_id).await?;
+
+    let Some(region_system_name) = party.ticket_region_system_name.take() else {
+        return Err(Error::InvalidParameter("party_id".to_owned()));
+    };
+
+    validate_region(&matchmaking_settings_dal, &region_system_name)?;
+
+    matchmaking_command_dal
+        .queue_command(
+            &region_system_name,
+            &MatchmakingCommand::DeleteTicket {
+                owner_profile_id: party.leader_profile_id,
+                player_who_canceled_profile_id: profile_id,
+            },
+        )
+        .await?;
+
+    party.reset_ready_check();
+    save_party(party_dal.as_ref(), &mut party).await?;
+
+    notify_party_updated(notification_manager.as_ref(), &party).await
+}
+
+/// Check that the players of a ticket request are the members of the party of its owner,
+/// and that they are all ready. A single player does not need a party
+pub async fn validate_party_members(
+    party_dal: &dyn PartyDAL,
+    owner_profile_id: ProfileId,
+    request: &CreateMatchmakingTicketRequest,
+) -> Result<(), Error> {
+    if request.players.len() <= 1 {
+        return Ok(());
+    }
+
+    let party = match party_dal.get_party_id(owner_profile_id).await? {
+        Some(party_id) => party_dal.get_party(&party_id).await?,
+        None => None,
+    };
+
+    let Some(party) = party else {
+        return Err(Error::InvalidParameter("body.players".to_owned()));
+    };
+
+    if !party.is_ready() {
+        return Err(Error::Conflict("Party members are not ready".to_owned()));
+    }
+
+    if party.leader_profile_id != owner_profile_id
+        || party.members.len() != request.players.len()
+        || !request
+            .players
+            .iter()
+            .all(|p| party.is_member(p.profile_id))
+    {
+        return Err(Error::InvalidParameter("body.players".to_owned()));
+    }
+
+    Ok(())
+}
+
+//-------------------------------------------------------------------------------------------------
+fn get_player_profile_id(user: &User) -> Result<ProfileId, Error> {
+    if !matches!(user.role, JwtRole::Player) {
+        return Err(Error::Unauthorized);
+    }
+    Ok(user.get_profile_id())
+}
+
+//-------------------------------------------------------------------------------------------------
+async fn get_member_party(
+    party_dal: &dyn PartyDAL,
+    party_id: &PartyId,
+    profile_id: ProfileId,
+) -> Result<Party, Error> {
+    let Some(party) = party_dal.get_party(party_id).await? else {
+        return Err(Error::InvalidParameter("party_id".to_owned()));
+    };
+
+    if !party.is_member(profile_id) {
+        return Err(Error::Unauthorized);
+    }
+
+    Ok(party)
+}
+
+//-------------------------------------------------------------------------------------------------
+async fn get_leader_party(
+    party_dal: &dyn PartyDAL,
+    party_id: &PartyId,
+    profile_id: ProfileId,
+) -> Result<Party, Error> {
+    let party = get_member_party(party_dal, party_id, profile_id).await?;
+
+    if party.leader_profile_id != profile_id {
+        return Err(Error::Unauthorized);
+    }
+
+    Ok(party)
+}
+
+//-------------------------------------------------------------------------------------------------
+async fn save_party(party_dal: &dyn PartyDAL, party: &mut Party) -> Result<(), Error> {
+    if !party_dal.save_party(party).await? {
+        return Err(concurrent_modification_error());
+    }
+    Ok(())
+}
+
+//-------------------------------------------------------------------------------------------------
+fn concurrent_modification_error() -> Error {
+    Error::Conflict("Party was modified concurrently, please retry".to_owned())
+}
+
+//-------------------------------------------------------------------------------------------------
+async fn notify_party_updated(
+    notification_manager: &dyn NotificationManager,
+    party: &Party,
+) -> Result<(), Error> {
+    for member in &party.members {
+        notification_manager
+            .send_notification(
+                &member.profile_id.to_string(),
+                &PartyUpdatedNotification {
+                    party: party.clone(),
+                },
+            )
+            .await?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cotonou_common::{matchmaking::MemoryPartyDAL, notifications::MemoryNotificationManager};
+
+    fn player(profile_id: ProfileId) -> Extension<User> {
+        Extension(User {
+            subject: profile_id.to_string(),
+            role: JwtRole::Player,
+            country: "FR".to_owned(),
+            currency: "EUR".to_owned(),
+        })
+    }
+
+    #[tokio::test]
+    async fn invited_player_joins_party_after_accepting() {
+        let matchmaking_settings_dal = Arc::new(MatchmakingSettingsDAL::new());
+        let party_dal = Arc::new(MemoryPartyDAL::new());
+        let notification_manager = Arc::new(MemoryNotificationManager::new());
+        let profile_id1: ProfileId = "1".parse().unwrap();
+        let profile_id2: ProfileId = "2".parse().unwrap();
+
+        let Json(party) = create_party(State(party_dal.clone()), player(profile_id1))
+            .await
+            .unwrap();
+        let party_id = party.party_id;
+
+        // only the invited player can accept
+        let result = accept_party_invitation(
+            State(party_dal.clone()),
+            State(notification_manager.clone()),
+            player(profile_id2),
+            Path((party_id, profile_id2)),
+        )
+        .await;
+        assert!(matches!(result, Err(Error::InvalidParameter(_))));
+
+        invite_party_member(
+            State(matchmaking_settings_dal),
+            State(party_dal.clone()),
+            State(notification_manager.clone()),
+            player(profile_id1),
+            Path((party_id, profile_id2)),
+        )
+        .await
+        .unwrap();
+
+        let notifications = notification_manager
+            .get_notifications_from_queue(&profile_id2.to_string())
+            .await
+            .unwrap();
+        assert!(notifications[0].contains("PartyInvitationNotification"));
+
+        accept_party_invitation(
+            State(party_dal.clone()),
+            State(notification_manager.clone()),
+            player(profile_id2),
+            Path((party_id, profile_id2)),
+        )
+        .await
+        .unwrap();
+
+        let party = party_dal.get_party(&party_id).await.unwrap().unwrap();
+        assert!(party.is_member(profile_id2));
+        assert!(party.invitations.is_empty());
+        assert!(!party.is_ready());
+        assert_eq!(
+            Some(party_id),
+            party_dal.get_party_id(profile_id2).await.unwrap()
+        );
+
+        // the leader kicks the new member
+        remove_party_member(
+            State(party_dal.clone()),
+            State(notification_manager.clone()),
+            player(profile_id1),
+            Path((party_id, profile_id2)),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(None, party_dal.get_party_id(profile_id2).await.unwrap());
+        let notifications = notification_manager
+            .get_notifications_from_queue(&profile_id2.to_string())
+            .await
+            .unwrap();
+        assert!(notifications
+            .iter()
+            .any(|n| n.contains("PartyRemovedNotification")));
+    }
+
+    #[tokio::test]
+    async fn stale_party_is_not_saved() {
+        let party_dal = Arc::new(MemoryPartyDAL::new());
+        let notification_manager = Arc::new(MemoryNotificationManager::new());
+        let profile_id1: ProfileId = "1".parse().unwrap();
+        let profile_id2: ProfileId = "2".parse().unwrap();
+
+        let Json(party) = create_party(State(party_dal.clone()), player(profile_id1))
+            .await
+            .unwrap();
+        let mut stale_party = party_dal.get_party(&party.party_id).await.unwrap().unwrap();
+
+        set_party_member_ready(
+            State(party_dal.clone()),
+            State(notification_manager),
+            player(profile_id1),
+            Path((party.party_id, profile_id1)),
+            Query(SetPartyMemberReadyQuery { is_ready: true }),
+        )
+        .await
+        .unwrap();
+
+        // a player already in a party cannot be added to another one
+        let Json(mut other_party) = create_party(State(party_dal.clone()), player(profile_id2))
+            .await
+            .unwrap();
+        other_party.members.push(PartyMember {
+            profile_id: profile_id1,
+            is_ready: false,
+        });
+        assert!(!party_dal.save_party(&mut other_party).await.unwrap());
+
+        stale_party.members.clear();
+        assert!(!party_dal.save_party(&mut stale_party).await.unwrap());
+        assert!(!party_dal.delete_party(&stale_party).await.unwrap());
+        let party = party_dal.get_party(&party.party_id).await.unwrap().unwrap();
+        assert!(party.members[0].is_ready);
+    }
+}
@@ -1,4 +1,6 @@
-use cotonou_common::{database::MongoDbCollection, profile::profile_entity, types::ProfileId};
+use cotonou_common::{
+    database::MongoDbCollection, matchmaking::Rating, profile::profile_entity, types::ProfileId,
+};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -11,8 +13,14 @@ pub struct ProfileForMatchmakingEntity {
     /// Matchmaking ratings indexed by Game mode name
     #[serde(rename = "mmrs", default)]
     pub mmrs: HashMap<String, u32>,
+    /// Full ratings indexed by Game mode name, `mmrs` being their rounded values
+    #[serde(rename = "rtgs", default)]
+    pub ratings: HashMap<String, Rating>,
     #[serde(rename = "nmp", default)]
     pub num_matches_played: u32,
+    /// Sessions of the last match results applied to the ratings, most recent last
+    #[serde(rename = "rss", default)]
+    pub rated_session_ids: Vec<String>,
 }
 
 impl MongoDbCollection for ProfileForMatchmakingEntity {
@@ -1,11 +1,16 @@
 use crate::{Error, ProfileForMatchmakingEntity};
+use bson::{doc, Bson};
 use cotonou_common::{
-    database::{master_entity, GenericDAL},
+    database::{self, master_entity, GenericDAL},
+    matchmaking::Rating,
     profile::profile_entity,
     types::ProfileId,
 };
 use std::result;
 
+/// Number of rated sessions kept per profile, to skip the results reported again
+const MAX_RATED_SESSION_IDS: usize = 20;
+
 #[derive(Clone)]
 pub struct ProfileForMatchmakingManager {
     generic_dal: GenericDAL,
@@ -25,8 +30,10 @@ impl ProfileForMatchmakingManager {
         let attributes_to_get = [
             master_entity::KEY,
             profile_entity::DISPLAY_NAME_PROPERTY,
-            profile_entity::ELOS_PROPERTY,
+            profile_entity::MMRS_PROPERTY,
+            profile_entity::RATINGS_PROPERTY,
             profile_entity::NUM_MATCHES_PLAYED_PROPERTY,
+            profile_entity::RATED_SESSION_IDS_PROPERTY,
         ];
 
         Ok(self
@@ -35,6 +42,52 @@ impl ProfileForMatchmakingManager {
             .await?)
     }
 
+    /// Atomically set the rating of a game mode, increment the number of matches played
+    /// and record the rated session among the last ones, provided that the profile did not play another match since it was read
+    ///
+    /// # Return
+    /// false if the profile has been updated concurrently
+    pub async fn update_rating(
+        &self,
+        profile: &ProfileForMatchmakingEntity,
+        game_mode: &str,
+        rating: &Rating,
+        session_id: &str,
+    ) -> Result<bool> {
+        // the number of matches played acts as a version of the ratings
+        let mut num_matches_played = vec![Bson::from(profile.num_matches_played)];
+        if profile.num_matches_played == 0 {
+            num_matches_played.push(Bson::Null);
+        }
+
+        // results of concurrent matches may be reported in any order
+        let mut rated_session_ids = profile.rated_session_ids.clone();
+        rated_session_ids.push(session_id.to_owned());
+        if rated_session_ids.len() > MAX_RATED_SESSION_IDS {
+            rated_session_ids.drain(..rated_session_ids.len() - MAX_RATED_SESSION_IDS);
+        }
+
+        let filter = doc! {
+            master_entity::KEY: profile.id,
+            profile_entity::NUM_MATCHES_PLAYED_PROPERTY: { "$in": num_matches_played },
+        };
+        let update = doc! {
+            "$set": {
+                format!("{}.{game_mode}", profile_entity::RATINGS_PROPERTY):
+                    bson::to_bson(rating).map_err(database::Error::from)?,
+                format!("{}.{game_mode}", profile_entity::MMRS_PROPERTY): rating.to_mmr(),
+                profile_entity::RATED_SESSION_IDS_PROPERTY: rated_session_ids,
+            },
+            "$inc": { profile_entity::NUM_MATCHES_PLAYED_PROPERTY: 1 },
+        };
+
+        let profile = self
+            .generic_dal
+            .find_and_update_entity::<ProfileForMatchmakingEntity>(filter, update)
+            .await?;
+        Ok(profile.is_some())
+    }
+
     // pub async fn get_profile_for_matchmaking(
     //     &self,
     //     profile_id: ProfileId,
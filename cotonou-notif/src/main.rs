@@ -1,9 +1,9 @@
 use crate::{error::*, notification_service::*};
 use axum::{middleware, routing::get, Router};
-use common_macros::hash_map;
 use cotonou_common::{
     authentication::{jwt_auth_middleware, JwtSecret},
-    notifications::NotificationManager,
+    metrics::{get_metrics, metrics_middleware, MetricsRegistry},
+    notifications::{NotificationManager, RedisNotificationManager},
     redis::{RedisConfig, RedisConnectionManager},
 };
 use std::{net::SocketAddr, sync::Arc};
@@ -15,17 +15,17 @@ mod notification_service;
 async fn main() -> Result<(), Error> {
     println!("Starting cotonou-notif...");
 
-    let redis_connection_manager = RedisConnectionManager::initialize(RedisConfig {
-        connection_strings: hash_map! {
-            "NOTIFICATIONS".to_owned() => "redis://redis:6379/0".to_owned(),
-            "NOTIFICATIONS_PUBSUB".to_owned() => "redis://redis:6379/0".to_owned()
-        },
-    })
+    let redis_connection_manager = RedisConnectionManager::initialize(RedisConfig::from_env(&[
+        ("NOTIFICATIONS", "redis://redis:6379"),
+        ("NOTIFICATIONS_PUBSUB", "redis://redis:6379"),
+    ])?)
     .await?;
 
-    let notification_manager = Arc::new(NotificationManager::new(&redis_connection_manager));
+    let notification_manager: Arc<dyn NotificationManager> =
+        Arc::new(RedisNotificationManager::new(&redis_connection_manager));
 
     let jwt_secret = JwtSecret::new("secret");
+    let metrics_registry = Arc::new(MetricsRegistry::new());
 
     println!("cotonou-notif started!");
 
@@ -41,6 +41,15 @@ async fn main() -> Result<(), Error> {
             jwt_secret,
             jwt_auth_middleware,
         ))
+        // added after the authentication layer, so that Prometheus can scrape it
+        .route(
+            "/metrics",
+            get(get_metrics).with_state(metrics_registry.clone()),
+        )
+        .layer(middleware::from_fn_with_state(
+            metrics_registry,
+            metrics_middleware,
+        ))
         .with_state(notification_manager);
 
     // run it
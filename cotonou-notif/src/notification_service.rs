@@ -1,13 +1,11 @@
 use crate::Error;
 use axum::{extract::State, response::Response, Extension};
-use cotonou_common::{
-    authentication::User, notifications::NotificationManager,
-};
+use cotonou_common::{authentication::User, notifications::NotificationManager};
 use hyper::StatusCode;
 use std::{sync::Arc, time::Duration};
 
 pub async fn get_notifications(
-    State(notification_manager): State<Arc<NotificationManager>>,
+    State(notification_manager): State<Arc<dyn NotificationManager>>,
     Extension(user): Extension<User>,
 ) -> Result<Response<String>, Error> {
     let channel_name = build_channel_name(&user);
@@ -23,7 +21,7 @@ pub async fn get_notifications(
 }
 
 pub async fn clear_notifications(
-    State(notification_manager): State<Arc<NotificationManager>>,
+    State(notification_manager): State<Arc<dyn NotificationManager>>,
     Extension(user): Extension<User>,
 ) -> Result<(), Error> {
     let channel_name = build_channel_name(&user);
@@ -35,7 +33,7 @@ pub async fn clear_notifications(
 }
 
 pub async fn test_publish(
-    State(notification_manager): State<Arc<NotificationManager>>,
+    State(notification_manager): State<Arc<dyn NotificationManager>>,
     Extension(user): Extension<User>,
 ) -> Result<(), Error> {
     let channel_name = build_channel_name(&user);